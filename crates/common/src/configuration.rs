@@ -0,0 +1,245 @@
+//! Reads Yarrbot's optional TOML configuration file. Every key in the file mirrors one of the environment variables in
+//! [crate::environment::variables]; [crate::environment::get_env_var] falls back to the file's value when neither the
+//! environment variable nor its `_FILE` counterpart is set.
+
+use crate::environment_variables::*;
+use anyhow::{Context, Result};
+use lazy_static::lazy_static;
+use serde::Deserialize;
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::RwLock;
+
+lazy_static! {
+    static ref LOADED: RwLock<Option<LoadedFile>> = RwLock::new(None);
+}
+
+struct LoadedFile {
+    path: PathBuf,
+    values: HashMap<&'static str, String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct ConfigurationFile {
+    database: DatabaseSection,
+    matrix: MatrixSection,
+    web: WebSection,
+    log: LogSection,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct DatabaseSection {
+    url: Option<String>,
+    pool_size: Option<u32>,
+    acquire_timeout: Option<u64>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct MatrixSection {
+    homeserver_url: Option<String>,
+    username: Option<String>,
+    password: Option<String>,
+    storage_dir: Option<String>,
+    initialization_user: Option<String>,
+    notification_images: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct WebSection {
+    port: Option<u16>,
+    bind_addresses: Option<Vec<String>>,
+    unix_socket: Option<String>,
+    tls_certificate: Option<String>,
+    tls_key: Option<String>,
+    trusted_proxies: Option<Vec<String>>,
+    trusted_proxy_header: Option<String>,
+    paused_events: Option<String>,
+}
+
+#[derive(Debug, Default, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+struct LogSection {
+    filter: Option<String>,
+    format: Option<String>,
+    directory: Option<String>,
+    rotation: Option<String>,
+    otlp_endpoint: Option<String>,
+}
+
+impl ConfigurationFile {
+    /// Flatten the file into the environment variable names each value stands in for.
+    fn into_values(self) -> HashMap<&'static str, String> {
+        let ConfigurationFile {
+            database,
+            matrix,
+            web,
+            log,
+        } = self;
+        let entries: Vec<(&'static str, Option<String>)> = vec![
+            (DB_URL, database.url),
+            (DB_POOL, database.pool_size.map(|s| s.to_string())),
+            (
+                DB_ACQUIRE_TIMEOUT,
+                database.acquire_timeout.map(|t| t.to_string()),
+            ),
+            (MATRIX_HOMESERVER_URL, matrix.homeserver_url),
+            (MATRIX_USER, matrix.username),
+            (MATRIX_PASS, matrix.password),
+            (BOT_STORAGE_DIR, matrix.storage_dir),
+            (FIRST_MATRIX_USER, matrix.initialization_user),
+            (NOTIFICATION_IMAGES, matrix.notification_images),
+            (WEB_PORT, web.port.map(|p| p.to_string())),
+            (BIND_ADDRESSES, web.bind_addresses.map(|a| a.join(","))),
+            (UNIX_SOCKET, web.unix_socket),
+            (TLS_CERTIFICATE, web.tls_certificate),
+            (TLS_KEY, web.tls_key),
+            (TRUSTED_PROXIES, web.trusted_proxies.map(|p| p.join(","))),
+            (TRUSTED_PROXY_HEADER, web.trusted_proxy_header),
+            (PAUSED_EVENTS, web.paused_events),
+            (LOG_FILTER, log.filter),
+            (LOG_FORMAT, log.format),
+            (LOG_DIRECTORY, log.directory),
+            (LOG_ROTATION, log.rotation),
+            (OTLP_ENDPOINT, log.otlp_endpoint),
+        ];
+        entries
+            .into_iter()
+            .filter_map(|(name, value)| value.map(|v| (name, v)))
+            .collect()
+    }
+}
+
+fn parse(contents: &str) -> Result<HashMap<&'static str, String>> {
+    let file: ConfigurationFile = toml::from_str(contents)?;
+    Ok(file.into_values())
+}
+
+fn read(path: &Path) -> Result<HashMap<&'static str, String>> {
+    let contents = fs::read_to_string(path)
+        .with_context(|| format!("Could not read the configuration file {}.", path.display()))?;
+    parse(&contents)
+        .with_context(|| format!("The configuration file {} is invalid.", path.display()))
+}
+
+/// Load the configuration file at the given path, replacing any previously loaded file.
+pub fn load_configuration_file(path: &Path) -> Result<()> {
+    let loaded = LoadedFile {
+        path: path.to_path_buf(),
+        values: read(path)?,
+    };
+    *LOADED
+        .write()
+        .expect("Configuration file lock is poisoned.") = Some(loaded);
+    Ok(())
+}
+
+/// Read the previously loaded configuration file again, returning the environment variables whose values changed.
+/// Does nothing if no file was loaded. The old values are kept if the file can no longer be read or is invalid.
+pub fn reload_configuration_file() -> Result<Vec<&'static str>> {
+    let (path, old) = match &*LOADED.read().expect("Configuration file lock is poisoned.") {
+        Some(f) => (f.path.clone(), f.values.clone()),
+        None => return Ok(Vec::new()),
+    };
+    load_configuration_file(&path)?;
+    let new = LOADED
+        .read()
+        .expect("Configuration file lock is poisoned.")
+        .as_ref()
+        .map(|f| f.values.clone())
+        .unwrap_or_default();
+    Ok(changed_settings(&old, &new))
+}
+
+/// List the environment variables that were added, removed or given another value, in alphabetical order.
+fn changed_settings(
+    old: &HashMap<&'static str, String>,
+    new: &HashMap<&'static str, String>,
+) -> Vec<&'static str> {
+    let mut changed: Vec<&'static str> = old
+        .keys()
+        .chain(new.keys())
+        .filter(|name| old.get(*name) != new.get(*name))
+        .copied()
+        .collect();
+    changed.sort_unstable();
+    changed.dedup();
+    changed
+}
+
+/// Retrieve the value the loaded configuration file has for the given environment variable, if any.
+pub(crate) fn get_file_value(name: &str) -> Option<String> {
+    LOADED
+        .read()
+        .expect("Configuration file lock is poisoned.")
+        .as_ref()
+        .and_then(|f| f.values.get(name).cloned())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_maps_keys_to_environment_variables() {
+        // Arrange
+        let contents = r#"
+            [database]
+            url = "postgres://localhost"
+            pool_size = 5
+
+            [web]
+            bind_addresses = ["0.0.0.0", "[::]"]
+        "#;
+
+        // Act
+        let actual = parse(contents).unwrap();
+
+        // Assert
+        assert_eq!("postgres://localhost", actual[DB_URL]);
+        assert_eq!("5", actual[DB_POOL]);
+        assert_eq!("0.0.0.0,[::]", actual[BIND_ADDRESSES]);
+        assert!(!actual.contains_key(MATRIX_USER));
+    }
+
+    #[test]
+    fn parse_returns_error_given_unknown_key() {
+        // Act
+        let actual = parse("[matrix]\nhomeserver = \"https://example.org\"");
+
+        // Assert
+        assert!(actual.is_err());
+    }
+
+    #[test]
+    fn parse_returns_error_given_wrong_type() {
+        // Act
+        let actual = parse("[web]\nport = \"eighty\"");
+
+        // Assert
+        assert!(actual.is_err());
+    }
+
+    #[test]
+    fn changed_settings_lists_added_removed_and_changed_values() {
+        // Arrange
+        let old = parse(
+            "[database]\nurl = \"postgres://a\"\n[log]\nfilter = \"info\"\nformat = \"json\"",
+        )
+        .unwrap();
+        let new = parse(
+            "[database]\nurl = \"postgres://a\"\n[log]\nfilter = \"debug\"\n[web]\nport = 80",
+        )
+        .unwrap();
+
+        // Act
+        let actual = changed_settings(&old, &new);
+
+        // Assert
+        assert_eq!(vec![LOG_FILTER, LOG_FORMAT, WEB_PORT], actual);
+    }
+}
@@ -1,9 +1,17 @@
 //! Cryptographic functions that support Yarrbot functions.
 
 use anyhow::{ensure, Context, Result};
-use sodiumoxide::{crypto::pwhash::argon2id13, randombytes::randombytes_uniform};
+use base64::{CharacterSet, Config};
+use sodiumoxide::{
+    crypto::{auth::hmacsha256, hash::sha256, pwhash::argon2id13},
+    randombytes::{randombytes, randombytes_uniform},
+    utils::memcmp,
+};
 use tokio::task::spawn_blocking;
 
+const DIGEST_CONFIG: Config = Config::new(CharacterSet::UrlSafe, false);
+const TOKEN_BYTES: usize = 32;
+
 /// Initializes Sodiumoxide, the cryptography library used in Yarrbot..
 pub fn initialize_cryptography() -> Result<()> {
     ensure!(
@@ -27,6 +35,32 @@ pub async fn hash(password: String) -> Result<[u8; 128]> {
     Ok(result.unwrap().0)
 }
 
+/// The Argon2id parameters used by [hash], in the same form as [hash_parameters].
+pub fn current_hash_parameters() -> String {
+    format!(
+        "argon2id$v=19$m={},t={},p=1",
+        argon2id13::MEMLIMIT_INTERACTIVE.0 / 1024,
+        argon2id13::OPSLIMIT_INTERACTIVE.0
+    )
+}
+
+/// Read the algorithm, version, and cost parameters out of a hash produced by [hash], such as
+/// `argon2id$v=19$m=65536,t=2,p=1`. Returns [None] if the hash isn't in the expected format.
+pub fn hash_parameters(hash: &[u8]) -> Option<String> {
+    let encoded = std::str::from_utf8(hash).ok()?.trim_end_matches('\0');
+    let mut pieces = encoded.strip_prefix('$')?.split('$');
+    let (algorithm, version, costs) = (pieces.next()?, pieces.next()?, pieces.next()?);
+    if !version.starts_with("v=") || !costs.starts_with("m=") {
+        return None;
+    }
+    Some(format!("{}${}${}", algorithm, version, costs))
+}
+
+/// Whether a password hashed with the given [hash_parameters] should be re-hashed with the current parameters.
+pub fn needs_rehash(parameters: &str) -> bool {
+    parameters != current_hash_parameters()
+}
+
 /// Verify that the given password matches the given hash. Returns
 /// true if the passwords match; false otherwise.
 ///
@@ -52,9 +86,40 @@ pub fn generate_password(length: Option<u8>) -> Result<String> {
     String::from_utf8(buf).context("Failed to generate a random password.")
 }
 
+/// Computes the SHA-256 digest of the given data and returns it as a URL-safe base64 string, which makes
+/// the result safe to use in file names.
+pub fn digest(data: &[u8]) -> String {
+    base64::encode_config(sha256::hash(data).as_ref(), DIGEST_CONFIG)
+}
+
+/// Generates a random, URL-safe token suitable for authenticating requests.
+pub fn generate_token() -> String {
+    base64::encode_config(randombytes(TOKEN_BYTES), DIGEST_CONFIG)
+}
+
+/// Compare the [digest] of the given data with an expected digest in constant time.
+pub fn digest_matches(data: &[u8], expected: &str) -> bool {
+    memcmp(digest(data).as_bytes(), expected.as_bytes())
+}
+
+/// Computes the HMAC-SHA256 of the given data with the given key.
+pub fn hmac_sha256(key: &[u8], data: &[u8]) -> Vec<u8> {
+    let mut state = hmacsha256::State::init(key);
+    state.update(data);
+    state.finalize().as_ref().to_vec()
+}
+
+/// Verify in constant time that the given signature is the HMAC-SHA256 of the given data with the given key.
+pub fn verify_hmac_sha256(key: &[u8], data: &[u8], signature: &[u8]) -> bool {
+    memcmp(&hmac_sha256(key, data), signature)
+}
+
 #[cfg(test)]
 mod tests {
-    use crate::crypto::{hash, verify};
+    use crate::crypto::{
+        current_hash_parameters, digest, digest_matches, generate_token, hash, hash_parameters,
+        hmac_sha256, needs_rehash, verify, verify_hmac_sha256,
+    };
 
     #[tokio::test]
     async fn verify_given_matching_password_returns_true() {
@@ -74,4 +139,55 @@ mod tests {
 
         assert!(!verify(expected, &hashed).await);
     }
+
+    #[tokio::test]
+    async fn hash_parameters_given_new_hash_returns_current_parameters() {
+        let hashed = hash(String::from("I am a password")).await.unwrap();
+
+        let actual = hash_parameters(&hashed).unwrap();
+
+        assert_eq!(current_hash_parameters(), actual);
+        assert!(!needs_rehash(&actual));
+        assert!(needs_rehash("argon2id$v=19$m=4096,t=1,p=1"));
+    }
+
+    #[test]
+    fn hash_parameters_given_malformed_hash_returns_none() {
+        assert!(hash_parameters(b"not a hash").is_none());
+        assert!(hash_parameters(&[0xff, 0xfe]).is_none());
+    }
+
+    #[test]
+    fn digest_given_same_input_returns_same_url_safe_output() {
+        let expected = digest(b"https://example.org/poster.jpg");
+        let actual = digest(b"https://example.org/poster.jpg");
+
+        assert_eq!(expected, actual);
+        assert!(!actual.contains('/') && !actual.contains('+'));
+    }
+
+    #[test]
+    fn digest_matches_given_digest_of_token_returns_true() {
+        let token = generate_token();
+        let stored = digest(token.as_bytes());
+
+        assert!(digest_matches(token.as_bytes(), &stored));
+        assert!(!digest_matches(b"some other token", &stored));
+    }
+
+    #[test]
+    fn hmac_sha256_given_rfc_4231_test_case_returns_expected() {
+        // Test Case 2 from RFC 4231.
+        let expected = "5bdcc146bf60754e6a042426089575c75a003f089d2739839dec58b964ec3843";
+
+        let actual = hmac_sha256(b"Jefe", b"what do ya want for nothing?");
+
+        assert_eq!(expected, hex::encode(&actual));
+        assert!(verify_hmac_sha256(
+            b"Jefe",
+            b"what do ya want for nothing?",
+            &actual
+        ));
+        assert!(!verify_hmac_sha256(b"Jefe", b"something else", &actual));
+    }
 }
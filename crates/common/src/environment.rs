@@ -1,3 +1,4 @@
+use crate::configuration::get_file_value;
 use anyhow::{Context, Result};
 use std::env;
 use std::fs;
@@ -8,17 +9,21 @@ pub mod variables {
 
 /// Retrieve some environment variable value by its name. Also checks if the environment variable
 /// value is in some file, the path to which is retrieved from an environment variable by the
-/// given name concatenated with `_FILE`.
+/// given name concatenated with `_FILE`, and then the configuration file, if one was loaded.
 ///
-/// Returns [Result::Ok()] if a value is successfully retrieved from either environment variable;
+/// Returns [Result::Ok()] if a value is successfully retrieved from any of these sources;
 /// returns [Result::Err()] otherwise.
 pub fn get_env_var(name: &str) -> Result<String> {
     let result = match env::var(name) {
         Ok(s) => Ok(s),
-        Err(e) => Ok(get_from_file(name).context(format!(
-            "Could not find a value for {} nor for {}_FILE. Original Error: {:?}",
-            name, name, e
-        ))?),
+        Err(e) => match env::var(format!("{}_FILE", name)) {
+            Ok(path) => fs::read_to_string(&path)
+                .with_context(|| format!("Could not read the value for {} from {}.", name, path)),
+            Err(_) => get_file_value(name).context(format!(
+                "Could not find a value for {} nor for {}_FILE, nor in the configuration file. Original Error: {:?}",
+                name, name, e
+            )),
+        },
     };
     if let Ok(s) = result {
         Ok(s.trim().to_string())
@@ -26,8 +31,3 @@ pub fn get_env_var(name: &str) -> Result<String> {
         result
     }
 }
-
-fn get_from_file(name: &str) -> Result<String> {
-    let path = env::var(format!("{}_FILE", name))?;
-    Ok(fs::read_to_string(path)?)
-}
@@ -3,6 +3,7 @@
 // Database environment variables
 pub const DB_URL: &str = "YARRBOT_DATABASE_URL";
 pub const DB_POOL: &str = "YARRBOT_DATABASE_POOL_SIZE";
+pub const DB_ACQUIRE_TIMEOUT: &str = "YARRBOT_DATABASE_ACQUIRE_TIMEOUT";
 
 // Matrix environment variables
 pub const MATRIX_USER: &str = "YARRBOT_MATRIX_USERNAME";
@@ -10,9 +11,22 @@ pub const MATRIX_PASS: &str = "YARRBOT_MATRIX_PASSWORD";
 pub const MATRIX_HOMESERVER_URL: &str = "YARRBOT_MATRIX_HOMESERVER_URL";
 pub const BOT_STORAGE_DIR: &str = "YARRBOT_STORAGE_DIR";
 pub const FIRST_MATRIX_USER: &str = "YARRBOT_INITIALIZATION_USER";
+pub const NOTIFICATION_IMAGES: &str = "YARRBOT_NOTIFICATION_IMAGES";
 
 // Web API environment variables
 pub const WEB_PORT: &str = "YARRBOT_WEB_PORT";
+pub const TRUSTED_PROXIES: &str = "YARRBOT_TRUSTED_PROXIES";
+pub const TRUSTED_PROXY_HEADER: &str = "YARRBOT_TRUSTED_PROXY_HEADER";
+pub const BIND_ADDRESSES: &str = "YARRBOT_BIND_ADDRESSES";
+pub const UNIX_SOCKET: &str = "YARRBOT_UNIX_SOCKET";
+pub const TLS_CERTIFICATE: &str = "YARRBOT_TLS_CERTIFICATE";
+pub const TLS_KEY: &str = "YARRBOT_TLS_KEY";
+pub const PAUSED_EVENTS: &str = "YARRBOT_PAUSED_EVENTS";
 
 // Miscellaneous
+pub const CONFIG_FILE: &str = "YARRBOT_CONFIG_FILE";
 pub const LOG_FILTER: &str = "YARRBOT_LOG_FILTER";
+pub const LOG_FORMAT: &str = "YARRBOT_LOG_FORMAT";
+pub const LOG_DIRECTORY: &str = "YARRBOT_LOG_DIRECTORY";
+pub const LOG_ROTATION: &str = "YARRBOT_LOG_ROTATION";
+pub const OTLP_ENDPOINT: &str = "YARRBOT_OTLP_ENDPOINT";
@@ -1,7 +1,12 @@
+pub mod configuration;
 pub mod crypto;
 pub mod environment;
 mod environment_variables;
+pub mod metrics;
+pub mod network;
 pub mod short_id;
 mod shutdown;
 
-pub use shutdown::{ShutdownActor, ShutdownNotice, SubscribeToShutdown};
+pub use shutdown::{
+    ReloadNotice, ShutdownActor, ShutdownNotice, SubscribeToReload, SubscribeToShutdown,
+};
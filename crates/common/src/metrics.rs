@@ -0,0 +1,119 @@
+//! Prometheus metrics recorded throughout Yarrbot. Every metric is registered with the default registry, which the
+//! web API serves at `/metrics`.
+
+use anyhow::Result;
+use lazy_static::lazy_static;
+use prometheus::{
+    register_histogram, register_int_counter, register_int_counter_vec, register_int_gauge,
+    Encoder, Histogram, IntCounter, IntCounterVec, IntGauge, TextEncoder,
+};
+
+/// The content type of [render]'s output.
+pub const CONTENT_TYPE: &str = prometheus::TEXT_FORMAT;
+
+/// Label value used when the real value is unknown, such as the event type of a webhook body that couldn't be read.
+pub const UNKNOWN: &str = "unknown";
+
+/// Argon2id verification takes tens to hundreds of milliseconds depending on the hardware.
+const PASSWORD_VERIFICATION_BUCKETS: &[f64] = &[0.01, 0.025, 0.05, 0.1, 0.25, 0.5, 1.0, 2.5, 5.0];
+
+lazy_static! {
+    pub static ref WEBHOOKS_RECEIVED: IntCounterVec = register_int_counter_vec!(
+        "yarrbot_webhooks_received_total",
+        "Webhooks received, by *arr application, event type, and outcome.",
+        &["app", "event_type", "outcome"]
+    )
+    .unwrap();
+    pub static ref WEBHOOK_AUTH_FAILURES: IntCounter = register_int_counter!(
+        "yarrbot_webhook_auth_failures_total",
+        "Webhook requests rejected because they failed authentication."
+    )
+    .unwrap();
+    pub static ref WEBHOOK_DESERIALIZATION_FAILURES: IntCounter = register_int_counter!(
+        "yarrbot_webhook_deserialization_failures_total",
+        "Webhook bodies that couldn't be parsed."
+    )
+    .unwrap();
+    pub static ref WEBHOOK_DURATION: Histogram = register_histogram!(
+        "yarrbot_webhook_duration_seconds",
+        "Time taken to read, convert, and queue the messages for a webhook."
+    )
+    .unwrap();
+    pub static ref PASSWORD_VERIFICATION_DURATION: Histogram = register_histogram!(
+        "yarrbot_password_verification_duration_seconds",
+        "Time taken to verify a webhook password with Argon2id.",
+        PASSWORD_VERIFICATION_BUCKETS.to_vec()
+    )
+    .unwrap();
+    pub static ref CREDENTIAL_CACHE_LOOKUPS: IntCounterVec = register_int_counter_vec!(
+        "yarrbot_credential_cache_lookups_total",
+        "Lookups in the verified webhook credential cache, by whether they were a hit or a miss.",
+        &["result"]
+    )
+    .unwrap();
+    pub static ref MATRIX_MESSAGES_SENT: IntCounterVec = register_int_counter_vec!(
+        "yarrbot_matrix_messages_sent_total",
+        "Messages sent to Matrix rooms, by room and whether sending succeeded.",
+        &["room_id", "outcome"]
+    )
+    .unwrap();
+    pub static ref MATRIX_SEND_DURATION: Histogram = register_histogram!(
+        "yarrbot_matrix_send_duration_seconds",
+        "Time taken to send a message, including any image, to a Matrix room."
+    )
+    .unwrap();
+    pub static ref COMMANDS_EXECUTED: IntCounterVec = register_int_counter_vec!(
+        "yarrbot_commands_executed_total",
+        "!yarrbot commands executed, by command.",
+        &["command"]
+    )
+    .unwrap();
+    pub static ref DB_POOL_CONNECTIONS: IntGauge = register_int_gauge!(
+        "yarrbot_db_pool_connections",
+        "Connections currently held by the database connection pool."
+    )
+    .unwrap();
+    pub static ref DB_POOL_IDLE_CONNECTIONS: IntGauge = register_int_gauge!(
+        "yarrbot_db_pool_idle_connections",
+        "Idle connections in the database connection pool."
+    )
+    .unwrap();
+    pub static ref DB_POOL_MAX_CONNECTIONS: IntGauge = register_int_gauge!(
+        "yarrbot_db_pool_max_connections",
+        "The maximum number of connections the database connection pool will open."
+    )
+    .unwrap();
+}
+
+/// The outcome label for a Matrix message.
+pub fn send_outcome(succeeded: bool) -> &'static str {
+    if succeeded {
+        "succeeded"
+    } else {
+        "failed"
+    }
+}
+
+/// Render every registered metric in the Prometheus text exposition format.
+pub fn render() -> Result<String> {
+    let mut buffer = Vec::new();
+    TextEncoder::new().encode(&prometheus::gather(), &mut buffer)?;
+    Ok(String::from_utf8(buffer)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_includes_recorded_metrics() {
+        // Arrange
+        COMMANDS_EXECUTED.with_label_values(&["ping"]).inc();
+
+        // Act
+        let actual = render().unwrap();
+
+        // Assert
+        assert!(actual.contains("yarrbot_commands_executed_total{command=\"ping\"}"));
+    }
+}
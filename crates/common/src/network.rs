@@ -0,0 +1,80 @@
+//! Helpers for matching IP addresses against networks written in CIDR notation.
+
+use anyhow::{Context, Result};
+pub use ipnet::IpNet;
+use std::net::{IpAddr, Ipv4Addr};
+use std::str::FromStr;
+
+/// Parse a network in CIDR notation, such as `192.168.1.0/24` or `fd00::/8`. A bare IP address is treated as a
+/// network containing only that address.
+pub fn parse_network(value: &str) -> Result<IpNet> {
+    let value = value.trim();
+    if value.contains('/') {
+        let network = IpNet::from_str(value)
+            .with_context(|| format!("Failed to parse \"{}\" as a network.", value))?;
+        Ok(network.trunc())
+    } else {
+        let address = IpAddr::from_str(value)
+            .with_context(|| format!("Failed to parse \"{}\" as an IP address.", value))?;
+        Ok(IpNet::from(address))
+    }
+}
+
+/// Convert IPv4-mapped IPv6 addresses (`::ffff:a.b.c.d`), as reported by dual-stack listeners, to plain IPv4
+/// addresses so that they match IPv4 networks.
+pub fn canonical_ip(address: IpAddr) -> IpAddr {
+    match address {
+        IpAddr::V6(v6) => match v6.octets() {
+            [0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0xff, 0xff, a, b, c, d] => {
+                IpAddr::V4(Ipv4Addr::new(a, b, c, d))
+            }
+            _ => IpAddr::V6(v6),
+        },
+        v4 => v4,
+    }
+}
+
+/// Whether the address is within any of the networks.
+pub fn is_in_any(address: IpAddr, networks: &[IpNet]) -> bool {
+    let address = canonical_ip(address);
+    networks.iter().any(|n| n.contains(&address))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_network_given_bare_address_returns_single_address_network() {
+        let network = parse_network("192.0.2.7").unwrap();
+
+        assert_eq!("192.0.2.7/32", network.to_string());
+    }
+
+    #[test]
+    fn parse_network_given_host_bits_returns_truncated_network() {
+        let network = parse_network(" 10.1.2.3/8 ").unwrap();
+
+        assert_eq!("10.0.0.0/8", network.to_string());
+        assert!(parse_network("10.0.0.0/33").is_err());
+        assert!(parse_network("example.org").is_err());
+    }
+
+    #[test]
+    fn is_in_any_given_ipv4_mapped_address_matches_ipv4_network() {
+        let networks = vec![
+            parse_network("10.0.0.0/8").unwrap(),
+            parse_network("fd00::/8").unwrap(),
+        ];
+
+        assert!(is_in_any(
+            IpAddr::from_str("::ffff:10.1.2.3").unwrap(),
+            &networks
+        ));
+        assert!(is_in_any(IpAddr::from_str("fd12::1").unwrap(), &networks));
+        assert!(!is_in_any(
+            IpAddr::from_str("192.0.2.7").unwrap(),
+            &networks
+        ));
+    }
+}
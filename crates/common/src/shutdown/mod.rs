@@ -1,7 +1,11 @@
+mod reload_notice;
 mod shutdown_actor;
 mod shutdown_notice;
+mod subscribe_to_reload;
 mod subscribe_to_shutdown;
 
+pub use reload_notice::ReloadNotice;
 pub use shutdown_actor::ShutdownActor;
 pub use shutdown_notice::ShutdownNotice;
+pub use subscribe_to_reload::SubscribeToReload;
 pub use subscribe_to_shutdown::SubscribeToShutdown;
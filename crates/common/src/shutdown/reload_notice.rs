@@ -0,0 +1,20 @@
+use actix::Message;
+
+/// Sent when Yarrbot receives a SIGHUP, asking it to reload its configuration.
+pub struct ReloadNotice;
+
+impl Message for ReloadNotice {
+    type Result = ();
+}
+
+impl ReloadNotice {
+    pub fn new() -> Self {
+        ReloadNotice {}
+    }
+}
+
+impl Default for ReloadNotice {
+    fn default() -> Self {
+        ReloadNotice::new()
+    }
+}
@@ -1,18 +1,29 @@
+use crate::shutdown::reload_notice::ReloadNotice;
 use crate::shutdown::shutdown_notice::ShutdownNotice;
+use crate::shutdown::subscribe_to_reload::SubscribeToReload;
 use crate::shutdown::subscribe_to_shutdown::SubscribeToShutdown;
 use actix::{
-    Actor, ActorContext, ActorFutureExt, AsyncContext, Context, Handler, Recipient, WrapFuture,
+    Actor, ActorContext, ActorFutureExt, Addr, AsyncContext, Context, Handler, Message, Recipient,
+    WrapFuture,
 };
 use tokio::signal::{
     ctrl_c,
     unix::{signal, SignalKind},
 };
-use tracing::{debug, error, info};
+use tracing::{debug, error, info, warn};
 
 /// Relays notices to shutdown the Yarrbot by listening for SIGTERM or SIGINT then sending messages to
-/// any registered [subscribers].
+/// any registered [subscribers]. Likewise relays a [ReloadNotice] to any [reload_subscribers] upon each SIGHUP.
 pub struct ShutdownActor {
     subscribers: Vec<Recipient<ShutdownNotice>>,
+    reload_subscribers: Vec<Recipient<ReloadNotice>>,
+}
+
+/// Sent by the [ShutdownActor] to itself whenever a SIGHUP is received.
+struct HangUp;
+
+impl Message for HangUp {
+    type Result = ();
 }
 
 impl Actor for ShutdownActor {
@@ -33,6 +44,9 @@ impl Actor for ShutdownActor {
         });
         let pinned = Box::pin(update_self);
         ctx.spawn(pinned);
+
+        let hangups = ShutdownActor::wait_for_hangups(ctx.address()).into_actor(self);
+        ctx.spawn(hangups);
     }
 
     fn stopped(&mut self, _ctx: &mut Self::Context) {
@@ -49,11 +63,33 @@ impl Handler<SubscribeToShutdown> for ShutdownActor {
     }
 }
 
+impl Handler<SubscribeToReload> for ShutdownActor {
+    type Result = ();
+
+    fn handle(&mut self, msg: SubscribeToReload, _ctx: &mut Self::Context) -> Self::Result {
+        self.reload_subscribers.push(msg.0);
+    }
+}
+
+impl Handler<HangUp> for ShutdownActor {
+    type Result = ();
+
+    fn handle(&mut self, _msg: HangUp, _ctx: &mut Self::Context) -> Self::Result {
+        info!("Reloading configuration.");
+        for subscriber in &self.reload_subscribers {
+            if let Err(e) = subscriber.do_send(ReloadNotice::new()) {
+                warn!(error = ?e, "Failed to send reload notice to a subscriber.");
+            }
+        }
+    }
+}
+
 impl ShutdownActor {
     /// Creates a new [ShutdownActor] with no subscribers.
     pub fn new() -> Self {
         ShutdownActor {
             subscribers: vec![],
+            reload_subscribers: vec![],
         }
     }
 
@@ -75,6 +111,15 @@ impl ShutdownActor {
 
         info!("Shutdown signal received.");
     }
+
+    /// Notifies the actor at [addr] of every SIGHUP until the signal stream closes.
+    async fn wait_for_hangups(addr: Addr<ShutdownActor>) {
+        let mut sighup = signal(SignalKind::hangup()).expect("Could not set up SIGHUP stream.");
+        while sighup.recv().await.is_some() {
+            debug!("Received SIGHUP.");
+            addr.do_send(HangUp);
+        }
+    }
 }
 
 impl Default for ShutdownActor {
@@ -0,0 +1,10 @@
+use crate::shutdown::reload_notice::ReloadNotice;
+use actix::{Message, Recipient};
+
+/// Sending this message ensures that the [Recipient] receives notice whenever Yarrbot is asked to reload its
+/// configuration.
+pub struct SubscribeToReload(pub Recipient<ReloadNotice>);
+
+impl Message for SubscribeToReload {
+    type Result = ();
+}
@@ -1,3 +0,0 @@
-pub mod matrix_room_actions;
-pub mod user_actions;
-pub mod webhook_actions;
@@ -1,2 +0,0 @@
-pub use crate::enums::*;
-pub use diesel::sql_types::*;
@@ -1,12 +1,15 @@
 use diesel_derive_enum::DbEnum;
+use serde::{Deserialize, Serialize};
 use strum_macros::AsRefStr;
 
 /// The roles that a user may have with Yarrbot. These roles are only required
 /// for those users actively interacting with Yarrbot; users that are only
 /// listening for messages from Yarrbot do not need a role.
-#[derive(DbEnum, Debug, Clone, AsRefStr)]
+#[derive(DbEnum, sqlx::Type, Debug, Clone, AsRefStr, Serialize, Deserialize)]
 #[PgType = "user_role"]
+#[sqlx(type_name = "user_role", rename_all = "snake_case")]
 #[DieselType = "User_role"]
+#[serde(rename_all = "snake_case")]
 pub enum UserRole {
     /// Can modify other [UserRole::Administrator] users and their content. May also
     /// modify webhooks and associated rooms.
@@ -15,4 +18,48 @@ pub enum UserRole {
     /// Users can modify webhooks and the rooms that messages from the webhooks are
     /// relayed to.
     Administrator,
+
+    /// Users can see the webhooks shared with them but can't create webhooks, and can
+    /// only change a webhook in the ways they have been granted.
+    Viewer,
+}
+
+/// How file system paths are shown in the notifications sent for a webhook.
+#[derive(DbEnum, sqlx::Type, Debug, Clone, Copy, PartialEq, AsRefStr, Serialize, Deserialize)]
+#[PgType = "path_policy"]
+#[sqlx(type_name = "path_policy", rename_all = "snake_case")]
+#[DieselType = "Path_policy"]
+#[serde(rename_all = "snake_case")]
+pub enum PathPolicy {
+    /// Paths are shown as-is.
+    Full,
+
+    /// Only the last component of each path (e.g. the file name) is shown.
+    Basename,
+
+    /// Paths are not shown at all.
+    Hide,
+
+    /// Configured path prefixes are replaced (e.g. `/mnt/media` with `/media`) before paths are shown.
+    Replace,
+}
+
+/// How requests to a webhook are authenticated.
+#[derive(DbEnum, sqlx::Type, Debug, Clone, Copy, PartialEq, AsRefStr, Serialize, Deserialize)]
+#[PgType = "webhook_auth_mode"]
+#[sqlx(type_name = "webhook_auth_mode", rename_all = "snake_case")]
+#[DieselType = "Webhook_auth_mode"]
+#[serde(rename_all = "snake_case")]
+pub enum WebhookAuthMode {
+    /// HTTP Basic authentication with the webhook's username and password.
+    Basic,
+
+    /// A random token in the `token` query parameter of the webhook URL.
+    UrlToken,
+
+    /// A random token in either the `X-Api-Key` header or an `Authorization: Bearer` header.
+    HeaderToken,
+
+    /// An HMAC-SHA256 signature of the request's timestamp and body.
+    Hmac,
 }
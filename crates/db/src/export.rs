@@ -0,0 +1,517 @@
+//! Exports all of Yarrbot's configuration (users, webhooks with their hashed passwords, the rooms webhooks post to,
+//! and who webhooks are shared with) to a versioned document, and imports such a document into another database.
+//! Webhooks' *arr API keys and HMAC secrets are stored as given rather than hashed, so they're only exported on
+//! request.
+
+use crate::enums::{PathPolicy, UserRole, WebhookAuthMode};
+use crate::models::{MatrixRoom, User, Webhook, WebhookCollaborator};
+use crate::repositories::Repositories;
+use anyhow::{bail, Result};
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use uuid::Uuid;
+
+/// The version of the document produced by [export]. Bumped whenever the document changes in a way older versions of
+/// Yarrbot can't import.
+pub const FORMAT_VERSION: u32 = 1;
+
+/// A snapshot of Yarrbot's configuration.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ConfigurationExport {
+    pub version: u32,
+    pub exported_at: DateTime<Utc>,
+    pub users: Vec<ExportedUser>,
+    pub webhooks: Vec<ExportedWebhook>,
+    pub rooms: Vec<ExportedRoom>,
+    /// Missing from documents exported before webhooks could be shared.
+    #[serde(default)]
+    pub collaborators: Vec<ExportedCollaborator>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedUser {
+    pub id: Uuid,
+    pub service_username: String,
+    pub user_role: UserRole,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedWebhook {
+    pub id: Uuid,
+    pub username: String,
+    #[serde(with = "base64_bytes")]
+    pub password: Vec<u8>,
+    pub user_id: Uuid,
+    pub server_name: Option<String>,
+    pub arr_url: Option<String>,
+    pub arr_api_key: Option<String>,
+    pub path_policy: PathPolicy,
+    pub path_replacements: Vec<String>,
+    pub auth_mode: WebhookAuthMode,
+    pub auth_token_digest: Option<String>,
+    pub hmac_secret: Option<String>,
+    pub password_params: String,
+    #[serde(with = "base64_optional_bytes")]
+    pub previous_password: Option<Vec<u8>>,
+    pub previous_password_expires_at: Option<DateTime<Utc>>,
+    pub allowed_sources: Vec<String>,
+    #[serde(default)]
+    pub created_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub last_event_at: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub events_received: i64,
+    #[serde(default)]
+    pub delivery_failures: i64,
+    #[serde(default)]
+    pub paused: bool,
+    #[serde(default)]
+    pub paused_until: Option<DateTime<Utc>>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedRoom {
+    pub id: Uuid,
+    pub room_id: String,
+    pub webhook_id: Uuid,
+    pub hide_spoilers: bool,
+    #[serde(default)]
+    pub muted_until: Option<DateTime<Utc>>,
+    #[serde(default)]
+    pub quiet_hours_start: Option<String>,
+    #[serde(default)]
+    pub quiet_hours_end: Option<String>,
+    #[serde(default)]
+    pub quiet_hours_timezone: Option<String>,
+    #[serde(default)]
+    pub quiet_hours_bypass_health: bool,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportedCollaborator {
+    pub id: Uuid,
+    pub webhook_id: Uuid,
+    pub user_id: Uuid,
+    pub manage_rooms: bool,
+    pub manage_filters: bool,
+    pub rotate_credentials: bool,
+}
+
+/// What to do when an imported record has the same ID (or, for users, the same username) as an existing record.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ConflictPolicy {
+    /// Abort the import without changing anything.
+    Fail,
+
+    /// Keep the existing record. A user matched by username keeps its webhooks and gains the imported ones.
+    Skip,
+
+    /// Replace the existing record with the imported one.
+    Overwrite,
+}
+
+/// How many records an import created, overwrote, and skipped.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ImportCounts {
+    pub created: usize,
+    pub overwritten: usize,
+    pub skipped: usize,
+}
+
+/// The outcome of an import, per kind of record.
+#[derive(Debug, Default, Clone, Copy, PartialEq)]
+pub struct ImportReport {
+    pub users: ImportCounts,
+    pub webhooks: ImportCounts,
+    pub rooms: ImportCounts,
+    pub collaborators: ImportCounts,
+}
+
+impl ConfigurationExport {
+    /// Build a document from records that have already been retrieved, such as through [crate::repositories].
+    pub fn from_records(
+        users: Vec<User>,
+        webhooks: Vec<Webhook>,
+        rooms: Vec<MatrixRoom>,
+        collaborators: Vec<WebhookCollaborator>,
+    ) -> Self {
+        let mut users: Vec<ExportedUser> = users.into_iter().map(ExportedUser::from).collect();
+        let mut webhooks: Vec<ExportedWebhook> =
+            webhooks.into_iter().map(ExportedWebhook::from).collect();
+        let mut rooms: Vec<ExportedRoom> = rooms.into_iter().map(ExportedRoom::from).collect();
+        let mut collaborators: Vec<ExportedCollaborator> = collaborators
+            .into_iter()
+            .map(ExportedCollaborator::from)
+            .collect();
+        users.sort_by(|a, b| a.service_username.cmp(&b.service_username));
+        webhooks.sort_by_key(|w| w.id);
+        rooms.sort_by_key(|r| r.id);
+        collaborators.sort_by_key(|c| c.id);
+        ConfigurationExport {
+            version: FORMAT_VERSION,
+            exported_at: Utc::now(),
+            users,
+            webhooks,
+            rooms,
+            collaborators,
+        }
+    }
+
+    /// Leave out the secrets that are stored as given rather than hashed: each webhook's *arr API key and HMAC secret.
+    pub fn without_secrets(mut self) -> Self {
+        for webhook in &mut self.webhooks {
+            webhook.arr_api_key = None;
+            webhook.hmac_secret = None;
+        }
+        self
+    }
+}
+
+impl From<User> for ExportedUser {
+    fn from(user: User) -> Self {
+        ExportedUser {
+            id: user.id,
+            service_username: user.service_username,
+            user_role: user.user_role,
+        }
+    }
+}
+
+impl From<Webhook> for ExportedWebhook {
+    fn from(webhook: Webhook) -> Self {
+        ExportedWebhook {
+            id: webhook.id,
+            username: webhook.username,
+            password: webhook.password,
+            user_id: webhook.user_id,
+            server_name: webhook.server_name,
+            arr_url: webhook.arr_url,
+            arr_api_key: webhook.arr_api_key,
+            path_policy: webhook.path_policy,
+            path_replacements: webhook.path_replacements,
+            auth_mode: webhook.auth_mode,
+            auth_token_digest: webhook.auth_token_digest,
+            hmac_secret: webhook.hmac_secret,
+            password_params: webhook.password_params,
+            previous_password: webhook.previous_password,
+            previous_password_expires_at: webhook.previous_password_expires_at,
+            allowed_sources: webhook.allowed_sources,
+            created_at: webhook.created_at,
+            last_event_at: webhook.last_event_at,
+            events_received: webhook.events_received,
+            delivery_failures: webhook.delivery_failures,
+            paused: webhook.paused,
+            paused_until: webhook.paused_until,
+        }
+    }
+}
+
+impl From<MatrixRoom> for ExportedRoom {
+    fn from(room: MatrixRoom) -> Self {
+        ExportedRoom {
+            id: room.id,
+            room_id: room.room_id,
+            webhook_id: room.webhook_id,
+            hide_spoilers: room.hide_spoilers,
+            muted_until: room.muted_until,
+            quiet_hours_start: room.quiet_hours_start,
+            quiet_hours_end: room.quiet_hours_end,
+            quiet_hours_timezone: room.quiet_hours_timezone,
+            quiet_hours_bypass_health: room.quiet_hours_bypass_health,
+        }
+    }
+}
+
+impl From<WebhookCollaborator> for ExportedCollaborator {
+    fn from(collaborator: WebhookCollaborator) -> Self {
+        ExportedCollaborator {
+            id: collaborator.id,
+            webhook_id: collaborator.webhook_id,
+            user_id: collaborator.user_id,
+            manage_rooms: collaborator.manage_rooms,
+            manage_filters: collaborator.manage_filters,
+            rotate_credentials: collaborator.rotate_credentials,
+        }
+    }
+}
+
+impl From<ExportedUser> for User {
+    fn from(user: ExportedUser) -> Self {
+        User {
+            id: user.id,
+            service_username: user.service_username,
+            user_role: user.user_role,
+        }
+    }
+}
+
+impl From<ExportedWebhook> for Webhook {
+    fn from(webhook: ExportedWebhook) -> Self {
+        Webhook {
+            id: webhook.id,
+            username: webhook.username,
+            password: webhook.password,
+            user_id: webhook.user_id,
+            server_name: webhook.server_name,
+            arr_url: webhook.arr_url,
+            arr_api_key: webhook.arr_api_key,
+            path_policy: webhook.path_policy,
+            path_replacements: webhook.path_replacements,
+            auth_mode: webhook.auth_mode,
+            auth_token_digest: webhook.auth_token_digest,
+            hmac_secret: webhook.hmac_secret,
+            password_params: webhook.password_params,
+            previous_password: webhook.previous_password,
+            previous_password_expires_at: webhook.previous_password_expires_at,
+            allowed_sources: webhook.allowed_sources,
+            created_at: webhook.created_at,
+            last_event_at: webhook.last_event_at,
+            events_received: webhook.events_received,
+            delivery_failures: webhook.delivery_failures,
+            paused: webhook.paused,
+            paused_until: webhook.paused_until,
+        }
+    }
+}
+
+impl From<ExportedRoom> for MatrixRoom {
+    fn from(room: ExportedRoom) -> Self {
+        MatrixRoom {
+            id: room.id,
+            room_id: room.room_id,
+            webhook_id: room.webhook_id,
+            hide_spoilers: room.hide_spoilers,
+            muted_until: room.muted_until,
+            quiet_hours_start: room.quiet_hours_start,
+            quiet_hours_end: room.quiet_hours_end,
+            quiet_hours_timezone: room.quiet_hours_timezone,
+            quiet_hours_bypass_health: room.quiet_hours_bypass_health,
+        }
+    }
+}
+
+impl From<ExportedCollaborator> for WebhookCollaborator {
+    fn from(collaborator: ExportedCollaborator) -> Self {
+        WebhookCollaborator {
+            id: collaborator.id,
+            webhook_id: collaborator.webhook_id,
+            user_id: collaborator.user_id,
+            manage_rooms: collaborator.manage_rooms,
+            manage_filters: collaborator.manage_filters,
+            rotate_credentials: collaborator.rotate_credentials,
+        }
+    }
+}
+
+/// Read all of Yarrbot's configuration, leaving out secrets that aren't hashed unless `include_secrets` is set (see
+/// [ConfigurationExport::without_secrets]).
+pub async fn export(
+    repositories: &Repositories,
+    include_secrets: bool,
+) -> Result<ConfigurationExport> {
+    let document = ConfigurationExport::from_records(
+        repositories.users.get_all().await?,
+        repositories.webhooks.get_all().await?,
+        repositories.rooms.get_all().await?,
+        repositories.collaborators.get_all().await?,
+    );
+    Ok(if include_secrets {
+        document
+    } else {
+        document.without_secrets()
+    })
+}
+
+/// Write an exported configuration in a single transaction (see
+/// [crate::repositories::ConfigurationRepository::import]), rejecting documents in a format this version of Yarrbot
+/// can't import.
+pub async fn import(
+    repositories: &Repositories,
+    document: ConfigurationExport,
+    policy: ConflictPolicy,
+    dry_run: bool,
+) -> Result<ImportReport> {
+    if document.version != FORMAT_VERSION {
+        bail!(
+            "Can't import version {} of the export format; this version of Yarrbot imports version {}.",
+            document.version,
+            FORMAT_VERSION
+        );
+    }
+    repositories
+        .configuration
+        .import(document, policy, dry_run)
+        .await
+}
+
+/// What an import does with a single record.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub(crate) enum Action {
+    Create,
+    Overwrite,
+    Skip,
+}
+
+impl ImportCounts {
+    pub(crate) fn record(&mut self, action: Action) {
+        match action {
+            Action::Create => self.created += 1,
+            Action::Overwrite => self.overwritten += 1,
+            Action::Skip => self.skipped += 1,
+        }
+    }
+}
+
+/// Find the existing user an imported user refers to, matching either their ID or their username. Returns an error if
+/// the two match different users, since the imported user can't be both.
+pub(crate) fn match_user(
+    user: &ExportedUser,
+    by_id: Option<Uuid>,
+    by_username: Option<Uuid>,
+) -> Result<Option<Uuid>> {
+    match (by_id, by_username) {
+        (Some(id), Some(other)) if id != other => bail!(
+            "The user {} ({}) conflicts with two existing users: one with the same ID and another with the same \
+            username.",
+            user.service_username,
+            user.id
+        ),
+        (by_id, by_username) => Ok(by_id.or(by_username)),
+    }
+}
+
+/// Decide what to do with an imported record given whether it already exists.
+pub(crate) fn resolve(
+    policy: ConflictPolicy,
+    exists: bool,
+    kind: &str,
+    name: &str,
+) -> Result<Action> {
+    match (exists, policy) {
+        (false, _) => Ok(Action::Create),
+        (true, ConflictPolicy::Skip) => Ok(Action::Skip),
+        (true, ConflictPolicy::Overwrite) => Ok(Action::Overwrite),
+        (true, ConflictPolicy::Fail) => bail!("The {} {} already exists.", kind, name),
+    }
+}
+
+/// Password hashes are stored as base64 rather than as arrays of numbers.
+mod base64_bytes {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(bytes: &[u8], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&base64::encode(bytes))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<Vec<u8>, D::Error> {
+        let encoded = String::deserialize(deserializer)?;
+        base64::decode(encoded).map_err(serde::de::Error::custom)
+    }
+}
+
+mod base64_optional_bytes {
+    use serde::{Deserialize, Deserializer, Serializer};
+
+    pub fn serialize<S: Serializer>(
+        bytes: &Option<Vec<u8>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match bytes {
+            Some(b) => serializer.serialize_some(&base64::encode(b)),
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Vec<u8>>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|encoded| base64::decode(encoded).map_err(serde::de::Error::custom))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{NewUser, NewWebhook};
+
+    #[test]
+    fn resolve_creates_records_that_do_not_exist() {
+        // Act
+        let actual = resolve(ConflictPolicy::Fail, false, "user", "@a:example.org");
+
+        // Assert
+        assert_eq!(Action::Create, actual.unwrap());
+    }
+
+    #[test]
+    fn resolve_returns_error_given_conflict_and_fail_policy() {
+        // Act
+        let actual = resolve(ConflictPolicy::Fail, true, "user", "@a:example.org");
+
+        // Assert
+        assert!(actual.is_err());
+    }
+
+    #[test]
+    fn match_user_returns_error_given_id_and_username_of_different_users() {
+        // Arrange
+        let user = ExportedUser::from(User::from(NewUser::new("@a:example.org", None)));
+
+        // Act
+        let actual = match_user(&user, Some(Uuid::new_v4()), Some(Uuid::new_v4()));
+
+        // Assert
+        assert!(actual.is_err());
+    }
+
+    #[test]
+    fn exported_room_round_trips_through_json() {
+        // Arrange
+        let document = ConfigurationExport {
+            version: FORMAT_VERSION,
+            exported_at: Utc::now(),
+            users: vec![],
+            webhooks: vec![],
+            rooms: vec![ExportedRoom {
+                id: Uuid::new_v4(),
+                room_id: String::from("!room:example.org"),
+                webhook_id: Uuid::new_v4(),
+                hide_spoilers: true,
+                muted_until: None,
+                quiet_hours_start: None,
+                quiet_hours_end: None,
+                quiet_hours_timezone: None,
+                quiet_hours_bypass_health: false,
+            }],
+            collaborators: vec![],
+        };
+
+        // Act
+        let json = serde_json::to_string(&document).unwrap();
+        let actual: ConfigurationExport = serde_json::from_str(&json).unwrap();
+
+        // Assert
+        assert_eq!(document.rooms[0].id, actual.rooms[0].id);
+        assert!(actual.rooms[0].hide_spoilers);
+    }
+
+    #[test]
+    fn without_secrets_removes_api_keys_and_hmac_secrets() {
+        // Arrange
+        let user = User::from(NewUser::new("@a:example.org", None));
+        let mut webhook = Webhook::from(NewWebhook::new("user", vec![0, 1], &user, None));
+        webhook.arr_api_key = Some(String::from("api-key"));
+        webhook.hmac_secret = Some(String::from("secret"));
+        let document = ConfigurationExport::from_records(vec![user], vec![webhook], vec![], vec![]);
+
+        // Act
+        let actual = document.without_secrets();
+
+        // Assert
+        assert_eq!(None, actual.webhooks[0].arr_api_key);
+        assert_eq!(None, actual.webhooks[0].hmac_secret);
+        assert_eq!(vec![0, 1], actual.webhooks[0].password);
+    }
+}
@@ -2,35 +2,86 @@
 
 #[macro_use]
 extern crate diesel_migrations;
-#[macro_use]
-extern crate diesel;
-use diesel::r2d2::PooledConnection;
-use diesel::{
-    r2d2::{ConnectionManager, Pool},
-    PgConnection,
-};
+use std::path::Path;
+
+mod postgres_migrations {
+    embed_migrations!("../../migrations");
+    pub(crate) use embedded_migrations::run;
+}
 
-embed_migrations!();
+mod sqlite_migrations {
+    embed_migrations!("../../migrations_sqlite");
+    pub(crate) use embedded_migrations::run;
+}
 
-pub mod actions;
-mod diesel_types;
+#[macro_use]
+mod connection;
 pub mod enums;
+pub mod export;
 pub mod models;
 mod pool_helper;
-mod schema;
-
-pub type DbPool = Pool<ConnectionManager<PgConnection>>;
-pub type DbPoolConnection = PooledConnection<ConnectionManager<PgConnection>>;
+pub mod quiet_hours;
+pub mod repositories;
+mod sqlx_types;
 
-pub use pool_helper::build_pool;
+pub use connection::{AsyncDbPool, DbBackend, DbPool, DbPoolConnection};
+pub use pool_helper::{build_async_pool, build_pool, validate_database_configuration};
 
-/// Given some [DbPoolConnection], run the migrations embedded in Yarrbot on the
-/// database.
+/// Given some [DbPoolConnection], run the migrations embedded in Yarrbot for its
+/// backend on the database.
 ///
 /// # Remarks
 ///
 /// This function takes ownership of the given [DbPoolConnection].
 pub fn migrate(connection: DbPoolConnection) -> Result<(), anyhow::Error> {
-    embedded_migrations::run(&connection)?;
+    match &connection {
+        DbPoolConnection::Postgres(c) => postgres_migrations::run(c)?,
+        DbPoolConnection::Sqlite(c) => sqlite_migrations::run(c)?,
+    }
     Ok(())
 }
+
+/// Revert the most recently run migration, reading the migrations from the given directory since
+/// embedded migrations can only be run. The directory must hold the migrations of the connection's
+/// backend (see [DbBackend::migrations_directory]). Returns the name of the reverted migration.
+pub fn revert_latest_migration(
+    connection: &DbPoolConnection,
+    directory: &Path,
+) -> Result<String, anyhow::Error> {
+    let name = db_run!(connection: {
+        diesel_migrations::revert_latest_migration_in_directory(connection, directory)?
+    });
+    Ok(name)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::connection::SqliteConnectionCustomizer;
+    use diesel::r2d2::{ConnectionManager, Pool};
+
+    /// An in-memory SQLite database only lives as long as its connection, so the pool holds a single connection.
+    fn sqlite_pool() -> DbPool {
+        let manager = ConnectionManager::new(":memory:");
+        let pool = Pool::builder()
+            .max_size(1)
+            .connection_customizer(Box::new(SqliteConnectionCustomizer))
+            .build(manager)
+            .unwrap();
+        DbPool::Sqlite(pool)
+    }
+
+    #[test]
+    fn sqlite_reverts_latest_migration_after_migrating() {
+        // Arrange
+        let pool = sqlite_pool();
+        migrate(pool.get().unwrap()).unwrap();
+        let directory = Path::new(env!("CARGO_MANIFEST_DIR")).join("../../migrations_sqlite");
+
+        // Act
+        let actual = revert_latest_migration(&pool.get().unwrap(), &directory);
+
+        // Assert
+        assert_eq!("20261018220000", actual.unwrap());
+    }
+}
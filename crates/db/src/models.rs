@@ -1,13 +1,13 @@
 //! Defines the models that represent database objects belonging to Yarrbot.
 
 use crate::enums::*;
-use crate::schema::*;
-use diesel::Queryable;
+use crate::quiet_hours::QuietHours;
+use chrono::{DateTime, Utc};
 use uuid::Uuid;
+use yarrbot_common::crypto::hash_parameters;
 
 /// Some chat room user that can manage [Webhook] endpoints for one of the *arr services to push to.
-#[derive(Queryable, Identifiable, Debug, Clone)]
-#[table_name = "users"]
+#[derive(Debug, Clone)]
 pub struct User {
     pub id: Uuid,
 
@@ -19,8 +19,6 @@ pub struct User {
 }
 
 /// Model specifically for creating a new [User].
-#[derive(Insertable)]
-#[table_name = "users"]
 pub struct NewUser {
     id: Uuid,
 
@@ -53,9 +51,7 @@ impl From<NewUser> for User {
 }
 
 /// A definition of a webhook endpoint URL for one of the *arr services to push messages to.
-#[derive(Queryable, Identifiable, Associations, Clone)]
-#[belongs_to(User)]
-#[table_name = "webhooks"]
+#[derive(Clone)]
 pub struct Webhook {
     pub id: Uuid,
 
@@ -70,11 +66,68 @@ pub struct Webhook {
 
     /// A user-friendly name for the server invoking the webhook to be displayed in notifications.
     pub server_name: Option<String>,
+
+    /// The base URL of the *arr invoking the webhook, used to retrieve media covers.
+    pub arr_url: Option<String>,
+
+    /// The API key used to authenticate with the *arr at [Webhook::arr_url].
+    pub arr_api_key: Option<String>,
+
+    /// How file system paths are shown in notifications for this webhook.
+    pub path_policy: PathPolicy,
+
+    /// Path prefixes to replace under [PathPolicy::Replace], each in the form `from=to`.
+    pub path_replacements: Vec<String>,
+
+    /// How requests to this webhook are authenticated.
+    pub auth_mode: WebhookAuthMode,
+
+    /// The digest of the token used by [WebhookAuthMode::UrlToken] and [WebhookAuthMode::HeaderToken].
+    pub auth_token_digest: Option<String>,
+
+    /// The shared secret used to sign requests under [WebhookAuthMode::Hmac].
+    pub hmac_secret: Option<String>,
+
+    /// The Argon2id parameters [Webhook::password] was hashed with; passwords hashed with older parameters are
+    /// re-hashed the next time they are used.
+    pub password_params: String,
+
+    /// The password hash replaced by the last rotation, which is still accepted until
+    /// [Webhook::previous_password_expires_at].
+    pub previous_password: Option<Vec<u8>>,
+
+    /// When [Webhook::previous_password] stops being accepted.
+    pub previous_password_expires_at: Option<DateTime<Utc>>,
+
+    /// Networks in CIDR notation that requests to this webhook must come from. Any source is allowed if empty.
+    pub allowed_sources: Vec<String>,
+
+    /// When the webhook was created; unknown for webhooks created before it was recorded.
+    pub created_at: Option<DateTime<Utc>>,
+
+    /// When the webhook last received an event.
+    pub last_event_at: Option<DateTime<Utc>>,
+
+    /// How many events the webhook has received.
+    pub events_received: i64,
+
+    /// How many Matrix messages for the webhook's events couldn't be sent.
+    pub delivery_failures: i64,
+
+    /// Whether the webhook has been paused. A paused webhook accepts events but doesn't post them to its rooms.
+    pub paused: bool,
+
+    /// When a timed pause ends; [None] if the webhook is paused until it is resumed.
+    pub paused_until: Option<DateTime<Utc>>,
+}
+
+impl Webhook {
+    /// Whether the webhook is paused at the given time; timed pauses end on their own.
+    pub fn is_paused(&self, now: DateTime<Utc>) -> bool {
+        self.paused && self.paused_until.map_or(true, |until| now < until)
+    }
 }
 
-#[derive(Insertable, Associations)]
-#[belongs_to(User)]
-#[table_name = "webhooks"]
 pub struct NewWebhook {
     id: Uuid,
 
@@ -89,6 +142,37 @@ pub struct NewWebhook {
 
     /// A user-friendly name for the server invoking the webhook to be displayed in notifications.
     pub server_name: Option<String>,
+
+    /// The base URL of the *arr invoking the webhook, used to retrieve media covers.
+    pub arr_url: Option<String>,
+
+    /// The API key used to authenticate with the *arr at [Webhook::arr_url].
+    pub arr_api_key: Option<String>,
+
+    /// How file system paths are shown in notifications for this webhook.
+    pub path_policy: PathPolicy,
+
+    /// Path prefixes to replace under [PathPolicy::Replace], each in the form `from=to`.
+    pub path_replacements: Vec<String>,
+
+    /// How requests to this webhook are authenticated.
+    pub auth_mode: WebhookAuthMode,
+
+    /// The digest of the token used by [WebhookAuthMode::UrlToken] and [WebhookAuthMode::HeaderToken].
+    pub auth_token_digest: Option<String>,
+
+    /// The shared secret used to sign requests under [WebhookAuthMode::Hmac].
+    pub hmac_secret: Option<String>,
+
+    /// The Argon2id parameters [Webhook::password] was hashed with; passwords hashed with older parameters are
+    /// re-hashed the next time they are used.
+    pub password_params: String,
+
+    /// Networks in CIDR notation that requests to this webhook must come from. Any source is allowed if empty.
+    pub allowed_sources: Vec<String>,
+
+    /// When the webhook was created.
+    created_at: Option<DateTime<Utc>>,
 }
 
 impl NewWebhook {
@@ -98,12 +182,23 @@ impl NewWebhook {
         user: &User,
         server_name: Option<String>,
     ) -> NewWebhook {
+        let password_params = hash_parameters(&password).unwrap_or_default();
         NewWebhook {
             id: Uuid::new_v4(),
             username: String::from(username),
             password,
             user_id: user.id,
             server_name,
+            arr_url: None,
+            arr_api_key: None,
+            path_policy: PathPolicy::Full,
+            path_replacements: Vec::new(),
+            auth_mode: WebhookAuthMode::Basic,
+            auth_token_digest: None,
+            hmac_secret: None,
+            password_params,
+            allowed_sources: Vec::new(),
+            created_at: Some(Utc::now()),
         }
     }
 }
@@ -116,15 +211,30 @@ impl From<NewWebhook> for Webhook {
             password: webhook.password,
             user_id: webhook.user_id,
             server_name: webhook.server_name,
+            arr_url: webhook.arr_url,
+            arr_api_key: webhook.arr_api_key,
+            path_policy: webhook.path_policy,
+            path_replacements: webhook.path_replacements,
+            auth_mode: webhook.auth_mode,
+            auth_token_digest: webhook.auth_token_digest,
+            hmac_secret: webhook.hmac_secret,
+            password_params: webhook.password_params,
+            previous_password: None,
+            previous_password_expires_at: None,
+            allowed_sources: webhook.allowed_sources,
+            created_at: webhook.created_at,
+            last_event_at: None,
+            events_received: 0,
+            delivery_failures: 0,
+            paused: false,
+            paused_until: None,
         }
     }
 }
 
 /// A definition of a room on Yarrbot's homeserver to post messages received from
 /// a [Webhook] to.
-#[derive(Queryable, Identifiable, Associations, Clone)]
-#[belongs_to(Webhook)]
-#[table_name = "matrix_rooms"]
+#[derive(Clone)]
 pub struct MatrixRoom {
     pub id: Uuid,
 
@@ -133,10 +243,47 @@ pub struct MatrixRoom {
 
     /// The webhook to get message data from for messages posted in this room.
     pub webhook_id: Uuid,
+
+    /// Whether to hide episode titles and other potential spoilers in messages posted in this room.
+    pub hide_spoilers: bool,
+
+    /// When the room's mute ends. Nothing is posted in a muted room.
+    pub muted_until: Option<DateTime<Utc>>,
+
+    /// The local time the room's quiet hours start, as `HH:MM` (see [MatrixRoom::quiet_hours]).
+    pub quiet_hours_start: Option<String>,
+
+    /// The local time the room's quiet hours end, as `HH:MM` (see [MatrixRoom::quiet_hours]).
+    pub quiet_hours_end: Option<String>,
+
+    /// The IANA time zone the room's quiet hours are given in (see [MatrixRoom::quiet_hours]).
+    pub quiet_hours_timezone: Option<String>,
+
+    /// Whether health check failures are still posted in the room during its quiet hours.
+    pub quiet_hours_bypass_health: bool,
+}
+
+impl MatrixRoom {
+    /// Whether the room is muted at the given time.
+    pub fn is_muted(&self, now: DateTime<Utc>) -> bool {
+        self.muted_until.map_or(false, |until| now < until)
+    }
+
+    /// The room's quiet hours, during which messages are held and posted as a summary afterwards, if it has any.
+    pub fn quiet_hours(&self) -> Option<QuietHours> {
+        match (
+            &self.quiet_hours_start,
+            &self.quiet_hours_end,
+            &self.quiet_hours_timezone,
+        ) {
+            (Some(start), Some(end), Some(timezone)) => {
+                QuietHours::from_parts(start, end, timezone).ok()
+            }
+            _ => None,
+        }
+    }
 }
 
-#[derive(Insertable)]
-#[table_name = "matrix_rooms"]
 pub struct NewMatrixRoom {
     id: Uuid,
 
@@ -145,6 +292,9 @@ pub struct NewMatrixRoom {
 
     /// The webhook to get message data from for messages posted in this room.
     pub webhook_id: Uuid,
+
+    /// Whether to hide episode titles and other potential spoilers in messages posted in this room.
+    pub hide_spoilers: bool,
 }
 
 impl NewMatrixRoom {
@@ -153,6 +303,7 @@ impl NewMatrixRoom {
             id: Uuid::new_v4(),
             room_id: String::from(room_id),
             webhook_id: webhook.id,
+            hide_spoilers: false,
         }
     }
 }
@@ -163,6 +314,162 @@ impl From<NewMatrixRoom> for MatrixRoom {
             id: room.id,
             room_id: room.room_id,
             webhook_id: room.webhook_id,
+            hide_spoilers: room.hide_spoilers,
+            muted_until: None,
+            quiet_hours_start: None,
+            quiet_hours_end: None,
+            quiet_hours_timezone: None,
+            quiet_hours_bypass_health: false,
+        }
+    }
+}
+
+/// A message that arrived for a [MatrixRoom] during its quiet hours, held so that it can be posted as part of a
+/// summary once they end.
+#[derive(Debug, Clone)]
+pub struct HeldMessage {
+    pub id: Uuid,
+
+    /// The room the message will be posted in.
+    pub matrix_room_id: Uuid,
+
+    /// When the message arrived.
+    pub received_at: DateTime<Utc>,
+
+    /// A one-line description of the message, shown in the summary.
+    pub summary: String,
+}
+
+pub struct NewHeldMessage {
+    id: Uuid,
+
+    /// The room the message will be posted in.
+    pub matrix_room_id: Uuid,
+
+    /// When the message arrived.
+    pub received_at: DateTime<Utc>,
+
+    /// A one-line description of the message, shown in the summary.
+    pub summary: String,
+}
+
+impl NewHeldMessage {
+    pub fn new(room: &MatrixRoom, summary: &str) -> NewHeldMessage {
+        NewHeldMessage {
+            id: Uuid::new_v4(),
+            matrix_room_id: room.id,
+            received_at: Utc::now(),
+            summary: String::from(summary),
+        }
+    }
+}
+
+impl From<NewHeldMessage> for HeldMessage {
+    fn from(message: NewHeldMessage) -> Self {
+        HeldMessage {
+            id: message.id,
+            matrix_room_id: message.matrix_room_id,
+            received_at: message.received_at,
+            summary: message.summary,
+        }
+    }
+}
+
+/// The permissions on a [Webhook] that can be granted to a [WebhookCollaborator].
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WebhookPermission {
+    /// Change which rooms the webhook posts to and the rooms' settings.
+    ManageRooms,
+
+    /// Change what the webhook's notifications show.
+    ManageFilters,
+
+    /// Replace the webhook's password.
+    RotateCredentials,
+}
+
+/// A [User] granted access to a [Webhook] they don't own. Collaborators can always see the webhook, and may change it
+/// in the ways they've been granted.
+#[derive(Debug, Clone)]
+pub struct WebhookCollaborator {
+    pub id: Uuid,
+
+    /// The webhook the [User] has been granted access to.
+    pub webhook_id: Uuid,
+
+    /// The [User] granted access to the webhook.
+    pub user_id: Uuid,
+
+    /// Whether the [User] has [WebhookPermission::ManageRooms].
+    pub manage_rooms: bool,
+
+    /// Whether the [User] has [WebhookPermission::ManageFilters].
+    pub manage_filters: bool,
+
+    /// Whether the [User] has [WebhookPermission::RotateCredentials].
+    pub rotate_credentials: bool,
+}
+
+impl WebhookCollaborator {
+    pub fn has_permission(&self, permission: WebhookPermission) -> bool {
+        match permission {
+            WebhookPermission::ManageRooms => self.manage_rooms,
+            WebhookPermission::ManageFilters => self.manage_filters,
+            WebhookPermission::RotateCredentials => self.rotate_credentials,
+        }
+    }
+
+    pub fn set_permission(&mut self, permission: WebhookPermission, granted: bool) {
+        match permission {
+            WebhookPermission::ManageRooms => self.manage_rooms = granted,
+            WebhookPermission::ManageFilters => self.manage_filters = granted,
+            WebhookPermission::RotateCredentials => self.rotate_credentials = granted,
+        }
+    }
+}
+
+pub struct NewWebhookCollaborator {
+    id: Uuid,
+
+    /// The webhook the [User] has been granted access to.
+    pub webhook_id: Uuid,
+
+    /// The [User] granted access to the webhook.
+    pub user_id: Uuid,
+
+    /// Whether the [User] has [WebhookPermission::ManageRooms].
+    pub manage_rooms: bool,
+
+    /// Whether the [User] has [WebhookPermission::ManageFilters].
+    pub manage_filters: bool,
+
+    /// Whether the [User] has [WebhookPermission::RotateCredentials].
+    pub rotate_credentials: bool,
+}
+
+impl NewWebhookCollaborator {
+    /// Grant a [User] access to see a [Webhook], without any [WebhookPermission]s.
+    pub fn new(webhook: &Webhook, user: &User) -> NewWebhookCollaborator {
+        NewWebhookCollaborator {
+            id: Uuid::new_v4(),
+            webhook_id: webhook.id,
+            user_id: user.id,
+            manage_rooms: false,
+            manage_filters: false,
+            rotate_credentials: false,
+        }
+    }
+}
+
+impl From<NewWebhookCollaborator> for WebhookCollaborator {
+    fn from(collaborator: NewWebhookCollaborator) -> Self {
+        WebhookCollaborator {
+            id: collaborator.id,
+            webhook_id: collaborator.webhook_id,
+            user_id: collaborator.user_id,
+            manage_rooms: collaborator.manage_rooms,
+            manage_filters: collaborator.manage_filters,
+            rotate_credentials: collaborator.rotate_credentials,
         }
     }
 }
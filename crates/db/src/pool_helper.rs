@@ -1,37 +1,144 @@
-//! Private helper functions to aid in building the [DbPool].
+//! Private helper functions to aid in building the [DbPool] and [AsyncDbPool].
 
-use crate::DbPool;
-use anyhow::{Context, Result};
+use crate::connection::{DbBackend, SqliteConnectionCustomizer, SQLITE_URL_PREFIX};
+use crate::{AsyncDbPool, DbPool};
+use anyhow::{bail, Context, Result};
 use diesel::{
     r2d2::{ConnectionManager, Pool},
-    PgConnection,
+    PgConnection, SqliteConnection,
 };
+use sqlx::pool::PoolOptions;
+use sqlx::postgres::PgConnectOptions;
+use sqlx::sqlite::{SqliteConnectOptions, SqliteJournalMode};
+use std::str::FromStr;
+use std::time::Duration;
 use tracing::info;
 use yarrbot_common::environment::{
     get_env_var,
-    variables::{DB_POOL, DB_URL},
+    variables::{DB_ACQUIRE_TIMEOUT, DB_POOL, DB_URL},
 };
 
 const DB_POOL_DEFAULT: u32 = 20;
+const DB_ACQUIRE_TIMEOUT_DEFAULT: u64 = 5;
+const SQLITE_BUSY_TIMEOUT: Duration = Duration::from_secs(5);
 
-/// Initializes a [DbPool] that manages [DbPoolConnection]s to the sqlite database.
+/// Initializes a [DbPool] that manages [DbPoolConnection]s to the Postgres or SQLite database.
 /// The YARRBOT_DATABASE_URL and YARRBOT_DATABASE_POOL_SIZE environment variables
 /// adjust the various properties of the database to connect to.
 pub fn build_pool() -> Result<DbPool> {
     let database_url = get_database_url().context(format!("{} must be set.", DB_URL))?;
     let pool_size = get_pool_size();
+    let acquire_timeout = get_acquire_timeout();
 
-    let manager = ConnectionManager::<PgConnection>::new(database_url);
-    let pool = Pool::builder()
-        .max_size(pool_size)
-        .build(manager)
+    let pool = match DbBackend::from_url(&database_url) {
+        DbBackend::Postgres => {
+            let manager = ConnectionManager::<PgConnection>::new(database_url);
+            Pool::builder()
+                .max_size(pool_size)
+                .connection_timeout(acquire_timeout)
+                .build(manager)
+                .map(DbPool::Postgres)
+        }
+        DbBackend::Sqlite => {
+            let path = &database_url[SQLITE_URL_PREFIX.len()..];
+            let manager = ConnectionManager::<SqliteConnection>::new(path);
+            Pool::builder()
+                .max_size(pool_size)
+                .connection_timeout(acquire_timeout)
+                .connection_customizer(Box::new(SqliteConnectionCustomizer))
+                .build(manager)
+                .map(DbPool::Sqlite)
+        }
+    };
+    pool.context(format!(
+        "Failed to start the connection pool. Is {} correct?",
+        DB_URL
+    ))
+}
+
+/// Initializes an [AsyncDbPool] to the Postgres or SQLite database, configured by the same environment variables as
+/// [build_pool]. Requests for a connection fail once YARRBOT_DATABASE_ACQUIRE_TIMEOUT seconds pass without one
+/// becoming available.
+pub async fn build_async_pool() -> Result<AsyncDbPool> {
+    let database_url = get_database_url().context(format!("{} must be set.", DB_URL))?;
+    connect_async_pool(&database_url, get_pool_size(), get_acquire_timeout())
+        .await
         .context(format!(
             "Failed to start the connection pool. Is {} correct?",
             DB_URL
-        ))?;
+        ))
+}
+
+pub(crate) async fn connect_async_pool(
+    database_url: &str,
+    pool_size: u32,
+    acquire_timeout: Duration,
+) -> Result<AsyncDbPool> {
+    // sqlx's connect timeout bounds the whole wait for a connection, including waiting on a busy pool.
+    let pool = match DbBackend::from_url(database_url) {
+        DbBackend::Postgres => {
+            let options = PgConnectOptions::from_str(database_url)?;
+            PoolOptions::new()
+                .max_connections(pool_size)
+                .connect_timeout(acquire_timeout)
+                .connect_with(options)
+                .await
+                .map(|pool| AsyncDbPool::Postgres {
+                    pool,
+                    max_connections: pool_size,
+                })?
+        }
+        DbBackend::Sqlite => {
+            // The same settings as SqliteConnectionCustomizer applies to the blocking pool.
+            let options = SqliteConnectOptions::new()
+                .filename(&database_url[SQLITE_URL_PREFIX.len()..])
+                .create_if_missing(true)
+                .foreign_keys(true)
+                .journal_mode(SqliteJournalMode::Wal)
+                .busy_timeout(SQLITE_BUSY_TIMEOUT);
+            PoolOptions::new()
+                .max_connections(pool_size)
+                .connect_timeout(acquire_timeout)
+                .connect_with(options)
+                .await
+                .map(|pool| AsyncDbPool::Sqlite {
+                    pool,
+                    max_connections: pool_size,
+                })?
+        }
+    };
     Ok(pool)
 }
 
+/// Check that the database settings in the environment are present and valid without connecting to the database.
+pub fn validate_database_configuration() -> Result<()> {
+    let database_url = get_database_url().context(format!("{} must be set.", DB_URL))?;
+    if database_url == SQLITE_URL_PREFIX {
+        bail!(
+            "{} must include the path to the SQLite database after \"{}\".",
+            DB_URL,
+            SQLITE_URL_PREFIX
+        );
+    }
+    if let Ok(size) = get_env_var(DB_POOL) {
+        match size.parse::<u32>() {
+            Ok(s) if s > 0 => (),
+            _ => bail!("{} must be a positive number, not \"{}\".", DB_POOL, size),
+        }
+    }
+    if let Ok(timeout) = get_env_var(DB_ACQUIRE_TIMEOUT) {
+        match timeout.parse::<u64>() {
+            Ok(t) if t > 0 => (),
+            _ => bail!(
+                "{} must be a positive number of seconds, not \"{}\".",
+                DB_ACQUIRE_TIMEOUT,
+                timeout
+            ),
+        }
+    }
+    Ok(())
+}
+
 fn get_database_url() -> Result<String> {
     get_env_var(DB_URL)
 }
@@ -45,3 +152,13 @@ fn get_pool_size() -> u32 {
 
     size
 }
+
+fn get_acquire_timeout() -> Duration {
+    let seconds = match get_env_var(DB_ACQUIRE_TIMEOUT) {
+        Ok(seconds) => seconds.parse().unwrap_or(DB_ACQUIRE_TIMEOUT_DEFAULT),
+        Err(_) => DB_ACQUIRE_TIMEOUT_DEFAULT,
+    };
+    info!("Using connection acquire timeout of {} seconds", seconds);
+
+    Duration::from_secs(seconds)
+}
@@ -0,0 +1,123 @@
+//! A room's quiet hours: a daily window, in the room's time zone, during which notifications are held rather than
+//! posted, so that they don't wake anyone up.
+
+use anyhow::{anyhow, bail, Context, Result};
+use chrono::{DateTime, NaiveTime, Utc};
+use chrono_tz::Tz;
+use std::fmt::{Display, Formatter};
+use std::str::FromStr;
+
+const TIME_FORMAT: &str = "%H:%M";
+
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct QuietHours {
+    pub start: NaiveTime,
+    pub end: NaiveTime,
+    pub timezone: Tz,
+}
+
+impl QuietHours {
+    /// Parse a range of times such as `22:00-07:00` and an IANA time zone name such as `Europe/Berlin`.
+    pub fn parse(range: &str, timezone: &str) -> Result<Self> {
+        let (start, end) = range
+            .split_once('-')
+            .context("Quiet hours must be given as a range of times, e.g. 22:00-07:00.")?;
+        QuietHours::from_parts(start, end, timezone)
+    }
+
+    /// Build quiet hours from their start and end times and time zone, as stored for a room.
+    pub fn from_parts(start: &str, end: &str, timezone: &str) -> Result<Self> {
+        let start = parse_time(start)?;
+        let end = parse_time(end)?;
+        if start == end {
+            bail!("Quiet hours must end at a different time than they start.");
+        }
+        let timezone = Tz::from_str(timezone.trim()).map_err(|_| {
+            anyhow!(
+                "\"{}\" is not a known time zone; use a name such as Europe/Berlin.",
+                timezone
+            )
+        })?;
+        Ok(QuietHours {
+            start,
+            end,
+            timezone,
+        })
+    }
+
+    /// The start and end times and time zone, in the form they're stored for a room.
+    pub fn to_parts(&self) -> (String, String, String) {
+        (
+            self.start.format(TIME_FORMAT).to_string(),
+            self.end.format(TIME_FORMAT).to_string(),
+            String::from(self.timezone.name()),
+        )
+    }
+
+    /// Whether the given time falls within the quiet hours. Quiet hours that end earlier in the day than they start
+    /// run overnight.
+    pub fn contains(&self, time: DateTime<Utc>) -> bool {
+        let local = time.with_timezone(&self.timezone).time();
+        if self.start < self.end {
+            self.start <= local && local < self.end
+        } else {
+            self.start <= local || local < self.end
+        }
+    }
+}
+
+impl Display for QuietHours {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        let (start, end, timezone) = self.to_parts();
+        write!(f, "{}-{} {}", start, end, timezone)
+    }
+}
+
+fn parse_time(raw: &str) -> Result<NaiveTime> {
+    NaiveTime::parse_from_str(raw.trim(), TIME_FORMAT)
+        .with_context(|| format!("\"{}\" is not a time such as 07:30.", raw))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn contains_handles_quiet_hours_running_overnight() {
+        // Arrange
+        let quiet_hours = QuietHours::parse("22:00-07:00", "Europe/Berlin").unwrap();
+
+        // Act
+        // Berlin is two hours ahead of UTC in summer.
+        let late = quiet_hours.contains(Utc.ymd(2026, 7, 1).and_hms(21, 30, 0));
+        let early = quiet_hours.contains(Utc.ymd(2026, 7, 1).and_hms(4, 59, 0));
+        let morning = quiet_hours.contains(Utc.ymd(2026, 7, 1).and_hms(5, 0, 0));
+
+        // Assert
+        assert!(late);
+        assert!(early);
+        assert!(!morning);
+    }
+
+    #[test]
+    fn parse_returns_error_given_unknown_time_zone() {
+        // Act
+        let actual = QuietHours::parse("22:00-07:00", "Mars/Olympus_Mons");
+
+        // Assert
+        assert!(actual.is_err());
+    }
+
+    #[test]
+    fn display_round_trips_through_parse() {
+        // Arrange
+        let quiet_hours = QuietHours::parse("23:15-06:45", "America/New_York").unwrap();
+
+        // Act
+        let actual = quiet_hours.to_string();
+
+        // Assert
+        assert_eq!("23:15-06:45 America/New_York", actual);
+    }
+}
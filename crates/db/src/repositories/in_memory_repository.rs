@@ -0,0 +1,931 @@
+//! Repositories that keep their records in memory, enforcing the same constraints as the database: unique usernames,
+//! records referring to existing owners, and deletes cascading to the records that belong to the deleted one.
+
+use super::{
+    grace_period_end, CollaboratorRepository, ConfigurationRepository, HeldMessageRepository,
+    MatrixRoomRepository, UserRepository, WebhookRepository,
+};
+use crate::enums::{PathPolicy, UserRole, WebhookAuthMode};
+use crate::export::{
+    match_user, resolve, Action, ConfigurationExport, ConflictPolicy, ImportReport,
+};
+use crate::models::{
+    HeldMessage, MatrixRoom, NewHeldMessage, NewMatrixRoom, NewUser, NewWebhook,
+    NewWebhookCollaborator, User, Webhook, WebhookCollaborator,
+};
+use crate::quiet_hours::QuietHours;
+use anyhow::{bail, Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock, RwLockReadGuard, RwLockWriteGuard};
+use std::time::Duration;
+use uuid::Uuid;
+
+#[derive(Clone, Default)]
+struct Records {
+    users: Vec<User>,
+    webhooks: Vec<Webhook>,
+    rooms: Vec<MatrixRoom>,
+    collaborators: Vec<WebhookCollaborator>,
+    held_messages: Vec<HeldMessage>,
+}
+
+impl Records {
+    /// Keep only the rooms matching the predicate, deleting the messages held for the others.
+    fn retain_rooms<F>(&mut self, keep: F)
+    where
+        F: Fn(&MatrixRoom) -> bool,
+    {
+        let deleted: Vec<Uuid> = self
+            .rooms
+            .iter()
+            .filter(|r| !keep(r))
+            .map(|r| r.id)
+            .collect();
+        self.held_messages
+            .retain(|m| !deleted.contains(&m.matrix_room_id));
+        self.rooms.retain(keep);
+    }
+
+    /// Whether the user with the given ID is the only System Administrator.
+    fn is_last_system_administrator(&self, identifier: &Uuid) -> bool {
+        let system_administrators: Vec<&User> = self
+            .users
+            .iter()
+            .filter(|u| matches!(u.user_role, UserRole::SystemAdministrator))
+            .collect();
+        system_administrators.len() == 1 && system_administrators[0].id == *identifier
+    }
+
+    fn webhook_mut(&mut self, identifier: &Uuid) -> Result<&mut Webhook> {
+        self.webhooks
+            .iter_mut()
+            .find(|w| w.id == *identifier)
+            .context("The webhook does not exist.")
+    }
+}
+
+#[derive(Clone, Default)]
+pub struct InMemoryRepository {
+    records: Arc<RwLock<Records>>,
+}
+
+impl InMemoryRepository {
+    fn read(&self) -> RwLockReadGuard<'_, Records> {
+        self.records
+            .read()
+            .expect("In-memory repository lock is poisoned.")
+    }
+
+    fn write(&self) -> RwLockWriteGuard<'_, Records> {
+        self.records
+            .write()
+            .expect("In-memory repository lock is poisoned.")
+    }
+
+    /// Apply a change to a webhook and return the updated webhook.
+    fn update_webhook<F>(&self, webhook: &Webhook, change: F) -> Result<Webhook>
+    where
+        F: FnOnce(&mut Webhook),
+    {
+        let mut records = self.write();
+        let stored = records.webhook_mut(&webhook.id)?;
+        change(stored);
+        Ok(stored.clone())
+    }
+}
+
+#[async_trait]
+impl UserRepository for InMemoryRepository {
+    async fn create_user(&self, new_user: NewUser) -> Result<User> {
+        let user = User::from(new_user);
+        let mut records = self.write();
+        if records
+            .users
+            .iter()
+            .any(|u| u.service_username == user.service_username)
+        {
+            bail!("{} is already a user.", user.service_username);
+        }
+        records.users.push(user.clone());
+        Ok(user)
+    }
+
+    async fn try_get(&self, identifier: &Uuid) -> Result<Option<User>> {
+        Ok(self
+            .read()
+            .users
+            .iter()
+            .find(|u| u.id == *identifier)
+            .cloned())
+    }
+
+    async fn try_get_by_username(&self, username: &str) -> Result<Option<User>> {
+        Ok(self
+            .read()
+            .users
+            .iter()
+            .find(|u| u.service_username == username)
+            .cloned())
+    }
+
+    async fn get_all(&self) -> Result<Vec<User>> {
+        let mut users = self.read().users.clone();
+        users.sort_by(|a, b| a.service_username.cmp(&b.service_username));
+        Ok(users)
+    }
+
+    async fn update_role(&self, user: &User, role: UserRole) -> Result<Option<User>> {
+        let mut records = self.write();
+        if !matches!(role, UserRole::SystemAdministrator)
+            && records.is_last_system_administrator(&user.id)
+        {
+            return Ok(None);
+        }
+        let stored = records
+            .users
+            .iter_mut()
+            .find(|u| u.id == user.id)
+            .context("The user does not exist.")?;
+        stored.user_role = role;
+        Ok(Some(stored.clone()))
+    }
+
+    async fn delete(&self, user: &User) -> Result<bool> {
+        let mut records = self.write();
+        if records.is_last_system_administrator(&user.id) {
+            return Ok(false);
+        }
+        let webhook_ids: Vec<Uuid> = records
+            .webhooks
+            .iter()
+            .filter(|w| w.user_id == user.id)
+            .map(|w| w.id)
+            .collect();
+        records.retain_rooms(|r| !webhook_ids.contains(&r.webhook_id));
+        records
+            .collaborators
+            .retain(|c| c.user_id != user.id && !webhook_ids.contains(&c.webhook_id));
+        records.webhooks.retain(|w| w.user_id != user.id);
+        records.users.retain(|u| u.id != user.id);
+        Ok(true)
+    }
+
+    async fn any(&self) -> Result<bool> {
+        Ok(!self.read().users.is_empty())
+    }
+}
+
+#[async_trait]
+impl WebhookRepository for InMemoryRepository {
+    async fn create_webhook(&self, new_webhook: NewWebhook) -> Result<Webhook> {
+        let webhook = Webhook::from(new_webhook);
+        let mut records = self.write();
+        if !records.users.iter().any(|u| u.id == webhook.user_id) {
+            bail!("The webhook's owner does not exist.");
+        }
+        records.webhooks.push(webhook.clone());
+        Ok(webhook)
+    }
+
+    async fn try_get(&self, identifier: &Uuid) -> Result<Option<Webhook>> {
+        Ok(self
+            .read()
+            .webhooks
+            .iter()
+            .find(|w| w.id == *identifier)
+            .cloned())
+    }
+
+    async fn delete(&self, webhook: &Webhook) -> Result<()> {
+        let mut records = self.write();
+        records.retain_rooms(|r| r.webhook_id != webhook.id);
+        records.collaborators.retain(|c| c.webhook_id != webhook.id);
+        records.webhooks.retain(|w| w.id != webhook.id);
+        Ok(())
+    }
+
+    async fn get_all(&self) -> Result<Vec<Webhook>> {
+        Ok(self.read().webhooks.clone())
+    }
+
+    async fn get_all_by_user_id(&self, user_id: &Uuid) -> Result<Vec<Webhook>> {
+        Ok(self
+            .read()
+            .webhooks
+            .iter()
+            .filter(|w| w.user_id == *user_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn update_arr_connection(
+        &self,
+        webhook: &Webhook,
+        url: Option<String>,
+        api_key: Option<String>,
+    ) -> Result<Webhook> {
+        self.update_webhook(webhook, |w| {
+            w.arr_url = url;
+            w.arr_api_key = api_key;
+        })
+    }
+
+    async fn update_path_policy(
+        &self,
+        webhook: &Webhook,
+        policy: PathPolicy,
+        replacements: Vec<String>,
+    ) -> Result<Webhook> {
+        self.update_webhook(webhook, |w| {
+            w.path_policy = policy;
+            w.path_replacements = replacements;
+        })
+    }
+
+    async fn update_auth_mode(
+        &self,
+        webhook: &Webhook,
+        mode: WebhookAuthMode,
+        token_digest: Option<String>,
+        secret: Option<String>,
+    ) -> Result<Webhook> {
+        self.update_webhook(webhook, |w| {
+            w.auth_mode = mode;
+            w.auth_token_digest = token_digest;
+            w.hmac_secret = secret;
+        })
+    }
+
+    async fn update_allowed_sources(
+        &self,
+        webhook: &Webhook,
+        sources: Vec<String>,
+    ) -> Result<Webhook> {
+        self.update_webhook(webhook, |w| w.allowed_sources = sources)
+    }
+
+    async fn rotate_password(
+        &self,
+        webhook: &Webhook,
+        new_password: Vec<u8>,
+        new_params: String,
+        grace_period: Option<Duration>,
+    ) -> Result<Webhook> {
+        let grace_until = grace_period_end(grace_period);
+        let previous = grace_until.map(|_| webhook.password.clone());
+        self.update_webhook(webhook, |w| {
+            w.password = new_password;
+            w.password_params = new_params;
+            w.previous_password = previous;
+            w.previous_password_expires_at = grace_until;
+        })
+    }
+
+    async fn rehash_password(
+        &self,
+        webhook: &Webhook,
+        new_password: Vec<u8>,
+        new_params: String,
+    ) -> Result<usize> {
+        let mut records = self.write();
+        match records.webhook_mut(&webhook.id) {
+            Ok(w) if w.password == webhook.password => {
+                w.password = new_password;
+                w.password_params = new_params;
+                Ok(1)
+            }
+            _ => Ok(0),
+        }
+    }
+
+    async fn transfer_ownership(&self, webhook: &Webhook, new_owner: &User) -> Result<Webhook> {
+        let mut records = self.write();
+        if !records.users.iter().any(|u| u.id == new_owner.id) {
+            bail!("The webhook's new owner does not exist.");
+        }
+        let stored = records.webhook_mut(&webhook.id)?;
+        stored.user_id = new_owner.id;
+        Ok(stored.clone())
+    }
+
+    async fn update_details(
+        &self,
+        webhook: &Webhook,
+        username: String,
+        server_name: Option<String>,
+    ) -> Result<Webhook> {
+        self.update_webhook(webhook, |w| {
+            w.username = username;
+            w.server_name = server_name;
+        })
+    }
+
+    async fn record_event(&self, webhook_id: &Uuid, failed_deliveries: i64) -> Result<()> {
+        let mut records = self.write();
+        let stored = records.webhook_mut(webhook_id)?;
+        stored.last_event_at = Some(Utc::now());
+        stored.events_received += 1;
+        stored.delivery_failures += failed_deliveries;
+        Ok(())
+    }
+
+    async fn record_delivery_failure(&self, webhook_id: &Uuid) -> Result<()> {
+        let mut records = self.write();
+        records.webhook_mut(webhook_id)?.delivery_failures += 1;
+        Ok(())
+    }
+
+    async fn update_paused(
+        &self,
+        webhook: &Webhook,
+        paused: bool,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Webhook> {
+        self.update_webhook(webhook, |w| {
+            w.paused = paused;
+            w.paused_until = until;
+        })
+    }
+}
+
+#[async_trait]
+impl MatrixRoomRepository for InMemoryRepository {
+    async fn create_room(&self, new_room: NewMatrixRoom) -> Result<MatrixRoom> {
+        let room = MatrixRoom::from(new_room);
+        let mut records = self.write();
+        if !records.webhooks.iter().any(|w| w.id == room.webhook_id) {
+            bail!("The room's webhook does not exist.");
+        }
+        records.rooms.push(room.clone());
+        Ok(room)
+    }
+
+    async fn get_many(&self, limit: Option<u8>) -> Result<Vec<MatrixRoom>> {
+        let limit = limit.unwrap_or(10) as usize;
+        Ok(self.read().rooms.iter().take(limit).cloned().collect())
+    }
+
+    async fn get_all(&self) -> Result<Vec<MatrixRoom>> {
+        Ok(self.read().rooms.clone())
+    }
+
+    async fn get_by_webhook_id(&self, webhook_id: &Uuid) -> Result<Vec<MatrixRoom>> {
+        Ok(self
+            .read()
+            .rooms
+            .iter()
+            .filter(|r| r.webhook_id == *webhook_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_by_room_id(&self, room_id: &str) -> Result<Vec<MatrixRoom>> {
+        Ok(self
+            .read()
+            .rooms
+            .iter()
+            .filter(|r| r.room_id == room_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn update_hide_spoilers(
+        &self,
+        webhook_id: &Uuid,
+        room_id: Option<&str>,
+        value: bool,
+    ) -> Result<usize> {
+        let mut records = self.write();
+        let mut updated = 0;
+        for room in records
+            .rooms
+            .iter_mut()
+            .filter(|r| r.webhook_id == *webhook_id && room_id.map_or(true, |id| r.room_id == id))
+        {
+            room.hide_spoilers = value;
+            updated += 1;
+        }
+        Ok(updated)
+    }
+
+    async fn update_muted_until(
+        &self,
+        webhook_id: &Uuid,
+        room_id: Option<&str>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<usize> {
+        let mut records = self.write();
+        let mut updated = 0;
+        for room in records
+            .rooms
+            .iter_mut()
+            .filter(|r| r.webhook_id == *webhook_id && room_id.map_or(true, |id| r.room_id == id))
+        {
+            room.muted_until = until;
+            updated += 1;
+        }
+        Ok(updated)
+    }
+
+    async fn update_quiet_hours(
+        &self,
+        webhook_id: &Uuid,
+        room_id: Option<&str>,
+        quiet_hours: Option<&QuietHours>,
+        bypass_health: bool,
+    ) -> Result<usize> {
+        let parts = quiet_hours.map(QuietHours::to_parts);
+        let mut records = self.write();
+        let mut updated = 0;
+        for room in records
+            .rooms
+            .iter_mut()
+            .filter(|r| r.webhook_id == *webhook_id && room_id.map_or(true, |id| r.room_id == id))
+        {
+            room.quiet_hours_start = parts.as_ref().map(|(start, _, _)| start.clone());
+            room.quiet_hours_end = parts.as_ref().map(|(_, end, _)| end.clone());
+            room.quiet_hours_timezone = parts.as_ref().map(|(_, _, timezone)| timezone.clone());
+            room.quiet_hours_bypass_health = bypass_health;
+            updated += 1;
+        }
+        Ok(updated)
+    }
+
+    async fn delete(&self, room: &MatrixRoom) -> Result<()> {
+        self.write().retain_rooms(|r| r.id != room.id);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CollaboratorRepository for InMemoryRepository {
+    async fn create_collaborator(
+        &self,
+        new_collaborator: NewWebhookCollaborator,
+    ) -> Result<WebhookCollaborator> {
+        let collaborator = WebhookCollaborator::from(new_collaborator);
+        let mut records = self.write();
+        if !records
+            .webhooks
+            .iter()
+            .any(|w| w.id == collaborator.webhook_id)
+            || !records.users.iter().any(|u| u.id == collaborator.user_id)
+        {
+            bail!("The collaborator's webhook or user does not exist.");
+        }
+        if records
+            .collaborators
+            .iter()
+            .any(|c| c.webhook_id == collaborator.webhook_id && c.user_id == collaborator.user_id)
+        {
+            bail!("The user has already been granted access to the webhook.");
+        }
+        records.collaborators.push(collaborator.clone());
+        Ok(collaborator)
+    }
+
+    async fn try_get(
+        &self,
+        webhook_id: &Uuid,
+        user_id: &Uuid,
+    ) -> Result<Option<WebhookCollaborator>> {
+        Ok(self
+            .read()
+            .collaborators
+            .iter()
+            .find(|c| c.webhook_id == *webhook_id && c.user_id == *user_id)
+            .cloned())
+    }
+
+    async fn get_by_webhook_id(&self, webhook_id: &Uuid) -> Result<Vec<WebhookCollaborator>> {
+        Ok(self
+            .read()
+            .collaborators
+            .iter()
+            .filter(|c| c.webhook_id == *webhook_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_by_user_id(&self, user_id: &Uuid) -> Result<Vec<WebhookCollaborator>> {
+        Ok(self
+            .read()
+            .collaborators
+            .iter()
+            .filter(|c| c.user_id == *user_id)
+            .cloned()
+            .collect())
+    }
+
+    async fn get_all(&self) -> Result<Vec<WebhookCollaborator>> {
+        Ok(self.read().collaborators.clone())
+    }
+
+    async fn update_permissions(
+        &self,
+        collaborator: &WebhookCollaborator,
+    ) -> Result<WebhookCollaborator> {
+        let mut records = self.write();
+        let stored = records
+            .collaborators
+            .iter_mut()
+            .find(|c| c.id == collaborator.id)
+            .context("The collaborator does not exist.")?;
+        stored.manage_rooms = collaborator.manage_rooms;
+        stored.manage_filters = collaborator.manage_filters;
+        stored.rotate_credentials = collaborator.rotate_credentials;
+        Ok(stored.clone())
+    }
+
+    async fn delete(&self, collaborator: &WebhookCollaborator) -> Result<()> {
+        self.write()
+            .collaborators
+            .retain(|c| c.id != collaborator.id);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl HeldMessageRepository for InMemoryRepository {
+    async fn create_held_message(&self, new_message: NewHeldMessage) -> Result<HeldMessage> {
+        let message = HeldMessage::from(new_message);
+        let mut records = self.write();
+        if !records.rooms.iter().any(|r| r.id == message.matrix_room_id) {
+            bail!("The held message's room does not exist.");
+        }
+        records.held_messages.push(message.clone());
+        Ok(message)
+    }
+
+    async fn get_all(&self) -> Result<Vec<HeldMessage>> {
+        let mut messages = self.read().held_messages.clone();
+        messages.sort_by_key(|m| m.received_at);
+        Ok(messages)
+    }
+
+    async fn delete(&self, message: &HeldMessage) -> Result<()> {
+        self.write().held_messages.retain(|m| m.id != message.id);
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ConfigurationRepository for InMemoryRepository {
+    async fn import(
+        &self,
+        document: ConfigurationExport,
+        policy: ConflictPolicy,
+        dry_run: bool,
+    ) -> Result<ImportReport> {
+        let mut records = self.write();
+        // The import works on a copy so that a failed import, or a dry run, leaves the records as they were.
+        let mut imported = records.clone();
+        let mut report = ImportReport::default();
+        // Imported users matched to an existing user by username take on the existing user's ID.
+        let mut user_ids: HashMap<Uuid, Uuid> = HashMap::new();
+        for user in document.users {
+            let by_id = imported
+                .users
+                .iter()
+                .find(|u| u.id == user.id)
+                .map(|u| u.id);
+            let by_username = imported
+                .users
+                .iter()
+                .find(|u| u.service_username == user.service_username)
+                .map(|u| u.id);
+            let existing = match_user(&user, by_id, by_username)?;
+            let action = resolve(policy, existing.is_some(), "user", &user.service_username)?;
+            if let Some(existing_id) = existing {
+                user_ids.insert(user.id, existing_id);
+            }
+            match (action, existing) {
+                (Action::Create, _) => imported.users.push(User::from(user)),
+                (Action::Overwrite, Some(existing_id)) => {
+                    if let Some(stored) = imported.users.iter_mut().find(|u| u.id == existing_id) {
+                        stored.service_username = user.service_username;
+                        stored.user_role = user.user_role;
+                    }
+                }
+                _ => (),
+            }
+            report.users.record(action);
+        }
+
+        let mut skipped_webhooks = Vec::new();
+        for mut webhook in document.webhooks {
+            if let Some(id) = user_ids.get(&webhook.user_id) {
+                webhook.user_id = *id;
+            }
+            let exists = imported.webhooks.iter().any(|w| w.id == webhook.id);
+            let action = resolve(policy, exists, "webhook", &webhook.id.to_string())?;
+            if action == Action::Skip {
+                skipped_webhooks.push(webhook.id);
+            }
+            if action != Action::Skip {
+                if !imported.users.iter().any(|u| u.id == webhook.user_id) {
+                    bail!("The owner of webhook {} does not exist.", webhook.id);
+                }
+                let webhook = Webhook::from(webhook);
+                match imported.webhooks.iter_mut().find(|w| w.id == webhook.id) {
+                    Some(stored) => *stored = webhook,
+                    None => imported.webhooks.push(webhook),
+                }
+            }
+            report.webhooks.record(action);
+        }
+
+        for room in document.rooms {
+            let exists = imported.rooms.iter().any(|r| r.id == room.id);
+            // Rooms follow the webhook they belong to when it is skipped.
+            let action = if skipped_webhooks.contains(&room.webhook_id) {
+                Action::Skip
+            } else {
+                resolve(policy, exists, "room", &room.room_id)?
+            };
+            if action != Action::Skip {
+                if !imported.webhooks.iter().any(|w| w.id == room.webhook_id) {
+                    bail!("The webhook of room {} does not exist.", room.room_id);
+                }
+                let room = MatrixRoom::from(room);
+                match imported.rooms.iter_mut().find(|r| r.id == room.id) {
+                    Some(stored) => *stored = room,
+                    None => imported.rooms.push(room),
+                }
+            }
+            report.rooms.record(action);
+        }
+
+        for mut collaborator in document.collaborators {
+            if let Some(id) = user_ids.get(&collaborator.user_id) {
+                collaborator.user_id = *id;
+            }
+            // A user can only be granted access to a webhook once, whatever the grant's ID.
+            let existing = imported
+                .collaborators
+                .iter()
+                .find(|c| {
+                    c.id == collaborator.id
+                        || (c.webhook_id == collaborator.webhook_id
+                            && c.user_id == collaborator.user_id)
+                })
+                .map(|c| c.id);
+            // Grants follow the webhook they belong to when it is skipped.
+            let action = if skipped_webhooks.contains(&collaborator.webhook_id) {
+                Action::Skip
+            } else {
+                resolve(
+                    policy,
+                    existing.is_some(),
+                    "collaborator",
+                    &collaborator.id.to_string(),
+                )?
+            };
+            match (action, existing) {
+                (Action::Create, _) => {
+                    if !imported
+                        .webhooks
+                        .iter()
+                        .any(|w| w.id == collaborator.webhook_id)
+                        || !imported.users.iter().any(|u| u.id == collaborator.user_id)
+                    {
+                        bail!(
+                            "The webhook or user of collaborator {} does not exist.",
+                            collaborator.id
+                        );
+                    }
+                    imported
+                        .collaborators
+                        .push(WebhookCollaborator::from(collaborator));
+                }
+                (Action::Overwrite, Some(existing_id)) => {
+                    if let Some(stored) = imported
+                        .collaborators
+                        .iter_mut()
+                        .find(|c| c.id == existing_id)
+                    {
+                        stored.manage_rooms = collaborator.manage_rooms;
+                        stored.manage_filters = collaborator.manage_filters;
+                        stored.rotate_credentials = collaborator.rotate_credentials;
+                    }
+                }
+                _ => (),
+            }
+            report.collaborators.record(action);
+        }
+
+        if !dry_run {
+            *records = imported;
+        }
+        Ok(report)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn create_user_returns_error_given_duplicate_username() {
+        // Arrange
+        let repository = InMemoryRepository::default();
+        repository
+            .create_user(NewUser::new("@a:example.org", None))
+            .await
+            .unwrap();
+
+        // Act
+        let actual = repository
+            .create_user(NewUser::new("@a:example.org", None))
+            .await;
+
+        // Assert
+        assert!(actual.is_err());
+    }
+
+    #[tokio::test]
+    async fn delete_user_deletes_their_webhooks_and_rooms() {
+        // Arrange
+        let repository = InMemoryRepository::default();
+        let user = repository
+            .create_user(NewUser::new("@a:example.org", None))
+            .await
+            .unwrap();
+        let webhook = repository
+            .create_webhook(NewWebhook::new("user", vec![0], &user, None))
+            .await
+            .unwrap();
+        let room = repository
+            .create_room(NewMatrixRoom::new("!room:example.org", &webhook))
+            .await
+            .unwrap();
+        repository
+            .create_held_message(NewHeldMessage::new(&room, "Grabbed: Some Show"))
+            .await
+            .unwrap();
+
+        // Act
+        UserRepository::delete(&repository, &user).await.unwrap();
+
+        // Assert
+        assert!(WebhookRepository::get_all(&repository)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(MatrixRoomRepository::get_all(&repository)
+            .await
+            .unwrap()
+            .is_empty());
+        assert!(HeldMessageRepository::get_all(&repository)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn rehash_password_does_nothing_given_changed_password() {
+        // Arrange
+        let repository = InMemoryRepository::default();
+        let user = repository
+            .create_user(NewUser::new("@a:example.org", None))
+            .await
+            .unwrap();
+        let webhook = repository
+            .create_webhook(NewWebhook::new("user", vec![0], &user, None))
+            .await
+            .unwrap();
+        repository
+            .rotate_password(&webhook, vec![1], String::new(), None)
+            .await
+            .unwrap();
+
+        // Act
+        let actual = repository
+            .rehash_password(&webhook, vec![2], String::new())
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(0, actual);
+    }
+
+    #[tokio::test]
+    async fn create_collaborator_returns_error_given_existing_grant() {
+        // Arrange
+        let repository = InMemoryRepository::default();
+        let owner = repository
+            .create_user(NewUser::new("@owner:example.org", None))
+            .await
+            .unwrap();
+        let viewer = repository
+            .create_user(NewUser::new("@viewer:example.org", Some(UserRole::Viewer)))
+            .await
+            .unwrap();
+        let webhook = repository
+            .create_webhook(NewWebhook::new("user", vec![0], &owner, None))
+            .await
+            .unwrap();
+        repository
+            .create_collaborator(NewWebhookCollaborator::new(&webhook, &viewer))
+            .await
+            .unwrap();
+
+        // Act
+        let actual = repository
+            .create_collaborator(NewWebhookCollaborator::new(&webhook, &viewer))
+            .await;
+
+        // Assert
+        assert!(actual.is_err());
+    }
+
+    #[tokio::test]
+    async fn delete_user_keeps_last_system_administrator() {
+        // Arrange
+        let repository = InMemoryRepository::default();
+        let sysadmin = repository
+            .create_user(NewUser::new(
+                "@a:example.org",
+                Some(UserRole::SystemAdministrator),
+            ))
+            .await
+            .unwrap();
+        let admin = repository
+            .create_user(NewUser::new("@b:example.org", None))
+            .await
+            .unwrap();
+
+        // Act
+        let sysadmin_deleted = UserRepository::delete(&repository, &sysadmin)
+            .await
+            .unwrap();
+        let admin_deleted = UserRepository::delete(&repository, &admin).await.unwrap();
+
+        // Assert
+        assert!(!sysadmin_deleted);
+        assert!(admin_deleted);
+        assert_eq!(1, UserRepository::get_all(&repository).await.unwrap().len());
+    }
+
+    #[tokio::test]
+    async fn update_role_demotes_either_system_administrator_given_two() {
+        // Arrange
+        let repository = InMemoryRepository::default();
+        let first = repository
+            .create_user(NewUser::new(
+                "@a:example.org",
+                Some(UserRole::SystemAdministrator),
+            ))
+            .await
+            .unwrap();
+        let second = repository
+            .create_user(NewUser::new(
+                "@b:example.org",
+                Some(UserRole::SystemAdministrator),
+            ))
+            .await
+            .unwrap();
+
+        // Act
+        let second_demoted = repository
+            .update_role(&second, UserRole::Administrator)
+            .await
+            .unwrap();
+        let first_demoted = repository
+            .update_role(&first, UserRole::Administrator)
+            .await
+            .unwrap();
+
+        // Assert
+        assert!(second_demoted.is_some());
+        assert!(first_demoted.is_none());
+    }
+
+    #[tokio::test]
+    async fn delete_user_deletes_later_system_administrator_given_two() {
+        // Arrange
+        let repository = InMemoryRepository::default();
+        repository
+            .create_user(NewUser::new(
+                "@a:example.org",
+                Some(UserRole::SystemAdministrator),
+            ))
+            .await
+            .unwrap();
+        let second = repository
+            .create_user(NewUser::new(
+                "@b:example.org",
+                Some(UserRole::SystemAdministrator),
+            ))
+            .await
+            .unwrap();
+
+        // Act
+        let actual = UserRepository::delete(&repository, &second).await.unwrap();
+
+        // Assert
+        assert!(actual);
+    }
+}
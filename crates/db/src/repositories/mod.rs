@@ -0,0 +1,306 @@
+//! Storage-agnostic, asynchronous access to Yarrbot's data. The rest of Yarrbot is handed [Repositories] rather than
+//! a [crate::AsyncDbPool], so that handlers can be tested against [Repositories::in_memory] instead of a live database.
+
+mod in_memory_repository;
+mod sqlx_repository;
+
+use crate::enums::{PathPolicy, UserRole, WebhookAuthMode};
+use crate::export::{ConfigurationExport, ConflictPolicy, ImportReport};
+use crate::models::{
+    HeldMessage, MatrixRoom, NewHeldMessage, NewMatrixRoom, NewUser, NewWebhook,
+    NewWebhookCollaborator, User, Webhook, WebhookCollaborator,
+};
+use crate::quiet_hours::QuietHours;
+use crate::AsyncDbPool;
+use anyhow::Result;
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+use std::time::Duration;
+use uuid::Uuid;
+
+pub use in_memory_repository::InMemoryRepository;
+pub use sqlx_repository::SqlxRepository;
+
+#[async_trait]
+pub trait UserRepository: Send + Sync {
+    /// Create a new [User] and return the result.
+    async fn create_user(&self, new_user: NewUser) -> Result<User>;
+
+    /// Retrieve a [User] by their ID if they exist.
+    async fn try_get(&self, identifier: &Uuid) -> Result<Option<User>>;
+
+    /// Retrieve a [User] by their username if they exist.
+    async fn try_get_by_username(&self, username: &str) -> Result<Option<User>>;
+
+    /// Retrieve all [User]s, ordered by username.
+    async fn get_all(&self) -> Result<Vec<User>>;
+
+    /// Change a [User]'s role and return the updated [User], or [None] without changing it if they're the last System
+    /// Administrator and the new role isn't. The check and the change happen atomically.
+    async fn update_role(&self, user: &User, role: UserRole) -> Result<Option<User>>;
+
+    /// Delete a [User] along with their [Webhook]s, the webhooks' [MatrixRoom]s, and any access they've been granted
+    /// to other [Webhook]s, returning whether they were deleted. The last System Administrator isn't deleted. The check
+    /// and the delete happen atomically.
+    async fn delete(&self, user: &User) -> Result<bool>;
+
+    /// Check if any [User]s exist.
+    async fn any(&self) -> Result<bool>;
+}
+
+#[async_trait]
+pub trait WebhookRepository: Send + Sync {
+    /// Create a new [Webhook] and return the result.
+    async fn create_webhook(&self, new_webhook: NewWebhook) -> Result<Webhook>;
+
+    /// Get a [Webhook] by its ID.
+    async fn try_get(&self, identifier: &Uuid) -> Result<Option<Webhook>>;
+
+    /// Delete a [Webhook] along with its [MatrixRoom]s and [WebhookCollaborator]s.
+    async fn delete(&self, webhook: &Webhook) -> Result<()>;
+
+    /// Retrieve all [Webhook]s.
+    async fn get_all(&self) -> Result<Vec<Webhook>>;
+
+    /// Retrieve all of a given User's [Webhook]s.
+    async fn get_all_by_user_id(&self, user_id: &Uuid) -> Result<Vec<Webhook>>;
+
+    /// Set the base URL and API key of the *arr that invokes a [Webhook] and return the updated [Webhook].
+    /// Passing [None] for both clears the connection.
+    async fn update_arr_connection(
+        &self,
+        webhook: &Webhook,
+        url: Option<String>,
+        api_key: Option<String>,
+    ) -> Result<Webhook>;
+
+    /// Set how file system paths are shown in notifications for a [Webhook] and return the updated [Webhook].
+    async fn update_path_policy(
+        &self,
+        webhook: &Webhook,
+        policy: PathPolicy,
+        replacements: Vec<String>,
+    ) -> Result<Webhook>;
+
+    /// Set how requests to a [Webhook] are authenticated and return the updated [Webhook].
+    async fn update_auth_mode(
+        &self,
+        webhook: &Webhook,
+        mode: WebhookAuthMode,
+        token_digest: Option<String>,
+        secret: Option<String>,
+    ) -> Result<Webhook>;
+
+    /// Set the networks that requests to a [Webhook] must come from and return the updated [Webhook]. An empty
+    /// list allows requests from anywhere.
+    async fn update_allowed_sources(
+        &self,
+        webhook: &Webhook,
+        sources: Vec<String>,
+    ) -> Result<Webhook>;
+
+    /// Replace a [Webhook]'s password and return the updated [Webhook]. If a grace period is given, the current
+    /// password keeps working for that long; otherwise it stops working immediately.
+    async fn rotate_password(
+        &self,
+        webhook: &Webhook,
+        new_password: Vec<u8>,
+        new_params: String,
+        grace_period: Option<Duration>,
+    ) -> Result<Webhook>;
+
+    /// Replace a [Webhook]'s password hash with a new hash of the same password. Does nothing if the password
+    /// has been changed since the [Webhook] was retrieved. Returns the number of webhooks updated.
+    async fn rehash_password(
+        &self,
+        webhook: &Webhook,
+        new_password: Vec<u8>,
+        new_params: String,
+    ) -> Result<usize>;
+
+    /// Make another [User] the owner of a [Webhook] and return the updated [Webhook].
+    async fn transfer_ownership(&self, webhook: &Webhook, new_owner: &User) -> Result<Webhook>;
+
+    /// Set the username and server name of a [Webhook] and return the updated [Webhook].
+    async fn update_details(
+        &self,
+        webhook: &Webhook,
+        username: String,
+        server_name: Option<String>,
+    ) -> Result<Webhook>;
+
+    /// Record that a [Webhook] received an event, some of whose Matrix messages may not have been sent.
+    async fn record_event(&self, webhook_id: &Uuid, failed_deliveries: i64) -> Result<()>;
+
+    /// Record that a Matrix message reporting one of a [Webhook]'s events was queued but couldn't be sent.
+    async fn record_delivery_failure(&self, webhook_id: &Uuid) -> Result<()>;
+
+    /// Pause or resume a [Webhook] and return the updated [Webhook]. A pause with an end time ends on its own.
+    async fn update_paused(
+        &self,
+        webhook: &Webhook,
+        paused: bool,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Webhook>;
+}
+
+#[async_trait]
+pub trait MatrixRoomRepository: Send + Sync {
+    /// Create a new [MatrixRoom] and return the result.
+    async fn create_room(&self, new_room: NewMatrixRoom) -> Result<MatrixRoom>;
+
+    /// Retrieve a list of rooms up to some limit.
+    async fn get_many(&self, limit: Option<u8>) -> Result<Vec<MatrixRoom>>;
+
+    /// Retrieve all rooms.
+    async fn get_all(&self) -> Result<Vec<MatrixRoom>>;
+
+    /// Retrieve the rooms a given webhook posts to.
+    async fn get_by_webhook_id(&self, webhook_id: &Uuid) -> Result<Vec<MatrixRoom>>;
+
+    /// Retrieve the records of every webhook posting to a given Matrix room.
+    async fn get_by_room_id(&self, room_id: &str) -> Result<Vec<MatrixRoom>>;
+
+    /// Set whether to hide spoilers for the rooms of a given webhook, optionally limited to a single Matrix room.
+    /// Returns the number of rooms updated.
+    async fn update_hide_spoilers(
+        &self,
+        webhook_id: &Uuid,
+        room_id: Option<&str>,
+        value: bool,
+    ) -> Result<usize>;
+
+    /// Mute the rooms of a given webhook until the given time, optionally limited to a single Matrix room. Passing
+    /// [None] unmutes them. Returns the number of rooms updated.
+    async fn update_muted_until(
+        &self,
+        webhook_id: &Uuid,
+        room_id: Option<&str>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<usize>;
+
+    /// Set the quiet hours of the rooms of a given webhook, optionally limited to a single Matrix room, and whether
+    /// health check failures are still posted during them. Passing [None] removes the quiet hours. Returns the number
+    /// of rooms updated.
+    async fn update_quiet_hours(
+        &self,
+        webhook_id: &Uuid,
+        room_id: Option<&str>,
+        quiet_hours: Option<&QuietHours>,
+        bypass_health: bool,
+    ) -> Result<usize>;
+
+    /// Delete a [MatrixRoom].
+    async fn delete(&self, room: &MatrixRoom) -> Result<()>;
+}
+
+#[async_trait]
+pub trait CollaboratorRepository: Send + Sync {
+    /// Grant a [User] access to a [Webhook] and return the result. A [User] can only be granted access to a given
+    /// [Webhook] once.
+    async fn create_collaborator(
+        &self,
+        new_collaborator: NewWebhookCollaborator,
+    ) -> Result<WebhookCollaborator>;
+
+    /// Retrieve the access a [User] has been granted to a [Webhook] if they have any.
+    async fn try_get(
+        &self,
+        webhook_id: &Uuid,
+        user_id: &Uuid,
+    ) -> Result<Option<WebhookCollaborator>>;
+
+    /// Retrieve everyone granted access to a given webhook.
+    async fn get_by_webhook_id(&self, webhook_id: &Uuid) -> Result<Vec<WebhookCollaborator>>;
+
+    /// Retrieve the access a given user has been granted to webhooks.
+    async fn get_by_user_id(&self, user_id: &Uuid) -> Result<Vec<WebhookCollaborator>>;
+
+    /// Retrieve all granted access.
+    async fn get_all(&self) -> Result<Vec<WebhookCollaborator>>;
+
+    /// Store the permissions of a [WebhookCollaborator] and return the updated [WebhookCollaborator].
+    async fn update_permissions(
+        &self,
+        collaborator: &WebhookCollaborator,
+    ) -> Result<WebhookCollaborator>;
+
+    /// Revoke a [User]'s access to a [Webhook].
+    async fn delete(&self, collaborator: &WebhookCollaborator) -> Result<()>;
+}
+
+#[async_trait]
+pub trait HeldMessageRepository: Send + Sync {
+    /// Hold a message for a [MatrixRoom] until its quiet hours end and return the result.
+    async fn create_held_message(&self, new_message: NewHeldMessage) -> Result<HeldMessage>;
+
+    /// Retrieve all held messages, oldest first.
+    async fn get_all(&self) -> Result<Vec<HeldMessage>>;
+
+    /// Delete a [HeldMessage] once it has been posted.
+    async fn delete(&self, message: &HeldMessage) -> Result<()>;
+}
+
+#[async_trait]
+pub trait ConfigurationRepository: Send + Sync {
+    /// Write an exported configuration in a single transaction. When `dry_run` is set the transaction is rolled back
+    /// rather than committed, so the import fails wherever a real one would, and the report describes what it would
+    /// have done.
+    async fn import(
+        &self,
+        document: ConfigurationExport,
+        policy: ConflictPolicy,
+        dry_run: bool,
+    ) -> Result<ImportReport>;
+}
+
+/// The repositories for each kind of record, shared by the web API and the Matrix client.
+#[derive(Clone)]
+pub struct Repositories {
+    pub users: Arc<dyn UserRepository>,
+    pub webhooks: Arc<dyn WebhookRepository>,
+    pub rooms: Arc<dyn MatrixRoomRepository>,
+    pub collaborators: Arc<dyn CollaboratorRepository>,
+    pub held_messages: Arc<dyn HeldMessageRepository>,
+    pub configuration: Arc<dyn ConfigurationRepository>,
+}
+
+impl Repositories {
+    /// Repositories backed by the database behind the given pool.
+    pub fn database(pool: AsyncDbPool) -> Self {
+        Self::from_repository(Arc::new(SqlxRepository::new(pool)))
+    }
+
+    /// Empty repositories that only keep their records in memory, for tests.
+    pub fn in_memory() -> Self {
+        Self::from_repository(Arc::new(InMemoryRepository::default()))
+    }
+
+    fn from_repository<R>(repository: Arc<R>) -> Self
+    where
+        R: UserRepository
+            + WebhookRepository
+            + MatrixRoomRepository
+            + CollaboratorRepository
+            + HeldMessageRepository
+            + ConfigurationRepository
+            + 'static,
+    {
+        Repositories {
+            users: repository.clone(),
+            webhooks: repository.clone(),
+            rooms: repository.clone(),
+            collaborators: repository.clone(),
+            held_messages: repository.clone(),
+            configuration: repository,
+        }
+    }
+}
+
+/// When the password replaced by a rotation with the given grace period stops being accepted.
+fn grace_period_end(grace_period: Option<Duration>) -> Option<DateTime<Utc>> {
+    grace_period
+        .and_then(|g| chrono::Duration::from_std(g).ok())
+        .map(|g| Utc::now() + g)
+}
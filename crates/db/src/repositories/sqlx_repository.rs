@@ -0,0 +1,1632 @@
+//! Repositories backed by the database, queried asynchronously through an [AsyncDbPool] so that no request ties up
+//! a thread while it waits on the database.
+
+use super::{
+    grace_period_end, CollaboratorRepository, ConfigurationRepository, HeldMessageRepository,
+    MatrixRoomRepository, UserRepository, WebhookRepository,
+};
+use crate::enums::{PathPolicy, UserRole, WebhookAuthMode};
+use crate::export::{
+    match_user, resolve, Action, ConfigurationExport, ConflictPolicy, ImportReport,
+};
+use crate::models::{
+    HeldMessage, MatrixRoom, NewHeldMessage, NewMatrixRoom, NewUser, NewWebhook,
+    NewWebhookCollaborator, User, Webhook, WebhookCollaborator,
+};
+use crate::quiet_hours::QuietHours;
+use crate::sqlx_types::{DbTextList, DbTimestamp, DbUuid};
+use crate::AsyncDbPool;
+use anyhow::{Context, Result};
+use async_trait::async_trait;
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::time::Duration;
+use uuid::Uuid;
+
+const USER_COLUMNS: &str = "id, service_username, user_role";
+const WEBHOOK_COLUMNS: &str = "id, username, password, user_id, server_name, arr_url, arr_api_key, path_policy, \
+    path_replacements, auth_mode, auth_token_digest, hmac_secret, password_params, previous_password, \
+    previous_password_expires_at, allowed_sources, created_at, last_event_at, events_received, delivery_failures, paused, \
+    paused_until";
+const MATRIX_ROOM_COLUMNS: &str =
+    "id, room_id, webhook_id, hide_spoilers, muted_until, quiet_hours_start, \
+    quiet_hours_end, quiet_hours_timezone, quiet_hours_bypass_health";
+const COLLABORATOR_COLUMNS: &str =
+    "id, webhook_id, user_id, manage_rooms, manage_filters, rotate_credentials";
+const HELD_MESSAGE_COLUMNS: &str = "id, matrix_room_id, received_at, summary";
+
+/// Bind every column of a [Webhook] to a query, in the order of [WEBHOOK_COLUMNS].
+macro_rules! bind_webhook {
+    ($query:expr, $webhook:expr) => {
+        $query
+            .bind(DbUuid($webhook.id))
+            .bind(&$webhook.username)
+            .bind(&$webhook.password)
+            .bind(DbUuid($webhook.user_id))
+            .bind(&$webhook.server_name)
+            .bind(&$webhook.arr_url)
+            .bind(&$webhook.arr_api_key)
+            .bind($webhook.path_policy)
+            .bind(DbTextList($webhook.path_replacements.clone()))
+            .bind($webhook.auth_mode)
+            .bind(&$webhook.auth_token_digest)
+            .bind(&$webhook.hmac_secret)
+            .bind(&$webhook.password_params)
+            .bind(&$webhook.previous_password)
+            .bind($webhook.previous_password_expires_at.map(DbTimestamp))
+            .bind(DbTextList($webhook.allowed_sources.clone()))
+            .bind($webhook.created_at.map(DbTimestamp))
+            .bind($webhook.last_event_at.map(DbTimestamp))
+            .bind($webhook.events_received)
+            .bind($webhook.delivery_failures)
+            .bind($webhook.paused)
+            .bind($webhook.paused_until.map(DbTimestamp))
+    };
+}
+
+/// Bind every column of a [MatrixRoom] to a query, in the order of [MATRIX_ROOM_COLUMNS].
+macro_rules! bind_room {
+    ($query:expr, $room:expr) => {
+        $query
+            .bind(DbUuid($room.id))
+            .bind(&$room.room_id)
+            .bind(DbUuid($room.webhook_id))
+            .bind($room.hide_spoilers)
+            .bind($room.muted_until.map(DbTimestamp))
+            .bind(&$room.quiet_hours_start)
+            .bind(&$room.quiet_hours_end)
+            .bind(&$room.quiet_hours_timezone)
+            .bind($room.quiet_hours_bypass_health)
+    };
+}
+
+/// Check, within a transaction, whether the user with the given ID is the only System Administrator. The System
+/// Administrators' rows are written first so that they stay locked until the transaction ends; otherwise two
+/// concurrent demotions could each count the other administrator and leave none.
+macro_rules! is_last_system_administrator {
+    ($transaction:expr, $user_id:expr) => {{
+        sqlx::query("UPDATE users SET user_role = user_role WHERE user_role = $1")
+            .bind(UserRole::SystemAdministrator)
+            .execute(&mut $transaction)
+            .await?;
+        let system_administrators =
+            sqlx::query_as::<_, (DbUuid,)>("SELECT id FROM users WHERE user_role = $1")
+                .bind(UserRole::SystemAdministrator)
+                .fetch_all(&mut $transaction)
+                .await?;
+        system_administrators.len() == 1 && system_administrators[0].0 .0 == $user_id
+    }};
+}
+
+/// An `INSERT` of the given columns, numbering the placeholders in the order of the columns.
+fn insert_sql(table: &str, columns: &str) -> String {
+    let placeholders: Vec<String> = (1..=columns.split(',').count())
+        .map(|i| format!("${}", i))
+        .collect();
+    format!(
+        "INSERT INTO {} ({}) VALUES ({})",
+        table,
+        columns,
+        placeholders.join(", ")
+    )
+}
+
+/// An `UPDATE` of all of the given columns but the leading `id`, numbering the placeholders as [insert_sql] does so
+/// that both can be bound the same way.
+fn update_sql(table: &str, columns: &str) -> String {
+    let assignments: Vec<String> = columns
+        .split(',')
+        .map(str::trim)
+        .enumerate()
+        .skip(1)
+        .map(|(i, column)| format!("{} = ${}", column, i + 1))
+        .collect();
+    format!(
+        "UPDATE {} SET {} WHERE id = $1",
+        table,
+        assignments.join(", ")
+    )
+}
+
+#[derive(sqlx::FromRow)]
+struct UserRow {
+    id: DbUuid,
+    service_username: String,
+    user_role: UserRole,
+}
+
+impl From<UserRow> for User {
+    fn from(row: UserRow) -> Self {
+        User {
+            id: row.id.0,
+            service_username: row.service_username,
+            user_role: row.user_role,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct WebhookRow {
+    id: DbUuid,
+    username: String,
+    password: Vec<u8>,
+    user_id: DbUuid,
+    server_name: Option<String>,
+    arr_url: Option<String>,
+    arr_api_key: Option<String>,
+    path_policy: PathPolicy,
+    path_replacements: DbTextList,
+    auth_mode: WebhookAuthMode,
+    auth_token_digest: Option<String>,
+    hmac_secret: Option<String>,
+    password_params: String,
+    previous_password: Option<Vec<u8>>,
+    previous_password_expires_at: Option<DbTimestamp>,
+    allowed_sources: DbTextList,
+    created_at: Option<DbTimestamp>,
+    last_event_at: Option<DbTimestamp>,
+    events_received: i64,
+    delivery_failures: i64,
+    paused: bool,
+    paused_until: Option<DbTimestamp>,
+}
+
+impl From<WebhookRow> for Webhook {
+    fn from(row: WebhookRow) -> Self {
+        Webhook {
+            id: row.id.0,
+            username: row.username,
+            password: row.password,
+            user_id: row.user_id.0,
+            server_name: row.server_name,
+            arr_url: row.arr_url,
+            arr_api_key: row.arr_api_key,
+            path_policy: row.path_policy,
+            path_replacements: row.path_replacements.0,
+            auth_mode: row.auth_mode,
+            auth_token_digest: row.auth_token_digest,
+            hmac_secret: row.hmac_secret,
+            password_params: row.password_params,
+            previous_password: row.previous_password,
+            previous_password_expires_at: row.previous_password_expires_at.map(|t| t.0),
+            allowed_sources: row.allowed_sources.0,
+            created_at: row.created_at.map(|t| t.0),
+            last_event_at: row.last_event_at.map(|t| t.0),
+            events_received: row.events_received,
+            delivery_failures: row.delivery_failures,
+            paused: row.paused,
+            paused_until: row.paused_until.map(|t| t.0),
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct MatrixRoomRow {
+    id: DbUuid,
+    room_id: String,
+    webhook_id: DbUuid,
+    hide_spoilers: bool,
+    muted_until: Option<DbTimestamp>,
+    quiet_hours_start: Option<String>,
+    quiet_hours_end: Option<String>,
+    quiet_hours_timezone: Option<String>,
+    quiet_hours_bypass_health: bool,
+}
+
+impl From<MatrixRoomRow> for MatrixRoom {
+    fn from(row: MatrixRoomRow) -> Self {
+        MatrixRoom {
+            id: row.id.0,
+            room_id: row.room_id,
+            webhook_id: row.webhook_id.0,
+            hide_spoilers: row.hide_spoilers,
+            muted_until: row.muted_until.map(|t| t.0),
+            quiet_hours_start: row.quiet_hours_start,
+            quiet_hours_end: row.quiet_hours_end,
+            quiet_hours_timezone: row.quiet_hours_timezone,
+            quiet_hours_bypass_health: row.quiet_hours_bypass_health,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct CollaboratorRow {
+    id: DbUuid,
+    webhook_id: DbUuid,
+    user_id: DbUuid,
+    manage_rooms: bool,
+    manage_filters: bool,
+    rotate_credentials: bool,
+}
+
+impl From<CollaboratorRow> for WebhookCollaborator {
+    fn from(row: CollaboratorRow) -> Self {
+        WebhookCollaborator {
+            id: row.id.0,
+            webhook_id: row.webhook_id.0,
+            user_id: row.user_id.0,
+            manage_rooms: row.manage_rooms,
+            manage_filters: row.manage_filters,
+            rotate_credentials: row.rotate_credentials,
+        }
+    }
+}
+
+#[derive(sqlx::FromRow)]
+struct HeldMessageRow {
+    id: DbUuid,
+    matrix_room_id: DbUuid,
+    received_at: DbTimestamp,
+    summary: String,
+}
+
+impl From<HeldMessageRow> for HeldMessage {
+    fn from(row: HeldMessageRow) -> Self {
+        HeldMessage {
+            id: row.id.0,
+            matrix_room_id: row.matrix_room_id.0,
+            received_at: row.received_at.0,
+            summary: row.summary,
+        }
+    }
+}
+
+pub struct SqlxRepository {
+    pool: AsyncDbPool,
+}
+
+impl SqlxRepository {
+    pub fn new(pool: AsyncDbPool) -> Self {
+        SqlxRepository { pool }
+    }
+
+    /// Retrieve a [Webhook] that is known to exist, such as one that was just updated.
+    async fn get_webhook(&self, identifier: &Uuid) -> Result<Webhook> {
+        WebhookRepository::try_get(self, identifier)
+            .await?
+            .context("The webhook does not exist.")
+    }
+}
+
+#[async_trait]
+impl UserRepository for SqlxRepository {
+    async fn create_user(&self, new_user: NewUser) -> Result<User> {
+        let user = User::from(new_user);
+        let pool = &self.pool;
+        let sql = format!("INSERT INTO users ({}) VALUES ($1, $2, $3)", USER_COLUMNS);
+        async_db_run!(pool: {
+            sqlx::query(&sql)
+                .bind(DbUuid(user.id))
+                .bind(&user.service_username)
+                .bind(user.user_role.clone())
+                .execute(pool)
+                .await?;
+        });
+        Ok(user)
+    }
+
+    async fn try_get(&self, identifier: &Uuid) -> Result<Option<User>> {
+        let pool = &self.pool;
+        let sql = format!("SELECT {} FROM users WHERE id = $1", USER_COLUMNS);
+        let row = async_db_run!(pool: {
+            sqlx::query_as::<_, UserRow>(&sql)
+                .bind(DbUuid(*identifier))
+                .fetch_optional(pool)
+                .await?
+        });
+        Ok(row.map(User::from))
+    }
+
+    async fn try_get_by_username(&self, username: &str) -> Result<Option<User>> {
+        let pool = &self.pool;
+        let sql = format!(
+            "SELECT {} FROM users WHERE service_username = $1",
+            USER_COLUMNS
+        );
+        let row = async_db_run!(pool: {
+            sqlx::query_as::<_, UserRow>(&sql)
+                .bind(username)
+                .fetch_optional(pool)
+                .await?
+        });
+        Ok(row.map(User::from))
+    }
+
+    async fn get_all(&self) -> Result<Vec<User>> {
+        let pool = &self.pool;
+        let sql = format!(
+            "SELECT {} FROM users ORDER BY service_username",
+            USER_COLUMNS
+        );
+        let rows = async_db_run!(pool: {
+            sqlx::query_as::<_, UserRow>(&sql).fetch_all(pool).await?
+        });
+        Ok(rows.into_iter().map(User::from).collect())
+    }
+
+    async fn update_role(&self, user: &User, role: UserRole) -> Result<Option<User>> {
+        let pool = &self.pool;
+        let updated = async_db_run!(pool: {
+            let mut transaction = pool.begin().await?;
+            if !matches!(role, UserRole::SystemAdministrator)
+                && is_last_system_administrator!(transaction, user.id)
+            {
+                transaction.rollback().await?;
+                false
+            } else {
+                sqlx::query("UPDATE users SET user_role = $1 WHERE id = $2")
+                    .bind(role)
+                    .bind(DbUuid(user.id))
+                    .execute(&mut transaction)
+                    .await?;
+                transaction.commit().await?;
+                true
+            }
+        });
+        if !updated {
+            return Ok(None);
+        }
+        UserRepository::try_get(self, &user.id)
+            .await?
+            .context("The user does not exist.")
+            .map(Some)
+    }
+
+    async fn delete(&self, user: &User) -> Result<bool> {
+        let pool = &self.pool;
+        let deleted = async_db_run!(pool: {
+            let mut transaction = pool.begin().await?;
+            if is_last_system_administrator!(transaction, user.id) {
+                transaction.rollback().await?;
+                false
+            } else {
+                sqlx::query("DELETE FROM users WHERE id = $1")
+                    .bind(DbUuid(user.id))
+                    .execute(&mut transaction)
+                    .await?;
+                transaction.commit().await?;
+                true
+            }
+        });
+        Ok(deleted)
+    }
+
+    async fn any(&self) -> Result<bool> {
+        let pool = &self.pool;
+        let exists = async_db_run!(pool: {
+            sqlx::query("SELECT 1 FROM users LIMIT 1")
+                .fetch_optional(pool)
+                .await?
+                .is_some()
+        });
+        Ok(exists)
+    }
+}
+
+#[async_trait]
+impl WebhookRepository for SqlxRepository {
+    async fn create_webhook(&self, new_webhook: NewWebhook) -> Result<Webhook> {
+        let webhook = Webhook::from(new_webhook);
+        let pool = &self.pool;
+        let sql = format!(
+            "INSERT INTO webhooks ({}) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13, $14, $15, $16, \
+            $17, $18, $19, $20, $21, $22)",
+            WEBHOOK_COLUMNS
+        );
+        async_db_run!(pool: {
+            bind_webhook!(sqlx::query(&sql), webhook)
+                .execute(pool)
+                .await?;
+        });
+        Ok(webhook)
+    }
+
+    async fn try_get(&self, identifier: &Uuid) -> Result<Option<Webhook>> {
+        let pool = &self.pool;
+        let sql = format!("SELECT {} FROM webhooks WHERE id = $1", WEBHOOK_COLUMNS);
+        let row = async_db_run!(pool: {
+            sqlx::query_as::<_, WebhookRow>(&sql)
+                .bind(DbUuid(*identifier))
+                .fetch_optional(pool)
+                .await?
+        });
+        Ok(row.map(Webhook::from))
+    }
+
+    async fn delete(&self, webhook: &Webhook) -> Result<()> {
+        let pool = &self.pool;
+        async_db_run!(pool: {
+            sqlx::query("DELETE FROM webhooks WHERE id = $1")
+                .bind(DbUuid(webhook.id))
+                .execute(pool)
+                .await?;
+        });
+        Ok(())
+    }
+
+    async fn get_all(&self) -> Result<Vec<Webhook>> {
+        let pool = &self.pool;
+        let sql = format!("SELECT {} FROM webhooks", WEBHOOK_COLUMNS);
+        let rows = async_db_run!(pool: {
+            sqlx::query_as::<_, WebhookRow>(&sql).fetch_all(pool).await?
+        });
+        Ok(rows.into_iter().map(Webhook::from).collect())
+    }
+
+    async fn get_all_by_user_id(&self, user_id: &Uuid) -> Result<Vec<Webhook>> {
+        let pool = &self.pool;
+        let sql = format!(
+            "SELECT {} FROM webhooks WHERE user_id = $1",
+            WEBHOOK_COLUMNS
+        );
+        let rows = async_db_run!(pool: {
+            sqlx::query_as::<_, WebhookRow>(&sql)
+                .bind(DbUuid(*user_id))
+                .fetch_all(pool)
+                .await?
+        });
+        Ok(rows.into_iter().map(Webhook::from).collect())
+    }
+
+    async fn update_arr_connection(
+        &self,
+        webhook: &Webhook,
+        url: Option<String>,
+        api_key: Option<String>,
+    ) -> Result<Webhook> {
+        let pool = &self.pool;
+        async_db_run!(pool: {
+            sqlx::query("UPDATE webhooks SET arr_url = $1, arr_api_key = $2 WHERE id = $3")
+                .bind(&url)
+                .bind(&api_key)
+                .bind(DbUuid(webhook.id))
+                .execute(pool)
+                .await?;
+        });
+        self.get_webhook(&webhook.id).await
+    }
+
+    async fn update_path_policy(
+        &self,
+        webhook: &Webhook,
+        policy: PathPolicy,
+        replacements: Vec<String>,
+    ) -> Result<Webhook> {
+        let pool = &self.pool;
+        async_db_run!(pool: {
+            sqlx::query("UPDATE webhooks SET path_policy = $1, path_replacements = $2 WHERE id = $3")
+                .bind(policy)
+                .bind(DbTextList(replacements.clone()))
+                .bind(DbUuid(webhook.id))
+                .execute(pool)
+                .await?;
+        });
+        self.get_webhook(&webhook.id).await
+    }
+
+    async fn update_auth_mode(
+        &self,
+        webhook: &Webhook,
+        mode: WebhookAuthMode,
+        token_digest: Option<String>,
+        secret: Option<String>,
+    ) -> Result<Webhook> {
+        let pool = &self.pool;
+        async_db_run!(pool: {
+            sqlx::query(
+                "UPDATE webhooks SET auth_mode = $1, auth_token_digest = $2, hmac_secret = $3 WHERE id = $4",
+            )
+            .bind(mode)
+            .bind(&token_digest)
+            .bind(&secret)
+            .bind(DbUuid(webhook.id))
+            .execute(pool)
+            .await?;
+        });
+        self.get_webhook(&webhook.id).await
+    }
+
+    async fn update_allowed_sources(
+        &self,
+        webhook: &Webhook,
+        sources: Vec<String>,
+    ) -> Result<Webhook> {
+        let pool = &self.pool;
+        async_db_run!(pool: {
+            sqlx::query("UPDATE webhooks SET allowed_sources = $1 WHERE id = $2")
+                .bind(DbTextList(sources.clone()))
+                .bind(DbUuid(webhook.id))
+                .execute(pool)
+                .await?;
+        });
+        self.get_webhook(&webhook.id).await
+    }
+
+    async fn rotate_password(
+        &self,
+        webhook: &Webhook,
+        new_password: Vec<u8>,
+        new_params: String,
+        grace_period: Option<Duration>,
+    ) -> Result<Webhook> {
+        let grace_until = grace_period_end(grace_period);
+        let previous = grace_until.map(|_| webhook.password.clone());
+        let pool = &self.pool;
+        async_db_run!(pool: {
+            sqlx::query(
+                "UPDATE webhooks SET password = $1, password_params = $2, previous_password = $3, \
+                previous_password_expires_at = $4 WHERE id = $5",
+            )
+            .bind(&new_password)
+            .bind(&new_params)
+            .bind(&previous)
+            .bind(grace_until.map(DbTimestamp))
+            .bind(DbUuid(webhook.id))
+            .execute(pool)
+            .await?;
+        });
+        self.get_webhook(&webhook.id).await
+    }
+
+    async fn rehash_password(
+        &self,
+        webhook: &Webhook,
+        new_password: Vec<u8>,
+        new_params: String,
+    ) -> Result<usize> {
+        let pool = &self.pool;
+        let updated = async_db_run!(pool: {
+            sqlx::query(
+                "UPDATE webhooks SET password = $1, password_params = $2 WHERE id = $3 AND password = $4",
+            )
+            .bind(&new_password)
+            .bind(&new_params)
+            .bind(DbUuid(webhook.id))
+            .bind(&webhook.password)
+            .execute(pool)
+            .await?
+            .rows_affected()
+        });
+        Ok(updated as usize)
+    }
+
+    async fn transfer_ownership(&self, webhook: &Webhook, new_owner: &User) -> Result<Webhook> {
+        let pool = &self.pool;
+        async_db_run!(pool: {
+            sqlx::query("UPDATE webhooks SET user_id = $1 WHERE id = $2")
+                .bind(DbUuid(new_owner.id))
+                .bind(DbUuid(webhook.id))
+                .execute(pool)
+                .await?;
+        });
+        self.get_webhook(&webhook.id).await
+    }
+
+    async fn update_details(
+        &self,
+        webhook: &Webhook,
+        username: String,
+        server_name: Option<String>,
+    ) -> Result<Webhook> {
+        let pool = &self.pool;
+        async_db_run!(pool: {
+            sqlx::query("UPDATE webhooks SET username = $1, server_name = $2 WHERE id = $3")
+                .bind(&username)
+                .bind(&server_name)
+                .bind(DbUuid(webhook.id))
+                .execute(pool)
+                .await?;
+        });
+        self.get_webhook(&webhook.id).await
+    }
+
+    async fn record_event(&self, webhook_id: &Uuid, failed_deliveries: i64) -> Result<()> {
+        let pool = &self.pool;
+        async_db_run!(pool: {
+            sqlx::query(
+                "UPDATE webhooks SET last_event_at = $1, events_received = events_received + 1, \
+                delivery_failures = delivery_failures + $2 WHERE id = $3",
+            )
+            .bind(DbTimestamp(Utc::now()))
+            .bind(failed_deliveries)
+            .bind(DbUuid(*webhook_id))
+            .execute(pool)
+            .await?;
+        });
+        Ok(())
+    }
+
+    async fn record_delivery_failure(&self, webhook_id: &Uuid) -> Result<()> {
+        let pool = &self.pool;
+        async_db_run!(pool: {
+            sqlx::query("UPDATE webhooks SET delivery_failures = delivery_failures + 1 WHERE id = $1")
+                .bind(DbUuid(*webhook_id))
+                .execute(pool)
+                .await?;
+        });
+        Ok(())
+    }
+
+    async fn update_paused(
+        &self,
+        webhook: &Webhook,
+        paused: bool,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<Webhook> {
+        let pool = &self.pool;
+        async_db_run!(pool: {
+            sqlx::query("UPDATE webhooks SET paused = $1, paused_until = $2 WHERE id = $3")
+                .bind(paused)
+                .bind(until.map(DbTimestamp))
+                .bind(DbUuid(webhook.id))
+                .execute(pool)
+                .await?;
+        });
+        self.get_webhook(&webhook.id).await
+    }
+}
+
+#[async_trait]
+impl MatrixRoomRepository for SqlxRepository {
+    async fn create_room(&self, new_room: NewMatrixRoom) -> Result<MatrixRoom> {
+        let room = MatrixRoom::from(new_room);
+        let pool = &self.pool;
+        let sql = format!(
+            "INSERT INTO matrix_rooms ({}) VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9)",
+            MATRIX_ROOM_COLUMNS
+        );
+        async_db_run!(pool: {
+            bind_room!(sqlx::query(&sql), room).execute(pool).await?;
+        });
+        Ok(room)
+    }
+
+    async fn get_many(&self, limit: Option<u8>) -> Result<Vec<MatrixRoom>> {
+        let limit = i64::from(limit.unwrap_or(10));
+        let pool = &self.pool;
+        let sql = format!("SELECT {} FROM matrix_rooms LIMIT $1", MATRIX_ROOM_COLUMNS);
+        let rows = async_db_run!(pool: {
+            sqlx::query_as::<_, MatrixRoomRow>(&sql)
+                .bind(limit)
+                .fetch_all(pool)
+                .await?
+        });
+        Ok(rows.into_iter().map(MatrixRoom::from).collect())
+    }
+
+    async fn get_all(&self) -> Result<Vec<MatrixRoom>> {
+        let pool = &self.pool;
+        let sql = format!("SELECT {} FROM matrix_rooms", MATRIX_ROOM_COLUMNS);
+        let rows = async_db_run!(pool: {
+            sqlx::query_as::<_, MatrixRoomRow>(&sql).fetch_all(pool).await?
+        });
+        Ok(rows.into_iter().map(MatrixRoom::from).collect())
+    }
+
+    async fn get_by_webhook_id(&self, webhook_id: &Uuid) -> Result<Vec<MatrixRoom>> {
+        let pool = &self.pool;
+        let sql = format!(
+            "SELECT {} FROM matrix_rooms WHERE webhook_id = $1",
+            MATRIX_ROOM_COLUMNS
+        );
+        let rows = async_db_run!(pool: {
+            sqlx::query_as::<_, MatrixRoomRow>(&sql)
+                .bind(DbUuid(*webhook_id))
+                .fetch_all(pool)
+                .await?
+        });
+        Ok(rows.into_iter().map(MatrixRoom::from).collect())
+    }
+
+    async fn get_by_room_id(&self, room_id: &str) -> Result<Vec<MatrixRoom>> {
+        let pool = &self.pool;
+        let sql = format!(
+            "SELECT {} FROM matrix_rooms WHERE room_id = $1",
+            MATRIX_ROOM_COLUMNS
+        );
+        let rows = async_db_run!(pool: {
+            sqlx::query_as::<_, MatrixRoomRow>(&sql)
+                .bind(room_id)
+                .fetch_all(pool)
+                .await?
+        });
+        Ok(rows.into_iter().map(MatrixRoom::from).collect())
+    }
+
+    async fn update_hide_spoilers(
+        &self,
+        webhook_id: &Uuid,
+        room_id: Option<&str>,
+        value: bool,
+    ) -> Result<usize> {
+        let pool = &self.pool;
+        let updated = async_db_run!(pool: {
+            sqlx::query(
+                "UPDATE matrix_rooms SET hide_spoilers = $1 WHERE webhook_id = $2 AND ($3 IS NULL OR room_id = $3)",
+            )
+            .bind(value)
+            .bind(DbUuid(*webhook_id))
+            .bind(room_id)
+            .execute(pool)
+            .await?
+            .rows_affected()
+        });
+        Ok(updated as usize)
+    }
+
+    async fn update_muted_until(
+        &self,
+        webhook_id: &Uuid,
+        room_id: Option<&str>,
+        until: Option<DateTime<Utc>>,
+    ) -> Result<usize> {
+        let pool = &self.pool;
+        let updated = async_db_run!(pool: {
+            sqlx::query(
+                "UPDATE matrix_rooms SET muted_until = $1 WHERE webhook_id = $2 AND ($3 IS NULL OR room_id = $3)",
+            )
+            .bind(until.map(DbTimestamp))
+            .bind(DbUuid(*webhook_id))
+            .bind(room_id)
+            .execute(pool)
+            .await?
+            .rows_affected()
+        });
+        Ok(updated as usize)
+    }
+
+    async fn update_quiet_hours(
+        &self,
+        webhook_id: &Uuid,
+        room_id: Option<&str>,
+        quiet_hours: Option<&QuietHours>,
+        bypass_health: bool,
+    ) -> Result<usize> {
+        let (start, end, timezone) = match quiet_hours.map(QuietHours::to_parts) {
+            Some((start, end, timezone)) => (Some(start), Some(end), Some(timezone)),
+            None => (None, None, None),
+        };
+        let pool = &self.pool;
+        let updated = async_db_run!(pool: {
+            sqlx::query(
+                "UPDATE matrix_rooms SET quiet_hours_start = $1, quiet_hours_end = $2, quiet_hours_timezone = $3, \
+                quiet_hours_bypass_health = $4 WHERE webhook_id = $5 AND ($6 IS NULL OR room_id = $6)",
+            )
+            .bind(&start)
+            .bind(&end)
+            .bind(&timezone)
+            .bind(bypass_health)
+            .bind(DbUuid(*webhook_id))
+            .bind(room_id)
+            .execute(pool)
+            .await?
+            .rows_affected()
+        });
+        Ok(updated as usize)
+    }
+
+    async fn delete(&self, room: &MatrixRoom) -> Result<()> {
+        let pool = &self.pool;
+        async_db_run!(pool: {
+            sqlx::query("DELETE FROM matrix_rooms WHERE id = $1")
+                .bind(DbUuid(room.id))
+                .execute(pool)
+                .await?;
+        });
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl CollaboratorRepository for SqlxRepository {
+    async fn create_collaborator(
+        &self,
+        new_collaborator: NewWebhookCollaborator,
+    ) -> Result<WebhookCollaborator> {
+        let collaborator = WebhookCollaborator::from(new_collaborator);
+        let pool = &self.pool;
+        let sql = format!(
+            "INSERT INTO webhook_collaborators ({}) VALUES ($1, $2, $3, $4, $5, $6)",
+            COLLABORATOR_COLUMNS
+        );
+        async_db_run!(pool: {
+            sqlx::query(&sql)
+                .bind(DbUuid(collaborator.id))
+                .bind(DbUuid(collaborator.webhook_id))
+                .bind(DbUuid(collaborator.user_id))
+                .bind(collaborator.manage_rooms)
+                .bind(collaborator.manage_filters)
+                .bind(collaborator.rotate_credentials)
+                .execute(pool)
+                .await?;
+        });
+        Ok(collaborator)
+    }
+
+    async fn try_get(
+        &self,
+        webhook_id: &Uuid,
+        user_id: &Uuid,
+    ) -> Result<Option<WebhookCollaborator>> {
+        let pool = &self.pool;
+        let sql = format!(
+            "SELECT {} FROM webhook_collaborators WHERE webhook_id = $1 AND user_id = $2",
+            COLLABORATOR_COLUMNS
+        );
+        let row = async_db_run!(pool: {
+            sqlx::query_as::<_, CollaboratorRow>(&sql)
+                .bind(DbUuid(*webhook_id))
+                .bind(DbUuid(*user_id))
+                .fetch_optional(pool)
+                .await?
+        });
+        Ok(row.map(WebhookCollaborator::from))
+    }
+
+    async fn get_by_webhook_id(&self, webhook_id: &Uuid) -> Result<Vec<WebhookCollaborator>> {
+        let pool = &self.pool;
+        let sql = format!(
+            "SELECT {} FROM webhook_collaborators WHERE webhook_id = $1",
+            COLLABORATOR_COLUMNS
+        );
+        let rows = async_db_run!(pool: {
+            sqlx::query_as::<_, CollaboratorRow>(&sql)
+                .bind(DbUuid(*webhook_id))
+                .fetch_all(pool)
+                .await?
+        });
+        Ok(rows.into_iter().map(WebhookCollaborator::from).collect())
+    }
+
+    async fn get_by_user_id(&self, user_id: &Uuid) -> Result<Vec<WebhookCollaborator>> {
+        let pool = &self.pool;
+        let sql = format!(
+            "SELECT {} FROM webhook_collaborators WHERE user_id = $1",
+            COLLABORATOR_COLUMNS
+        );
+        let rows = async_db_run!(pool: {
+            sqlx::query_as::<_, CollaboratorRow>(&sql)
+                .bind(DbUuid(*user_id))
+                .fetch_all(pool)
+                .await?
+        });
+        Ok(rows.into_iter().map(WebhookCollaborator::from).collect())
+    }
+
+    async fn get_all(&self) -> Result<Vec<WebhookCollaborator>> {
+        let pool = &self.pool;
+        let sql = format!("SELECT {} FROM webhook_collaborators", COLLABORATOR_COLUMNS);
+        let rows = async_db_run!(pool: {
+            sqlx::query_as::<_, CollaboratorRow>(&sql).fetch_all(pool).await?
+        });
+        Ok(rows.into_iter().map(WebhookCollaborator::from).collect())
+    }
+
+    async fn update_permissions(
+        &self,
+        collaborator: &WebhookCollaborator,
+    ) -> Result<WebhookCollaborator> {
+        let pool = &self.pool;
+        async_db_run!(pool: {
+            sqlx::query(
+                "UPDATE webhook_collaborators SET manage_rooms = $1, manage_filters = $2, rotate_credentials = $3 \
+                WHERE id = $4",
+            )
+            .bind(collaborator.manage_rooms)
+            .bind(collaborator.manage_filters)
+            .bind(collaborator.rotate_credentials)
+            .bind(DbUuid(collaborator.id))
+            .execute(pool)
+            .await?;
+        });
+        CollaboratorRepository::try_get(self, &collaborator.webhook_id, &collaborator.user_id)
+            .await?
+            .context("The collaborator does not exist.")
+    }
+
+    async fn delete(&self, collaborator: &WebhookCollaborator) -> Result<()> {
+        let pool = &self.pool;
+        async_db_run!(pool: {
+            sqlx::query("DELETE FROM webhook_collaborators WHERE id = $1")
+                .bind(DbUuid(collaborator.id))
+                .execute(pool)
+                .await?;
+        });
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl HeldMessageRepository for SqlxRepository {
+    async fn create_held_message(&self, new_message: NewHeldMessage) -> Result<HeldMessage> {
+        let message = HeldMessage::from(new_message);
+        let pool = &self.pool;
+        let sql = format!(
+            "INSERT INTO held_messages ({}) VALUES ($1, $2, $3, $4)",
+            HELD_MESSAGE_COLUMNS
+        );
+        async_db_run!(pool: {
+            sqlx::query(&sql)
+                .bind(DbUuid(message.id))
+                .bind(DbUuid(message.matrix_room_id))
+                .bind(DbTimestamp(message.received_at))
+                .bind(&message.summary)
+                .execute(pool)
+                .await?;
+        });
+        Ok(message)
+    }
+
+    async fn get_all(&self) -> Result<Vec<HeldMessage>> {
+        let pool = &self.pool;
+        let sql = format!(
+            "SELECT {} FROM held_messages ORDER BY received_at",
+            HELD_MESSAGE_COLUMNS
+        );
+        let rows = async_db_run!(pool: {
+            sqlx::query_as::<_, HeldMessageRow>(&sql).fetch_all(pool).await?
+        });
+        Ok(rows.into_iter().map(HeldMessage::from).collect())
+    }
+
+    async fn delete(&self, message: &HeldMessage) -> Result<()> {
+        let pool = &self.pool;
+        async_db_run!(pool: {
+            sqlx::query("DELETE FROM held_messages WHERE id = $1")
+                .bind(DbUuid(message.id))
+                .execute(pool)
+                .await?;
+        });
+        Ok(())
+    }
+}
+
+#[async_trait]
+impl ConfigurationRepository for SqlxRepository {
+    async fn import(
+        &self,
+        document: ConfigurationExport,
+        policy: ConflictPolicy,
+        dry_run: bool,
+    ) -> Result<ImportReport> {
+        let insert_user = insert_sql("users", USER_COLUMNS);
+        let insert_webhook = insert_sql("webhooks", WEBHOOK_COLUMNS);
+        let update_webhook = update_sql("webhooks", WEBHOOK_COLUMNS);
+        let insert_room = insert_sql("matrix_rooms", MATRIX_ROOM_COLUMNS);
+        let update_room = update_sql("matrix_rooms", MATRIX_ROOM_COLUMNS);
+        let insert_collaborator = insert_sql("webhook_collaborators", COLLABORATOR_COLUMNS);
+        let pool = &self.pool;
+        async_db_run!(pool: {
+            let mut transaction = pool.begin().await?;
+            let mut report = ImportReport::default();
+            // Imported users matched to an existing user by username take on the existing user's ID.
+            let mut user_ids: HashMap<Uuid, Uuid> = HashMap::new();
+            for user in document.users {
+                let by_id = sqlx::query_as::<_, (DbUuid,)>("SELECT id FROM users WHERE id = $1")
+                    .bind(DbUuid(user.id))
+                    .fetch_optional(&mut transaction)
+                    .await?
+                    .map(|(id,)| id.0);
+                let by_username =
+                    sqlx::query_as::<_, (DbUuid,)>("SELECT id FROM users WHERE service_username = $1")
+                        .bind(&user.service_username)
+                        .fetch_optional(&mut transaction)
+                        .await?
+                        .map(|(id,)| id.0);
+                let existing = match_user(&user, by_id, by_username)?;
+                let action = resolve(policy, existing.is_some(), "user", &user.service_username)?;
+                if let Some(existing_id) = existing {
+                    user_ids.insert(user.id, existing_id);
+                }
+                match (action, existing) {
+                    (Action::Create, _) => {
+                        sqlx::query(&insert_user)
+                            .bind(DbUuid(user.id))
+                            .bind(&user.service_username)
+                            .bind(user.user_role.clone())
+                            .execute(&mut transaction)
+                            .await?;
+                    }
+                    (Action::Overwrite, Some(existing_id)) => {
+                        sqlx::query(
+                            "UPDATE users SET service_username = $1, user_role = $2 WHERE id = $3",
+                        )
+                        .bind(&user.service_username)
+                        .bind(user.user_role.clone())
+                        .bind(DbUuid(existing_id))
+                        .execute(&mut transaction)
+                        .await?;
+                    }
+                    _ => (),
+                }
+                report.users.record(action);
+            }
+
+            let mut skipped_webhooks = Vec::new();
+            for mut webhook in document.webhooks {
+                if let Some(id) = user_ids.get(&webhook.user_id) {
+                    webhook.user_id = *id;
+                }
+                let exists = sqlx::query("SELECT 1 FROM webhooks WHERE id = $1")
+                    .bind(DbUuid(webhook.id))
+                    .fetch_optional(&mut transaction)
+                    .await?
+                    .is_some();
+                let action = resolve(policy, exists, "webhook", &webhook.id.to_string())?;
+                if action == Action::Skip {
+                    skipped_webhooks.push(webhook.id);
+                }
+                let webhook = Webhook::from(webhook);
+                match action {
+                    Action::Create => {
+                        bind_webhook!(sqlx::query(&insert_webhook), webhook)
+                            .execute(&mut transaction)
+                            .await?;
+                    }
+                    Action::Overwrite => {
+                        bind_webhook!(sqlx::query(&update_webhook), webhook)
+                            .execute(&mut transaction)
+                            .await?;
+                    }
+                    Action::Skip => (),
+                }
+                report.webhooks.record(action);
+            }
+
+            for room in document.rooms {
+                let exists = sqlx::query("SELECT 1 FROM matrix_rooms WHERE id = $1")
+                    .bind(DbUuid(room.id))
+                    .fetch_optional(&mut transaction)
+                    .await?
+                    .is_some();
+                // Rooms follow the webhook they belong to when it is skipped.
+                let action = if skipped_webhooks.contains(&room.webhook_id) {
+                    Action::Skip
+                } else {
+                    resolve(policy, exists, "room", &room.room_id)?
+                };
+                let room = MatrixRoom::from(room);
+                match action {
+                    Action::Create => {
+                        bind_room!(sqlx::query(&insert_room), room)
+                            .execute(&mut transaction)
+                            .await?;
+                    }
+                    Action::Overwrite => {
+                        bind_room!(sqlx::query(&update_room), room)
+                            .execute(&mut transaction)
+                            .await?;
+                    }
+                    Action::Skip => (),
+                }
+                report.rooms.record(action);
+            }
+
+            for mut collaborator in document.collaborators {
+                if let Some(id) = user_ids.get(&collaborator.user_id) {
+                    collaborator.user_id = *id;
+                }
+                // A user can only be granted access to a webhook once, whatever the grant's ID.
+                let existing = sqlx::query_as::<_, (DbUuid,)>(
+                    "SELECT id FROM webhook_collaborators WHERE id = $1 OR (webhook_id = $2 AND user_id = $3)",
+                )
+                .bind(DbUuid(collaborator.id))
+                .bind(DbUuid(collaborator.webhook_id))
+                .bind(DbUuid(collaborator.user_id))
+                .fetch_optional(&mut transaction)
+                .await?
+                .map(|(id,)| id.0);
+                // Grants follow the webhook they belong to when it is skipped.
+                let action = if skipped_webhooks.contains(&collaborator.webhook_id) {
+                    Action::Skip
+                } else {
+                    resolve(
+                        policy,
+                        existing.is_some(),
+                        "collaborator",
+                        &collaborator.id.to_string(),
+                    )?
+                };
+                match (action, existing) {
+                    (Action::Create, _) => {
+                        sqlx::query(&insert_collaborator)
+                            .bind(DbUuid(collaborator.id))
+                            .bind(DbUuid(collaborator.webhook_id))
+                            .bind(DbUuid(collaborator.user_id))
+                            .bind(collaborator.manage_rooms)
+                            .bind(collaborator.manage_filters)
+                            .bind(collaborator.rotate_credentials)
+                            .execute(&mut transaction)
+                            .await?;
+                    }
+                    (Action::Overwrite, Some(existing_id)) => {
+                        sqlx::query(
+                            "UPDATE webhook_collaborators SET manage_rooms = $1, manage_filters = $2, \
+                            rotate_credentials = $3 WHERE id = $4",
+                        )
+                        .bind(collaborator.manage_rooms)
+                        .bind(collaborator.manage_filters)
+                        .bind(collaborator.rotate_credentials)
+                        .bind(DbUuid(existing_id))
+                        .execute(&mut transaction)
+                        .await?;
+                    }
+                    _ => (),
+                }
+                report.collaborators.record(action);
+            }
+
+            // A dry run makes every change so that it fails wherever the import would, then throws them away.
+            if dry_run {
+                transaction.rollback().await?;
+            } else {
+                transaction.commit().await?;
+            }
+            Ok(report)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::pool_helper::connect_async_pool;
+    use sqlx::Executor;
+
+    const SQLITE_MIGRATIONS: &[&str] = &[
+        include_str!("../../../../migrations_sqlite/2026-10-18-180000_initial_schema/up.sql"),
+        include_str!(
+            "../../../../migrations_sqlite/2026-10-18-190000_add_webhook_collaborators/up.sql"
+        ),
+        include_str!("../../../../migrations_sqlite/2026-10-18-200000_add_webhook_activity/up.sql"),
+        include_str!("../../../../migrations_sqlite/2026-10-18-210000_add_webhook_pause/up.sql"),
+        include_str!("../../../../migrations_sqlite/2026-10-18-220000_add_quiet_hours/up.sql"),
+    ];
+
+    /// An in-memory SQLite database only lives as long as its connection, so the pool holds a single connection.
+    async fn sqlite_repository() -> SqlxRepository {
+        let pool = connect_async_pool("sqlite://:memory:", 1, Duration::from_secs(5))
+            .await
+            .unwrap();
+        if let AsyncDbPool::Sqlite { pool: p, .. } = &pool {
+            for migration in SQLITE_MIGRATIONS {
+                p.execute(*migration).await.unwrap();
+            }
+        }
+        SqlxRepository::new(pool)
+    }
+
+    #[tokio::test]
+    async fn sqlite_round_trips_webhook() {
+        // Arrange
+        let repository = sqlite_repository().await;
+        let user = repository
+            .create_user(NewUser::new("@a:example.org", None))
+            .await
+            .unwrap();
+        let webhook = repository
+            .create_webhook(NewWebhook::new("user", vec![0, 1, 2], &user, None))
+            .await
+            .unwrap();
+        repository
+            .update_path_policy(
+                &webhook,
+                PathPolicy::Replace,
+                vec![String::from("/mnt=/media")],
+            )
+            .await
+            .unwrap();
+
+        // Act
+        let actual = repository
+            .rotate_password(
+                &webhook,
+                vec![3, 4],
+                String::new(),
+                Some(Duration::from_secs(60)),
+            )
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(user.id, actual.user_id);
+        assert_eq!(vec![3, 4], actual.password);
+        assert_eq!(Some(vec![0, 1, 2]), actual.previous_password);
+        assert!(actual.previous_password_expires_at.is_some());
+        assert_eq!(PathPolicy::Replace, actual.path_policy);
+        assert_eq!(vec![String::from("/mnt=/media")], actual.path_replacements);
+    }
+
+    #[tokio::test]
+    async fn sqlite_update_hide_spoilers_only_updates_given_room() {
+        // Arrange
+        let repository = sqlite_repository().await;
+        let user = repository
+            .create_user(NewUser::new("@a:example.org", None))
+            .await
+            .unwrap();
+        let webhook = repository
+            .create_webhook(NewWebhook::new("user", vec![0], &user, None))
+            .await
+            .unwrap();
+        for room_id in ["!a:example.org", "!b:example.org"] {
+            repository
+                .create_room(NewMatrixRoom::new(room_id, &webhook))
+                .await
+                .unwrap();
+        }
+
+        // Act
+        let actual = repository
+            .update_hide_spoilers(&webhook.id, Some("!a:example.org"), true)
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(1, actual);
+        let hidden: Vec<String> = MatrixRoomRepository::get_by_webhook_id(&repository, &webhook.id)
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|r| r.hide_spoilers)
+            .map(|r| r.room_id)
+            .collect();
+        assert_eq!(vec![String::from("!a:example.org")], hidden);
+    }
+
+    #[tokio::test]
+    async fn sqlite_update_paused_round_trips_pause() {
+        // Arrange
+        let repository = sqlite_repository().await;
+        let user = repository
+            .create_user(NewUser::new("@a:example.org", None))
+            .await
+            .unwrap();
+        let webhook = repository
+            .create_webhook(NewWebhook::new("user", vec![0], &user, None))
+            .await
+            .unwrap();
+        let until = Utc::now() + chrono::Duration::hours(1);
+
+        // Act
+        let actual = repository
+            .update_paused(&webhook, true, Some(until))
+            .await
+            .unwrap();
+
+        // Assert
+        assert!(actual.paused);
+        assert_eq!(Some(until), actual.paused_until);
+        assert!(actual.is_paused(Utc::now()));
+        assert!(!actual.is_paused(until));
+    }
+
+    #[tokio::test]
+    async fn sqlite_update_muted_until_round_trips_mute() {
+        // Arrange
+        let repository = sqlite_repository().await;
+        let user = repository
+            .create_user(NewUser::new("@a:example.org", None))
+            .await
+            .unwrap();
+        let webhook = repository
+            .create_webhook(NewWebhook::new("user", vec![0], &user, None))
+            .await
+            .unwrap();
+        repository
+            .create_room(NewMatrixRoom::new("!a:example.org", &webhook))
+            .await
+            .unwrap();
+        repository
+            .create_room(NewMatrixRoom::new("!b:example.org", &webhook))
+            .await
+            .unwrap();
+        let until = Utc::now() + chrono::Duration::hours(1);
+
+        // Act
+        let updated = repository
+            .update_muted_until(&webhook.id, Some("!a:example.org"), Some(until))
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(1, updated);
+        let rooms = MatrixRoomRepository::get_by_webhook_id(&repository, &webhook.id)
+            .await
+            .unwrap();
+        let muted = rooms
+            .iter()
+            .find(|r| r.room_id == "!a:example.org")
+            .unwrap();
+        let unmuted = rooms
+            .iter()
+            .find(|r| r.room_id == "!b:example.org")
+            .unwrap();
+        assert_eq!(Some(until), muted.muted_until);
+        assert!(muted.is_muted(Utc::now()));
+        assert_eq!(None, unmuted.muted_until);
+    }
+
+    #[tokio::test]
+    async fn sqlite_update_quiet_hours_round_trips_quiet_hours() {
+        // Arrange
+        let repository = sqlite_repository().await;
+        let user = repository
+            .create_user(NewUser::new("@a:example.org", None))
+            .await
+            .unwrap();
+        let webhook = repository
+            .create_webhook(NewWebhook::new("user", vec![0], &user, None))
+            .await
+            .unwrap();
+        let room = repository
+            .create_room(NewMatrixRoom::new("!a:example.org", &webhook))
+            .await
+            .unwrap();
+        let quiet_hours = QuietHours::parse("22:00-07:00", "Europe/Berlin").unwrap();
+        repository
+            .create_held_message(NewHeldMessage::new(&room, "Grabbed: Some Show"))
+            .await
+            .unwrap();
+
+        // Act
+        let actual = repository
+            .update_quiet_hours(&webhook.id, None, Some(&quiet_hours), true)
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(1, actual);
+        let room = MatrixRoomRepository::get_by_webhook_id(&repository, &webhook.id)
+            .await
+            .unwrap()
+            .remove(0);
+        assert_eq!(Some(quiet_hours), room.quiet_hours());
+        assert!(room.quiet_hours_bypass_health);
+        let held = HeldMessageRepository::get_all(&repository).await.unwrap();
+        assert_eq!(
+            vec![String::from("Grabbed: Some Show")],
+            held.into_iter().map(|m| m.summary).collect::<Vec<_>>()
+        );
+    }
+
+    #[tokio::test]
+    async fn sqlite_deleting_webhook_revokes_collaborators() {
+        // Arrange
+        let repository = sqlite_repository().await;
+        let owner = repository
+            .create_user(NewUser::new("@owner:example.org", None))
+            .await
+            .unwrap();
+        let viewer = repository
+            .create_user(NewUser::new("@viewer:example.org", Some(UserRole::Viewer)))
+            .await
+            .unwrap();
+        let webhook = repository
+            .create_webhook(NewWebhook::new("user", vec![0], &owner, None))
+            .await
+            .unwrap();
+        let mut collaborator = repository
+            .create_collaborator(NewWebhookCollaborator::new(&webhook, &viewer))
+            .await
+            .unwrap();
+        collaborator.manage_rooms = true;
+        let updated = repository.update_permissions(&collaborator).await.unwrap();
+
+        // Act
+        WebhookRepository::delete(&repository, &webhook)
+            .await
+            .unwrap();
+
+        // Assert
+        assert!(updated.manage_rooms);
+        assert!(CollaboratorRepository::get_all(&repository)
+            .await
+            .unwrap()
+            .is_empty());
+    }
+
+    #[tokio::test]
+    async fn sqlite_record_event_counts_events_and_failures() {
+        // Arrange
+        let repository = sqlite_repository().await;
+        let user = repository
+            .create_user(NewUser::new("@a:example.org", None))
+            .await
+            .unwrap();
+        let webhook = repository
+            .create_webhook(NewWebhook::new("user", vec![0], &user, None))
+            .await
+            .unwrap();
+
+        // Act
+        repository.record_event(&webhook.id, 0).await.unwrap();
+        repository.record_event(&webhook.id, 2).await.unwrap();
+        repository
+            .record_delivery_failure(&webhook.id)
+            .await
+            .unwrap();
+
+        // Assert
+        let actual = repository.get_webhook(&webhook.id).await.unwrap();
+        assert_eq!(2, actual.events_received);
+        assert_eq!(3, actual.delivery_failures);
+        assert!(actual.last_event_at.is_some());
+        assert!(actual.created_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn sqlite_round_trips_webhook_activity_timestamps() {
+        // Arrange
+        let repository = sqlite_repository().await;
+        let user = repository
+            .create_user(NewUser::new("@a:example.org", None))
+            .await
+            .unwrap();
+        let webhook = repository
+            .create_webhook(NewWebhook::new("user", vec![0], &user, None))
+            .await
+            .unwrap();
+        let before = Utc::now();
+
+        // Act
+        repository.record_event(&webhook.id, 0).await.unwrap();
+
+        // Assert
+        let actual = repository.get_webhook(&webhook.id).await.unwrap();
+        assert_eq!(webhook.created_at, actual.created_at);
+        let last_event_at = actual.last_event_at.unwrap();
+        assert!(before <= last_event_at && last_event_at <= Utc::now());
+    }
+
+    #[tokio::test]
+    async fn sqlite_import_creates_exported_records() {
+        // Arrange
+        let source = sqlite_repository().await;
+        let user = source
+            .create_user(NewUser::new("@a:example.org", None))
+            .await
+            .unwrap();
+        let webhook = source
+            .create_webhook(NewWebhook::new("user", vec![0], &user, None))
+            .await
+            .unwrap();
+        source
+            .create_room(NewMatrixRoom::new("!room:example.org", &webhook))
+            .await
+            .unwrap();
+        let document = ConfigurationExport::from_records(
+            UserRepository::get_all(&source).await.unwrap(),
+            WebhookRepository::get_all(&source).await.unwrap(),
+            MatrixRoomRepository::get_all(&source).await.unwrap(),
+            CollaboratorRepository::get_all(&source).await.unwrap(),
+        );
+        let destination = sqlite_repository().await;
+
+        // Act
+        let actual = destination
+            .import(document, ConflictPolicy::Fail, false)
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(1, actual.users.created);
+        assert_eq!(1, actual.webhooks.created);
+        assert_eq!(1, actual.rooms.created);
+        let imported = destination.get_webhook(&webhook.id).await.unwrap();
+        assert_eq!(user.id, imported.user_id);
+        assert_eq!(webhook.created_at, imported.created_at);
+    }
+
+    #[tokio::test]
+    async fn sqlite_import_dry_run_changes_nothing() {
+        // Arrange
+        let source = sqlite_repository().await;
+        source
+            .create_user(NewUser::new("@a:example.org", None))
+            .await
+            .unwrap();
+        let document = ConfigurationExport::from_records(
+            UserRepository::get_all(&source).await.unwrap(),
+            vec![],
+            vec![],
+            vec![],
+        );
+        let destination = sqlite_repository().await;
+
+        // Act
+        let actual = destination
+            .import(document, ConflictPolicy::Fail, true)
+            .await
+            .unwrap();
+
+        // Assert
+        assert_eq!(1, actual.users.created);
+        assert!(!destination.any().await.unwrap());
+    }
+
+    #[tokio::test]
+    async fn sqlite_import_returns_error_given_user_matching_two_users() {
+        // Arrange
+        let repository = sqlite_repository().await;
+        let a = repository
+            .create_user(NewUser::new("@a:example.org", None))
+            .await
+            .unwrap();
+        repository
+            .create_user(NewUser::new("@b:example.org", None))
+            .await
+            .unwrap();
+        // The export has user A's ID but user B's username.
+        let mut document = ConfigurationExport::from_records(vec![a], vec![], vec![], vec![]);
+        document.users[0].service_username = String::from("@b:example.org");
+
+        // Act
+        let actual = repository
+            .import(document, ConflictPolicy::Overwrite, false)
+            .await;
+
+        // Assert
+        assert!(actual.is_err());
+    }
+
+    #[tokio::test]
+    async fn sqlite_keeps_last_system_administrator() {
+        // Arrange
+        let repository = sqlite_repository().await;
+        let sysadmin = repository
+            .create_user(NewUser::new(
+                "@a:example.org",
+                Some(UserRole::SystemAdministrator),
+            ))
+            .await
+            .unwrap();
+
+        // Act
+        let demoted = repository
+            .update_role(&sysadmin, UserRole::Administrator)
+            .await
+            .unwrap();
+        let deleted = UserRepository::delete(&repository, &sysadmin)
+            .await
+            .unwrap();
+
+        // Assert
+        assert!(demoted.is_none());
+        assert!(!deleted);
+        let stored = UserRepository::try_get(&repository, &sysadmin.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(stored.user_role, UserRole::SystemAdministrator));
+    }
+
+    #[tokio::test]
+    async fn sqlite_demotes_system_administrator_given_another() {
+        // Arrange
+        let repository = sqlite_repository().await;
+        let sysadmin = repository
+            .create_user(NewUser::new(
+                "@a:example.org",
+                Some(UserRole::SystemAdministrator),
+            ))
+            .await
+            .unwrap();
+        repository
+            .create_user(NewUser::new(
+                "@b:example.org",
+                Some(UserRole::SystemAdministrator),
+            ))
+            .await
+            .unwrap();
+
+        // Act
+        let actual = repository
+            .update_role(&sysadmin, UserRole::Viewer)
+            .await
+            .unwrap();
+
+        // Assert
+        assert!(matches!(actual.unwrap().user_role, UserRole::Viewer));
+    }
+}
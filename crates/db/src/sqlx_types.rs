@@ -0,0 +1,138 @@
+//! The column types used by [crate::repositories::SqlxRepository] that `sqlx` can't map on both backends itself. Each
+//! wrapper stores its value natively on Postgres and as `TEXT` on SQLite, as the migrations declare the columns. The
+//! enums in [crate::enums] derive [sqlx::Type] themselves.
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use sqlx::encode::IsNull;
+use sqlx::error::BoxDynError;
+use sqlx::postgres::{PgArgumentBuffer, PgTypeInfo, PgValueRef};
+use sqlx::sqlite::{SqliteArgumentValue, SqliteTypeInfo, SqliteValueRef};
+use sqlx::{Decode, Encode, Postgres, Sqlite, Type};
+use uuid::Uuid;
+
+/// The format timestamps are stored in on SQLite, which is also how Diesel wrote them before the move to `sqlx`.
+const SQLITE_TIMESTAMP_FORMAT: &str = "%Y-%m-%d %H:%M:%S%.f";
+
+/// A [Uuid], stored as `UUID` on Postgres and as hyphenated `TEXT` on SQLite.
+#[derive(Debug, Clone, Copy)]
+pub struct DbUuid(pub Uuid);
+
+/// A [DateTime<Utc>], stored as `TIMESTAMPTZ` on Postgres and as UTC `TEXT` on SQLite.
+#[derive(Debug, Clone, Copy)]
+pub struct DbTimestamp(pub DateTime<Utc>);
+
+/// A list of strings, stored as `TEXT[]` on Postgres and as a JSON array in `TEXT` on SQLite.
+#[derive(Debug, Clone)]
+pub struct DbTextList(pub Vec<String>);
+
+/// Implement [Type] for `$wrapper` as `$pg` on Postgres and as `TEXT` on SQLite.
+macro_rules! portable_type {
+    ($wrapper:ty, $pg:ty) => {
+        impl Type<Postgres> for $wrapper {
+            fn type_info() -> PgTypeInfo {
+                <$pg as Type<Postgres>>::type_info()
+            }
+
+            fn compatible(ty: &PgTypeInfo) -> bool {
+                <$pg as Type<Postgres>>::compatible(ty)
+            }
+        }
+
+        impl Type<Sqlite> for $wrapper {
+            fn type_info() -> SqliteTypeInfo {
+                <String as Type<Sqlite>>::type_info()
+            }
+
+            fn compatible(ty: &SqliteTypeInfo) -> bool {
+                <String as Type<Sqlite>>::compatible(ty)
+            }
+        }
+    };
+}
+
+portable_type!(DbUuid, Uuid);
+portable_type!(DbTimestamp, DateTime<Utc>);
+portable_type!(DbTextList, Vec<String>);
+
+impl<'q> Encode<'q, Postgres> for DbUuid {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> IsNull {
+        <Uuid as Encode<'q, Postgres>>::encode_by_ref(&self.0, buf)
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for DbUuid {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+        <Uuid as Decode<'r, Postgres>>::decode(value).map(DbUuid)
+    }
+}
+
+impl<'q> Encode<'q, Sqlite> for DbUuid {
+    fn encode_by_ref(&self, buf: &mut Vec<SqliteArgumentValue<'q>>) -> IsNull {
+        <String as Encode<'q, Sqlite>>::encode(self.0.to_hyphenated().to_string(), buf)
+    }
+}
+
+impl<'r> Decode<'r, Sqlite> for DbUuid {
+    fn decode(value: SqliteValueRef<'r>) -> Result<Self, BoxDynError> {
+        let text = <&str as Decode<'r, Sqlite>>::decode(value)?;
+        Ok(DbUuid(Uuid::parse_str(text)?))
+    }
+}
+
+impl<'q> Encode<'q, Postgres> for DbTimestamp {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> IsNull {
+        <DateTime<Utc> as Encode<'q, Postgres>>::encode_by_ref(&self.0, buf)
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for DbTimestamp {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+        <DateTime<Utc> as Decode<'r, Postgres>>::decode(value).map(DbTimestamp)
+    }
+}
+
+impl<'q> Encode<'q, Sqlite> for DbTimestamp {
+    fn encode_by_ref(&self, buf: &mut Vec<SqliteArgumentValue<'q>>) -> IsNull {
+        let text = self
+            .0
+            .naive_utc()
+            .format(SQLITE_TIMESTAMP_FORMAT)
+            .to_string();
+        <String as Encode<'q, Sqlite>>::encode(text, buf)
+    }
+}
+
+impl<'r> Decode<'r, Sqlite> for DbTimestamp {
+    fn decode(value: SqliteValueRef<'r>) -> Result<Self, BoxDynError> {
+        let text = <&str as Decode<'r, Sqlite>>::decode(value)?;
+        let naive = NaiveDateTime::parse_from_str(text, SQLITE_TIMESTAMP_FORMAT)?;
+        Ok(DbTimestamp(DateTime::from_utc(naive, Utc)))
+    }
+}
+
+impl<'q> Encode<'q, Postgres> for DbTextList {
+    fn encode_by_ref(&self, buf: &mut PgArgumentBuffer) -> IsNull {
+        <Vec<String> as Encode<'q, Postgres>>::encode_by_ref(&self.0, buf)
+    }
+}
+
+impl<'r> Decode<'r, Postgres> for DbTextList {
+    fn decode(value: PgValueRef<'r>) -> Result<Self, BoxDynError> {
+        <Vec<String> as Decode<'r, Postgres>>::decode(value).map(DbTextList)
+    }
+}
+
+impl<'q> Encode<'q, Sqlite> for DbTextList {
+    fn encode_by_ref(&self, buf: &mut Vec<SqliteArgumentValue<'q>>) -> IsNull {
+        // Serializing a list of strings can't fail.
+        let text = serde_json::to_string(&self.0).unwrap_or_else(|_| String::from("[]"));
+        <String as Encode<'q, Sqlite>>::encode(text, buf)
+    }
+}
+
+impl<'r> Decode<'r, Sqlite> for DbTextList {
+    fn decode(value: SqliteValueRef<'r>) -> Result<Self, BoxDynError> {
+        let text = <&str as Decode<'r, Sqlite>>::decode(value)?;
+        Ok(DbTextList(serde_json::from_str(text)?))
+    }
+}
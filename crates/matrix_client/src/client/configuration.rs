@@ -1,12 +1,15 @@
-use anyhow::{bail, Context, Result};
+use anyhow::{bail, Context, Error, Result};
 use std::env;
 use std::fs;
 use std::path::PathBuf;
+use std::str::FromStr;
 use tracing::info;
 use url::Url;
 use yarrbot_common::environment::{
     get_env_var,
-    variables::{BOT_STORAGE_DIR, MATRIX_HOMESERVER_URL, MATRIX_PASS, MATRIX_USER},
+    variables::{
+        BOT_STORAGE_DIR, MATRIX_HOMESERVER_URL, MATRIX_PASS, MATRIX_USER, NOTIFICATION_IMAGES,
+    },
 };
 
 /// Settings to configure a [YarrbotMatrixClient].
@@ -17,28 +20,77 @@ pub(crate) struct YarrbotMatrixClientSettings {
     pub storage_dir: PathBuf,
 }
 
-impl Default for YarrbotMatrixClientSettings {
+impl YarrbotMatrixClientSettings {
     /// Create a [YarrbotMatrixClientSettings] by retrieving the values from the environment variables available to
-    /// Yarrbot.
-    ///
-    /// # Note
-    ///
-    /// This method will panic if the homeserver URL, username, password, or storage directory are not available.
-    fn default() -> Self {
-        YarrbotMatrixClientSettings {
-            url: get_homeserver_url().expect("Could not retrieve Matrix homeserver URL."),
-            username: get_username().expect("Could not retrieve Matrix username."),
-            password: get_password().expect("Could not retrieve Matrix user password."),
-            storage_dir: get_storage_dir()
-                .expect("Could not retrieve storage directory for Matrix data."),
+    /// Yarrbot. Returns an error if the homeserver URL, username, password, or storage directory are not available.
+    pub fn from_env() -> Result<Self> {
+        Ok(YarrbotMatrixClientSettings {
+            url: get_homeserver_url()?,
+            username: get_username()?,
+            password: get_password()?,
+            storage_dir: get_storage_dir("matrix")
+                .context("Could not retrieve storage directory for Matrix data.")?,
+        })
+    }
+}
+
+/// How images, such as series posters, are sent alongside notifications.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ImageMode {
+    /// Images are not sent.
+    Off,
+
+    /// Images are sent as a separate `m.image` message after the notification.
+    Attachment,
+
+    /// Images are uploaded to the homeserver and embedded in the notification's HTML.
+    Inline,
+}
+
+impl FromStr for ImageMode {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "off" | "none" => Ok(ImageMode::Off),
+            "attachment" => Ok(ImageMode::Attachment),
+            "inline" => Ok(ImageMode::Inline),
+            _ => bail!("\"{}\" is not a recognized image mode.", s),
         }
     }
 }
 
+/// Settings that control whether and how images are sent alongside notifications.
+pub(crate) struct NotificationImageSettings {
+    pub mode: ImageMode,
+    pub cache_dir: PathBuf,
+}
+
+impl NotificationImageSettings {
+    /// Create a [NotificationImageSettings] by retrieving the values from the environment variables available to
+    /// Yarrbot. Images are turned off if the mode is missing. Returns an error if the mode is invalid or the storage
+    /// directory is not available.
+    pub fn from_env() -> Result<Self> {
+        Ok(NotificationImageSettings {
+            mode: get_image_mode()?,
+            cache_dir: get_storage_dir("images")
+                .context("Could not retrieve storage directory for cached images.")?,
+        })
+    }
+}
+
+/// Check that the Matrix settings in the environment are present and valid without connecting to the homeserver.
+pub fn validate_matrix_configuration() -> Result<()> {
+    YarrbotMatrixClientSettings::from_env()?;
+    NotificationImageSettings::from_env()?;
+    Ok(())
+}
+
 fn get_homeserver_url() -> Result<Url> {
     let raw = get_env_var(MATRIX_HOMESERVER_URL)?;
     info!(homeserver_url = %raw, "Found homeserver URL.");
-    Url::parse(&raw).with_context(|| "Parsing of homeserver URL failed.")
+    Url::parse(&raw)
+        .with_context(|| format!("{} is not a valid URL: {}", MATRIX_HOMESERVER_URL, raw))
 }
 
 fn get_username() -> Result<String> {
@@ -51,7 +103,16 @@ fn get_password() -> Result<String> {
         .with_context(|| "Could not retrieve the Matrix password from the environment.")
 }
 
-fn get_storage_dir() -> Result<PathBuf> {
+fn get_image_mode() -> Result<ImageMode> {
+    match get_env_var(NOTIFICATION_IMAGES) {
+        Ok(raw) => ImageMode::from_str(&raw)
+            .with_context(|| format!("{} is not a valid image mode.", NOTIFICATION_IMAGES)),
+        Err(_) => Ok(ImageMode::Off),
+    }
+}
+
+/// Retrieve a subdirectory of Yarrbot's storage directory, creating it if it doesn't exist.
+fn get_storage_dir(subdirectory: &str) -> Result<PathBuf> {
     let mut path = match get_env_var(BOT_STORAGE_DIR) {
         Ok(s) => PathBuf::from(s),
         Err(_) => {
@@ -72,7 +133,7 @@ fn get_storage_dir() -> Result<PathBuf> {
     if metadata.permissions().readonly() {
         bail!("Storage directory path is readonly: {}", path.display());
     }
-    path.push("matrix");
+    path.push(subdirectory);
     match fs::metadata(&path) {
         Ok(_) => (),
         Err(_) => {
@@ -81,3 +142,27 @@ fn get_storage_dir() -> Result<PathBuf> {
     }
     Ok(path)
 }
+
+#[cfg(test)]
+mod tests {
+    use crate::client::configuration::ImageMode;
+    use std::str::FromStr;
+
+    #[test]
+    fn image_mode_from_str_ignores_case() {
+        // Act
+        let actual = ImageMode::from_str("Inline");
+
+        // Assert
+        assert_eq!(ImageMode::Inline, actual.unwrap());
+    }
+
+    #[test]
+    fn image_mode_from_str_returns_error_given_unknown_mode() {
+        // Act
+        let actual = ImageMode::from_str("sideways");
+
+        // Assert
+        assert!(actual.is_err());
+    }
+}
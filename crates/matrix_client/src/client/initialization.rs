@@ -1,12 +1,13 @@
-use crate::client::configuration::YarrbotMatrixClientSettings;
+use crate::client::configuration::{NotificationImageSettings, YarrbotMatrixClientSettings};
 use crate::client::YarrbotMatrixClient;
-use crate::send_handler::SendMessageActor;
+use crate::health::MatrixHealth;
+use crate::send_handler::{ImageCache, SendMessageActor};
 use crate::{RoomMessageActor, StrippedStateMemberActor};
 use actix::{Actor, Addr};
 use anyhow::{Context, Result};
 use matrix_sdk::{Client, ClientConfig, SyncSettings};
 use tracing::debug;
-use yarrbot_db::DbPool;
+use yarrbot_db::repositories::Repositories;
 
 /// Initialize the [Client] belonging to the Matrix SDK.
 ///
@@ -19,7 +20,7 @@ pub async fn initialize_matrix_sdk_client() -> Result<Client> {
         username,
         password,
         storage_dir,
-    } = YarrbotMatrixClientSettings::default();
+    } = YarrbotMatrixClientSettings::from_env()?;
     debug!("Logging into the homeserver.");
     let client_config = ClientConfig::new().store_path(storage_dir);
     let client: Client = Client::new_with_config(url, client_config)?;
@@ -41,27 +42,37 @@ pub async fn initialize_matrix_sdk_client() -> Result<Client> {
 /// Initialize the Matrix components of Yarrbot.
 pub fn initialize_matrix_actors(
     client: Client,
-    pool: DbPool,
+    repositories: Repositories,
+    health: MatrixHealth,
 ) -> Result<(
     Addr<RoomMessageActor>,
     Addr<SendMessageActor>,
     Addr<StrippedStateMemberActor>,
 )> {
-    let send_addr = SendMessageActor::new(client.clone()).start();
+    let NotificationImageSettings { mode, cache_dir } = NotificationImageSettings::from_env()?;
+    let image_cache = ImageCache::new(cache_dir)?;
+    let send_addr = SendMessageActor::new(
+        client.clone(),
+        repositories.clone(),
+        mode,
+        image_cache,
+        health,
+    )
+    .start();
     Ok((
-        RoomMessageActor::new(client.clone(), pool.clone(), send_addr.clone()).start(),
+        RoomMessageActor::new(client.clone(), repositories.clone(), send_addr.clone()).start(),
         send_addr,
-        StrippedStateMemberActor::new(client, pool).start(),
+        StrippedStateMemberActor::new(client, repositories).start(),
     ))
 }
 
 /// Initialize Yarrbot's wrapper around the Matrix SDK [Client].
 pub async fn initialize_yarrbot_matrix_client(
     client: Client,
-    pool: DbPool,
+    repositories: Repositories,
     send_addr: Addr<SendMessageActor>,
 ) -> Result<YarrbotMatrixClient> {
-    YarrbotMatrixClient::new(client.clone(), pool.clone(), send_addr.clone())
+    YarrbotMatrixClient::new(client.clone(), repositories.clone(), send_addr.clone())
         .await
         .context("Failed to create a YarrbotMatrixClient.")
 }
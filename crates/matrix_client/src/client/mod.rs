@@ -5,7 +5,7 @@ mod on_stripped_state_member;
 mod room_message_actor;
 mod stripped_state_member_actor;
 
-use crate::message::Message;
+use crate::message::{Message, MessageData};
 use crate::{MatrixClient, SendMessageActor, SendToMatrix};
 use actix::Addr;
 use anyhow::{Context, Result};
@@ -13,15 +13,18 @@ use async_trait::async_trait;
 use futures::stream::FuturesUnordered;
 use futures::StreamExt;
 use itertools::Itertools;
+use matrix_sdk::ruma::api::client::r0::room::create_room::{
+    Request as CreateRoomRequest, RoomPreset,
+};
+use matrix_sdk::ruma::events::room::message::MessageEventContent;
+use matrix_sdk::ruma::identifiers::UserId;
 use matrix_sdk::{ruma::identifiers::RoomId, Client};
 use std::convert::TryFrom;
-use tokio::task::spawn_blocking;
 use tracing::{error, error_span, info};
 use tracing_futures::Instrument;
-use yarrbot_db::actions::matrix_room_actions::MatrixRoomActions;
-use yarrbot_db::models::MatrixRoom;
-use yarrbot_db::DbPool;
+use yarrbot_db::repositories::Repositories;
 
+pub use configuration::{validate_matrix_configuration, ImageMode};
 pub use initialization::{
     initialize_matrix_actors, initialize_matrix_sdk_client, initialize_yarrbot_matrix_client,
 };
@@ -33,15 +36,14 @@ pub use stripped_state_member_actor::StrippedStateMemberActor;
 #[derive(Clone)]
 pub struct YarrbotMatrixClient {
     client: Client,
-    pool: DbPool,
+    repositories: Repositories,
     message_addr: Addr<SendMessageActor>,
 }
 
 impl YarrbotMatrixClient {
     async fn init(&self) -> Result<()> {
-        let conn = self.pool.get()?;
         info!("Retrieving list of MatrixRooms from the database.");
-        let matrix_rooms = spawn_blocking(move || MatrixRoom::get_many(&conn, None)).await??;
+        let matrix_rooms = self.repositories.rooms.get_many(None).await?;
         let join_room_tasks = matrix_rooms
             .iter()
             .map(|r| r.room_id.as_str())
@@ -63,15 +65,15 @@ impl YarrbotMatrixClient {
     }
 
     /// Create a new [YarrbotMatrixClient] and connect it to a Matrix homeserver.
-    /// The client will attempt to join all [MatrixRoom]s that Yarrbot is configured for.
+    /// The client will attempt to join all Matrix rooms that Yarrbot is configured for.
     pub(crate) async fn new(
         client: Client,
-        pool: DbPool,
+        repositories: Repositories,
         message_addr: Addr<SendMessageActor>,
     ) -> Result<Self> {
         let yarrbot_matrix_client = YarrbotMatrixClient {
             client,
-            pool,
+            repositories,
             message_addr,
         };
         yarrbot_matrix_client.init().await?;
@@ -83,12 +85,50 @@ impl YarrbotMatrixClient {
 impl MatrixClient for YarrbotMatrixClient {
     async fn send_message(&self, message: Message) -> Result<()> {
         self.message_addr
-            .try_send(SendToMatrix::new(
-                message.destination.as_str(),
-                message.message_data,
-            ))
+            .try_send(SendToMatrix::from(message))
             .context("Failed to send message over mpsc channel.")
     }
+
+    async fn send_direct_message(&self, user_id: &str, message_data: MessageData) -> Result<()> {
+        let user = UserId::try_from(user_id)?;
+        let room_id = match find_direct_room(&self.client, &user).await {
+            Some(r) => r,
+            None => {
+                info!(user_id = %user, "Creating a direct message room.");
+                create_direct_room(&self.client, &user).await?
+            }
+        };
+        // A newly created room won't be in the client's store until the next sync, which room_send handles.
+        self.client
+            .room_send(&room_id, MessageEventContent::from(&message_data), None)
+            .await
+            .context("Failed to send direct message.")?;
+        Ok(())
+    }
+}
+
+/// Find a room containing only Yarrbot and the given user.
+async fn find_direct_room(client: &Client, user: &UserId) -> Option<RoomId> {
+    for room in client.joined_rooms() {
+        // Note: room.is_direct() doesn't return true when expected, so check the members instead.
+        match room.members().await {
+            Ok(m) if m.len() == 2 && m.iter().any(|member| member.user_id() == user) => {
+                return Some(room.room_id().clone())
+            }
+            Ok(_) => {}
+            Err(e) => error!(error = ?e, "Failed to retrieve the members of a room."),
+        }
+    }
+    None
+}
+
+async fn create_direct_room(client: &Client, user: &UserId) -> Result<RoomId> {
+    let invite = [user.clone()];
+    let mut request = CreateRoomRequest::new();
+    request.invite = &invite;
+    request.is_direct = true;
+    request.preset = Some(RoomPreset::TrustedPrivateChat);
+    Ok(client.create_room(request).await?.room_id)
 }
 
 async fn join_room(client: &Client, id: &str) -> Result<()> {
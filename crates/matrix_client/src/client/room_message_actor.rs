@@ -15,22 +15,27 @@ use matrix_sdk::{
 use std::collections::VecDeque;
 use std::sync::Arc;
 use tracing::{debug, error, info};
-use yarrbot_db::DbPool;
+use yarrbot_common::metrics::{COMMANDS_EXECUTED, UNKNOWN};
+use yarrbot_db::repositories::Repositories;
 
 const YARRBOT_COMMAND: &str = "!yarrbot";
 
 /// Actor that responds to Matrix messages sent in rooms that Yarrbot is a member of.
 pub struct RoomMessageActor {
     client: Client,
-    pool: DbPool,
+    repositories: Repositories,
     send_addr: Addr<SendMessageActor>,
 }
 
 impl RoomMessageActor {
-    pub fn new(client: Client, pool: DbPool, send_addr: Addr<SendMessageActor>) -> Self {
+    pub fn new(
+        client: Client,
+        repositories: Repositories,
+        send_addr: Addr<SendMessageActor>,
+    ) -> Self {
         RoomMessageActor {
             client,
-            pool,
+            repositories,
             send_addr,
         }
     }
@@ -74,7 +79,7 @@ impl Handler<OnRoomMessage> for RoomMessageActor {
     fn handle(&mut self, msg: OnRoomMessage, ctx: &mut Self::Context) -> Self::Result {
         let fut = on_room_message(
             self.client.clone(),
-            self.pool.clone(),
+            self.repositories.clone(),
             msg.room,
             msg.event,
             self.send_addr.clone(),
@@ -86,10 +91,10 @@ impl Handler<OnRoomMessage> for RoomMessageActor {
 }
 
 /// Handle a message sent to a [Room] that the bot is a member of.
-#[tracing::instrument(skip(client, pool, room, event, send_addr), fields(event.sender = %event.sender, room.room_id = %room.room_id(), room.name))]
+#[tracing::instrument(skip(client, repositories, room, event, send_addr), fields(event.sender = %event.sender, room.room_id = %room.room_id(), room.name))]
 async fn on_room_message(
     client: Client,
-    pool: DbPool,
+    repositories: Repositories,
     room: Room,
     event: SyncMessageEvent<MessageEventContent>,
     send_addr: Addr<SendMessageActor>,
@@ -140,7 +145,7 @@ async fn on_room_message(
                     },
                 };
                 let data: VecDeque<&str> = split.collect();
-                execute_command(&client, &pool, key.as_str(), metadata, data)
+                execute_command(&client, &repositories, key.as_str(), metadata, data)
                     .await
                     .unwrap_or_else(|e| e.into())
             } else {
@@ -164,14 +169,24 @@ async fn on_room_message(
 
 async fn execute_command(
     client: &Client,
-    pool: &DbPool,
+    repositories: &Repositories,
     command: &str,
     metadata: CommandMetadata,
     data: VecDeque<&str>,
 ) -> Result<MessageData> {
+    COMMANDS_EXECUTED
+        .with_label_values(&[match command {
+            "ping" | "webhook" | "user" | "export" | "sourcecode" | "help" => command,
+            _ => UNKNOWN,
+        }])
+        .inc();
     let result = match command {
         "ping" => ping_command::get_message(),
-        "webhook" => webhook_command::handle_webhook_command(metadata, client, pool, data).await?,
+        "webhook" => {
+            webhook_command::handle_webhook_command(metadata, client, repositories, data).await?
+        }
+        "user" => user_command::handle_user_command(metadata, repositories, data).await?,
+        "export" => export_command::handle_export_command(metadata, repositories).await?,
         "sourcecode" => sourcecode_command::get_message(),
         "help" => help_command::get_message(),
         _ => {
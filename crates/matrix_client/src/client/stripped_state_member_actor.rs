@@ -9,22 +9,22 @@ use rand::distributions::Uniform;
 use rand::prelude::*;
 use rand::SeedableRng;
 use std::time::Duration;
-use tokio::task::spawn_blocking;
 use tokio::time::sleep;
 use tracing::{debug, error, info, warn};
-use yarrbot_db::actions::user_actions::UserActions;
-use yarrbot_db::models::User;
-use yarrbot_db::DbPool;
+use yarrbot_db::repositories::Repositories;
 
 /// Responds to stripped state events (e.g. Yarrbot is invited to a room).
 pub struct StrippedStateMemberActor {
     client: Client,
-    pool: DbPool,
+    repositories: Repositories,
 }
 
 impl StrippedStateMemberActor {
-    pub fn new(client: Client, pool: DbPool) -> Self {
-        StrippedStateMemberActor { client, pool }
+    pub fn new(client: Client, repositories: Repositories) -> Self {
+        StrippedStateMemberActor {
+            client,
+            repositories,
+        }
     }
 
     /// Configures the Matrix SDK [Client] to listen for stripped state events.
@@ -64,8 +64,12 @@ impl Handler<OnStrippedStateMember> for StrippedStateMemberActor {
     type Result = ();
 
     fn handle(&mut self, msg: OnStrippedStateMember, ctx: &mut Self::Context) -> Self::Result {
-        let fut =
-            on_stripped_state_member(self.client.clone(), self.pool.clone(), msg.room, msg.event);
+        let fut = on_stripped_state_member(
+            self.client.clone(),
+            self.repositories.clone(),
+            msg.room,
+            msg.event,
+        );
         let actor = fut.into_actor(self);
 
         ctx.spawn(actor);
@@ -73,10 +77,10 @@ impl Handler<OnStrippedStateMember> for StrippedStateMemberActor {
 }
 
 /// Respond to stripped state events; mostly just responding to invites.
-#[tracing::instrument(skip(client, pool, room, room_member), fields(room.room_id = %room.room_id(), room.name, username = %room_member.sender))]
+#[tracing::instrument(skip(client, repositories, room, room_member), fields(room.room_id = %room.room_id(), room.name, username = %room_member.sender))]
 async fn on_stripped_state_member(
     client: Client,
-    pool: DbPool,
+    repositories: Repositories,
     room: Room,
     room_member: StrippedStateEvent<MemberEventContent>,
 ) {
@@ -93,7 +97,7 @@ async fn on_stripped_state_member(
     if let Room::Invited(room) = room {
         // Don't let users that aren't admins invite the bot to rooms.
         let username = room_member.sender.as_str();
-        match user_exists(&pool, username).await {
+        match user_exists(&repositories, username).await {
             Ok(exists) => {
                 if !exists {
                     match room.reject_invitation().await {
@@ -149,11 +153,8 @@ async fn on_stripped_state_member(
     }
 }
 
-async fn user_exists(pool: &DbPool, username: &str) -> Result<bool> {
-    let conn = pool.get()?;
-    let username2 = String::from(username);
-    let u = spawn_blocking(move || User::try_get_by_username(&conn, username2.as_str())).await??;
-    match u {
+async fn user_exists(repositories: &Repositories, username: &str) -> Result<bool> {
+    match repositories.users.try_get_by_username(username).await? {
         Some(u) => {
             debug!(username = %username, id = %u.id.to_string(), "User exists.");
             Ok(true)
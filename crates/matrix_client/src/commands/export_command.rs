@@ -0,0 +1,50 @@
+//! `!yarrbot export`: send a System Administrator a backup of Yarrbot's configuration.
+
+use crate::commands::CommandMetadata;
+use crate::message::{MessageData, MessageFile};
+use anyhow::{ensure, Result};
+use tracing::{info, warn};
+use yarrbot_db::enums::UserRole;
+use yarrbot_db::export::export;
+use yarrbot_db::repositories::Repositories;
+
+/// Export all users, webhooks, rooms and collaborators and attach the export to the response. The export includes
+/// hashed webhook passwords, so it is only sent to System Administrators in a private room. Secrets that aren't hashed
+/// are never sent over Matrix.
+#[tracing::instrument(skip(repositories))]
+pub async fn handle_export_command(
+    metadata: CommandMetadata,
+    repositories: &Repositories,
+) -> Result<MessageData> {
+    info!("Received export command.");
+    ensure!(
+        metadata.is_direct_message,
+        "Yarrbot will only respond to export commands in a private room."
+    );
+    let user = repositories
+        .users
+        .try_get_by_username(&metadata.user)
+        .await?;
+    if !matches!(user, Some(u) if matches!(u.user_role, UserRole::SystemAdministrator)) {
+        warn!("User attempted to export the configuration but is not authorized to do so.");
+        return Ok(MessageData::from(
+            "Only System Administrators may export the configuration.",
+        ));
+    }
+    let document = export(repositories, false).await?;
+
+    let name = format!(
+        "yarrbot-export-{}.json",
+        document.exported_at.format("%Y%m%dT%H%M%SZ")
+    );
+    let mut message_data = MessageData::from(
+        "Yarrbot's configuration is attached. It contains hashed webhook passwords, so store it somewhere safe. \
+        *arr API keys and HMAC secrets are left out; use `yarrbot export --include-secrets` to back them up.",
+    );
+    message_data.file = Some(MessageFile::new(
+        &name,
+        mime::APPLICATION_JSON,
+        serde_json::to_vec_pretty(&document)?,
+    ));
+    Ok(message_data)
+}
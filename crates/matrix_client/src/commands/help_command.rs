@@ -14,7 +14,91 @@ pub fn get_message() -> MessageData {
         "!yarrbot webhook add roomOrAliasId username [password] [serverName]",
     );
     builder.add_key_value_with_code("List configured webhooks", "!yarrbot webhook list");
+    builder.add_key_value_with_code(
+        "Show a webhook's details and activity",
+        "!yarrbot webhook info webhookId",
+    );
+    builder.add_key_value_with_code(
+        "Change a webhook's server name or username",
+        "!yarrbot webhook edit webhookId [name=serverName] [username=username]",
+    );
     builder.add_key_value_with_code("Remove a webhook", "!yarrbot webhook remove webhookId");
+    builder.add_key_value_with_code(
+        "Post a webhook's notifications to another room, or stop posting to one",
+        "!yarrbot webhook room (add|remove) webhookId roomOrAliasId",
+    );
+    builder.add_key_value_with_code(
+        "List the rooms a webhook posts to",
+        "!yarrbot webhook room list webhookId",
+    );
+    builder.add_key_value_with_code(
+        "Connect a webhook to its *arr for poster images",
+        "!yarrbot webhook arr webhookId (arrUrl apiKey|clear)",
+    );
+    builder.add_key_value_with_code(
+        "Hide episode titles in a webhook's rooms",
+        "!yarrbot webhook spoilers webhookId (on|off) [roomId]",
+    );
+    builder.add_key_value_with_code(
+        "Pause a webhook without removing it, or resume it",
+        "!yarrbot webhook (pause webhookId [duration]|resume webhookId)",
+    );
+    builder.add_key_value_with_code(
+        "Mute a webhook's rooms for a while, or unmute them",
+        "!yarrbot webhook (mute webhookId duration|unmute webhookId) [roomId]",
+    );
+    builder.add_key_value_with_code(
+        "Hold a webhook's notifications overnight and post a summary afterwards",
+        "!yarrbot webhook quiet webhookId (start-end timeZone [health]|off) [roomId]",
+    );
+    builder.add_key_value_with_code(
+        "Choose how paths are shown in a webhook's notifications",
+        "!yarrbot webhook paths webhookId (full|basename|hide|replace from=to...)",
+    );
+    builder.add_key_value_with_code(
+        "Change how requests to a webhook are authenticated",
+        "!yarrbot webhook auth webhookId (basic|url|header|hmac)",
+    );
+    builder.add_key_value_with_code(
+        "Replace a webhook's password",
+        "!yarrbot webhook rotate webhookId [now] [password]",
+    );
+    builder.add_key_value_with_code(
+        "Limit which networks may call a webhook",
+        "!yarrbot webhook sources webhookId (any|network...)",
+    );
+    builder.add_key_value_with_code(
+        "Share a webhook, optionally letting them change it",
+        "!yarrbot webhook grant webhookId userId [rooms|filters|credentials...]",
+    );
+    builder.add_key_value_with_code(
+        "Stop sharing a webhook, or take back some of its permissions",
+        "!yarrbot webhook revoke webhookId userId [rooms|filters|credentials...]",
+    );
+    builder.add_key_value_with_code(
+        "Give a webhook to someone else",
+        "!yarrbot webhook transfer webhookId userId",
+    );
+    builder.add_key_value_with_code(
+        "Allow someone to use Yarrbot (System Administrators only)",
+        "!yarrbot user add userId [administrator|system_administrator|viewer]",
+    );
+    builder.add_key_value_with_code(
+        "List the users of Yarrbot (System Administrators only)",
+        "!yarrbot user list",
+    );
+    builder.add_key_value_with_code(
+        "Remove a user and their webhooks (System Administrators only)",
+        "!yarrbot user remove userId",
+    );
+    builder.add_key_value_with_code(
+        "Change a user's role (System Administrators only)",
+        "!yarrbot user role userId (administrator|system_administrator|viewer)",
+    );
+    builder.add_key_value_with_code(
+        "Export the configuration (System Administrators only)",
+        "!yarrbot export",
+    );
 
     builder.to_message_data()
 }
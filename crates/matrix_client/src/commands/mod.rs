@@ -1,13 +1,47 @@
 //! Commands that administrators can send to Yarrbot.
 
+pub mod export_command;
 pub mod help_command;
 pub mod ping_command;
 pub mod sourcecode_command;
+pub mod user;
+pub mod user_command;
 pub mod webhook;
 pub mod webhook_command;
 
+use crate::message::MessageData;
+use tracing::error;
+use yarrbot_db::models::User;
+use yarrbot_db::repositories::Repositories;
+
 #[derive(Debug)]
 pub struct CommandMetadata {
     pub user: String,
     pub is_direct_message: bool,
 }
+
+/// Retrieve the [User] a command refers to by their Matrix ID, or the response to send if they can't be found.
+async fn get_target_user(
+    repositories: &Repositories,
+    username: Option<&str>,
+) -> Result<User, MessageData> {
+    let username = match username {
+        Some(u) => u,
+        None => return Err(MessageData::from("No user specified.")),
+    };
+    match repositories.users.try_get_by_username(username).await {
+        Ok(Some(u)) => Ok(u),
+        Ok(None) => Err(MessageData::from(
+            format!("{} is not a user.", username).as_str(),
+        )),
+        Err(e) => {
+            error!(
+                error = ?e,
+                "Encountered an error while retrieving user information from the database."
+            );
+            Err(MessageData::from(
+                "Encountered an error while retrieving user information.",
+            ))
+        }
+    }
+}
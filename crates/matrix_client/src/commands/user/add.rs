@@ -0,0 +1,154 @@
+//! Supporting functions for adding users.
+
+use super::{get_system_administrator, parse_role, role_name};
+use crate::commands::CommandMetadata;
+use crate::is_user_id;
+use crate::message::MessageData;
+use std::collections::VecDeque;
+use tracing::{error, info};
+use yarrbot_db::enums::UserRole;
+use yarrbot_db::models::NewUser;
+use yarrbot_db::repositories::Repositories;
+
+/// Allow a Matrix user to interact with Yarrbot, as an Administrator unless another role is given.
+#[tracing::instrument(skip(repositories, data), fields(target_user))]
+pub async fn handle_add(
+    metadata: CommandMetadata,
+    repositories: &Repositories,
+    mut data: VecDeque<&str>,
+) -> MessageData {
+    info!("Received user add command.");
+    if let Err(message) = get_system_administrator(repositories, &metadata.user).await {
+        return message;
+    }
+
+    let username = match data.pop_front() {
+        Some(u) if is_user_id(u) => {
+            tracing::Span::current().record("target_user", &u);
+            u
+        }
+        Some(_) => return MessageData::from("That is not a valid Matrix user ID."),
+        None => return MessageData::from("No user specified."),
+    };
+    let role =
+        match data.pop_front() {
+            Some(r) => match parse_role(r) {
+                Some(role) => role,
+                None => return MessageData::from(
+                    "The role must be \"system_administrator\", \"administrator\" or \"viewer\".",
+                ),
+            },
+            None => UserRole::Administrator,
+        };
+
+    match repositories.users.try_get_by_username(username).await {
+        Ok(Some(_)) => {
+            return MessageData::from(format!("{} is already a user.", username).as_str())
+        }
+        Ok(None) => (),
+        Err(e) => {
+            error!(
+                error = ?e,
+                "Encountered an error while retrieving user information from the database."
+            );
+            return MessageData::from("Encountered an error while retrieving user information.");
+        }
+    }
+    match repositories
+        .users
+        .create_user(NewUser::new(username, Some(role)))
+        .await
+    {
+        Ok(user) => {
+            info!("Added user.");
+            MessageData::from(
+                format!(
+                    "Added {} as {}.",
+                    user.service_username,
+                    role_name(&user.user_role)
+                )
+                .as_str(),
+            )
+        }
+        Err(e) => {
+            error!(error = ?e, "Encountered an error while adding a user.");
+            MessageData::from("Failed to add the user. Please try again.")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn handle_add_refuses_administrators() {
+        // Arrange
+        let repositories = Repositories::in_memory();
+        repositories
+            .users
+            .create_user(NewUser::new("@admin:example.org", None))
+            .await
+            .unwrap();
+        let metadata = CommandMetadata {
+            user: String::from("@admin:example.org"),
+            is_direct_message: true,
+        };
+
+        // Act
+        let actual = handle_add(
+            metadata,
+            &repositories,
+            VecDeque::from(["@new:example.org"]),
+        )
+        .await;
+
+        // Assert
+        assert_eq!("Only System Administrators may manage users.", actual.plain);
+        assert!(repositories
+            .users
+            .try_get_by_username("@new:example.org")
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn handle_add_creates_user_with_given_role() {
+        // Arrange
+        let repositories = Repositories::in_memory();
+        repositories
+            .users
+            .create_user(NewUser::new(
+                "@sysadmin:example.org",
+                Some(UserRole::SystemAdministrator),
+            ))
+            .await
+            .unwrap();
+        let metadata = CommandMetadata {
+            user: String::from("@sysadmin:example.org"),
+            is_direct_message: true,
+        };
+
+        // Act
+        let actual = handle_add(
+            metadata,
+            &repositories,
+            VecDeque::from(["@new:example.org", "sysadmin"]),
+        )
+        .await;
+
+        // Assert
+        assert_eq!(
+            "Added @new:example.org as system_administrator.",
+            actual.plain
+        );
+        let user = repositories
+            .users
+            .try_get_by_username("@new:example.org")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(user.user_role, UserRole::SystemAdministrator));
+    }
+}
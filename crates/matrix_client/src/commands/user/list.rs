@@ -0,0 +1,33 @@
+//! Supporting functions for listing users.
+
+use super::{get_system_administrator, role_name};
+use crate::commands::CommandMetadata;
+use crate::message::{MessageData, MessageDataBuilder};
+use tracing::{error, info};
+use yarrbot_db::repositories::Repositories;
+
+/// List every user allowed to interact with Yarrbot along with their role.
+#[tracing::instrument(skip(repositories))]
+pub async fn handle_list(metadata: CommandMetadata, repositories: &Repositories) -> MessageData {
+    info!("Received user list command.");
+    if let Err(message) = get_system_administrator(repositories, &metadata.user).await {
+        return message;
+    }
+
+    let users = match repositories.users.get_all().await {
+        Ok(u) => u,
+        Err(e) => {
+            error!(error = ?e, "Encountered an error while retrieving users.");
+            return MessageData::from(
+                "Couldn't retrieve the list of users, please try again later.",
+            );
+        }
+    };
+    let mut builder = MessageDataBuilder::new();
+    builder.add_line("Users:");
+    for user in &users {
+        builder.add_key_value_with_code(&user.service_username, role_name(&user.user_role));
+    }
+
+    builder.to_message_data()
+}
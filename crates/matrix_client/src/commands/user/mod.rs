@@ -0,0 +1,79 @@
+use crate::message::MessageData;
+use tracing::{error, warn};
+use yarrbot_db::enums::UserRole;
+use yarrbot_db::models::User;
+use yarrbot_db::repositories::Repositories;
+
+mod add;
+mod list;
+mod remove;
+mod role;
+
+pub use add::handle_add;
+pub use list::handle_list;
+pub use remove::handle_remove;
+pub use role::handle_role;
+
+/// Retrieve the [User] sending a user command, or the response to send if they aren't a System Administrator.
+async fn get_system_administrator(
+    repositories: &Repositories,
+    username: &str,
+) -> Result<User, MessageData> {
+    match repositories.users.try_get_by_username(username).await {
+        Ok(Some(u)) if matches!(u.user_role, UserRole::SystemAdministrator) => Ok(u),
+        Ok(_) => {
+            warn!("User attempted to manage users but is not authorized to do so.");
+            Err(MessageData::from(
+                "Only System Administrators may manage users.",
+            ))
+        }
+        Err(e) => {
+            error!(
+                error = ?e,
+                "Encountered an error while retrieving user information from the database."
+            );
+            Err(MessageData::from(
+                "Encountered an error while retrieving user information.",
+            ))
+        }
+    }
+}
+
+/// Parse a role given to a user command.
+fn parse_role(role: &str) -> Option<UserRole> {
+    match role.to_lowercase().as_str() {
+        "system_administrator" | "sysadmin" => Some(UserRole::SystemAdministrator),
+        "administrator" | "admin" => Some(UserRole::Administrator),
+        "viewer" => Some(UserRole::Viewer),
+        _ => None,
+    }
+}
+
+/// The name of a role as it is given to user commands.
+fn role_name(role: &UserRole) -> &'static str {
+    match role {
+        UserRole::SystemAdministrator => "system_administrator",
+        UserRole::Administrator => "administrator",
+        UserRole::Viewer => "viewer",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_role_accepts_short_names() {
+        // Act
+        let sysadmin = parse_role("SysAdmin");
+        let admin = parse_role("admin");
+        let viewer = parse_role("Viewer");
+        let unknown = parse_role("owner");
+
+        // Assert
+        assert!(matches!(sysadmin, Some(UserRole::SystemAdministrator)));
+        assert!(matches!(admin, Some(UserRole::Administrator)));
+        assert!(matches!(viewer, Some(UserRole::Viewer)));
+        assert!(unknown.is_none());
+    }
+}
@@ -0,0 +1,81 @@
+//! Supporting functions for removing users.
+
+use super::get_system_administrator;
+use crate::commands::{get_target_user, CommandMetadata};
+use crate::message::MessageData;
+use std::collections::VecDeque;
+use tracing::{error, info};
+use yarrbot_db::repositories::Repositories;
+
+/// Remove a user along with their webhooks. The last System Administrator can't be removed.
+#[tracing::instrument(skip(repositories, data), fields(target_user))]
+pub async fn handle_remove(
+    metadata: CommandMetadata,
+    repositories: &Repositories,
+    mut data: VecDeque<&str>,
+) -> MessageData {
+    info!("Received user remove command.");
+    if let Err(message) = get_system_administrator(repositories, &metadata.user).await {
+        return message;
+    }
+
+    let user = match get_target_user(repositories, data.pop_front()).await {
+        Ok(u) => u,
+        Err(message) => return message,
+    };
+    tracing::Span::current().record("target_user", &user.service_username.as_str());
+    match repositories.users.delete(&user).await {
+        Ok(false) => MessageData::from(
+            "You can't remove the last System Administrator. Make someone else a System Administrator first.",
+        ),
+        Ok(true) => {
+            info!("Removed user.");
+            MessageData::from(
+                format!("Removed {} and their webhooks.", user.service_username).as_str(),
+            )
+        }
+        Err(e) => {
+            error!(error = ?e, "Encountered an error while removing a user.");
+            MessageData::from("Failed to remove the user. Please try again.")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yarrbot_db::enums::UserRole;
+    use yarrbot_db::models::NewUser;
+
+    #[tokio::test]
+    async fn handle_remove_refuses_last_system_administrator() {
+        // Arrange
+        let repositories = Repositories::in_memory();
+        repositories
+            .users
+            .create_user(NewUser::new(
+                "@sysadmin:example.org",
+                Some(UserRole::SystemAdministrator),
+            ))
+            .await
+            .unwrap();
+        let metadata = CommandMetadata {
+            user: String::from("@sysadmin:example.org"),
+            is_direct_message: true,
+        };
+
+        // Act
+        let actual = handle_remove(
+            metadata,
+            &repositories,
+            VecDeque::from(["@sysadmin:example.org"]),
+        )
+        .await;
+
+        // Assert
+        assert!(actual
+            .plain
+            .starts_with("You can't remove the last System Administrator."));
+        assert!(repositories.users.any().await.unwrap());
+    }
+}
@@ -0,0 +1,138 @@
+//! Supporting functions for changing a user's role.
+
+use super::{get_system_administrator, parse_role, role_name};
+use crate::commands::{get_target_user, CommandMetadata};
+use crate::message::MessageData;
+use std::collections::VecDeque;
+use tracing::{error, info};
+use yarrbot_db::repositories::Repositories;
+
+/// Change a user's role. The last System Administrator can't be demoted.
+#[tracing::instrument(skip(repositories, data), fields(target_user))]
+pub async fn handle_role(
+    metadata: CommandMetadata,
+    repositories: &Repositories,
+    mut data: VecDeque<&str>,
+) -> MessageData {
+    info!("Received user role command.");
+    if let Err(message) = get_system_administrator(repositories, &metadata.user).await {
+        return message;
+    }
+
+    let user = match get_target_user(repositories, data.pop_front()).await {
+        Ok(u) => u,
+        Err(message) => return message,
+    };
+    tracing::Span::current().record("target_user", &user.service_username.as_str());
+    let role = match data.pop_front().and_then(parse_role) {
+        Some(r) => r,
+        None => {
+            return MessageData::from(
+                "The role must be \"system_administrator\", \"administrator\" or \"viewer\".",
+            )
+        }
+    };
+
+    match repositories.users.update_role(&user, role).await {
+        Ok(None) => MessageData::from(
+            "You can't demote the last System Administrator. Make someone else a System Administrator first.",
+        ),
+        Ok(Some(user)) => {
+            info!("Changed user's role.");
+            MessageData::from(
+                format!(
+                    "{} is now {}.",
+                    user.service_username,
+                    role_name(&user.user_role)
+                )
+                .as_str(),
+            )
+        }
+        Err(e) => {
+            error!(error = ?e, "Encountered an error while changing a user's role.");
+            MessageData::from("Failed to change the user's role. Please try again.")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yarrbot_db::enums::UserRole;
+    use yarrbot_db::models::NewUser;
+
+    #[tokio::test]
+    async fn handle_role_refuses_demoting_last_system_administrator() {
+        // Arrange
+        let repositories = Repositories::in_memory();
+        repositories
+            .users
+            .create_user(NewUser::new(
+                "@sysadmin:example.org",
+                Some(UserRole::SystemAdministrator),
+            ))
+            .await
+            .unwrap();
+        let metadata = CommandMetadata {
+            user: String::from("@sysadmin:example.org"),
+            is_direct_message: true,
+        };
+
+        // Act
+        let actual = handle_role(
+            metadata,
+            &repositories,
+            VecDeque::from(["@sysadmin:example.org", "administrator"]),
+        )
+        .await;
+
+        // Assert
+        assert!(actual
+            .plain
+            .starts_with("You can't demote the last System Administrator."));
+        let user = repositories
+            .users
+            .try_get_by_username("@sysadmin:example.org")
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(matches!(user.user_role, UserRole::SystemAdministrator));
+    }
+
+    #[tokio::test]
+    async fn handle_role_promotes_administrator() {
+        // Arrange
+        let repositories = Repositories::in_memory();
+        repositories
+            .users
+            .create_user(NewUser::new(
+                "@sysadmin:example.org",
+                Some(UserRole::SystemAdministrator),
+            ))
+            .await
+            .unwrap();
+        repositories
+            .users
+            .create_user(NewUser::new("@admin:example.org", None))
+            .await
+            .unwrap();
+        let metadata = CommandMetadata {
+            user: String::from("@sysadmin:example.org"),
+            is_direct_message: true,
+        };
+
+        // Act
+        let actual = handle_role(
+            metadata,
+            &repositories,
+            VecDeque::from(["@admin:example.org", "sysadmin"]),
+        )
+        .await;
+
+        // Assert
+        assert_eq!(
+            "@admin:example.org is now system_administrator.",
+            actual.plain
+        );
+    }
+}
@@ -0,0 +1,29 @@
+//! Entrypoint for `!yarrbot user ...` commands.
+
+use crate::commands::user::{handle_add, handle_list, handle_remove, handle_role};
+use crate::commands::CommandMetadata;
+use crate::message::MessageData;
+use anyhow::{bail, ensure, Result};
+use std::collections::VecDeque;
+use yarrbot_db::repositories::Repositories;
+
+/// Handles choosing which user subcommand to execute.
+#[tracing::instrument(skip(repositories, data))]
+pub async fn handle_user_command(
+    metadata: CommandMetadata,
+    repositories: &Repositories,
+    mut data: VecDeque<&str>,
+) -> Result<MessageData> {
+    ensure!(!data.is_empty(), "Not enough arguments");
+    ensure!(
+        metadata.is_direct_message,
+        "Yarrbot will only respond to user commands in a private room."
+    );
+    match data.pop_front().unwrap().to_lowercase().as_str() {
+        "add" => Ok(handle_add(metadata, repositories, data).await),
+        "remove" => Ok(handle_remove(metadata, repositories, data).await),
+        "list" => Ok(handle_list(metadata, repositories).await),
+        "role" => Ok(handle_role(metadata, repositories, data).await),
+        c => bail!(format!("Unknown user command \"{}\".", c)),
+    }
+}
@@ -1,5 +1,6 @@
 //! Supporting functions for adding a new webhook.
 
+use super::authorization::can_create_webhooks;
 use super::get_user;
 use crate::commands::CommandMetadata;
 use crate::message::{MessageData, MessageDataBuilder};
@@ -8,26 +9,26 @@ use matrix_sdk::ruma::identifiers::RoomIdOrAliasId;
 use matrix_sdk::{room::Room, ruma::identifiers::ServerName, Client};
 use std::collections::VecDeque;
 use std::convert::TryFrom;
-use tokio::task::spawn_blocking;
 use tracing::{error, info, warn};
 use yarrbot_common::crypto::{generate_password, hash};
 use yarrbot_common::short_id::ShortId;
-use yarrbot_db::actions::matrix_room_actions::MatrixRoomActions;
-use yarrbot_db::actions::webhook_actions::WebhookActions;
 use yarrbot_db::models::{MatrixRoom, NewMatrixRoom, NewWebhook, User, Webhook};
-use yarrbot_db::DbPool;
+use yarrbot_db::repositories::Repositories;
 
 /// Add a new webhook.
-#[tracing::instrument(skip(client, pool, data), fields(raw_room, webhook_user, has_password))]
+#[tracing::instrument(
+    skip(client, repositories, data),
+    fields(raw_room, webhook_user, has_password)
+)]
 pub async fn handle_add(
     metadata: CommandMetadata,
     client: &Client,
-    pool: &DbPool,
+    repositories: &Repositories,
     mut data: VecDeque<&str>,
 ) -> MessageData {
     info!("Received webhook add command.");
     let span = tracing::Span::current();
-    let user = match get_user(pool, &metadata.user).await {
+    let user = match get_user(repositories, &metadata.user).await {
         Ok(Some(u)) => u,
         Ok(None) => {
             warn!(
@@ -46,6 +47,12 @@ pub async fn handle_add(
             );
         }
     };
+    if !can_create_webhooks(&user) {
+        warn!("Viewer attempted to add a webhook.");
+        return MessageData::from(
+            "Viewers can't add webhooks. Ask a webhook's owner to share it with you instead.",
+        );
+    }
     if data.len() < 2 {
         warn!("Not enough parameters provided to add command.");
         return MessageData::from(
@@ -90,14 +97,15 @@ pub async fn handle_add(
         }
     };
     let server_name = data.pop_front();
-    let webhook = match create_webhook(pool, username, &password, &user, server_name).await {
+    let webhook = match create_webhook(repositories, username, &password, &user, server_name).await
+    {
         Ok(w) => w,
         Err(e) => {
             error!(error = ?e, "Failed to create webhook in the database.");
             return MessageData::from("Failed to create new webhook.");
         }
     };
-    if let Err(e) = create_matrixroom(pool, room_id, &webhook).await {
+    if let Err(e) = create_matrixroom(repositories, room_id, &webhook).await {
         let uuid = &webhook.id;
         error!(error = ?e, webhook_uuid = %uuid, "Failed to create Matrix Room for webhook.");
         return MessageData::from(
@@ -116,7 +124,7 @@ pub async fn handle_add(
 }
 
 /// Join a Matrix room by [RoomIdOrAliasId] through the bot's homeserver.
-async fn join_room(client: &Client, room_alias_id: &RoomIdOrAliasId) -> Result<Room> {
+pub(super) async fn join_room(client: &Client, room_alias_id: &RoomIdOrAliasId) -> Result<Room> {
     let user = match client.user_id().await {
         Some(u) => u,
         None => bail!("Couldn't retrieve the current user for its server name; was the user's session destroyed?")
@@ -133,9 +141,9 @@ async fn join_room(client: &Client, room_alias_id: &RoomIdOrAliasId) -> Result<R
     )))
 }
 
-/// Create a webhook in the database.
+/// Create a webhook with a hash of the given password.
 async fn create_webhook(
-    pool: &DbPool,
+    repositories: &Repositories,
     username: &str,
     password: &str,
     user: &User,
@@ -144,13 +152,15 @@ async fn create_webhook(
     let hashed = hash(String::from(password)).await?;
     let name: Option<String> = server_name.map(String::from).or(None);
     let new_webhook = NewWebhook::new(username, hashed.to_vec(), user, name);
-    let conn = pool.get()?;
-    Ok(spawn_blocking(move || Webhook::create_webhook(&conn, new_webhook)).await??)
+    repositories.webhooks.create_webhook(new_webhook).await
 }
 
-/// Create a Matrix Room record in the database.
-async fn create_matrixroom(pool: &DbPool, room_id: &str, webhook: &Webhook) -> Result<MatrixRoom> {
-    let conn = pool.get()?;
+/// Create a Matrix Room record for the webhook.
+async fn create_matrixroom(
+    repositories: &Repositories,
+    room_id: &str,
+    webhook: &Webhook,
+) -> Result<MatrixRoom> {
     let new_matrix_room = NewMatrixRoom::new(room_id, webhook);
-    Ok(spawn_blocking(move || MatrixRoom::create_room(&conn, new_matrix_room)).await??)
+    repositories.rooms.create_room(new_matrix_room).await
 }
@@ -0,0 +1,79 @@
+//! Supporting functions for connecting webhooks to the *arr that sends them.
+
+use super::authorization::{authorize, WebhookAccess};
+use super::get_user;
+use crate::commands::CommandMetadata;
+use crate::message::MessageData;
+use std::collections::VecDeque;
+use tracing::{error, info, warn};
+use url::Url;
+use yarrbot_db::repositories::Repositories;
+
+const CLEAR_ARGUMENT: &str = "clear";
+
+/// Set or clear the URL and API key Yarrbot uses to retrieve media covers from a webhook's *arr.
+#[tracing::instrument(skip(repositories, data), fields(webhook_id))]
+pub async fn handle_arr(
+    metadata: CommandMetadata,
+    repositories: &Repositories,
+    mut data: VecDeque<&str>,
+) -> MessageData {
+    info!("Received webhook arr command.");
+    let user = match get_user(repositories, &metadata.user).await {
+        Ok(Some(u)) => u,
+        Ok(None) => {
+            warn!("User attempted to modify a webhook but is not authorized to do so.");
+            return MessageData::from("You are not allowed to modify webhooks.");
+        }
+        Err(e) => {
+            error!(
+                error = ?e,
+                "Encountered an error while retrieving user information from the database."
+            );
+            return MessageData::from("Encountered an error while retrieving user information.");
+        }
+    };
+
+    let webhook_id = match data.pop_front() {
+        Some(w) => {
+            tracing::Span::current().record("webhook_id", &w);
+            w
+        }
+        None => return MessageData::from("No webhook specified."),
+    };
+    let (url, api_key) = match (data.pop_front(), data.pop_front()) {
+        (Some(c), None) if c.eq_ignore_ascii_case(CLEAR_ARGUMENT) => (None, None),
+        (Some(u), Some(k)) => match Url::parse(u) {
+            Ok(parsed) if parsed.scheme() == "http" || parsed.scheme() == "https" => {
+                (Some(String::from(u)), Some(String::from(k)))
+            }
+            _ => return MessageData::from("The *arr URL must be a valid HTTP or HTTPS URL."),
+        },
+        _ => return MessageData::from("Specify both the *arr URL and API key, or \"clear\"."),
+    };
+
+    let webhook = match authorize(repositories, &user, webhook_id, WebhookAccess::Owner).await {
+        Ok(w) => w,
+        Err(message) => return message,
+    };
+
+    let is_clearing = url.is_none();
+    match repositories
+        .webhooks
+        .update_arr_connection(&webhook, url, api_key)
+        .await
+    {
+        Ok(_) if is_clearing => {
+            info!("Cleared webhook *arr connection.");
+            MessageData::from("Yarrbot will no longer retrieve images from the *arr.")
+        }
+        Ok(_) => {
+            info!("Updated webhook *arr connection.");
+            MessageData::from("Yarrbot will retrieve images from the *arr.")
+        }
+        Err(e) => {
+            error!(error = ?e, "Encountered error while updating the webhook.");
+            MessageData::from("Failed to update the webhook. Please try again.")
+        }
+    }
+}
@@ -0,0 +1,102 @@
+//! Supporting functions for changing how requests to a webhook are authenticated.
+
+use super::authorization::{authorize, WebhookAccess};
+use super::get_user;
+use crate::commands::CommandMetadata;
+use crate::message::{MessageData, MessageDataBuilder};
+use std::collections::VecDeque;
+use tracing::{error, info, warn};
+use yarrbot_common::crypto::{digest, generate_token};
+use yarrbot_db::enums::WebhookAuthMode;
+use yarrbot_db::repositories::Repositories;
+
+/// Switch a webhook to another [WebhookAuthMode], generating a new token or secret for the mode if needed.
+#[tracing::instrument(skip(repositories, data), fields(webhook_id))]
+pub async fn handle_auth(
+    metadata: CommandMetadata,
+    repositories: &Repositories,
+    mut data: VecDeque<&str>,
+) -> MessageData {
+    info!("Received webhook auth command.");
+    let user = match get_user(repositories, &metadata.user).await {
+        Ok(Some(u)) => u,
+        Ok(None) => {
+            warn!("User attempted to modify a webhook but is not authorized to do so.");
+            return MessageData::from("You are not allowed to modify webhooks.");
+        }
+        Err(e) => {
+            error!(
+                error = ?e,
+                "Encountered an error while retrieving user information from the database."
+            );
+            return MessageData::from("Encountered an error while retrieving user information.");
+        }
+    };
+
+    let webhook_id = match data.pop_front() {
+        Some(w) => {
+            tracing::Span::current().record("webhook_id", &w);
+            w
+        }
+        None => return MessageData::from("No webhook specified."),
+    };
+    let mode = match data.pop_front().map(|m| m.to_lowercase()).as_deref() {
+        Some("basic") => WebhookAuthMode::Basic,
+        Some("url") => WebhookAuthMode::UrlToken,
+        Some("header") => WebhookAuthMode::HeaderToken,
+        Some("hmac") => WebhookAuthMode::Hmac,
+        _ => return MessageData::from(
+            "Specify one of the authentication modes \"basic\", \"url\", \"header\", or \"hmac\".",
+        ),
+    };
+
+    let webhook = match authorize(repositories, &user, webhook_id, WebhookAccess::Owner).await {
+        Ok(w) => w,
+        Err(message) => return message,
+    };
+
+    let secret = match mode {
+        WebhookAuthMode::Basic => None,
+        _ => Some(generate_token()),
+    };
+    let (token_digest, hmac_secret) = match mode {
+        WebhookAuthMode::UrlToken | WebhookAuthMode::HeaderToken => {
+            (secret.as_ref().map(|s| digest(s.as_bytes())), None)
+        }
+        WebhookAuthMode::Hmac => (None, secret.clone()),
+        WebhookAuthMode::Basic => (None, None),
+    };
+    if let Err(e) = repositories
+        .webhooks
+        .update_auth_mode(&webhook, mode, token_digest, hmac_secret)
+        .await
+    {
+        error!(error = ?e, "Encountered error while updating the webhook.");
+        return MessageData::from("Failed to update the webhook. Please try again.");
+    }
+
+    info!(auth_mode = ?mode, "Updated webhook authentication mode.");
+    let mut builder = MessageDataBuilder::new();
+    match (mode, secret) {
+        (WebhookAuthMode::UrlToken, Some(s)) => {
+            builder.add_line("Add the token to the end of the webhook URL:");
+            builder.add_key_value_with_code("URL Suffix", &format!("?token={}", s));
+        }
+        (WebhookAuthMode::HeaderToken, Some(s)) => {
+            builder.add_line(
+                "Send the token in an \"X-Api-Key\" or \"Authorization: Bearer\" header:",
+            );
+            builder.add_key_value_with_code("Token", &s);
+        }
+        (WebhookAuthMode::Hmac, Some(s)) => {
+            builder.add_line(
+                "Sign requests with HMAC-SHA256 over \"{timestamp}.{body}\" using the secret, then send the \
+                Unix timestamp in \"X-Yarrbot-Timestamp\" and \"sha256={hex signature}\" in \"X-Yarrbot-Signature\":",
+            );
+            builder.add_key_value_with_code("Secret", &s);
+        }
+        _ => builder.add_line("The webhook now uses its username and password."),
+    }
+
+    builder.to_message_data()
+}
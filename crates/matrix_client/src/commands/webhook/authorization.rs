@@ -0,0 +1,303 @@
+//! Decides what a user may do with a webhook. System Administrators and a webhook's owner may do anything with it,
+//! while users it has been shared with may see it and only change it in the ways they have been granted.
+
+use super::get_webhook;
+use crate::message::MessageData;
+use std::collections::VecDeque;
+use tracing::{error, warn};
+use yarrbot_db::enums::UserRole;
+use yarrbot_db::models::{User, Webhook, WebhookCollaborator, WebhookPermission};
+use yarrbot_db::repositories::Repositories;
+
+/// The names [WebhookPermission]s are given by in commands.
+const PERMISSION_NAMES: [(&str, WebhookPermission); 3] = [
+    ("rooms", WebhookPermission::ManageRooms),
+    ("filters", WebhookPermission::ManageFilters),
+    ("credentials", WebhookPermission::RotateCredentials),
+];
+
+/// What a command needs to be allowed to do with a webhook.
+#[derive(Debug, Clone, Copy)]
+pub(super) enum WebhookAccess {
+    /// See the webhook.
+    View,
+
+    /// Change the webhook in a way that can be granted to the users it is shared with.
+    Delegated(WebhookPermission),
+
+    /// Change the webhook in a way only its owner may, such as removing it or sharing it.
+    Owner,
+}
+
+/// Look up a webhook by its short ID and check that the [User] may access it as requested, returning the response to
+/// send if they can't.
+pub(super) async fn authorize(
+    repositories: &Repositories,
+    user: &User,
+    webhook_id: &str,
+    access: WebhookAccess,
+) -> Result<Webhook, MessageData> {
+    let webhook = match get_webhook(repositories, webhook_id).await {
+        Ok(Some(w)) => w,
+        Ok(None) => return Err(MessageData::from("That webhook doesn't exist.")),
+        Err(e) => {
+            error!(
+                error = ?e,
+                "Encountered an error while retrieving Webhook data from the database."
+            );
+            return Err(MessageData::from(
+                "Error encountered while looking up the webhook.",
+            ));
+        }
+    };
+
+    // Owners and System Administrators don't need a grant, so don't look one up.
+    let collaborator = if webhook.user_id == user.id || is_system_administrator(user) {
+        None
+    } else {
+        match repositories
+            .collaborators
+            .try_get(&webhook.id, &user.id)
+            .await
+        {
+            Ok(c) => c,
+            Err(e) => {
+                error!(
+                    error = ?e,
+                    "Encountered an error while retrieving collaborator data from the database."
+                );
+                return Err(MessageData::from(
+                    "Error encountered while looking up the webhook.",
+                ));
+            }
+        }
+    };
+
+    if is_allowed(user, &webhook, collaborator.as_ref(), access) {
+        Ok(webhook)
+    } else {
+        warn!(
+            ?access,
+            "User attempted to access a webhook but is not authorized to do so."
+        );
+        Err(MessageData::from(
+            "You are not allowed to modify this webhook.",
+        ))
+    }
+}
+
+/// Check whether a [User] may create webhooks of their own.
+pub(super) fn can_create_webhooks(user: &User) -> bool {
+    !matches!(user.user_role, UserRole::Viewer)
+}
+
+/// Check whether a [User] may see and change every webhook.
+pub(super) fn is_system_administrator(user: &User) -> bool {
+    matches!(user.user_role, UserRole::SystemAdministrator)
+}
+
+/// Parse the permission names given to a command, returning the reply to send if one isn't known.
+pub(super) fn parse_permissions(names: VecDeque<&str>) -> Result<Vec<WebhookPermission>, String> {
+    names
+        .into_iter()
+        .map(|name| {
+            PERMISSION_NAMES
+                .iter()
+                .find(|(n, _)| n.eq_ignore_ascii_case(name))
+                .map(|(_, p)| *p)
+                .ok_or_else(|| {
+                    format!(
+                        "Unknown permission \"{}\". Permissions are \"rooms\", \"filters\" and \"credentials\".",
+                        name
+                    )
+                })
+        })
+        .collect()
+}
+
+/// List the names of the permissions a [WebhookCollaborator] has been granted.
+pub(super) fn describe_permissions(collaborator: &WebhookCollaborator) -> String {
+    let names: Vec<&str> = PERMISSION_NAMES
+        .iter()
+        .filter(|(_, p)| collaborator.has_permission(*p))
+        .map(|(n, _)| *n)
+        .collect();
+    if names.is_empty() {
+        String::from("none, so they can only see it")
+    } else {
+        names.join(", ")
+    }
+}
+
+/// Check whether a [User] may access a [Webhook] as requested, given their grant for it if they have one. An owner
+/// who has become a Viewer can still see their webhooks but can no longer change them.
+fn is_allowed(
+    user: &User,
+    webhook: &Webhook,
+    collaborator: Option<&WebhookCollaborator>,
+    access: WebhookAccess,
+) -> bool {
+    if is_system_administrator(user) {
+        return true;
+    }
+    let is_owner = webhook.user_id == user.id;
+    let can_modify = is_owner && can_create_webhooks(user);
+    match access {
+        WebhookAccess::View => is_owner || collaborator.is_some(),
+        WebhookAccess::Delegated(permission) => {
+            can_modify || collaborator.map_or(false, |c| c.has_permission(permission))
+        }
+        WebhookAccess::Owner => can_modify,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yarrbot_common::short_id::ShortId;
+    use yarrbot_db::models::{NewUser, NewWebhook, NewWebhookCollaborator};
+
+    async fn create_shared_webhook(
+        repositories: &Repositories,
+        permission: Option<WebhookPermission>,
+    ) -> (User, Webhook) {
+        let owner = repositories
+            .users
+            .create_user(NewUser::new("@owner:example.org", None))
+            .await
+            .unwrap();
+        let viewer = repositories
+            .users
+            .create_user(NewUser::new("@viewer:example.org", Some(UserRole::Viewer)))
+            .await
+            .unwrap();
+        let webhook = repositories
+            .webhooks
+            .create_webhook(NewWebhook::new("user", vec![0], &owner, None))
+            .await
+            .unwrap();
+        let mut collaborator = repositories
+            .collaborators
+            .create_collaborator(NewWebhookCollaborator::new(&webhook, &viewer))
+            .await
+            .unwrap();
+        if let Some(p) = permission {
+            collaborator.set_permission(p, true);
+            repositories
+                .collaborators
+                .update_permissions(&collaborator)
+                .await
+                .unwrap();
+        }
+        (viewer, webhook)
+    }
+
+    #[tokio::test]
+    async fn authorize_allows_granted_permission() {
+        // Arrange
+        let repositories = Repositories::in_memory();
+        let (viewer, webhook) =
+            create_shared_webhook(&repositories, Some(WebhookPermission::ManageRooms)).await;
+
+        // Act
+        let actual = authorize(
+            &repositories,
+            &viewer,
+            &webhook.id.to_short_id(),
+            WebhookAccess::Delegated(WebhookPermission::ManageRooms),
+        )
+        .await;
+
+        // Assert
+        assert_eq!(webhook.id, actual.unwrap().id);
+    }
+
+    #[tokio::test]
+    async fn authorize_refuses_permission_not_granted() {
+        // Arrange
+        let repositories = Repositories::in_memory();
+        let (viewer, webhook) =
+            create_shared_webhook(&repositories, Some(WebhookPermission::ManageRooms)).await;
+
+        // Act
+        let actual = authorize(
+            &repositories,
+            &viewer,
+            &webhook.id.to_short_id(),
+            WebhookAccess::Delegated(WebhookPermission::RotateCredentials),
+        )
+        .await;
+
+        // Assert
+        assert_eq!(
+            "You are not allowed to modify this webhook.",
+            actual.err().unwrap().plain
+        );
+    }
+
+    #[tokio::test]
+    async fn authorize_refuses_owner_access_to_collaborator() {
+        // Arrange
+        let repositories = Repositories::in_memory();
+        let (viewer, webhook) = create_shared_webhook(&repositories, None).await;
+
+        // Act
+        let view = authorize(
+            &repositories,
+            &viewer,
+            &webhook.id.to_short_id(),
+            WebhookAccess::View,
+        )
+        .await;
+        let owner = authorize(
+            &repositories,
+            &viewer,
+            &webhook.id.to_short_id(),
+            WebhookAccess::Owner,
+        )
+        .await;
+
+        // Assert
+        assert!(view.is_ok());
+        assert!(owner.is_err());
+    }
+
+    #[test]
+    fn parse_permissions_rejects_unknown_names() {
+        // Act
+        let known = parse_permissions(VecDeque::from(["Rooms", "credentials"]));
+        let unknown = parse_permissions(VecDeque::from(["rooms", "everything"]));
+
+        // Assert
+        assert_eq!(
+            vec![
+                WebhookPermission::ManageRooms,
+                WebhookPermission::RotateCredentials
+            ],
+            known.unwrap()
+        );
+        assert!(unknown
+            .unwrap_err()
+            .starts_with("Unknown permission \"everything\"."));
+    }
+
+    #[test]
+    fn is_allowed_lets_viewer_owner_only_view() {
+        // Arrange
+        let user = User::from(NewUser::new("@a:example.org", Some(UserRole::Viewer)));
+        let webhook = Webhook::from(NewWebhook::new("user", vec![0], &user, None));
+
+        // Act
+        let view = is_allowed(&user, &webhook, None, WebhookAccess::View);
+        let rotate = is_allowed(
+            &user,
+            &webhook,
+            None,
+            WebhookAccess::Delegated(WebhookPermission::RotateCredentials),
+        );
+
+        // Assert
+        assert!(view);
+        assert!(!rotate);
+    }
+}
@@ -0,0 +1,158 @@
+//! Supporting functions for changing a webhook's username and server name.
+
+use super::authorization::{authorize, WebhookAccess};
+use super::get_user;
+use crate::commands::CommandMetadata;
+use crate::message::{MessageData, MessageDataBuilder};
+use std::collections::VecDeque;
+use tracing::{error, info, warn};
+use yarrbot_db::repositories::Repositories;
+
+/// The changes requested by an edit command. [None] leaves a field as it is.
+#[derive(Debug, Default, PartialEq)]
+struct WebhookEdits {
+    username: Option<String>,
+    server_name: Option<Option<String>>,
+}
+
+/// Change a webhook's username or server name, given as `username=...` and `name=...`.
+#[tracing::instrument(skip(repositories, data), fields(webhook_id))]
+pub async fn handle_edit(
+    metadata: CommandMetadata,
+    repositories: &Repositories,
+    mut data: VecDeque<&str>,
+) -> MessageData {
+    info!("Received webhook edit command.");
+    let user = match get_user(repositories, &metadata.user).await {
+        Ok(Some(u)) => u,
+        Ok(None) => {
+            warn!("User attempted to modify a webhook but is not authorized to do so.");
+            return MessageData::from("You are not allowed to modify webhooks.");
+        }
+        Err(e) => {
+            error!(
+                error = ?e,
+                "Encountered an error while retrieving user information from the database."
+            );
+            return MessageData::from("Encountered an error while retrieving user information.");
+        }
+    };
+
+    let webhook_id = match data.pop_front() {
+        Some(w) => {
+            tracing::Span::current().record("webhook_id", &w);
+            w
+        }
+        None => return MessageData::from("No webhook specified."),
+    };
+    let edits = match parse_edits(data) {
+        Ok(e) => e,
+        Err(message) => return MessageData::from(message),
+    };
+
+    let webhook = match authorize(repositories, &user, webhook_id, WebhookAccess::Owner).await {
+        Ok(w) => w,
+        Err(message) => return message,
+    };
+    let username = edits.username.unwrap_or_else(|| webhook.username.clone());
+    let server_name = edits
+        .server_name
+        .unwrap_or_else(|| webhook.server_name.clone());
+    match repositories
+        .webhooks
+        .update_details(&webhook, username, server_name)
+        .await
+    {
+        Ok(w) => {
+            info!("Updated webhook details.");
+            let mut builder = MessageDataBuilder::new();
+            builder.add_line("Updated the webhook.");
+            builder.add_key_value_with_code("Username", &w.username);
+            builder.add_key_value("Server name", w.server_name.as_deref().unwrap_or("Not set"));
+            builder.to_message_data()
+        }
+        Err(e) => {
+            error!(error = ?e, "Encountered error while updating the webhook.");
+            MessageData::from("Failed to update the webhook. Please try again.")
+        }
+    }
+}
+
+/// Parse `field=value` arguments. Arguments without `=` continue the previous value, so that names can contain
+/// spaces; an empty name clears it.
+fn parse_edits(arguments: VecDeque<&str>) -> Result<WebhookEdits, &'static str> {
+    let mut fields: Vec<(String, String)> = Vec::new();
+    for argument in arguments {
+        if let Some((field, value)) = argument.split_once('=') {
+            fields.push((field.to_lowercase(), String::from(value)));
+            continue;
+        }
+        match fields.last_mut() {
+            Some((_, value)) => {
+                value.push(' ');
+                value.push_str(argument);
+            }
+            None => return Err("Changes must be given as name=... or username=..."),
+        }
+    }
+    if fields.is_empty() {
+        return Err("Specify what to change, e.g. name=Sonarr or username=sonarr.");
+    }
+
+    let mut edits = WebhookEdits::default();
+    for (field, value) in fields {
+        match field.as_str() {
+            "name" if value.is_empty() => edits.server_name = Some(None),
+            "name" => edits.server_name = Some(Some(value)),
+            "username" if value.is_empty() || value.contains(' ') => {
+                return Err("The username can't be empty or contain spaces.")
+            }
+            "username" => edits.username = Some(value),
+            _ => return Err("Only the name and username can be changed."),
+        }
+    }
+    Ok(edits)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_edits_joins_names_containing_spaces() {
+        // Act
+        let actual = parse_edits(VecDeque::from([
+            "name=Living",
+            "Room",
+            "Sonarr",
+            "username=tv",
+        ]));
+
+        // Assert
+        assert_eq!(
+            WebhookEdits {
+                username: Some(String::from("tv")),
+                server_name: Some(Some(String::from("Living Room Sonarr"))),
+            },
+            actual.unwrap()
+        );
+    }
+
+    #[test]
+    fn parse_edits_clears_empty_name() {
+        // Act
+        let actual = parse_edits(VecDeque::from(["name="]));
+
+        // Assert
+        assert_eq!(Some(None), actual.unwrap().server_name);
+    }
+
+    #[test]
+    fn parse_edits_rejects_unknown_fields() {
+        // Act
+        let actual = parse_edits(VecDeque::from(["password=secret"]));
+
+        // Assert
+        assert!(actual.is_err());
+    }
+}
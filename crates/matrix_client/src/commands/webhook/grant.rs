@@ -0,0 +1,182 @@
+//! Supporting functions for sharing a webhook with another user.
+
+use super::authorization::{authorize, describe_permissions, parse_permissions, WebhookAccess};
+use super::get_user;
+use crate::commands::get_target_user;
+use crate::commands::CommandMetadata;
+use crate::message::MessageData;
+use std::collections::VecDeque;
+use tracing::{error, info, warn};
+use yarrbot_db::models::NewWebhookCollaborator;
+use yarrbot_db::repositories::Repositories;
+
+/// Share a webhook with another user, granting them the given permissions on top of any they already have. Without any
+/// permissions the user can only see the webhook.
+#[tracing::instrument(skip(repositories, data), fields(webhook_id, target_user))]
+pub async fn handle_grant(
+    metadata: CommandMetadata,
+    repositories: &Repositories,
+    mut data: VecDeque<&str>,
+) -> MessageData {
+    info!("Received webhook grant command.");
+    let span = tracing::Span::current();
+    let user = match get_user(repositories, &metadata.user).await {
+        Ok(Some(u)) => u,
+        Ok(None) => {
+            warn!("User attempted to share a webhook but is not authorized to do so.");
+            return MessageData::from("You are not allowed to modify webhooks.");
+        }
+        Err(e) => {
+            error!(
+                error = ?e,
+                "Encountered an error while retrieving user information from the database."
+            );
+            return MessageData::from("Encountered an error while retrieving user information.");
+        }
+    };
+
+    let webhook_id = match data.pop_front() {
+        Some(w) => {
+            span.record("webhook_id", &w);
+            w
+        }
+        None => return MessageData::from("No webhook specified."),
+    };
+    let target = match get_target_user(repositories, data.pop_front()).await {
+        Ok(u) => u,
+        Err(message) => return message,
+    };
+    span.record("target_user", &target.service_username.as_str());
+    let permissions = match parse_permissions(data) {
+        Ok(p) => p,
+        Err(message) => return MessageData::from(message.as_str()),
+    };
+
+    let webhook = match authorize(repositories, &user, webhook_id, WebhookAccess::Owner).await {
+        Ok(w) => w,
+        Err(message) => return message,
+    };
+    if webhook.user_id == target.id {
+        return MessageData::from("That user already owns the webhook.");
+    }
+
+    let existing = match repositories
+        .collaborators
+        .try_get(&webhook.id, &target.id)
+        .await
+    {
+        Ok(c) => c,
+        Err(e) => {
+            error!(error = ?e, "Encountered an error while retrieving collaborator data.");
+            return MessageData::from("Failed to share the webhook. Please try again.");
+        }
+    };
+    let mut collaborator = match existing {
+        Some(c) => c,
+        None => match repositories
+            .collaborators
+            .create_collaborator(NewWebhookCollaborator::new(&webhook, &target))
+            .await
+        {
+            Ok(c) => c,
+            Err(e) => {
+                error!(error = ?e, "Encountered an error while sharing the webhook.");
+                return MessageData::from("Failed to share the webhook. Please try again.");
+            }
+        },
+    };
+    if !permissions.is_empty() {
+        for permission in permissions {
+            collaborator.set_permission(permission, true);
+        }
+        collaborator = match repositories
+            .collaborators
+            .update_permissions(&collaborator)
+            .await
+        {
+            Ok(c) => c,
+            Err(e) => {
+                error!(error = ?e, "Encountered an error while granting permissions.");
+                return MessageData::from("Failed to grant the permissions. Please try again.");
+            }
+        };
+    }
+
+    info!("Shared webhook.");
+    MessageData::from(
+        format!(
+            "Shared the webhook with {}. Their permissions: {}.",
+            target.service_username,
+            describe_permissions(&collaborator)
+        )
+        .as_str(),
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yarrbot_common::short_id::ShortId;
+    use yarrbot_db::enums::UserRole;
+    use yarrbot_db::models::{NewUser, NewWebhook};
+
+    #[tokio::test]
+    async fn handle_grant_adds_permissions_to_existing_grant() {
+        // Arrange
+        let repositories = Repositories::in_memory();
+        let owner = repositories
+            .users
+            .create_user(NewUser::new("@owner:example.org", None))
+            .await
+            .unwrap();
+        let viewer = repositories
+            .users
+            .create_user(NewUser::new("@viewer:example.org", Some(UserRole::Viewer)))
+            .await
+            .unwrap();
+        let webhook = repositories
+            .webhooks
+            .create_webhook(NewWebhook::new("user", vec![0], &owner, None))
+            .await
+            .unwrap();
+        let mut collaborator = repositories
+            .collaborators
+            .create_collaborator(NewWebhookCollaborator::new(&webhook, &viewer))
+            .await
+            .unwrap();
+        collaborator.manage_rooms = true;
+        repositories
+            .collaborators
+            .update_permissions(&collaborator)
+            .await
+            .unwrap();
+        let webhook_id = webhook.id.to_short_id();
+        let metadata = CommandMetadata {
+            user: String::from("@owner:example.org"),
+            is_direct_message: true,
+        };
+
+        // Act
+        let actual = handle_grant(
+            metadata,
+            &repositories,
+            VecDeque::from([webhook_id.as_str(), "@viewer:example.org", "credentials"]),
+        )
+        .await;
+
+        // Assert
+        assert_eq!(
+            "Shared the webhook with @viewer:example.org. Their permissions: rooms, credentials.",
+            actual.plain
+        );
+        let collaborator = repositories
+            .collaborators
+            .try_get(&webhook.id, &viewer.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(collaborator.manage_rooms);
+        assert!(!collaborator.manage_filters);
+        assert!(collaborator.rotate_credentials);
+    }
+}
@@ -0,0 +1,194 @@
+//! Supporting functions for showing the details of a webhook.
+
+use super::authorization::{authorize, WebhookAccess};
+use super::{format_time, get_user};
+use crate::commands::CommandMetadata;
+use crate::message::{MessageData, MessageDataBuilder};
+use chrono::{DateTime, Utc};
+use matrix_sdk::ruma::identifiers::RoomId;
+use matrix_sdk::Client;
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use tracing::{error, info, warn};
+use yarrbot_common::short_id::ShortId;
+use yarrbot_db::models::Webhook;
+use yarrbot_db::quiet_hours::QuietHours;
+use yarrbot_db::repositories::Repositories;
+
+/// A room a webhook posts to, along with its name if Yarrbot knows it.
+struct RoomSummary {
+    room_id: String,
+    name: Option<String>,
+    muted_until: Option<DateTime<Utc>>,
+    quiet_hours: Option<QuietHours>,
+}
+
+/// Show who owns a webhook, where it posts to, and how much it has been used.
+#[tracing::instrument(skip(client, repositories, data), fields(webhook_id))]
+pub async fn handle_info(
+    metadata: CommandMetadata,
+    client: &Client,
+    repositories: &Repositories,
+    mut data: VecDeque<&str>,
+) -> MessageData {
+    info!("Received webhook info command.");
+    let user = match get_user(repositories, &metadata.user).await {
+        Ok(Some(u)) => u,
+        Ok(None) => {
+            warn!("User attempted to view a webhook but is not authorized to do so.");
+            return MessageData::from("You are not allowed to modify webhooks.");
+        }
+        Err(e) => {
+            error!(
+                error = ?e,
+                "Encountered an error while retrieving user information from the database."
+            );
+            return MessageData::from("Encountered an error while retrieving user information.");
+        }
+    };
+
+    let webhook_id = match data.pop_front() {
+        Some(w) => {
+            tracing::Span::current().record("webhook_id", &w);
+            w
+        }
+        None => return MessageData::from("No webhook specified."),
+    };
+    let webhook = match authorize(repositories, &user, webhook_id, WebhookAccess::View).await {
+        Ok(w) => w,
+        Err(message) => return message,
+    };
+
+    let owner = match repositories.users.try_get(&webhook.user_id).await {
+        Ok(Some(u)) => u.service_username,
+        Ok(None) => String::from("Unknown"),
+        Err(e) => {
+            error!(error = ?e, "Encountered an error while retrieving the webhook's owner.");
+            return MessageData::from(
+                "Couldn't retrieve the webhook's details, please try again later.",
+            );
+        }
+    };
+    let rooms = match repositories.rooms.get_by_webhook_id(&webhook.id).await {
+        Ok(r) => r,
+        Err(e) => {
+            error!(error = ?e, "Encountered an error while retrieving the webhook's rooms.");
+            return MessageData::from(
+                "Couldn't retrieve the webhook's details, please try again later.",
+            );
+        }
+    };
+    let mut summaries = Vec::with_capacity(rooms.len());
+    for room in rooms {
+        let name = get_room_name(client, &room.room_id).await;
+        let quiet_hours = room.quiet_hours();
+        summaries.push(RoomSummary {
+            room_id: room.room_id,
+            name,
+            muted_until: room.muted_until,
+            quiet_hours,
+        });
+    }
+
+    describe_webhook(&webhook, &owner, &summaries, Utc::now())
+}
+
+/// Get the display name of a room Yarrbot has joined.
+async fn get_room_name(client: &Client, room_id: &str) -> Option<String> {
+    let room = client.get_room(&RoomId::try_from(room_id).ok()?)?;
+    room.display_name().await.ok()
+}
+
+/// Build the message describing a webhook.
+fn describe_webhook(
+    webhook: &Webhook,
+    owner: &str,
+    rooms: &[RoomSummary],
+    now: DateTime<Utc>,
+) -> MessageData {
+    let mut builder = MessageDataBuilder::new();
+    builder.add_key_value_with_code("ID", &webhook.id.to_short_id());
+    builder.add_key_value_with_code("Username", &webhook.username);
+    builder.add_key_value(
+        "Server name",
+        webhook.server_name.as_deref().unwrap_or("Not set"),
+    );
+    builder.add_key_value("Owner", owner);
+    let status = match webhook.paused_until {
+        _ if !webhook.is_paused(now) => String::from("Active"),
+        Some(until) => format!("Paused until {}", format_time(until)),
+        None => String::from("Paused"),
+    };
+    builder.add_key_value("Status", &status);
+    builder.add_key_value(
+        "Created",
+        &format_optional_time(webhook.created_at, "Unknown"),
+    );
+    builder.add_key_value(
+        "Last event",
+        &format_optional_time(webhook.last_event_at, "Never"),
+    );
+    builder.add_key_value("Events received", &webhook.events_received.to_string());
+    builder.add_key_value("Failed deliveries", &webhook.delivery_failures.to_string());
+    if rooms.is_empty() {
+        builder.add_line("The webhook doesn't post to any rooms.");
+    } else {
+        builder.add_line("Rooms:");
+        for room in rooms {
+            let mut key = String::from(room.name.as_deref().unwrap_or("Unknown room"));
+            if let Some(until) = room.muted_until.filter(|until| now < *until) {
+                key.push_str(&format!(" (muted until {})", format_time(until)));
+            }
+            if let Some(quiet_hours) = &room.quiet_hours {
+                key.push_str(&format!(" (quiet hours {})", quiet_hours));
+            }
+            builder.add_key_value_with_code(&key, &room.room_id);
+        }
+    }
+    builder.to_message_data()
+}
+
+fn format_optional_time(time: Option<DateTime<Utc>>, fallback: &str) -> String {
+    time.map(format_time)
+        .unwrap_or_else(|| String::from(fallback))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+    use yarrbot_db::models::{NewUser, NewWebhook, User};
+
+    #[test]
+    fn describe_webhook_includes_activity_and_rooms() {
+        // Arrange
+        let user = User::from(NewUser::new("@owner:example.org", None));
+        let mut webhook = Webhook::from(NewWebhook::new(
+            "user",
+            vec![0],
+            &user,
+            Some(String::from("Sonarr")),
+        ));
+        webhook.last_event_at = Some(Utc.ymd(2026, 10, 18).and_hms(12, 30, 0));
+        webhook.events_received = 3;
+        let rooms = vec![RoomSummary {
+            room_id: String::from("!a:example.org"),
+            name: Some(String::from("TV")),
+            muted_until: None,
+            quiet_hours: None,
+        }];
+
+        // Act
+        let actual = describe_webhook(&webhook, "@owner:example.org", &rooms, Utc::now());
+
+        // Assert
+        assert!(actual.plain.contains("**Server name**: Sonarr"));
+        assert!(actual.plain.contains("**Owner**: @owner:example.org"));
+        assert!(actual.plain.contains("**Status**: Active"));
+        assert!(actual
+            .plain
+            .contains("**Last event**: 2026-10-18 12:30 UTC"));
+        assert!(actual.plain.contains("**Events received**: 3"));
+        assert!(actual.plain.contains("**TV**: !a:example.org"));
+    }
+}
@@ -1,28 +1,26 @@
 //! Supporting functions for listing all webhooks.
 
+use super::authorization::is_system_administrator;
 use super::get_user;
 use crate::commands::CommandMetadata;
 use crate::message::{MatrixMessageDataPart, MessageData, MessageDataBuilder};
 use anyhow::Result;
 use std::collections::VecDeque;
-use tokio::task::spawn_blocking;
 use tracing::{error, info, warn};
 use yarrbot_common::short_id::ShortId;
-use yarrbot_db::actions::webhook_actions::WebhookActions;
-use yarrbot_db::enums::UserRole;
 use yarrbot_db::models::{User, Webhook};
-use yarrbot_db::DbPool;
+use yarrbot_db::repositories::Repositories;
 
-/// Handle the list command. Specifying `!yarrbot webhook list all` will list all users
-/// webhooks if the requesting user is a System Administrator.
-#[tracing::instrument(skip(pool))]
+/// Handle the list command, listing the webhooks a user owns or has been granted access to. Specifying
+/// `!yarrbot webhook list all` will list all users webhooks if the requesting user is a System Administrator.
+#[tracing::instrument(skip(repositories))]
 pub async fn handle_list(
     metadata: CommandMetadata,
-    pool: &DbPool,
+    repositories: &Repositories,
     mut data: VecDeque<&str>,
 ) -> MessageData {
     info!("Received webhook list command.");
-    let user = match get_user(pool, &metadata.user).await {
+    let user = match get_user(repositories, &metadata.user).await {
         Ok(Some(u)) => u,
         Ok(None) => {
             warn!("User attempted to list webhooks but is not authorized to do so.");
@@ -39,7 +37,7 @@ pub async fn handle_list(
         }
     };
     let specifier = String::from(data.pop_front().unwrap_or(""));
-    let webhooks = match get_webhooks(pool, &user, specifier).await {
+    let webhooks = match get_webhooks(repositories, &user, specifier).await {
         Ok(v) => v,
         Err(e) => {
             error!(
@@ -52,63 +50,96 @@ pub async fn handle_list(
         }
     };
 
+    let mut rows = Vec::with_capacity(webhooks.len());
+    for webhook in &webhooks {
+        let rooms = match repositories.rooms.get_by_webhook_id(&webhook.id).await {
+            Ok(r) => r.len(),
+            Err(e) => {
+                error!(error = ?e, "Encountered an error while retrieving the webhook's rooms.");
+                return MessageData::from(
+                    "Couldn't retrieve the list of webhooks, please try again later.",
+                );
+            }
+        };
+        rows.push(WebhookRow {
+            id: webhook.id.to_short_id(),
+            server_name: webhook.server_name.clone().unwrap_or_default(),
+            rooms,
+        });
+    }
+
     info!("Listing webhooks.");
-    let items: Vec<String> = webhooks.iter().map(|w| w.id.to_short_id()).collect();
     let mut builder = MessageDataBuilder::new();
-    if items.is_empty() {
+    if rows.is_empty() {
         builder.add_line("No webhooks to list.");
     } else {
         builder.add_line("Webhooks:");
-        builder.add_matrix_message_part(WebhookList { items });
+        builder.add_matrix_message_part(WebhookTable { rows });
     }
 
     builder.to_message_data()
 }
 
-/// Get all webhooks for a user or all webhooks in the system if the user is a System Administrator.
-async fn get_webhooks(pool: &DbPool, user: &User, specifier: String) -> Result<Vec<Webhook>> {
-    let conn = pool.get()?;
-    let is_system_admin = matches!(user.user_role, UserRole::SystemAdministrator);
-    let user_id = user.id;
-    Ok(spawn_blocking(move || {
-        if specifier == "all" && is_system_admin {
-            Webhook::get_all(&conn)
-        } else {
-            Webhook::get_all_by_user_id(&conn, &user_id)
+/// Get the webhooks a user owns or has been granted access to, or all webhooks in the system if the user is a System
+/// Administrator.
+async fn get_webhooks(
+    repositories: &Repositories,
+    user: &User,
+    specifier: String,
+) -> Result<Vec<Webhook>> {
+    if specifier == "all" && is_system_administrator(user) {
+        return repositories.webhooks.get_all().await;
+    }
+
+    let mut webhooks = repositories.webhooks.get_all_by_user_id(&user.id).await?;
+    for collaborator in repositories.collaborators.get_by_user_id(&user.id).await? {
+        if let Some(w) = repositories
+            .webhooks
+            .try_get(&collaborator.webhook_id)
+            .await?
+        {
+            webhooks.push(w);
         }
-    })
-    .await??)
+    }
+    Ok(webhooks)
 }
 
-struct WebhookList {
-    items: Vec<String>,
+/// A webhook's entry in the list.
+struct WebhookRow {
+    id: String,
+    server_name: String,
+    rooms: usize,
 }
 
-impl MatrixMessageDataPart for WebhookList {
-    fn to_plain(&self, break_character: &str) -> String {
-        let items_len = self.items.len();
-        let mut plain_parts = String::from(' ');
-        for (i, item) in self.items.iter().enumerate() {
-            plain_parts.push_str(item);
-            if i < (items_len - 1) {
-                plain_parts.push_str(", ");
-            }
-        }
+struct WebhookTable {
+    rows: Vec<WebhookRow>,
+}
 
-        plain_parts.push(' ');
+impl MatrixMessageDataPart for WebhookTable {
+    fn to_plain(&self, break_character: &str) -> String {
+        let mut plain_parts = String::from(" ID | Server name | Rooms ");
         plain_parts.push_str(break_character);
+        for row in &self.rows {
+            plain_parts.push_str(&format!(
+                " {} | {} | {} ",
+                row.id, row.server_name, row.rooms
+            ));
+            plain_parts.push_str(break_character);
+        }
         plain_parts
     }
 
     fn to_html(&self, break_character: &str) -> String {
-        let mut html_parts = String::from("<ul>");
-        for item in &self.items {
-            html_parts.push_str("<li><code>");
-            html_parts.push_str(item);
-            html_parts.push_str("</code></li>");
+        let mut html_parts =
+            String::from("<table><tr><th>ID</th><th>Server name</th><th>Rooms</th></tr>");
+        for row in &self.rows {
+            html_parts.push_str(&format!(
+                "<tr><td><code>{}</code></td><td>{}</td><td>{}</td></tr>",
+                row.id, row.server_name, row.rooms
+            ));
         }
 
-        html_parts.push_str("</ul>");
+        html_parts.push_str("</table>");
 
         html_parts.push(' ');
         html_parts.push_str(break_character);
@@ -118,17 +149,21 @@ impl MatrixMessageDataPart for WebhookList {
 
 #[cfg(test)]
 mod tests {
-    use crate::commands::webhook::list::WebhookList;
+    use crate::commands::webhook::list::{WebhookRow, WebhookTable};
     use crate::message::MessageDataBuilder;
 
     #[test]
-    pub fn add_unordered_list_returns_list() {
+    pub fn add_webhook_table_returns_table() {
         // Arrange
-        let expected_plain = "1, 2, 3 \n **1**: 2 \n";
-        let expected_html = "<ul><li><code>1</code></li><li><code>2</code></li><li><code>3</code></li></ul> <br><strong>1</strong>: 2 <br>";
-        let items: Vec<String> = (1..4).map(|i| i.to_string()).collect();
+        let expected_plain = "ID | Server name | Rooms \n abc | Sonarr | 2 \n **1**: 2 \n";
+        let expected_html = "<table><tr><th>ID</th><th>Server name</th><th>Rooms</th></tr><tr><td><code>abc</code></td><td>Sonarr</td><td>2</td></tr></table> <br><strong>1</strong>: 2 <br>";
+        let rows = vec![WebhookRow {
+            id: String::from("abc"),
+            server_name: String::from("Sonarr"),
+            rooms: 2,
+        }];
         let mut builder = MessageDataBuilder::new();
-        builder.add_matrix_message_part(WebhookList { items });
+        builder.add_matrix_message_part(WebhookTable { rows });
         builder.add_key_value("1", "2");
 
         // Act
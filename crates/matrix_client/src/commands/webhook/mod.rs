@@ -1,19 +1,117 @@
 use anyhow::Result;
-use tokio::task::spawn_blocking;
-use yarrbot_db::actions::user_actions::UserActions;
-use yarrbot_db::models::User;
-use yarrbot_db::DbPool;
+use chrono::{DateTime, Duration, Utc};
+use tracing::warn;
+use uuid::Uuid;
+use yarrbot_common::short_id::ShortId;
+use yarrbot_db::models::{User, Webhook};
+use yarrbot_db::repositories::Repositories;
 
 mod add;
+mod arr;
+mod auth;
+mod authorization;
+mod edit;
+mod grant;
+mod info;
 mod list;
+mod mute;
+mod paths;
+mod pause;
+mod quiet;
 mod remove;
+mod revoke;
+mod room;
+mod rotate;
+mod sources;
+mod spoilers;
+mod transfer;
 
 pub use add::handle_add;
+pub use arr::handle_arr;
+pub use auth::handle_auth;
+pub use edit::handle_edit;
+pub use grant::handle_grant;
+pub use info::handle_info;
 pub use list::handle_list;
+pub use mute::{handle_mute, handle_unmute};
+pub use paths::handle_paths;
+pub use pause::{handle_pause, handle_resume};
+pub use quiet::handle_quiet;
 pub use remove::handle_remove;
+pub use revoke::handle_revoke;
+pub use room::handle_room;
+pub use rotate::handle_rotate;
+pub use sources::handle_sources;
+pub use spoilers::handle_spoilers;
+pub use transfer::handle_transfer;
 
-async fn get_user(pool: &DbPool, username: &str) -> Result<Option<User>> {
-    let conn = pool.get()?;
-    let username2 = String::from(username);
-    Ok(spawn_blocking(move || User::try_get_by_username(&conn, &username2)).await??)
+async fn get_user(repositories: &Repositories, username: &str) -> Result<Option<User>> {
+    repositories.users.try_get_by_username(username).await
+}
+
+/// Get a webhook record by its short ID.
+async fn get_webhook(repositories: &Repositories, webhook_id: &str) -> Result<Option<Webhook>> {
+    let webhook_uuid = match Uuid::from_short_id(webhook_id) {
+        Ok(u) => u,
+        Err(e) => {
+            warn!(
+                error = ?e,
+                "Encountered error decoding UUID from short ID."
+            );
+            return Ok(None);
+        }
+    };
+    repositories.webhooks.try_get(&webhook_uuid).await
+}
+
+/// Parse a duration such as `30m`, `12h`, `2d` or `1w`.
+fn parse_duration(raw: &str) -> Option<Duration> {
+    let (split, _) = raw.char_indices().last()?;
+    let (amount, unit) = raw.split_at(split);
+    // Keeps the end of the duration well within the range of dates chrono can represent.
+    let amount: i64 = amount.parse().ok().filter(|a| (1..100_000).contains(a))?;
+    match unit.to_lowercase().as_str() {
+        "m" => Some(Duration::minutes(amount)),
+        "h" => Some(Duration::hours(amount)),
+        "d" => Some(Duration::days(amount)),
+        "w" => Some(Duration::weeks(amount)),
+        _ => None,
+    }
+}
+
+/// Format a time to show in a message.
+fn format_time(time: DateTime<Utc>) -> String {
+    time.format("%Y-%m-%d %H:%M UTC").to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_duration_parses_each_unit() {
+        // Act
+        let actual: Vec<Option<Duration>> = ["30m", "12h", "2d", "1W"]
+            .iter()
+            .map(|d| parse_duration(d))
+            .collect();
+
+        // Assert
+        assert_eq!(
+            vec![
+                Some(Duration::minutes(30)),
+                Some(Duration::hours(12)),
+                Some(Duration::days(2)),
+                Some(Duration::weeks(1)),
+            ],
+            actual
+        );
+    }
+
+    #[test]
+    fn parse_duration_rejects_invalid_durations() {
+        for raw in ["", "h", "0h", "-1h", "12", "1y", "1é", "100000w"] {
+            assert_eq!(None, parse_duration(raw), "{}", raw);
+        }
+    }
 }
@@ -0,0 +1,208 @@
+//! Supporting functions for temporarily muting the rooms a webhook posts to.
+
+use super::authorization::{authorize, WebhookAccess};
+use super::{format_time, get_user, parse_duration};
+use crate::commands::CommandMetadata;
+use crate::message::MessageData;
+use chrono::Utc;
+use std::collections::VecDeque;
+use tracing::{error, info, warn};
+use yarrbot_db::models::WebhookPermission;
+use yarrbot_db::repositories::Repositories;
+
+/// Stop a webhook posting to one or all of its rooms for the given duration.
+#[tracing::instrument(skip(repositories, data), fields(webhook_id, room_id))]
+pub async fn handle_mute(
+    metadata: CommandMetadata,
+    repositories: &Repositories,
+    mut data: VecDeque<&str>,
+) -> MessageData {
+    info!("Received webhook mute command.");
+    let span = tracing::Span::current();
+    let user = match get_user(repositories, &metadata.user).await {
+        Ok(Some(u)) => u,
+        Ok(None) => {
+            warn!("User attempted to mute a webhook's room but is not authorized to do so.");
+            return MessageData::from("You are not allowed to modify webhooks.");
+        }
+        Err(e) => {
+            error!(
+                error = ?e,
+                "Encountered an error while retrieving user information from the database."
+            );
+            return MessageData::from("Encountered an error while retrieving user information.");
+        }
+    };
+
+    let webhook_id = match data.pop_front() {
+        Some(w) => {
+            span.record("webhook_id", &w);
+            w
+        }
+        None => return MessageData::from("No webhook specified."),
+    };
+    let until = match data.pop_front().and_then(parse_duration) {
+        Some(d) => Utc::now() + d,
+        None => {
+            return MessageData::from(
+                "Specify how long to mute the webhook's rooms for, e.g. 30m, 12h, 2d or 1w.",
+            )
+        }
+    };
+    let room_id = data.pop_front();
+    if let Some(r) = room_id {
+        span.record("room_id", &r);
+    }
+
+    let webhook = match authorize(
+        repositories,
+        &user,
+        webhook_id,
+        WebhookAccess::Delegated(WebhookPermission::ManageRooms),
+    )
+    .await
+    {
+        Ok(w) => w,
+        Err(message) => return message,
+    };
+
+    match repositories
+        .rooms
+        .update_muted_until(&webhook.id, room_id, Some(until))
+        .await
+    {
+        Ok(0) => MessageData::from("The webhook doesn't post to that room."),
+        Ok(count) => {
+            info!(count, %until, "Muted rooms.");
+            MessageData::from(
+                format!("Muted {} room(s) until {}.", count, format_time(until)).as_str(),
+            )
+        }
+        Err(e) => {
+            error!(error = ?e, "Encountered error while muting rooms.");
+            MessageData::from("Failed to mute the rooms. Please try again.")
+        }
+    }
+}
+
+/// Let a webhook post to one or all of its muted rooms again before their mute ends.
+#[tracing::instrument(skip(repositories, data), fields(webhook_id, room_id))]
+pub async fn handle_unmute(
+    metadata: CommandMetadata,
+    repositories: &Repositories,
+    mut data: VecDeque<&str>,
+) -> MessageData {
+    info!("Received webhook unmute command.");
+    let span = tracing::Span::current();
+    let user = match get_user(repositories, &metadata.user).await {
+        Ok(Some(u)) => u,
+        Ok(None) => {
+            warn!("User attempted to unmute a webhook's room but is not authorized to do so.");
+            return MessageData::from("You are not allowed to modify webhooks.");
+        }
+        Err(e) => {
+            error!(
+                error = ?e,
+                "Encountered an error while retrieving user information from the database."
+            );
+            return MessageData::from("Encountered an error while retrieving user information.");
+        }
+    };
+
+    let webhook_id = match data.pop_front() {
+        Some(w) => {
+            span.record("webhook_id", &w);
+            w
+        }
+        None => return MessageData::from("No webhook specified."),
+    };
+    let room_id = data.pop_front();
+    if let Some(r) = room_id {
+        span.record("room_id", &r);
+    }
+
+    let webhook = match authorize(
+        repositories,
+        &user,
+        webhook_id,
+        WebhookAccess::Delegated(WebhookPermission::ManageRooms),
+    )
+    .await
+    {
+        Ok(w) => w,
+        Err(message) => return message,
+    };
+
+    match repositories
+        .rooms
+        .update_muted_until(&webhook.id, room_id, None)
+        .await
+    {
+        Ok(0) => MessageData::from("The webhook doesn't post to that room."),
+        Ok(count) => {
+            info!(count, "Unmuted rooms.");
+            MessageData::from(format!("Unmuted {} room(s).", count).as_str())
+        }
+        Err(e) => {
+            error!(error = ?e, "Encountered error while unmuting rooms.");
+            MessageData::from("Failed to unmute the rooms. Please try again.")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yarrbot_common::short_id::ShortId;
+    use yarrbot_db::models::{NewMatrixRoom, NewUser, NewWebhook};
+
+    #[tokio::test]
+    async fn handle_mute_only_mutes_given_room() {
+        // Arrange
+        let repositories = Repositories::in_memory();
+        let owner = repositories
+            .users
+            .create_user(NewUser::new("@owner:example.org", None))
+            .await
+            .unwrap();
+        let webhook = repositories
+            .webhooks
+            .create_webhook(NewWebhook::new("user", vec![0], &owner, None))
+            .await
+            .unwrap();
+        for room_id in ["!a:example.org", "!b:example.org"] {
+            repositories
+                .rooms
+                .create_room(NewMatrixRoom::new(room_id, &webhook))
+                .await
+                .unwrap();
+        }
+        let metadata = CommandMetadata {
+            user: String::from("@owner:example.org"),
+            is_direct_message: true,
+        };
+        let webhook_id = webhook.id.to_short_id();
+
+        // Act
+        let actual = handle_mute(
+            metadata,
+            &repositories,
+            VecDeque::from([webhook_id.as_str(), "30m", "!a:example.org"]),
+        )
+        .await;
+
+        // Assert
+        assert!(actual.plain.starts_with("Muted 1 room(s) until"));
+        let now = Utc::now();
+        let muted: Vec<String> = repositories
+            .rooms
+            .get_by_webhook_id(&webhook.id)
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|r| r.is_muted(now))
+            .map(|r| r.room_id)
+            .collect();
+        assert_eq!(vec![String::from("!a:example.org")], muted);
+    }
+}
@@ -0,0 +1,94 @@
+//! Supporting functions for choosing how file system paths are shown in a webhook's notifications.
+
+use super::authorization::{authorize, WebhookAccess};
+use super::get_user;
+use crate::commands::CommandMetadata;
+use crate::message::MessageData;
+use std::collections::VecDeque;
+use tracing::{error, info, warn};
+use yarrbot_db::enums::PathPolicy;
+use yarrbot_db::models::WebhookPermission;
+use yarrbot_db::repositories::Repositories;
+
+/// Set the [PathPolicy] of a webhook. The `replace` policy takes one or more `from=to` prefix replacements.
+#[tracing::instrument(skip(repositories, data), fields(webhook_id))]
+pub async fn handle_paths(
+    metadata: CommandMetadata,
+    repositories: &Repositories,
+    mut data: VecDeque<&str>,
+) -> MessageData {
+    info!("Received webhook paths command.");
+    let user = match get_user(repositories, &metadata.user).await {
+        Ok(Some(u)) => u,
+        Ok(None) => {
+            warn!("User attempted to modify a webhook but is not authorized to do so.");
+            return MessageData::from("You are not allowed to modify webhooks.");
+        }
+        Err(e) => {
+            error!(
+                error = ?e,
+                "Encountered an error while retrieving user information from the database."
+            );
+            return MessageData::from("Encountered an error while retrieving user information.");
+        }
+    };
+
+    let webhook_id = match data.pop_front() {
+        Some(w) => {
+            tracing::Span::current().record("webhook_id", &w);
+            w
+        }
+        None => return MessageData::from("No webhook specified."),
+    };
+    let policy = match data.pop_front().map(|p| p.to_lowercase()).as_deref() {
+        Some("full") => PathPolicy::Full,
+        Some("basename") => PathPolicy::Basename,
+        Some("hide") => PathPolicy::Hide,
+        Some("replace") => PathPolicy::Replace,
+        _ => return MessageData::from(
+            "Specify one of the path policies \"full\", \"basename\", \"hide\", or \"replace\".",
+        ),
+    };
+    let replacements: Vec<String> = data.into_iter().map(String::from).collect();
+    if matches!(policy, PathPolicy::Replace) {
+        if replacements.is_empty() {
+            return MessageData::from("Specify at least one replacement in the form \"from=to\".");
+        }
+        if let Some(r) = replacements.iter().find(|r| !is_valid_replacement(r)) {
+            return MessageData::from(
+                format!("The replacement \"{}\" must be in the form \"from=to\".", r).as_str(),
+            );
+        }
+    }
+
+    let webhook = match authorize(
+        repositories,
+        &user,
+        webhook_id,
+        WebhookAccess::Delegated(WebhookPermission::ManageFilters),
+    )
+    .await
+    {
+        Ok(w) => w,
+        Err(message) => return message,
+    };
+
+    match repositories
+        .webhooks
+        .update_path_policy(&webhook, policy, replacements)
+        .await
+    {
+        Ok(_) => {
+            info!(policy = ?policy, "Updated webhook path policy.");
+            MessageData::from("Updated how paths are shown for the webhook.")
+        }
+        Err(e) => {
+            error!(error = ?e, "Encountered error while updating the webhook.");
+            MessageData::from("Failed to update the webhook. Please try again.")
+        }
+    }
+}
+
+fn is_valid_replacement(replacement: &str) -> bool {
+    matches!(replacement.split_once('='), Some((from, _)) if !from.is_empty())
+}
@@ -0,0 +1,179 @@
+//! Supporting functions for pausing and resuming a webhook without removing it.
+
+use super::authorization::{authorize, WebhookAccess};
+use super::{format_time, get_user, parse_duration};
+use crate::commands::CommandMetadata;
+use crate::message::MessageData;
+use chrono::Utc;
+use std::collections::VecDeque;
+use tracing::{error, info, warn};
+use yarrbot_db::repositories::Repositories;
+
+/// Pause a webhook, either until it is resumed or for the given duration. A paused webhook still accepts events so
+/// that the *arr keeps sending them, but doesn't post them to any room.
+#[tracing::instrument(skip(repositories, data), fields(webhook_id))]
+pub async fn handle_pause(
+    metadata: CommandMetadata,
+    repositories: &Repositories,
+    mut data: VecDeque<&str>,
+) -> MessageData {
+    info!("Received webhook pause command.");
+    let user = match get_user(repositories, &metadata.user).await {
+        Ok(Some(u)) => u,
+        Ok(None) => {
+            warn!("User attempted to pause a webhook but is not authorized to do so.");
+            return MessageData::from("You are not allowed to modify webhooks.");
+        }
+        Err(e) => {
+            error!(
+                error = ?e,
+                "Encountered an error while retrieving user information from the database."
+            );
+            return MessageData::from("Encountered an error while retrieving user information.");
+        }
+    };
+
+    let webhook_id = match data.pop_front() {
+        Some(w) => {
+            tracing::Span::current().record("webhook_id", &w);
+            w
+        }
+        None => return MessageData::from("No webhook specified."),
+    };
+    let until = match data.pop_front() {
+        Some(raw) => match parse_duration(raw) {
+            Some(d) => Some(Utc::now() + d),
+            None => {
+                return MessageData::from(
+                    "Specify how long to pause the webhook for, e.g. 30m, 12h, 2d or 1w.",
+                )
+            }
+        },
+        None => None,
+    };
+
+    let webhook = match authorize(repositories, &user, webhook_id, WebhookAccess::Owner).await {
+        Ok(w) => w,
+        Err(message) => return message,
+    };
+    match repositories
+        .webhooks
+        .update_paused(&webhook, true, until)
+        .await
+    {
+        Ok(_) => {
+            info!(?until, "Paused webhook.");
+            match until {
+                Some(u) => MessageData::from(
+                    format!("The webhook is paused until {}.", format_time(u)).as_str(),
+                ),
+                None => MessageData::from("The webhook is paused until it is resumed."),
+            }
+        }
+        Err(e) => {
+            error!(error = ?e, "Encountered error while pausing the webhook.");
+            MessageData::from("Failed to pause the webhook. Please try again.")
+        }
+    }
+}
+
+/// Resume a paused webhook so that it posts to its rooms again.
+#[tracing::instrument(skip(repositories, data), fields(webhook_id))]
+pub async fn handle_resume(
+    metadata: CommandMetadata,
+    repositories: &Repositories,
+    mut data: VecDeque<&str>,
+) -> MessageData {
+    info!("Received webhook resume command.");
+    let user = match get_user(repositories, &metadata.user).await {
+        Ok(Some(u)) => u,
+        Ok(None) => {
+            warn!("User attempted to resume a webhook but is not authorized to do so.");
+            return MessageData::from("You are not allowed to modify webhooks.");
+        }
+        Err(e) => {
+            error!(
+                error = ?e,
+                "Encountered an error while retrieving user information from the database."
+            );
+            return MessageData::from("Encountered an error while retrieving user information.");
+        }
+    };
+
+    let webhook_id = match data.pop_front() {
+        Some(w) => {
+            tracing::Span::current().record("webhook_id", &w);
+            w
+        }
+        None => return MessageData::from("No webhook specified."),
+    };
+    let webhook = match authorize(repositories, &user, webhook_id, WebhookAccess::Owner).await {
+        Ok(w) => w,
+        Err(message) => return message,
+    };
+    if !webhook.is_paused(Utc::now()) {
+        return MessageData::from("The webhook isn't paused.");
+    }
+
+    match repositories
+        .webhooks
+        .update_paused(&webhook, false, None)
+        .await
+    {
+        Ok(_) => {
+            info!("Resumed webhook.");
+            MessageData::from("The webhook will post to its rooms again.")
+        }
+        Err(e) => {
+            error!(error = ?e, "Encountered error while resuming the webhook.");
+            MessageData::from("Failed to resume the webhook. Please try again.")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yarrbot_common::short_id::ShortId;
+    use yarrbot_db::models::{NewUser, NewWebhook};
+
+    #[tokio::test]
+    async fn handle_pause_pauses_webhook_for_duration() {
+        // Arrange
+        let repositories = Repositories::in_memory();
+        let owner = repositories
+            .users
+            .create_user(NewUser::new("@owner:example.org", None))
+            .await
+            .unwrap();
+        let webhook = repositories
+            .webhooks
+            .create_webhook(NewWebhook::new("user", vec![0], &owner, None))
+            .await
+            .unwrap();
+        let metadata = CommandMetadata {
+            user: String::from("@owner:example.org"),
+            is_direct_message: true,
+        };
+        let webhook_id = webhook.id.to_short_id();
+
+        // Act
+        let actual = handle_pause(
+            metadata,
+            &repositories,
+            VecDeque::from([webhook_id.as_str(), "2h"]),
+        )
+        .await;
+
+        // Assert
+        assert!(actual.plain.starts_with("The webhook is paused until"));
+        let webhook = repositories
+            .webhooks
+            .try_get(&webhook.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert!(webhook.is_paused(Utc::now()));
+        assert!(!webhook.is_paused(Utc::now() + chrono::Duration::hours(3)));
+    }
+}
@@ -0,0 +1,188 @@
+//! Supporting functions for setting quiet hours on the rooms a webhook posts to.
+
+use super::authorization::{authorize, WebhookAccess};
+use super::get_user;
+use crate::commands::CommandMetadata;
+use crate::message::MessageData;
+use std::collections::VecDeque;
+use tracing::{error, info, warn};
+use yarrbot_db::models::WebhookPermission;
+use yarrbot_db::quiet_hours::QuietHours;
+use yarrbot_db::repositories::Repositories;
+
+/// Set or remove the quiet hours of one or all of the rooms a webhook posts to. Messages arriving during a room's
+/// quiet hours are held and posted as a summary once they end, except for health check errors if `health` is given.
+#[tracing::instrument(skip(repositories, data), fields(webhook_id, room_id))]
+pub async fn handle_quiet(
+    metadata: CommandMetadata,
+    repositories: &Repositories,
+    mut data: VecDeque<&str>,
+) -> MessageData {
+    info!("Received webhook quiet command.");
+    let span = tracing::Span::current();
+    let user = match get_user(repositories, &metadata.user).await {
+        Ok(Some(u)) => u,
+        Ok(None) => {
+            warn!("User attempted to set a room's quiet hours but is not authorized to do so.");
+            return MessageData::from("You are not allowed to modify webhooks.");
+        }
+        Err(e) => {
+            error!(
+                error = ?e,
+                "Encountered an error while retrieving user information from the database."
+            );
+            return MessageData::from("Encountered an error while retrieving user information.");
+        }
+    };
+
+    let webhook_id = match data.pop_front() {
+        Some(w) => {
+            span.record("webhook_id", &w);
+            w
+        }
+        None => return MessageData::from("No webhook specified."),
+    };
+    let (quiet_hours, bypass_health) = match parse_quiet_hours(&mut data) {
+        Ok(q) => q,
+        Err(message) => return MessageData::from(message.as_str()),
+    };
+    let room_id = data.pop_front();
+    if let Some(r) = room_id {
+        span.record("room_id", &r);
+    }
+
+    let webhook = match authorize(
+        repositories,
+        &user,
+        webhook_id,
+        WebhookAccess::Delegated(WebhookPermission::ManageRooms),
+    )
+    .await
+    {
+        Ok(w) => w,
+        Err(message) => return message,
+    };
+
+    match repositories
+        .rooms
+        .update_quiet_hours(&webhook.id, room_id, quiet_hours.as_ref(), bypass_health)
+        .await
+    {
+        Ok(0) => MessageData::from("The webhook doesn't post to that room."),
+        Ok(count) => {
+            info!(
+                count,
+                ?quiet_hours,
+                bypass_health,
+                "Updated quiet hours for rooms."
+            );
+            let message = match quiet_hours {
+                Some(q) if bypass_health => format!(
+                    "Quiet hours set to {} in {} room(s). Health check errors will still be posted.",
+                    q, count
+                ),
+                Some(q) => format!("Quiet hours set to {} in {} room(s).", q, count),
+                None => format!("Quiet hours removed from {} room(s).", count),
+            };
+            MessageData::from(message.as_str())
+        }
+        Err(e) => {
+            error!(error = ?e, "Encountered error while updating quiet hours.");
+            MessageData::from("Failed to update the quiet hours. Please try again.")
+        }
+    }
+}
+
+/// Parse either `off`, or a range of times and a time zone optionally followed by `health`, from the front of the
+/// command's arguments.
+fn parse_quiet_hours(data: &mut VecDeque<&str>) -> Result<(Option<QuietHours>, bool), String> {
+    let range = match data.pop_front() {
+        Some(r) if r.eq_ignore_ascii_case("off") => return Ok((None, false)),
+        Some(r) => r,
+        None => return Err(String::from(
+            "Specify the quiet hours and time zone, e.g. 22:00-07:00 Europe/Berlin, or \"off\".",
+        )),
+    };
+    let timezone = match data.pop_front() {
+        Some(t) => t,
+        None => {
+            return Err(String::from(
+                "Specify the time zone of the quiet hours, e.g. Europe/Berlin.",
+            ))
+        }
+    };
+    let quiet_hours = QuietHours::parse(range, timezone).map_err(|e| e.to_string())?;
+    let bypass_health = match data.front() {
+        Some(h) if h.eq_ignore_ascii_case("health") => {
+            data.pop_front();
+            true
+        }
+        _ => false,
+    };
+    Ok((Some(quiet_hours), bypass_health))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yarrbot_common::short_id::ShortId;
+    use yarrbot_db::models::{NewMatrixRoom, NewUser, NewWebhook};
+
+    #[tokio::test]
+    async fn handle_quiet_sets_quiet_hours_for_given_room() {
+        // Arrange
+        let repositories = Repositories::in_memory();
+        let owner = repositories
+            .users
+            .create_user(NewUser::new("@owner:example.org", None))
+            .await
+            .unwrap();
+        let webhook = repositories
+            .webhooks
+            .create_webhook(NewWebhook::new("user", vec![0], &owner, None))
+            .await
+            .unwrap();
+        for room_id in ["!a:example.org", "!b:example.org"] {
+            repositories
+                .rooms
+                .create_room(NewMatrixRoom::new(room_id, &webhook))
+                .await
+                .unwrap();
+        }
+        let metadata = CommandMetadata {
+            user: String::from("@owner:example.org"),
+            is_direct_message: true,
+        };
+        let webhook_id = webhook.id.to_short_id();
+
+        // Act
+        let actual = handle_quiet(
+            metadata,
+            &repositories,
+            VecDeque::from([
+                webhook_id.as_str(),
+                "22:00-07:00",
+                "Europe/Berlin",
+                "health",
+                "!a:example.org",
+            ]),
+        )
+        .await;
+
+        // Assert
+        assert_eq!(
+            "Quiet hours set to 22:00-07:00 Europe/Berlin in 1 room(s). Health check errors will still be posted.",
+            actual.plain
+        );
+        let quiet: Vec<(String, bool)> = repositories
+            .rooms
+            .get_by_webhook_id(&webhook.id)
+            .await
+            .unwrap()
+            .into_iter()
+            .filter(|r| r.quiet_hours().is_some())
+            .map(|r| (r.room_id, r.quiet_hours_bypass_health))
+            .collect();
+        assert_eq!(vec![(String::from("!a:example.org"), true)], quiet);
+    }
+}
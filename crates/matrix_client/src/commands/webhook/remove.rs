@@ -1,28 +1,26 @@
 //! Supporting functions for removing webhooks.
 
+use super::authorization::{authorize, WebhookAccess};
 use super::get_user;
+use super::room::leave_room;
 use crate::commands::CommandMetadata;
 use crate::message::MessageData;
-use anyhow::Result;
+use matrix_sdk::Client;
 use std::collections::VecDeque;
-use tokio::task::spawn_blocking;
 use tracing::{error, info, warn};
-use uuid::Uuid;
-use yarrbot_common::short_id::ShortId;
-use yarrbot_db::actions::webhook_actions::WebhookActions;
-use yarrbot_db::enums::UserRole;
-use yarrbot_db::models::Webhook;
-use yarrbot_db::DbPool;
-
-/// Remove a webhook from the database.
-#[tracing::instrument(skip(pool, data), fields(webhook_id))]
+use yarrbot_db::models::User;
+use yarrbot_db::repositories::Repositories;
+
+/// Remove a webhook from the database, leaving the rooms no other webhook posts to.
+#[tracing::instrument(skip(client, repositories, data), fields(webhook_id))]
 pub async fn handle_remove(
     metadata: CommandMetadata,
-    pool: &DbPool,
+    client: &Client,
+    repositories: &Repositories,
     mut data: VecDeque<&str>,
 ) -> MessageData {
     info!("Received webhook remove command.");
-    let user = match get_user(pool, &metadata.user).await {
+    let user = match get_user(repositories, &metadata.user).await {
         Ok(Some(u)) => u,
         Ok(None) => {
             warn!("User attempted to remove a webhook but is not authorized to do so.");
@@ -47,51 +45,161 @@ pub async fn handle_remove(
             return MessageData::from("No webhook specified.");
         }
     };
-    let webhook = match get_webhook(pool, webhook_id).await {
-        Ok(Some(w)) => w,
-        Ok(None) => return MessageData::from("That webhook doesn't exist."),
+    let unused_rooms = match remove_webhook(repositories, &user, webhook_id).await {
+        Ok(r) => r,
+        Err(message) => return message,
+    };
+    for room_id in &unused_rooms {
+        leave_room(client, room_id).await;
+    }
+    MessageData::from("Webhook removed.")
+}
+
+/// Delete a webhook the user owns, returning the IDs of its rooms that no other webhook posts to.
+async fn remove_webhook(
+    repositories: &Repositories,
+    user: &User,
+    webhook_id: &str,
+) -> Result<Vec<String>, MessageData> {
+    let webhook = authorize(repositories, user, webhook_id, WebhookAccess::Owner).await?;
+    let rooms = match repositories.rooms.get_by_webhook_id(&webhook.id).await {
+        Ok(r) => r,
         Err(e) => {
-            error!(
-                error = ?e,
-                "Encountered an error while retrieving Webhook data from the database."
-            );
-            return MessageData::from("Error encountered while looking up the webhook.");
+            error!(error = ?e, "Encountered an error while retrieving the webhook's rooms.");
+            return Err(MessageData::from(
+                "Failed to delete webhook. Please try again.",
+            ));
         }
     };
-    if webhook.user_id != user.id && !matches!(user.user_role, UserRole::SystemAdministrator) {
-        return MessageData::from("You are not allowed to modify this webhook.");
+
+    if let Err(e) = repositories.webhooks.delete(&webhook).await {
+        error!("Encountered error while deleting webhook: {:?}", e);
+        return Err(MessageData::from(
+            "Failed to delete webhook. Please try again.",
+        ));
     }
+    info!("Deleted webhook.");
 
-    match delete_webhook(pool, webhook).await {
-        Ok(_) => {
-            info!("Deleted webhook.");
-            MessageData::from("Webhook removed.")
-        }
-        Err(e) => {
-            error!("Encountered error while deleting webhook: {:?}", e);
-            MessageData::from("Failed to delete webhook. Please try again.")
+    let mut unused_rooms = Vec::new();
+    for room in rooms {
+        match repositories.rooms.get_by_room_id(&room.room_id).await {
+            Ok(remaining) if remaining.is_empty() => unused_rooms.push(room.room_id),
+            Ok(_) => (),
+            // Staying in a room nothing posts to is harmless, so don't fail the command.
+            Err(e) => warn!(error = ?e, "Couldn't check whether other webhooks post to the room."),
         }
     }
+    Ok(unused_rooms)
 }
 
-/// Get a webhook record from the database.
-async fn get_webhook(pool: &DbPool, webhook_id: &str) -> Result<Option<Webhook>> {
-    let webhook_uuid = match Uuid::from_short_id(webhook_id) {
-        Ok(u) => u,
-        Err(e) => {
-            warn!(
-                error = ?e,
-                "Encountered error decoding UUID from short ID."
-            );
-            return Ok(None);
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yarrbot_common::short_id::ShortId;
+    use yarrbot_db::models::{NewMatrixRoom, NewUser, NewWebhook};
+
+    #[tokio::test]
+    async fn remove_webhook_refuses_webhooks_owned_by_other_users() {
+        // Arrange
+        let repositories = Repositories::in_memory();
+        let owner = repositories
+            .users
+            .create_user(NewUser::new("@owner:example.org", None))
+            .await
+            .unwrap();
+        let other = repositories
+            .users
+            .create_user(NewUser::new("@other:example.org", None))
+            .await
+            .unwrap();
+        let webhook = repositories
+            .webhooks
+            .create_webhook(NewWebhook::new("user", vec![0], &owner, None))
+            .await
+            .unwrap();
+        let webhook_id = webhook.id.to_short_id();
+
+        // Act
+        let actual = remove_webhook(&repositories, &other, &webhook_id).await;
+
+        // Assert
+        assert_eq!(
+            "You are not allowed to modify this webhook.",
+            actual.unwrap_err().plain
+        );
+        assert!(repositories
+            .webhooks
+            .try_get(&webhook.id)
+            .await
+            .unwrap()
+            .is_some());
+    }
+
+    #[tokio::test]
+    async fn remove_webhook_deletes_own_webhook() {
+        // Arrange
+        let repositories = Repositories::in_memory();
+        let owner = repositories
+            .users
+            .create_user(NewUser::new("@owner:example.org", None))
+            .await
+            .unwrap();
+        let webhook = repositories
+            .webhooks
+            .create_webhook(NewWebhook::new("user", vec![0], &owner, None))
+            .await
+            .unwrap();
+        let webhook_id = webhook.id.to_short_id();
+
+        // Act
+        let actual = remove_webhook(&repositories, &owner, &webhook_id).await;
+
+        // Assert
+        assert!(actual.unwrap().is_empty());
+        assert!(repositories
+            .webhooks
+            .try_get(&webhook.id)
+            .await
+            .unwrap()
+            .is_none());
+    }
+
+    #[tokio::test]
+    async fn remove_webhook_reports_rooms_no_other_webhook_posts_to() {
+        // Arrange
+        let repositories = Repositories::in_memory();
+        let owner = repositories
+            .users
+            .create_user(NewUser::new("@owner:example.org", None))
+            .await
+            .unwrap();
+        let webhook = repositories
+            .webhooks
+            .create_webhook(NewWebhook::new("user", vec![0], &owner, None))
+            .await
+            .unwrap();
+        let other = repositories
+            .webhooks
+            .create_webhook(NewWebhook::new("other", vec![0], &owner, None))
+            .await
+            .unwrap();
+        for (room_id, webhook) in [
+            ("!a:example.org", &webhook),
+            ("!b:example.org", &webhook),
+            ("!b:example.org", &other),
+        ] {
+            repositories
+                .rooms
+                .create_room(NewMatrixRoom::new(room_id, webhook))
+                .await
+                .unwrap();
         }
-    };
-    let conn = pool.get()?;
-    Ok(spawn_blocking(move || Webhook::try_get(&conn, &webhook_uuid)).await??)
-}
+        let webhook_id = webhook.id.to_short_id();
+
+        // Act
+        let actual = remove_webhook(&repositories, &owner, &webhook_id).await;
 
-/// Delete the webhook from the database.
-async fn delete_webhook(pool: &DbPool, webhook: Webhook) -> Result<()> {
-    let conn2 = pool.get()?;
-    Ok(spawn_blocking(move || webhook.delete(&conn2)).await??)
+        // Assert
+        assert_eq!(vec![String::from("!a:example.org")], actual.unwrap());
+    }
 }
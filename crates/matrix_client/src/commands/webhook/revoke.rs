@@ -0,0 +1,172 @@
+//! Supporting functions for taking back access to a webhook shared with another user.
+
+use super::authorization::{authorize, describe_permissions, parse_permissions, WebhookAccess};
+use super::get_user;
+use crate::commands::get_target_user;
+use crate::commands::CommandMetadata;
+use crate::message::MessageData;
+use std::collections::VecDeque;
+use tracing::{error, info, warn};
+use yarrbot_db::repositories::Repositories;
+
+/// Take the given permissions for a webhook away from another user. Without any permissions the webhook is no longer
+/// shared with them at all.
+#[tracing::instrument(skip(repositories, data), fields(webhook_id, target_user))]
+pub async fn handle_revoke(
+    metadata: CommandMetadata,
+    repositories: &Repositories,
+    mut data: VecDeque<&str>,
+) -> MessageData {
+    info!("Received webhook revoke command.");
+    let span = tracing::Span::current();
+    let user = match get_user(repositories, &metadata.user).await {
+        Ok(Some(u)) => u,
+        Ok(None) => {
+            warn!("User attempted to revoke access to a webhook but is not authorized to do so.");
+            return MessageData::from("You are not allowed to modify webhooks.");
+        }
+        Err(e) => {
+            error!(
+                error = ?e,
+                "Encountered an error while retrieving user information from the database."
+            );
+            return MessageData::from("Encountered an error while retrieving user information.");
+        }
+    };
+
+    let webhook_id = match data.pop_front() {
+        Some(w) => {
+            span.record("webhook_id", &w);
+            w
+        }
+        None => return MessageData::from("No webhook specified."),
+    };
+    let target = match get_target_user(repositories, data.pop_front()).await {
+        Ok(u) => u,
+        Err(message) => return message,
+    };
+    span.record("target_user", &target.service_username.as_str());
+    let permissions = match parse_permissions(data) {
+        Ok(p) => p,
+        Err(message) => return MessageData::from(message.as_str()),
+    };
+
+    let webhook = match authorize(repositories, &user, webhook_id, WebhookAccess::Owner).await {
+        Ok(w) => w,
+        Err(message) => return message,
+    };
+    let mut collaborator = match repositories
+        .collaborators
+        .try_get(&webhook.id, &target.id)
+        .await
+    {
+        Ok(Some(c)) => c,
+        Ok(None) => return MessageData::from("The webhook isn't shared with that user."),
+        Err(e) => {
+            error!(error = ?e, "Encountered an error while retrieving collaborator data.");
+            return MessageData::from("Failed to revoke access. Please try again.");
+        }
+    };
+
+    if permissions.is_empty() {
+        return match repositories.collaborators.delete(&collaborator).await {
+            Ok(_) => {
+                info!("Stopped sharing webhook.");
+                MessageData::from(
+                    format!(
+                        "The webhook is no longer shared with {}.",
+                        target.service_username
+                    )
+                    .as_str(),
+                )
+            }
+            Err(e) => {
+                error!(error = ?e, "Encountered an error while revoking access.");
+                MessageData::from("Failed to revoke access. Please try again.")
+            }
+        };
+    }
+
+    for permission in permissions {
+        collaborator.set_permission(permission, false);
+    }
+    match repositories
+        .collaborators
+        .update_permissions(&collaborator)
+        .await
+    {
+        Ok(c) => {
+            info!("Revoked webhook permissions.");
+            MessageData::from(
+                format!(
+                    "Revoked the permissions. {}'s permissions: {}.",
+                    target.service_username,
+                    describe_permissions(&c)
+                )
+                .as_str(),
+            )
+        }
+        Err(e) => {
+            error!(error = ?e, "Encountered an error while revoking permissions.");
+            MessageData::from("Failed to revoke the permissions. Please try again.")
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yarrbot_common::short_id::ShortId;
+    use yarrbot_db::models::{NewUser, NewWebhook, NewWebhookCollaborator};
+
+    #[tokio::test]
+    async fn handle_revoke_without_permissions_stops_sharing() {
+        // Arrange
+        let repositories = Repositories::in_memory();
+        let owner = repositories
+            .users
+            .create_user(NewUser::new("@owner:example.org", None))
+            .await
+            .unwrap();
+        let other = repositories
+            .users
+            .create_user(NewUser::new("@other:example.org", None))
+            .await
+            .unwrap();
+        let webhook = repositories
+            .webhooks
+            .create_webhook(NewWebhook::new("user", vec![0], &owner, None))
+            .await
+            .unwrap();
+        repositories
+            .collaborators
+            .create_collaborator(NewWebhookCollaborator::new(&webhook, &other))
+            .await
+            .unwrap();
+        let metadata = CommandMetadata {
+            user: String::from("@owner:example.org"),
+            is_direct_message: true,
+        };
+        let webhook_id = webhook.id.to_short_id();
+
+        // Act
+        let actual = handle_revoke(
+            metadata,
+            &repositories,
+            VecDeque::from([webhook_id.as_str(), "@other:example.org"]),
+        )
+        .await;
+
+        // Assert
+        assert_eq!(
+            "The webhook is no longer shared with @other:example.org.",
+            actual.plain
+        );
+        assert!(repositories
+            .collaborators
+            .try_get(&webhook.id, &other.id)
+            .await
+            .unwrap()
+            .is_none());
+    }
+}
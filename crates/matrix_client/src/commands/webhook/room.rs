@@ -0,0 +1,386 @@
+//! Supporting functions for attaching rooms to and detaching them from an existing webhook.
+
+use super::add::join_room;
+use super::authorization::{authorize, WebhookAccess};
+use super::get_user;
+use crate::commands::CommandMetadata;
+use crate::message::{MessageData, MessageDataBuilder};
+use anyhow::Result;
+use matrix_sdk::ruma::api::client::r0::alias::get_alias;
+use matrix_sdk::ruma::identifiers::{RoomId, RoomIdOrAliasId};
+use matrix_sdk::Client;
+use std::collections::VecDeque;
+use std::convert::TryFrom;
+use tracing::{error, info, warn};
+use yarrbot_db::models::{NewMatrixRoom, User, WebhookPermission};
+use yarrbot_db::repositories::Repositories;
+
+/// Add a room to a webhook, remove one from it, or list the rooms it posts to.
+#[tracing::instrument(skip(client, repositories, data), fields(webhook_id, raw_room))]
+pub async fn handle_room(
+    metadata: CommandMetadata,
+    client: &Client,
+    repositories: &Repositories,
+    mut data: VecDeque<&str>,
+) -> MessageData {
+    info!("Received webhook room command.");
+    let span = tracing::Span::current();
+    let user = match get_user(repositories, &metadata.user).await {
+        Ok(Some(u)) => u,
+        Ok(None) => {
+            warn!("User attempted to modify a webhook but is not authorized to do so.");
+            return MessageData::from("You are not allowed to modify webhooks.");
+        }
+        Err(e) => {
+            error!(
+                error = ?e,
+                "Encountered an error while retrieving user information from the database."
+            );
+            return MessageData::from("Encountered an error while retrieving user information.");
+        }
+    };
+
+    let subcommand = match data.pop_front().map(|s| s.to_lowercase()).as_deref() {
+        Some(c @ ("add" | "remove" | "list")) => String::from(c),
+        _ => {
+            return MessageData::from(
+                "Specify whether to \"add\", \"remove\" or \"list\" the webhook's rooms.",
+            )
+        }
+    };
+    let webhook_id = match data.pop_front() {
+        Some(w) => {
+            span.record("webhook_id", &w);
+            w
+        }
+        None => return MessageData::from("No webhook specified."),
+    };
+    if subcommand == "list" {
+        return list_rooms(repositories, &user, webhook_id).await;
+    }
+
+    let raw_room = match data.pop_front() {
+        Some(r) => {
+            span.record("raw_room", &r);
+            r
+        }
+        None => return MessageData::from("No room specified."),
+    };
+    if subcommand == "add" {
+        attach_room(client, repositories, &user, webhook_id, raw_room).await
+    } else {
+        detach_room(client, repositories, &user, webhook_id, raw_room).await
+    }
+}
+
+/// Join a room and have the webhook post to it as well as to its existing rooms. The room is only joined once the user
+/// is known to be allowed to add it and the webhook doesn't already post to it.
+async fn attach_room(
+    client: &Client,
+    repositories: &Repositories,
+    user: &User,
+    webhook_id: &str,
+    raw_room: &str,
+) -> MessageData {
+    let room_alias = match RoomIdOrAliasId::try_from(raw_room) {
+        Ok(r) => r,
+        Err(e) => {
+            warn!(error = ?e, "Unable to parse the Room ID or Alias ID.");
+            return MessageData::from(
+                format!("Could not parse room or alias \"{}\".", raw_room).as_str(),
+            );
+        }
+    };
+    let webhook = match authorize(
+        repositories,
+        user,
+        webhook_id,
+        WebhookAccess::Delegated(WebhookPermission::ManageRooms),
+    )
+    .await
+    {
+        Ok(w) => w,
+        Err(message) => return message,
+    };
+
+    let room_id = match resolve_room_id(client, room_alias.clone()).await {
+        Ok(r) => r,
+        Err(e) => {
+            warn!(error = ?e, "Unable to resolve the room alias.");
+            return MessageData::from(
+                format!("Could not find the room \"{}\".", raw_room).as_str(),
+            );
+        }
+    };
+    let room_id = room_id.as_str();
+    match repositories.rooms.get_by_webhook_id(&webhook.id).await {
+        Ok(rooms) if rooms.iter().any(|r| r.room_id == room_id) => {
+            return MessageData::from("The webhook already posts to that room.")
+        }
+        Ok(_) => (),
+        Err(e) => {
+            error!(error = ?e, "Encountered an error while retrieving the webhook's rooms.");
+            return MessageData::from("Failed to add the room. Please try again.");
+        }
+    }
+
+    if let Err(e) = join_room(client, &room_alias).await {
+        error!(error = ?e, "Encountered an error while joining the room.");
+        return MessageData::from(
+            "Encountered issue while attempting to join room. You may need to invite yarrbot to the room first."
+        );
+    }
+    match repositories
+        .rooms
+        .create_room(NewMatrixRoom::new(room_id, &webhook))
+        .await
+    {
+        Ok(_) => {
+            info!(room_id, "Attached room to webhook.");
+            MessageData::from(format!("The webhook will also post to {}.", raw_room).as_str())
+        }
+        Err(e) => {
+            error!(error = ?e, "Encountered an error while adding the room.");
+            MessageData::from("Failed to add the room. Please try again.")
+        }
+    }
+}
+
+/// Find the ID of the room an alias points to; room IDs are returned as they are.
+async fn resolve_room_id(client: &Client, room: RoomIdOrAliasId) -> Result<RoomId> {
+    match RoomId::try_from(room) {
+        Ok(room_id) => Ok(room_id),
+        Err(alias) => Ok(client
+            .send(get_alias::Request::new(&alias), None)
+            .await?
+            .room_id),
+    }
+}
+
+/// Stop the webhook posting to a room, leaving the room if no other webhook posts to it.
+async fn detach_room(
+    client: &Client,
+    repositories: &Repositories,
+    user: &User,
+    webhook_id: &str,
+    room_id: &str,
+) -> MessageData {
+    let is_unused = match remove_room(repositories, user, webhook_id, room_id).await {
+        Ok(u) => u,
+        Err(message) => return message,
+    };
+    if is_unused {
+        leave_room(client, room_id).await;
+    }
+    MessageData::from(format!("The webhook no longer posts to {}.", room_id).as_str())
+}
+
+/// Delete the record of a webhook posting to a room, returning whether any webhook still posts to the room. A webhook
+/// keeps at least one room, since it would have nowhere to post to otherwise.
+async fn remove_room(
+    repositories: &Repositories,
+    user: &User,
+    webhook_id: &str,
+    room_id: &str,
+) -> Result<bool, MessageData> {
+    let webhook = authorize(
+        repositories,
+        user,
+        webhook_id,
+        WebhookAccess::Delegated(WebhookPermission::ManageRooms),
+    )
+    .await?;
+    let rooms = match repositories.rooms.get_by_webhook_id(&webhook.id).await {
+        Ok(r) => r,
+        Err(e) => {
+            error!(error = ?e, "Encountered an error while retrieving the webhook's rooms.");
+            return Err(MessageData::from(
+                "Failed to remove the room. Please try again.",
+            ));
+        }
+    };
+    let room = match rooms.iter().find(|r| r.room_id == room_id) {
+        Some(r) => r,
+        None => return Err(MessageData::from(
+            "The webhook doesn't post to that room. Use a room ID as shown by `webhook room list`.",
+        )),
+    };
+    if rooms.len() == 1 {
+        return Err(MessageData::from(
+            "That is the webhook's only room. Add another room first, or remove the webhook instead.",
+        ));
+    }
+
+    if let Err(e) = repositories.rooms.delete(room).await {
+        error!(error = ?e, "Encountered an error while removing the room.");
+        return Err(MessageData::from(
+            "Failed to remove the room. Please try again.",
+        ));
+    }
+    info!("Detached room from webhook.");
+
+    match repositories.rooms.get_by_room_id(room_id).await {
+        Ok(remaining) => Ok(remaining.is_empty()),
+        Err(e) => {
+            // Staying in a room nothing posts to is harmless, so don't fail the command.
+            warn!(error = ?e, "Couldn't check whether other webhooks post to the room.");
+            Ok(false)
+        }
+    }
+}
+
+/// Leave a room that no webhook posts to anymore.
+pub(super) async fn leave_room(client: &Client, room_id: &str) {
+    let room = RoomId::try_from(room_id)
+        .ok()
+        .and_then(|id| client.get_joined_room(&id));
+    match room {
+        Some(r) => match r.leave().await {
+            Ok(_) => info!(room_id, "Left room no webhook posts to."),
+            Err(e) => warn!(error = ?e, room_id, "Couldn't leave room no webhook posts to."),
+        },
+        None => warn!(
+            room_id,
+            "Couldn't find the room no webhook posts to in order to leave it."
+        ),
+    }
+}
+
+/// List the rooms a webhook posts to.
+async fn list_rooms(repositories: &Repositories, user: &User, webhook_id: &str) -> MessageData {
+    let webhook = match authorize(repositories, user, webhook_id, WebhookAccess::View).await {
+        Ok(w) => w,
+        Err(message) => return message,
+    };
+    let rooms = match repositories.rooms.get_by_webhook_id(&webhook.id).await {
+        Ok(r) => r,
+        Err(e) => {
+            error!(error = ?e, "Encountered an error while retrieving the webhook's rooms.");
+            return MessageData::from(
+                "Couldn't retrieve the webhook's rooms, please try again later.",
+            );
+        }
+    };
+
+    let mut builder = MessageDataBuilder::new();
+    if rooms.is_empty() {
+        builder.add_line("The webhook doesn't post to any rooms.");
+    } else {
+        builder.add_line("Rooms:");
+        for room in &rooms {
+            let spoilers = if room.hide_spoilers {
+                "Spoilers hidden"
+            } else {
+                "Spoilers shown"
+            };
+            builder.add_key_value_with_code(spoilers, &room.room_id);
+        }
+    }
+    builder.to_message_data()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yarrbot_common::short_id::ShortId;
+    use yarrbot_db::models::{NewUser, NewWebhook, Webhook};
+
+    async fn create_webhook_with_rooms(
+        repositories: &Repositories,
+        room_ids: &[&str],
+    ) -> (User, Webhook) {
+        let owner = repositories
+            .users
+            .create_user(NewUser::new("@owner:example.org", None))
+            .await
+            .unwrap();
+        let webhook = repositories
+            .webhooks
+            .create_webhook(NewWebhook::new("user", vec![0], &owner, None))
+            .await
+            .unwrap();
+        for room_id in room_ids {
+            repositories
+                .rooms
+                .create_room(NewMatrixRoom::new(room_id, &webhook))
+                .await
+                .unwrap();
+        }
+        (owner, webhook)
+    }
+
+    #[tokio::test]
+    async fn remove_room_refuses_only_room() {
+        // Arrange
+        let repositories = Repositories::in_memory();
+        let (owner, webhook) = create_webhook_with_rooms(&repositories, &["!a:example.org"]).await;
+
+        // Act
+        let actual = remove_room(
+            &repositories,
+            &owner,
+            &webhook.id.to_short_id(),
+            "!a:example.org",
+        )
+        .await;
+
+        // Assert
+        assert!(actual
+            .unwrap_err()
+            .plain
+            .starts_with("That is the webhook's only room."));
+    }
+
+    #[tokio::test]
+    async fn remove_room_reports_whether_room_is_unused() {
+        // Arrange
+        let repositories = Repositories::in_memory();
+        let (owner, webhook) =
+            create_webhook_with_rooms(&repositories, &["!a:example.org", "!b:example.org"]).await;
+        let other = repositories
+            .webhooks
+            .create_webhook(NewWebhook::new("other", vec![0], &owner, None))
+            .await
+            .unwrap();
+        repositories
+            .rooms
+            .create_room(NewMatrixRoom::new("!b:example.org", &other))
+            .await
+            .unwrap();
+        repositories
+            .rooms
+            .create_room(NewMatrixRoom::new("!c:example.org", &webhook))
+            .await
+            .unwrap();
+        let webhook_id = webhook.id.to_short_id();
+
+        // Act
+        let unused = remove_room(&repositories, &owner, &webhook_id, "!a:example.org").await;
+        let shared = remove_room(&repositories, &owner, &webhook_id, "!b:example.org").await;
+
+        // Assert
+        assert!(unused.unwrap());
+        assert!(!shared.unwrap());
+        let remaining = repositories
+            .rooms
+            .get_by_webhook_id(&webhook.id)
+            .await
+            .unwrap();
+        assert_eq!(1, remaining.len());
+        assert_eq!("!c:example.org", remaining[0].room_id);
+    }
+
+    #[tokio::test]
+    async fn list_rooms_lists_webhook_rooms() {
+        // Arrange
+        let repositories = Repositories::in_memory();
+        let (owner, webhook) = create_webhook_with_rooms(&repositories, &["!a:example.org"]).await;
+
+        // Act
+        let actual = list_rooms(&repositories, &owner, &webhook.id.to_short_id()).await;
+
+        // Assert
+        assert!(actual.plain.contains("!a:example.org"));
+        assert!(actual.plain.contains("Spoilers shown"));
+    }
+}
@@ -0,0 +1,204 @@
+//! Supporting functions for replacing a webhook's password in place.
+
+use super::authorization::{authorize, WebhookAccess};
+use super::get_user;
+use crate::commands::CommandMetadata;
+use crate::message::{MessageData, MessageDataBuilder};
+use anyhow::Result;
+use std::collections::VecDeque;
+use std::time::Duration;
+use tracing::{error, info, warn};
+use yarrbot_common::crypto::{generate_password, hash, hash_parameters};
+use yarrbot_db::enums::WebhookAuthMode;
+use yarrbot_db::models::{Webhook, WebhookPermission};
+use yarrbot_db::repositories::Repositories;
+
+/// How long the replaced password keeps working, giving time to update the *arr's configuration.
+const GRACE_PERIOD: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// Replace a webhook's password, generating a new one if none is given. The webhook keeps its ID. Passing `now`
+/// before the password stops the previous password from working straight away instead of after [GRACE_PERIOD].
+#[tracing::instrument(skip(repositories, data), fields(webhook_id, immediate, has_password))]
+pub async fn handle_rotate(
+    metadata: CommandMetadata,
+    repositories: &Repositories,
+    mut data: VecDeque<&str>,
+) -> MessageData {
+    info!("Received webhook rotate command.");
+    let span = tracing::Span::current();
+    let user = match get_user(repositories, &metadata.user).await {
+        Ok(Some(u)) => u,
+        Ok(None) => {
+            warn!("User attempted to modify a webhook but is not authorized to do so.");
+            return MessageData::from("You are not allowed to modify webhooks.");
+        }
+        Err(e) => {
+            error!(
+                error = ?e,
+                "Encountered an error while retrieving user information from the database."
+            );
+            return MessageData::from("Encountered an error while retrieving user information.");
+        }
+    };
+
+    let webhook_id = match data.pop_front() {
+        Some(w) => {
+            span.record("webhook_id", &w);
+            w
+        }
+        None => return MessageData::from("No webhook specified."),
+    };
+    let immediate = data.front() == Some(&"now");
+    if immediate {
+        data.pop_front();
+    }
+    span.record("immediate", &immediate);
+    let grace_period = if immediate { None } else { Some(GRACE_PERIOD) };
+    let password = match data.pop_front() {
+        Some(p) => {
+            span.record("has_password", &true);
+            String::from(p)
+        }
+        None => {
+            span.record("has_password", &false);
+            generate_password(None).unwrap()
+        }
+    };
+
+    let webhook = match authorize(
+        repositories,
+        &user,
+        webhook_id,
+        WebhookAccess::Delegated(WebhookPermission::RotateCredentials),
+    )
+    .await
+    {
+        Ok(w) => w,
+        Err(message) => return message,
+    };
+
+    let webhook = match rotate_password(repositories, webhook, &password, grace_period).await {
+        Ok(w) => w,
+        Err(e) => {
+            error!(error = ?e, "Encountered error while updating the webhook.");
+            return MessageData::from("Failed to update the webhook. Please try again.");
+        }
+    };
+
+    info!("Rotated webhook password.");
+    let mut builder = MessageDataBuilder::new();
+    builder.add_line("Replaced the webhook's password.");
+    builder.add_key_value_with_code("Username", &webhook.username);
+    builder.add_key_value_with_code("Password", &password);
+    match webhook.previous_password_expires_at {
+        Some(expires_at) => builder.add_line(&format!(
+            "The previous password will keep working until {}.",
+            expires_at.format("%Y-%m-%d %H:%M UTC")
+        )),
+        None => builder.add_line("The previous password no longer works."),
+    }
+    if !matches!(webhook.auth_mode, WebhookAuthMode::Basic) {
+        builder.add_line(
+            "This webhook doesn't currently authenticate with its password; the new password applies if it is \
+            switched back with \"!yarrbot webhook auth webhookId basic\".",
+        );
+    }
+
+    builder.to_message_data()
+}
+
+async fn rotate_password(
+    repositories: &Repositories,
+    webhook: Webhook,
+    password: &str,
+    grace_period: Option<Duration>,
+) -> Result<Webhook> {
+    let hashed = hash(String::from(password)).await?.to_vec();
+    let params = hash_parameters(&hashed).unwrap_or_default();
+    repositories
+        .webhooks
+        .rotate_password(&webhook, hashed, params, grace_period)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yarrbot_common::short_id::ShortId;
+    use yarrbot_db::models::{NewUser, NewWebhook};
+
+    async fn setup() -> (Repositories, Webhook, CommandMetadata) {
+        let repositories = Repositories::in_memory();
+        let owner = repositories
+            .users
+            .create_user(NewUser::new("@owner:example.org", None))
+            .await
+            .unwrap();
+        let webhook = repositories
+            .webhooks
+            .create_webhook(NewWebhook::new("user", vec![0], &owner, None))
+            .await
+            .unwrap();
+        let metadata = CommandMetadata {
+            user: String::from("@owner:example.org"),
+            is_direct_message: true,
+        };
+        (repositories, webhook, metadata)
+    }
+
+    #[tokio::test]
+    async fn handle_rotate_keeps_previous_password_for_grace_period() {
+        // Arrange
+        let (repositories, webhook, metadata) = setup().await;
+        let webhook_id = webhook.id.to_short_id();
+
+        // Act
+        let actual = handle_rotate(
+            metadata,
+            &repositories,
+            VecDeque::from([webhook_id.as_str(), "n3wP@55"]),
+        )
+        .await;
+
+        // Assert
+        assert!(actual
+            .plain
+            .contains("The previous password will keep working until"));
+        let webhook = repositories
+            .webhooks
+            .try_get(&webhook.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(Some(vec![0]), webhook.previous_password);
+        assert!(webhook.previous_password_expires_at.is_some());
+    }
+
+    #[tokio::test]
+    async fn handle_rotate_drops_previous_password_given_now() {
+        // Arrange
+        let (repositories, webhook, metadata) = setup().await;
+        let webhook_id = webhook.id.to_short_id();
+
+        // Act
+        let actual = handle_rotate(
+            metadata,
+            &repositories,
+            VecDeque::from([webhook_id.as_str(), "now", "n3wP@55"]),
+        )
+        .await;
+
+        // Assert
+        assert!(actual
+            .plain
+            .contains("The previous password no longer works."));
+        let webhook = repositories
+            .webhooks
+            .try_get(&webhook.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(None, webhook.previous_password);
+        assert_eq!(None, webhook.previous_password_expires_at);
+    }
+}
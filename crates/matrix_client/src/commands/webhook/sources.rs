@@ -0,0 +1,91 @@
+//! Supporting functions for restricting which networks may send requests to a webhook.
+
+use super::authorization::{authorize, WebhookAccess};
+use super::get_user;
+use crate::commands::CommandMetadata;
+use crate::message::MessageData;
+use std::collections::VecDeque;
+use tracing::{error, info, warn};
+use yarrbot_common::network::parse_network;
+use yarrbot_db::repositories::Repositories;
+
+/// Set the networks a webhook accepts requests from, or allow any source with `any`.
+#[tracing::instrument(skip(repositories, data), fields(webhook_id))]
+pub async fn handle_sources(
+    metadata: CommandMetadata,
+    repositories: &Repositories,
+    mut data: VecDeque<&str>,
+) -> MessageData {
+    info!("Received webhook sources command.");
+    let user = match get_user(repositories, &metadata.user).await {
+        Ok(Some(u)) => u,
+        Ok(None) => {
+            warn!("User attempted to modify a webhook but is not authorized to do so.");
+            return MessageData::from("You are not allowed to modify webhooks.");
+        }
+        Err(e) => {
+            error!(
+                error = ?e,
+                "Encountered an error while retrieving user information from the database."
+            );
+            return MessageData::from("Encountered an error while retrieving user information.");
+        }
+    };
+
+    let webhook_id = match data.pop_front() {
+        Some(w) => {
+            tracing::Span::current().record("webhook_id", &w);
+            w
+        }
+        None => return MessageData::from("No webhook specified."),
+    };
+    if data.is_empty() {
+        return MessageData::from(
+            "Specify \"any\" or one or more networks such as \"192.168.1.0/24\" or \"fd00::/8\".",
+        );
+    }
+    let sources: Vec<String> = if data.len() == 1 && data[0].eq_ignore_ascii_case("any") {
+        Vec::new()
+    } else {
+        let mut networks = Vec::with_capacity(data.len());
+        for s in data {
+            match parse_network(s) {
+                Ok(n) => networks.push(n.to_string()),
+                Err(_) => {
+                    return MessageData::from(
+                        format!("Could not parse \"{}\" as an IP address or network.", s).as_str(),
+                    )
+                }
+            }
+        }
+        networks
+    };
+
+    let webhook = match authorize(repositories, &user, webhook_id, WebhookAccess::Owner).await {
+        Ok(w) => w,
+        Err(message) => return message,
+    };
+
+    let message = if sources.is_empty() {
+        String::from("The webhook now accepts requests from any address.")
+    } else {
+        format!(
+            "The webhook now only accepts requests from {}.",
+            sources.join(", ")
+        )
+    };
+    match repositories
+        .webhooks
+        .update_allowed_sources(&webhook, sources)
+        .await
+    {
+        Ok(_) => {
+            info!("Updated webhook allowed sources.");
+            MessageData::from(message.as_str())
+        }
+        Err(e) => {
+            error!(error = ?e, "Encountered error while updating the webhook.");
+            MessageData::from("Failed to update the webhook. Please try again.")
+        }
+    }
+}
@@ -0,0 +1,87 @@
+//! Supporting functions for hiding spoilers in the rooms a webhook posts to.
+
+use super::authorization::{authorize, WebhookAccess};
+use super::get_user;
+use crate::commands::CommandMetadata;
+use crate::message::MessageData;
+use std::collections::VecDeque;
+use tracing::{error, info, warn};
+use yarrbot_db::models::WebhookPermission;
+use yarrbot_db::repositories::Repositories;
+
+/// Turn spoiler hiding on or off for one or all of the rooms a webhook posts to.
+#[tracing::instrument(skip(repositories, data), fields(webhook_id, room_id))]
+pub async fn handle_spoilers(
+    metadata: CommandMetadata,
+    repositories: &Repositories,
+    mut data: VecDeque<&str>,
+) -> MessageData {
+    info!("Received webhook spoilers command.");
+    let span = tracing::Span::current();
+    let user = match get_user(repositories, &metadata.user).await {
+        Ok(Some(u)) => u,
+        Ok(None) => {
+            warn!("User attempted to modify a webhook but is not authorized to do so.");
+            return MessageData::from("You are not allowed to modify webhooks.");
+        }
+        Err(e) => {
+            error!(
+                error = ?e,
+                "Encountered an error while retrieving user information from the database."
+            );
+            return MessageData::from("Encountered an error while retrieving user information.");
+        }
+    };
+
+    let webhook_id = match data.pop_front() {
+        Some(w) => {
+            span.record("webhook_id", &w);
+            w
+        }
+        None => return MessageData::from("No webhook specified."),
+    };
+    let hide_spoilers = match data.pop_front().map(|s| s.to_lowercase()).as_deref() {
+        Some("on") => true,
+        Some("off") => false,
+        _ => return MessageData::from("Specify whether to turn spoiler hiding \"on\" or \"off\"."),
+    };
+    let room_id = data.pop_front();
+    if let Some(r) = room_id {
+        span.record("room_id", &r);
+    }
+
+    let webhook = match authorize(
+        repositories,
+        &user,
+        webhook_id,
+        WebhookAccess::Delegated(WebhookPermission::ManageRooms),
+    )
+    .await
+    {
+        Ok(w) => w,
+        Err(message) => return message,
+    };
+
+    match repositories
+        .rooms
+        .update_hide_spoilers(&webhook.id, room_id, hide_spoilers)
+        .await
+    {
+        Ok(0) => MessageData::from("The webhook doesn't post to that room."),
+        Ok(count) => {
+            info!(count, hide_spoilers, "Updated spoiler setting for rooms.");
+            MessageData::from(
+                format!(
+                    "Spoilers will be {} in {} room(s).",
+                    if hide_spoilers { "hidden" } else { "shown" },
+                    count
+                )
+                .as_str(),
+            )
+        }
+        Err(e) => {
+            error!(error = ?e, "Encountered error while updating the spoiler setting.");
+            MessageData::from("Failed to update the spoiler setting. Please try again.")
+        }
+    }
+}
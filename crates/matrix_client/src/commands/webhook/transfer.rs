@@ -0,0 +1,187 @@
+//! Supporting functions for handing a webhook over to another user.
+
+use super::authorization::{authorize, can_create_webhooks, WebhookAccess};
+use super::get_user;
+use crate::commands::get_target_user;
+use crate::commands::CommandMetadata;
+use crate::message::MessageData;
+use std::collections::VecDeque;
+use tracing::{error, info, warn};
+use yarrbot_db::repositories::Repositories;
+
+/// Make another user the owner of a webhook. The previous owner loses access to it, and the new owner's grant for it,
+/// if they had one, is removed since owners don't need one.
+#[tracing::instrument(skip(repositories, data), fields(webhook_id, target_user))]
+pub async fn handle_transfer(
+    metadata: CommandMetadata,
+    repositories: &Repositories,
+    mut data: VecDeque<&str>,
+) -> MessageData {
+    info!("Received webhook transfer command.");
+    let span = tracing::Span::current();
+    let user = match get_user(repositories, &metadata.user).await {
+        Ok(Some(u)) => u,
+        Ok(None) => {
+            warn!("User attempted to transfer a webhook but is not authorized to do so.");
+            return MessageData::from("You are not allowed to modify webhooks.");
+        }
+        Err(e) => {
+            error!(
+                error = ?e,
+                "Encountered an error while retrieving user information from the database."
+            );
+            return MessageData::from("Encountered an error while retrieving user information.");
+        }
+    };
+
+    let webhook_id = match data.pop_front() {
+        Some(w) => {
+            span.record("webhook_id", &w);
+            w
+        }
+        None => return MessageData::from("No webhook specified."),
+    };
+    let new_owner = match get_target_user(repositories, data.pop_front()).await {
+        Ok(u) => u,
+        Err(message) => return message,
+    };
+    span.record("target_user", &new_owner.service_username.as_str());
+    if !can_create_webhooks(&new_owner) {
+        return MessageData::from(
+            "Viewers can't own webhooks. Share the webhook with them instead.",
+        );
+    }
+
+    let webhook = match authorize(repositories, &user, webhook_id, WebhookAccess::Owner).await {
+        Ok(w) => w,
+        Err(message) => return message,
+    };
+    if webhook.user_id == new_owner.id {
+        return MessageData::from("That user already owns the webhook.");
+    }
+
+    let grant = match repositories
+        .collaborators
+        .try_get(&webhook.id, &new_owner.id)
+        .await
+    {
+        Ok(c) => c,
+        Err(e) => {
+            error!(error = ?e, "Encountered an error while retrieving collaborator data.");
+            return MessageData::from("Failed to transfer the webhook. Please try again.");
+        }
+    };
+    if let Err(e) = repositories
+        .webhooks
+        .transfer_ownership(&webhook, &new_owner)
+        .await
+    {
+        error!(error = ?e, "Encountered an error while transferring the webhook.");
+        return MessageData::from("Failed to transfer the webhook. Please try again.");
+    }
+    if let Some(c) = grant {
+        if let Err(e) = repositories.collaborators.delete(&c).await {
+            // The new owner has full access regardless, so the stale grant is harmless.
+            warn!(error = ?e, "Couldn't remove the new owner's grant for the webhook.");
+        }
+    }
+
+    info!("Transferred webhook.");
+    MessageData::from(format!("{} now owns the webhook.", new_owner.service_username).as_str())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yarrbot_common::short_id::ShortId;
+    use yarrbot_db::enums::UserRole;
+    use yarrbot_db::models::{NewUser, NewWebhook};
+
+    #[tokio::test]
+    async fn handle_transfer_refuses_viewers() {
+        // Arrange
+        let repositories = Repositories::in_memory();
+        let owner = repositories
+            .users
+            .create_user(NewUser::new("@owner:example.org", None))
+            .await
+            .unwrap();
+        repositories
+            .users
+            .create_user(NewUser::new("@viewer:example.org", Some(UserRole::Viewer)))
+            .await
+            .unwrap();
+        let webhook = repositories
+            .webhooks
+            .create_webhook(NewWebhook::new("user", vec![0], &owner, None))
+            .await
+            .unwrap();
+        let metadata = CommandMetadata {
+            user: String::from("@owner:example.org"),
+            is_direct_message: true,
+        };
+        let webhook_id = webhook.id.to_short_id();
+
+        // Act
+        let actual = handle_transfer(
+            metadata,
+            &repositories,
+            VecDeque::from([webhook_id.as_str(), "@viewer:example.org"]),
+        )
+        .await;
+
+        // Assert
+        assert!(actual.plain.starts_with("Viewers can't own webhooks."));
+        let webhook = repositories
+            .webhooks
+            .try_get(&webhook.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(owner.id, webhook.user_id);
+    }
+
+    #[tokio::test]
+    async fn handle_transfer_changes_owner() {
+        // Arrange
+        let repositories = Repositories::in_memory();
+        let owner = repositories
+            .users
+            .create_user(NewUser::new("@owner:example.org", None))
+            .await
+            .unwrap();
+        let new_owner = repositories
+            .users
+            .create_user(NewUser::new("@new:example.org", None))
+            .await
+            .unwrap();
+        let webhook = repositories
+            .webhooks
+            .create_webhook(NewWebhook::new("user", vec![0], &owner, None))
+            .await
+            .unwrap();
+        let metadata = CommandMetadata {
+            user: String::from("@owner:example.org"),
+            is_direct_message: true,
+        };
+        let webhook_id = webhook.id.to_short_id();
+
+        // Act
+        let actual = handle_transfer(
+            metadata,
+            &repositories,
+            VecDeque::from([webhook_id.as_str(), "@new:example.org"]),
+        )
+        .await;
+
+        // Assert
+        assert_eq!("@new:example.org now owns the webhook.", actual.plain);
+        let webhook = repositories
+            .webhooks
+            .try_get(&webhook.id)
+            .await
+            .unwrap()
+            .unwrap();
+        assert_eq!(new_owner.id, webhook.user_id);
+    }
+}
@@ -1,19 +1,24 @@
 //! Entrypoint for `!yarrbot webhook ...` commands.
 
-use crate::commands::webhook::{handle_add, handle_list, handle_remove};
+use crate::commands::webhook::{
+    handle_add, handle_arr, handle_auth, handle_edit, handle_grant, handle_info, handle_list,
+    handle_mute, handle_paths, handle_pause, handle_quiet, handle_remove, handle_resume,
+    handle_revoke, handle_room, handle_rotate, handle_sources, handle_spoilers, handle_transfer,
+    handle_unmute,
+};
 use crate::commands::CommandMetadata;
 use crate::message::MessageData;
 use anyhow::{bail, ensure, Result};
 use matrix_sdk::Client;
 use std::collections::VecDeque;
-use yarrbot_db::DbPool;
+use yarrbot_db::repositories::Repositories;
 
 /// Handles choosing which webhook subcommand to execute.
-#[tracing::instrument(skip(client, pool, data))]
+#[tracing::instrument(skip(client, repositories, data))]
 pub async fn handle_webhook_command(
     metadata: CommandMetadata,
     client: &Client,
-    pool: &DbPool,
+    repositories: &Repositories,
     mut data: VecDeque<&str>,
 ) -> Result<MessageData> {
     ensure!(!data.is_empty(), "Not enough arguments");
@@ -22,9 +27,26 @@ pub async fn handle_webhook_command(
         "Yarrbot will only respond to webhook commands in a private room."
     );
     match data.pop_front().unwrap().to_lowercase().as_str() {
-        "add" => Ok(handle_add(metadata, client, pool, data).await),
-        "remove" => Ok(handle_remove(metadata, pool, data).await),
-        "list" => Ok(handle_list(metadata, pool, data).await),
+        "add" => Ok(handle_add(metadata, client, repositories, data).await),
+        "remove" => Ok(handle_remove(metadata, client, repositories, data).await),
+        "list" => Ok(handle_list(metadata, repositories, data).await),
+        "info" => Ok(handle_info(metadata, client, repositories, data).await),
+        "edit" => Ok(handle_edit(metadata, repositories, data).await),
+        "room" => Ok(handle_room(metadata, client, repositories, data).await),
+        "arr" => Ok(handle_arr(metadata, repositories, data).await),
+        "spoilers" => Ok(handle_spoilers(metadata, repositories, data).await),
+        "pause" => Ok(handle_pause(metadata, repositories, data).await),
+        "resume" => Ok(handle_resume(metadata, repositories, data).await),
+        "mute" => Ok(handle_mute(metadata, repositories, data).await),
+        "unmute" => Ok(handle_unmute(metadata, repositories, data).await),
+        "quiet" => Ok(handle_quiet(metadata, repositories, data).await),
+        "paths" => Ok(handle_paths(metadata, repositories, data).await),
+        "auth" => Ok(handle_auth(metadata, repositories, data).await),
+        "rotate" => Ok(handle_rotate(metadata, repositories, data).await),
+        "sources" => Ok(handle_sources(metadata, repositories, data).await),
+        "grant" => Ok(handle_grant(metadata, repositories, data).await),
+        "revoke" => Ok(handle_revoke(metadata, repositories, data).await),
+        "transfer" => Ok(handle_transfer(metadata, repositories, data).await),
         c => bail!(format!("Unknown webhook command \"{}\".", c)),
     }
 }
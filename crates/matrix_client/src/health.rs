@@ -0,0 +1,94 @@
+//! Tracks the state of Yarrbot's connection to the homeserver so that it can be reported by readiness checks.
+
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// Shared record of the Matrix client's login, sync, and message sending activity. Clones share the same record.
+#[derive(Clone, Default)]
+pub struct MatrixHealth {
+    inner: Arc<MatrixHealthInner>,
+}
+
+#[derive(Default)]
+struct MatrixHealthInner {
+    logged_in: AtomicBool,
+    last_sync: Mutex<Option<Instant>>,
+    pending_messages: AtomicUsize,
+}
+
+/// A point in time snapshot of [MatrixHealth].
+#[derive(Debug, Clone, PartialEq)]
+pub struct MatrixHealthStatus {
+    pub logged_in: bool,
+    /// How long ago the last successful sync with the homeserver finished, if there has been one.
+    pub since_last_sync: Option<Duration>,
+    /// The number of messages the SendMessageActor has received but not finished sending.
+    pub pending_messages: usize,
+}
+
+impl MatrixHealth {
+    pub fn record_login(&self, logged_in: bool) {
+        self.inner.logged_in.store(logged_in, Ordering::Relaxed);
+    }
+
+    pub fn record_sync(&self) {
+        *self.inner.last_sync.lock().unwrap() = Some(Instant::now());
+    }
+
+    pub fn message_queued(&self) {
+        self.inner.pending_messages.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn message_finished(&self) {
+        // Never wrap around, even if finished is called more often than queued.
+        let _ =
+            self.inner
+                .pending_messages
+                .fetch_update(Ordering::Relaxed, Ordering::Relaxed, |p| p.checked_sub(1));
+    }
+
+    pub fn status(&self) -> MatrixHealthStatus {
+        MatrixHealthStatus {
+            logged_in: self.inner.logged_in.load(Ordering::Relaxed),
+            since_last_sync: self.inner.last_sync.lock().unwrap().map(|s| s.elapsed()),
+            pending_messages: self.inner.pending_messages.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn status_counts_pending_messages_across_clones() {
+        // Arrange
+        let health = MatrixHealth::default();
+        let clone = health.clone();
+
+        // Act
+        health.message_queued();
+        health.message_queued();
+        clone.message_finished();
+        clone.record_sync();
+
+        // Assert
+        let status = health.status();
+        assert_eq!(1, status.pending_messages);
+        assert!(status.since_last_sync.is_some());
+        assert!(!status.logged_in);
+    }
+
+    #[test]
+    fn message_finished_does_not_underflow() {
+        // Arrange
+        let health = MatrixHealth::default();
+
+        // Act
+        health.message_finished();
+
+        // Assert
+        assert_eq!(0, health.status().pending_messages);
+    }
+}
@@ -1,17 +1,19 @@
 pub mod client;
 mod commands;
+mod health;
 pub mod message;
 pub mod send_handler;
 mod sync_handler;
 
 use crate::client::RoomMessageActor;
 use crate::client::StrippedStateMemberActor;
-use crate::message::Message;
+use crate::message::{Message, MessageData};
 use anyhow::Result;
 use async_trait::async_trait;
-use matrix_sdk::ruma::identifiers::UserId;
+use matrix_sdk::ruma::identifiers::{RoomId, UserId};
 use std::convert::TryFrom;
 
+pub use crate::health::{MatrixHealth, MatrixHealthStatus};
 use crate::send_handler::{SendMessageActor, SendToMatrix};
 pub use crate::sync_handler::MatrixSyncActor;
 
@@ -20,10 +22,18 @@ pub fn is_user_id(user_id: &str) -> bool {
     UserId::try_from(user_id).is_ok()
 }
 
+/// Check if a given [room_id] is a valid room ID (_not_ an alias).
+pub fn is_room_id(room_id: &str) -> bool {
+    RoomId::try_from(room_id).is_ok()
+}
+
 /// Represents the base functionality that Yarrbot wants out of a client that connects to a Matrix
 /// homeserver.
 #[async_trait]
 pub trait MatrixClient {
     /// Send a message contained within a [MessageData] to a given [MatrixRoom].
     async fn send_message(&self, message: Message) -> Result<()>;
+
+    /// Send a [MessageData] to a Matrix user in a direct message, creating the direct message room if needed.
+    async fn send_direct_message(&self, user_id: &str, message_data: MessageData) -> Result<()>;
 }
@@ -1,5 +1,6 @@
 //! Traits and utilities for sending messages to a Matrix server.
 
+use crate::message::{MessageFile, MessageImage};
 use anyhow::Error;
 use matrix_sdk::ruma::events::room::message::MessageEventContent;
 use std::fmt::Debug;
@@ -11,6 +12,21 @@ pub struct MessageData {
     pub plain: String,
     /// The HTML (rich text) version of the message to send.
     pub html: String,
+    /// An optional image to send alongside the message.
+    pub image: Option<MessageImage>,
+    /// An optional file to send as an attachment following the message.
+    pub file: Option<MessageFile>,
+    /// The message with potential spoilers hidden, if the message contains any.
+    pub spoiler_safe: Option<SpoilerSafeText>,
+    /// Whether the message reports a problem that rooms may want to hear about even during their quiet hours.
+    pub critical: bool,
+}
+
+/// The plain and HTML text of a message with potential spoilers hidden.
+#[derive(Debug, Clone)]
+pub struct SpoilerSafeText {
+    pub plain: String,
+    pub html: String,
 }
 
 impl MessageData {
@@ -18,8 +34,24 @@ impl MessageData {
         MessageData {
             plain: String::from(plain),
             html: String::from(html),
+            image: None,
+            file: None,
+            spoiler_safe: None,
+            critical: false,
         }
     }
+
+    /// Copy this message with its potential spoilers hidden. Returns [None] if the message has no spoilers.
+    pub fn without_spoilers(&self) -> Option<MessageData> {
+        self.spoiler_safe.as_ref().map(|s| MessageData {
+            plain: s.plain.clone(),
+            html: s.html.clone(),
+            image: self.image.clone(),
+            file: self.file.clone(),
+            spoiler_safe: None,
+            critical: self.critical,
+        })
+    }
 }
 
 impl From<MessageData> for MessageEventContent {
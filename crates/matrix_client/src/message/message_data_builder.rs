@@ -1,10 +1,10 @@
 //! Utilities for building [MessageData] structs for delivery of Matrix messages.
 
-use crate::message::MessageData;
-use std::fmt::Write;
+use crate::message::{MessageData, SpoilerSafeText};
 
 const DEFAULT_PLAIN_BREAK: &str = "\n";
 const DEFAULT_HTML_BREAK: &str = "<br>";
+const SPOILER_PLAIN_FALLBACK: &str = "[Spoiler]";
 
 /// Implementors may implement this trait to allow a struct to format its data for display in Matrix.
 pub trait MatrixMessageDataPart {
@@ -36,9 +36,15 @@ pub enum SectionHeadingLevel {
 }
 
 /// Builds [MessageData] structs with special formatting. Forward-only.
+///
+/// Alongside the message itself, the builder keeps a copy of the message with any spoilers (as added by
+/// [Self::add_key_value_spoiler()]) hidden for rooms that don't want to see them.
 pub struct MessageDataBuilder {
     plain_parts: String,
     html_parts: String,
+    safe_plain_parts: String,
+    safe_html_parts: String,
+    has_spoilers: bool,
 }
 
 impl MessageDataBuilder {
@@ -46,18 +52,28 @@ impl MessageDataBuilder {
         MessageDataBuilder {
             plain_parts: String::new(),
             html_parts: String::new(),
+            safe_plain_parts: String::new(),
+            safe_html_parts: String::new(),
+            has_spoilers: false,
         }
     }
 
+    /// Appends the given text to both the message and its spoiler-safe copy.
+    fn push(&mut self, plain: &str, html: &str) {
+        self.plain_parts.push_str(plain);
+        self.safe_plain_parts.push_str(plain);
+        self.html_parts.push_str(html);
+        self.safe_html_parts.push_str(html);
+    }
+
     /// Adds some character or set of characters that "break" or "separate" message data parts.
     /// For HTML text messages, a line break (`<br>`) after the last part added to the builder.
     /// For plain text messages, the default separator `\n` is added instead.
     pub fn break_character(&mut self) {
-        self.plain_parts.push(' ');
-        self.plain_parts.push_str(DEFAULT_PLAIN_BREAK);
-
-        self.html_parts.push(' ');
-        self.html_parts.push_str(DEFAULT_HTML_BREAK);
+        self.push(
+            &format!(" {}", DEFAULT_PLAIN_BREAK),
+            &format!(" {}", DEFAULT_HTML_BREAK),
+        );
     }
 
     /// Adds a key-value data pair to the message with a line break (or the default plain text separator).
@@ -66,15 +82,10 @@ impl MessageDataBuilder {
     /// Plain: `key: value`
     /// Rich: `<strong>key</strong>: value`
     pub fn add_key_value(&mut self, key: &str, value: &str) {
-        self.plain_parts.push_str(" **");
-        self.plain_parts.push_str(key);
-        self.plain_parts.push_str("**: ");
-        self.plain_parts.push_str(value);
-
-        self.html_parts.push_str("<strong>");
-        self.html_parts.push_str(key);
-        self.html_parts.push_str("</strong>: ");
-        self.html_parts.push_str(value);
+        self.push(
+            &format!(" **{}**: {}", key, value),
+            &format!("<strong>{}</strong>: {}", key, value),
+        );
 
         self.break_character();
     }
@@ -86,16 +97,33 @@ impl MessageDataBuilder {
     /// Plain: `key: value`
     /// Rich: `<strong>key</strong>: <code>value</code>`
     pub fn add_key_value_with_code(&mut self, key: &str, value: &str) {
-        self.plain_parts.push_str(" **");
-        self.plain_parts.push_str(key);
-        self.plain_parts.push_str("**: ");
-        self.plain_parts.push_str(value);
+        self.push(
+            &format!(" **{}**: {}", key, value),
+            &format!("<strong>{}</strong>: <code>{}</code>", key, value),
+        );
 
-        self.html_parts.push_str("<strong>");
-        self.html_parts.push_str(key);
-        self.html_parts.push_str("</strong>: <code>");
-        self.html_parts.push_str(value);
-        self.html_parts.push_str("</code>");
+        self.break_character();
+    }
+
+    /// Adds a key-value data pair whose value may spoil something, such as an episode title, to the message
+    /// with a line break (or the default plain text separator). The message is unchanged, but in its
+    /// spoiler-safe copy the value is wrapped in Matrix spoiler markup and replaced in the plain text.
+    ///
+    /// # Examples
+    /// Spoiler-safe plain: `key: [Spoiler]`
+    /// Spoiler-safe rich: `<strong>key</strong>: <span data-mx-spoiler>value</span>`
+    pub fn add_key_value_spoiler(&mut self, key: &str, value: &str) {
+        self.plain_parts
+            .push_str(&format!(" **{}**: {}", key, value));
+        self.html_parts
+            .push_str(&format!("<strong>{}</strong>: {}", key, value));
+        self.safe_plain_parts
+            .push_str(&format!(" **{}**: {}", key, SPOILER_PLAIN_FALLBACK));
+        self.safe_html_parts.push_str(&format!(
+            "<strong>{}</strong>: <span data-mx-spoiler>{}</span>",
+            key, value
+        ));
+        self.has_spoilers = true;
 
         self.break_character();
     }
@@ -104,15 +132,15 @@ impl MessageDataBuilder {
     /// This function _does not_ add any "break characters" after appending the [MatrixMessageDataPart] and
     /// requires that either the [MatrixMessageDataPart] supply the separators/line breaks or call [Self::break_character()].
     pub fn add_matrix_message_part(&mut self, part: impl MatrixMessageDataPart) {
-        self.plain_parts
-            .push_str(&part.to_plain(DEFAULT_PLAIN_BREAK));
-        self.html_parts.push_str(&part.to_html(DEFAULT_HTML_BREAK));
+        self.push(
+            &part.to_plain(DEFAULT_PLAIN_BREAK),
+            &part.to_html(DEFAULT_HTML_BREAK),
+        );
     }
 
     /// Adds a line of text followed by [Self::break_character()].
     pub fn add_line(&mut self, line: &str) {
-        self.plain_parts.push_str(line);
-        write!(self.html_parts, "<p>{}</p>", line).expect("Failed to write HTML String.");
+        self.push(line, &format!("<p>{}</p>", line));
         self.break_character();
     }
 
@@ -128,13 +156,6 @@ impl MessageDataBuilder {
             SectionHeadingLevel::Five => "h5",
             SectionHeadingLevel::Six => "h6",
         };
-        write!(
-            self.html_parts,
-            "<div><{}><i>{}</i></{}></div><br>",
-            html_heading, text, html_heading
-        )
-        .expect("Failed to write to underlying HTML String.");
-
         let plain_heading = match heading {
             SectionHeadingLevel::One => "#",
             SectionHeadingLevel::Two => "##",
@@ -144,12 +165,16 @@ impl MessageDataBuilder {
             SectionHeadingLevel::Six => "######",
         };
 
-        write!(
-            self.plain_parts,
-            "{} {} {}{}",
-            plain_heading, text, DEFAULT_PLAIN_BREAK, DEFAULT_PLAIN_BREAK
-        )
-        .expect("Failed to write to underlying plain String.");
+        self.push(
+            &format!(
+                "{} {} {}{}",
+                plain_heading, text, DEFAULT_PLAIN_BREAK, DEFAULT_PLAIN_BREAK
+            ),
+            &format!(
+                "<div><{}><i>{}</i></{}></div><br>",
+                html_heading, text, html_heading
+            ),
+        );
     }
 
     /// Copy the contents of this builder to a new [MessageData].
@@ -158,22 +183,37 @@ impl MessageDataBuilder {
     ///  * Preceding and trailing whitespace is trimmed.
     ///  * Trailing "break characters" (as inserted by [Self::break_character()]) are trimmed.
     ///  * For HTML messages only, a single "break character" is inserted after trimming for formatting.
+    ///  * The spoiler-safe copy of the message is only included if a spoiler was added.
     pub fn to_message_data(&self) -> MessageData {
-        let mut plain = String::from(
-            self.plain_parts
-                .trim_end_matches(DEFAULT_PLAIN_BREAK)
-                .trim(),
+        let mut message_data = MessageData::new(
+            &finish_plain(&self.plain_parts),
+            &finish_html(&self.html_parts),
         );
-        plain.push(' ');
-        plain.push_str(DEFAULT_PLAIN_BREAK);
-        let mut html = String::from(self.html_parts.trim_end_matches(DEFAULT_HTML_BREAK).trim());
-        html.push(' ');
-        html.push_str(DEFAULT_HTML_BREAK);
+        if self.has_spoilers {
+            message_data.spoiler_safe = Some(SpoilerSafeText {
+                plain: finish_plain(&self.safe_plain_parts),
+                html: finish_html(&self.safe_html_parts),
+            });
+        }
 
-        MessageData::new(&plain, &html)
+        message_data
     }
 }
 
+fn finish_plain(parts: &str) -> String {
+    let mut plain = String::from(parts.trim_end_matches(DEFAULT_PLAIN_BREAK).trim());
+    plain.push(' ');
+    plain.push_str(DEFAULT_PLAIN_BREAK);
+    plain
+}
+
+fn finish_html(parts: &str) -> String {
+    let mut html = String::from(parts.trim_end_matches(DEFAULT_HTML_BREAK).trim());
+    html.push(' ');
+    html.push_str(DEFAULT_HTML_BREAK);
+    html
+}
+
 impl Default for MessageDataBuilder {
     fn default() -> Self {
         MessageDataBuilder::new()
@@ -265,4 +305,38 @@ mod tests {
         assert_eq!(expected_plain, actual.plain);
         assert_eq!(expected_html, actual.html);
     }
+
+    #[test]
+    pub fn add_key_value_spoiler_hides_value_in_spoiler_safe_copy_only() {
+        // Arrange
+        let expected_plain = "**Title**: Secret \n";
+        let expected_html = "<strong>Title</strong>: Secret <br>";
+        let expected_safe_plain = "**Title**: [Spoiler] \n";
+        let expected_safe_html = "<strong>Title</strong>: <span data-mx-spoiler>Secret</span> <br>";
+        let mut builder = MessageDataBuilder::new();
+        builder.add_key_value_spoiler("Title", "Secret");
+
+        // Act
+        let actual = builder.to_message_data();
+
+        // Assert
+        assert_eq!(expected_plain, actual.plain);
+        assert_eq!(expected_html, actual.html);
+        let safe = actual.spoiler_safe.unwrap();
+        assert_eq!(expected_safe_plain, safe.plain);
+        assert_eq!(expected_safe_html, safe.html);
+    }
+
+    #[test]
+    pub fn to_message_data_omits_spoiler_safe_copy_given_no_spoilers() {
+        // Arrange
+        let mut builder = MessageDataBuilder::new();
+        builder.add_key_value("Test", "Of KeyValue");
+
+        // Act
+        let actual = builder.to_message_data();
+
+        // Assert
+        assert!(actual.spoiler_safe.is_none());
+    }
 }
@@ -0,0 +1,36 @@
+//! Files that may accompany a message sent to a Matrix room.
+
+use mime::Mime;
+use std::fmt::{Debug, Formatter};
+
+/// A file, such as a configuration export, to send as an attachment following a message.
+#[derive(Clone)]
+pub struct MessageFile {
+    /// The name the file is shown with.
+    pub name: String,
+
+    pub content_type: Mime,
+
+    pub data: Vec<u8>,
+}
+
+impl MessageFile {
+    pub fn new(name: &str, content_type: Mime, data: Vec<u8>) -> Self {
+        MessageFile {
+            name: String::from(name),
+            content_type,
+            data,
+        }
+    }
+}
+
+impl Debug for MessageFile {
+    /// Formats the [MessageFile] without its contents, which may be large or sensitive.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MessageFile")
+            .field("name", &self.name)
+            .field("content_type", &self.content_type.as_ref())
+            .field("length", &self.data.len())
+            .finish()
+    }
+}
@@ -0,0 +1,38 @@
+//! Images that may accompany a message sent to a Matrix room.
+
+use std::fmt::{Debug, Formatter};
+use url::Url;
+
+/// Some image, such as a series poster, to attach to a message.
+#[derive(Clone)]
+pub struct MessageImage {
+    /// Where to download the image from.
+    pub source_url: Url,
+
+    /// An API key to supply in the `X-Api-Key` header when downloading the image from an *arr.
+    pub api_key: Option<String>,
+
+    /// A short description of the image (e.g. the title of the series) used as the image's body and alt text.
+    pub description: String,
+}
+
+impl MessageImage {
+    pub fn new(source_url: Url, api_key: Option<String>, description: &str) -> Self {
+        MessageImage {
+            source_url,
+            api_key,
+            description: String::from(description),
+        }
+    }
+}
+
+impl Debug for MessageImage {
+    /// Formats the [MessageImage] without the API key, which must not end up in the logs.
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("MessageImage")
+            .field("source_url", &self.source_url.as_str())
+            .field("has_api_key", &self.api_key.is_some())
+            .field("description", &self.description)
+            .finish()
+    }
+}
@@ -1,10 +1,15 @@
 use std::sync::Arc;
+use uuid::Uuid;
 
 mod message_data;
 mod message_data_builder;
+mod message_file;
+mod message_image;
 
-pub use message_data::MessageData;
+pub use message_data::{MessageData, SpoilerSafeText};
 pub use message_data_builder::{MatrixMessageDataPart, MessageDataBuilder, SectionHeadingLevel};
+pub use message_file::MessageFile;
+pub use message_image::MessageImage;
 
 /// Represents all data needed to send a message to a given [MatrixRoom].
 #[derive(Debug)]
@@ -14,6 +19,9 @@ pub struct Message {
 
     /// The fully qualified Matrix ID for a Room.
     pub destination: String,
+
+    /// The webhook whose event the message reports, which is charged with a delivery failure if it can't be sent.
+    pub webhook_id: Option<Uuid>,
 }
 
 impl Message {
@@ -21,6 +29,13 @@ impl Message {
         Message {
             destination: String::from(destination),
             message_data: data,
+            webhook_id: None,
         }
     }
+
+    /// Charge the given webhook with a delivery failure if the message can't be sent.
+    pub fn for_webhook(mut self, webhook_id: &Uuid) -> Self {
+        self.webhook_id = Some(*webhook_id);
+        self
+    }
 }
@@ -1,13 +1,31 @@
+use crate::client::ImageMode;
+use crate::health::MatrixHealth;
+use crate::message::{MessageData, MessageFile, MessageImage};
+use crate::send_handler::image_cache::{CachedImage, ImageCache};
 use crate::send_handler::send_to_matrix::SendToMatrix;
 use actix::prelude::*;
+use anyhow::Result;
+use matrix_sdk::room::Joined;
 use matrix_sdk::ruma::events::room::message::MessageEventContent;
 use matrix_sdk::{ruma::events::AnyMessageEventContent, ruma::identifiers::RoomId, Client};
 use std::convert::TryFrom;
-use tracing::{debug, error, info};
+use std::io::Cursor;
+use std::sync::Arc;
+use tracing::field::{display, Empty};
+use tracing::{debug, error, info, info_span, warn, Span};
+use tracing_futures::Instrument;
+use yarrbot_common::metrics::{send_outcome, MATRIX_MESSAGES_SENT, MATRIX_SEND_DURATION};
+use yarrbot_db::repositories::Repositories;
+
+const INLINE_IMAGE_HEIGHT: u32 = 300;
 
 /// Listens for requests to send messages to a Matrix room.
 pub struct SendMessageActor {
     client: Client,
+    repositories: Repositories,
+    image_mode: ImageMode,
+    image_cache: Arc<ImageCache>,
+    health: MatrixHealth,
 }
 
 impl Actor for SendMessageActor {
@@ -28,22 +46,71 @@ impl Handler<SendToMatrix> for SendMessageActor {
     type Result = ();
 
     fn handle(&mut self, msg: SendToMatrix, ctx: &mut Self::Context) -> Self::Result {
+        let destination = msg.destination.clone();
+        let webhook_id = msg.webhook_id;
+        // Actix messages don't carry the tracing context, so continue the sender's trace explicitly.
+        let span = info_span!(
+            parent: &msg.span,
+            "Sending Matrix Message",
+            room.matrix_id = %destination,
+            matrix.event_id = Empty
+        );
         // Client's just a wrapper around an Arc<InnerClientThingy>.
-        let fut = send(self.client.clone(), msg);
-        let actor_future = fut.into_actor(self);
+        let fut = send(
+            self.client.clone(),
+            self.image_mode,
+            self.image_cache.clone(),
+            msg,
+        );
+        let health = self.health.clone();
+        let repositories = self.repositories.clone();
+        health.message_queued();
+        let actor_future = async move {
+            let timer = MATRIX_SEND_DURATION.start_timer();
+            let sent = fut.await;
+            timer.observe_duration();
+            MATRIX_MESSAGES_SENT
+                .with_label_values(&[&destination, send_outcome(sent)])
+                .inc();
+            if let (false, Some(id)) = (sent, webhook_id) {
+                if let Err(e) = repositories.webhooks.record_delivery_failure(&id).await {
+                    warn!(error = ?e, "Failed to record the webhook's delivery failure.");
+                }
+            }
+            health.message_finished();
+        }
+        .instrument(span)
+        .into_actor(self);
 
         ctx.spawn(actor_future);
     }
 }
 
 impl SendMessageActor {
-    pub fn new(client: Client) -> Self {
-        SendMessageActor { client }
+    pub fn new(
+        client: Client,
+        repositories: Repositories,
+        image_mode: ImageMode,
+        image_cache: ImageCache,
+        health: MatrixHealth,
+    ) -> Self {
+        SendMessageActor {
+            client,
+            repositories,
+            image_mode,
+            image_cache: Arc::new(image_cache),
+            health,
+        }
     }
 }
 
-/// Sends a given message to a Matrix room.
-async fn send(client: Client, msg: SendToMatrix) {
+/// Sends a given message to a Matrix room, returning whether the message itself was sent.
+async fn send(
+    client: Client,
+    image_mode: ImageMode,
+    image_cache: Arc<ImageCache>,
+    msg: SendToMatrix,
+) -> bool {
     let destination = msg.destination;
     let message_data = msg.message_data;
     info!(
@@ -53,22 +120,122 @@ async fn send(client: Client, msg: SendToMatrix) {
     debug!(message = ?message_data, "Sending Matrix message with the given contents.");
     if let Ok(room_id) = RoomId::try_from(destination.as_str()) {
         if let Some(room) = client.get_joined_room(&room_id) {
-            let event_content: MessageEventContent = MessageEventContent::notice_html(
-                message_data.plain.as_str(),
-                message_data.html.as_str(),
-            );
+            let image = match (&message_data.image, image_mode) {
+                (Some(i), ImageMode::Attachment | ImageMode::Inline) => {
+                    get_image(&image_cache, i).await.map(|c| (i, c))
+                }
+                _ => None,
+            };
+            // Inline images are uploaded unencrypted, so encrypted rooms receive them as attachments instead.
+            let send_inline = matches!(image_mode, ImageMode::Inline) && !room.is_encrypted();
+
+            let html = match &image {
+                Some((i, c)) if send_inline => inline_image(&client, &message_data, i, c).await,
+                _ => message_data.html.clone(),
+            };
+            let event_content: MessageEventContent =
+                MessageEventContent::notice_html(message_data.plain.as_str(), html.as_str());
             let content = AnyMessageEventContent::RoomMessage(event_content);
 
-            if let Err(e) = room.send(content, None).await {
-                error!(error = ?e, room_id = %destination, "Failed to send Matrix message.")
+            match room.send(content, None).await {
+                Ok(response) => {
+                    Span::current().record("matrix.event_id", &display(&response.event_id));
+                }
+                Err(e) => {
+                    error!(error = ?e, room_id = %destination, "Failed to send Matrix message.");
+                    return false;
+                }
             }
+
+            if let Some((i, c)) = image {
+                if !send_inline {
+                    send_attachment(&room, i, c).await;
+                }
+            }
+            if let Some(f) = &message_data.file {
+                send_file(&room, f).await;
+            }
+            true
         } else {
             error!(
                 room_id = %destination,
                 "Failed to send Matrix message because Yarrbot isn't a member of the desired room."
             );
+            false
         }
     } else {
         error!(room_id = %destination, "Failed to parse Room ID.");
+        false
+    }
+}
+
+/// Retrieve the image to send alongside a message, logging and discarding any errors so that the message
+/// itself is still sent.
+async fn get_image(image_cache: &ImageCache, image: &MessageImage) -> Option<CachedImage> {
+    match image_cache.get(image).await {
+        Ok(c) => Some(c),
+        Err(e) => {
+            warn!(error = ?e, image = ?image, "Failed to retrieve image; sending the message without it.");
+            None
+        }
+    }
+}
+
+/// Upload the image to the homeserver and append it to the message's HTML.
+async fn inline_image(
+    client: &Client,
+    message_data: &MessageData,
+    image: &MessageImage,
+    cached: &CachedImage,
+) -> String {
+    match upload(client, cached).await {
+        Ok(uri) => format!(
+            "{}<img src=\"{}\" alt=\"{}\" height=\"{}\">",
+            message_data.html,
+            uri,
+            image.description.replace('"', "&quot;"),
+            INLINE_IMAGE_HEIGHT
+        ),
+        Err(e) => {
+            warn!(error = ?e, "Failed to upload image; sending the message without it.");
+            message_data.html.clone()
+        }
+    }
+}
+
+async fn upload(client: &Client, cached: &CachedImage) -> Result<String> {
+    let response = client
+        .upload(&cached.content_type, &mut Cursor::new(&cached.data))
+        .await?;
+    Ok(response.content_uri.to_string())
+}
+
+/// Send the image as an `m.image` message following the notification.
+async fn send_attachment(room: &Joined, image: &MessageImage, cached: CachedImage) {
+    let result = room
+        .send_attachment(
+            image.description.as_str(),
+            &cached.content_type,
+            &mut Cursor::new(cached.data),
+            None,
+        )
+        .await;
+    if let Err(e) = result {
+        error!(error = ?e, room_id = %room.room_id(), "Failed to send image to Matrix room.");
+    }
+}
+
+/// Send the file as an `m.file` message following the message.
+async fn send_file(room: &Joined, file: &MessageFile) {
+    let result = room
+        .send_attachment(
+            file.name.as_str(),
+            &file.content_type,
+            &mut Cursor::new(&file.data),
+            None,
+        )
+        .await;
+    if let Err(e) = result {
+        error!(error = ?e, room_id = %room.room_id(), file = ?file, "Failed to send file to Matrix room.");
     }
 }
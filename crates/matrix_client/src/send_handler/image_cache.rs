@@ -0,0 +1,115 @@
+//! Downloads the images attached to notifications and caches them on disk.
+
+use crate::message::MessageImage;
+use anyhow::{ensure, Context, Result};
+use matrix_sdk::reqwest::{self, header::CONTENT_TYPE};
+use mime::Mime;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use tokio::fs;
+use tracing::{debug, warn};
+use yarrbot_common::crypto::digest;
+
+const MAX_IMAGE_SIZE: usize = 5_242_880; // Limit images to 5 MiB.
+const DOWNLOAD_TIMEOUT: Duration = Duration::from_secs(15);
+const CONTENT_TYPE_EXTENSION: &str = "type";
+
+/// Some image retrieved either from the *arr, a remote source, or the disk cache.
+pub struct CachedImage {
+    pub content_type: Mime,
+    pub data: Vec<u8>,
+}
+
+/// Retrieves images, keeping a copy of each on disk so that it is only downloaded once.
+pub struct ImageCache {
+    http_client: reqwest::Client,
+    directory: PathBuf,
+}
+
+impl ImageCache {
+    pub fn new(directory: PathBuf) -> Result<Self> {
+        let http_client = reqwest::Client::builder()
+            .timeout(DOWNLOAD_TIMEOUT)
+            .build()
+            .context("Failed to build the HTTP client for downloading images.")?;
+        Ok(ImageCache {
+            http_client,
+            directory,
+        })
+    }
+
+    /// Retrieve the given [MessageImage] from the disk cache, downloading and caching it if it isn't there yet.
+    pub async fn get(&self, image: &MessageImage) -> Result<CachedImage> {
+        let key = digest(image.source_url.as_str().as_bytes());
+        let data_path = self.directory.join(&key);
+        let type_path = data_path.with_extension(CONTENT_TYPE_EXTENSION);
+        if let Some(cached) = read_cached(&data_path, &type_path).await {
+            debug!(cache_key = %key, "Found image in the disk cache.");
+            return Ok(cached);
+        }
+
+        let downloaded = self.download(image).await?;
+        if let Err(e) = write_cached(&data_path, &type_path, &downloaded).await {
+            warn!(error = ?e, cache_key = %key, "Failed to write image to the disk cache.");
+        }
+
+        Ok(downloaded)
+    }
+
+    async fn download(&self, image: &MessageImage) -> Result<CachedImage> {
+        debug!(url = %image.source_url, "Downloading image.");
+        let mut request = self.http_client.get(image.source_url.clone());
+        if let Some(api_key) = &image.api_key {
+            request = request.header("X-Api-Key", api_key);
+        }
+        let response = request
+            .send()
+            .await
+            .context("Failed to request the image.")?
+            .error_for_status()
+            .context("The image source returned an error.")?;
+        if let Some(length) = response.content_length() {
+            ensure!(
+                length as usize <= MAX_IMAGE_SIZE,
+                "Image exceeded the maximum allowed size."
+            );
+        }
+        let content_type = response
+            .headers()
+            .get(CONTENT_TYPE)
+            .and_then(|v| v.to_str().ok())
+            .and_then(|v| v.parse::<Mime>().ok())
+            .unwrap_or(mime::IMAGE_JPEG);
+        ensure!(
+            content_type.type_() == mime::IMAGE,
+            "The image source returned something other than an image: {}",
+            content_type
+        );
+        let data = response
+            .bytes()
+            .await
+            .context("Failed to read the image.")?;
+        ensure!(
+            data.len() <= MAX_IMAGE_SIZE,
+            "Image exceeded the maximum allowed size."
+        );
+
+        Ok(CachedImage {
+            content_type,
+            data: data.to_vec(),
+        })
+    }
+}
+
+async fn read_cached(data_path: &Path, type_path: &Path) -> Option<CachedImage> {
+    let content_type = fs::read_to_string(type_path).await.ok()?.parse().ok()?;
+    let data = fs::read(data_path).await.ok()?;
+    Some(CachedImage { content_type, data })
+}
+
+async fn write_cached(data_path: &Path, type_path: &Path, image: &CachedImage) -> Result<()> {
+    fs::write(data_path, &image.data).await?;
+    // The content type is written last so that a partially written image is never read back.
+    fs::write(type_path, image.content_type.essence_str()).await?;
+    Ok(())
+}
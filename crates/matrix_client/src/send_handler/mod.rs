@@ -1,5 +1,7 @@
 mod actor;
+mod image_cache;
 mod send_to_matrix;
 
 pub use actor::SendMessageActor;
+pub use image_cache::ImageCache;
 pub use send_to_matrix::SendToMatrix;
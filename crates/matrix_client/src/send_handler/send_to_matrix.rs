@@ -1,6 +1,8 @@
 use crate::message::MessageData;
 use actix::prelude::*;
 use std::sync::Arc;
+use tracing::Span;
+use uuid::Uuid;
 
 /// Wrapper for Matrix message data.
 pub struct SendToMatrix {
@@ -9,6 +11,12 @@ pub struct SendToMatrix {
 
     /// The fully qualified Matrix ID for a Room.
     pub destination: String,
+
+    /// The span the message was queued from; sending the message is traced as its child.
+    pub span: Span,
+
+    /// The webhook whose event the message reports, if any.
+    pub webhook_id: Option<Uuid>,
 }
 
 impl Message for SendToMatrix {
@@ -20,6 +28,17 @@ impl SendToMatrix {
         SendToMatrix {
             destination: String::from(destination),
             message_data: data,
+            span: Span::current(),
+            webhook_id: None,
+        }
+    }
+}
+
+impl From<crate::message::Message> for SendToMatrix {
+    fn from(message: crate::message::Message) -> Self {
+        SendToMatrix {
+            webhook_id: message.webhook_id,
+            ..SendToMatrix::new(message.destination.as_str(), message.message_data)
         }
     }
 }
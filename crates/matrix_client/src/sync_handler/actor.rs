@@ -1,11 +1,13 @@
+use crate::health::MatrixHealth;
 use actix::{Actor, ActorContext, AsyncContext, Context, Handler, WrapFuture};
-use matrix_sdk::{Client, SyncSettings};
+use matrix_sdk::{Client, LoopCtrl, SyncSettings};
 use tracing::{debug, info};
 use yarrbot_common::ShutdownNotice;
 
 /// Manages the sync loop performed against the homeserver, which allows Yarrbot to respond to new messages and events.
 pub struct MatrixSyncActor {
     client: Client,
+    health: MatrixHealth,
 }
 
 impl Actor for MatrixSyncActor {
@@ -14,7 +16,7 @@ impl Actor for MatrixSyncActor {
     fn started(&mut self, ctx: &mut Self::Context) {
         debug!("Started MatrixSyncActor.");
         info!("Beginning Matrix sync loop.");
-        let fut = MatrixSyncActor::start_sync_loop(self.client.clone());
+        let fut = MatrixSyncActor::start_sync_loop(self.client.clone(), self.health.clone());
         let fut_actor = fut.into_actor(self);
         ctx.spawn(fut_actor);
     }
@@ -34,8 +36,8 @@ impl Handler<ShutdownNotice> for MatrixSyncActor {
 }
 
 impl MatrixSyncActor {
-    pub fn new(client: Client) -> Self {
-        MatrixSyncActor { client }
+    pub fn new(client: Client, health: MatrixHealth) -> Self {
+        MatrixSyncActor { client, health }
     }
 
     /// Performs an initial sync with the homeserver, then starts the sync loop.
@@ -44,7 +46,7 @@ impl MatrixSyncActor {
     ///
     /// As this calls [Client::sync], the future will never complete. The actor will cancel the future when it is
     /// shutdown.
-    async fn start_sync_loop(client: Client) {
+    async fn start_sync_loop(client: Client, health: MatrixHealth) {
         let token = match client.sync_token().await {
             Some(t) => t,
             None => {
@@ -58,8 +60,15 @@ impl MatrixSyncActor {
                     .expect("Yarrbot just synced with the homeserver, but no token was found.")
             }
         };
+        health.record_login(client.logged_in().await);
+        health.record_sync();
         let settings = SyncSettings::default().token(token);
         debug!("Beginning sync loop.");
-        client.sync(settings).await;
+        client
+            .sync_with_callback(settings, |_| {
+                health.record_sync();
+                async { LoopCtrl::Continue }
+            })
+            .await;
     }
 }
@@ -0,0 +1,262 @@
+//! Determines the IP address of the client that made a request, honoring the forwarding header set by a trusted
+//! reverse proxy.
+
+use actix_web::HttpRequest;
+use anyhow::{bail, Context, Result};
+use std::net::{IpAddr, SocketAddr};
+use std::str::FromStr;
+use yarrbot_common::environment::{
+    get_env_var,
+    variables::{TRUSTED_PROXIES, TRUSTED_PROXY_HEADER},
+};
+use yarrbot_common::network::{canonical_ip, is_in_any, parse_network, IpNet};
+
+/// The header a trusted proxy records the client's address in. Only one is trusted, since a client can send either.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ForwardedHeader {
+    /// `X-Forwarded-For: 192.0.2.60`
+    XForwardedFor,
+
+    /// `Forwarded: for=192.0.2.60` (RFC 7239)
+    Forwarded,
+}
+
+impl ForwardedHeader {
+    fn name(&self) -> &'static str {
+        match self {
+            ForwardedHeader::XForwardedFor => "X-Forwarded-For",
+            ForwardedHeader::Forwarded => "Forwarded",
+        }
+    }
+
+    /// Read the last address in the header, which is the one added by the proxy nearest to Yarrbot. Returns [None] if
+    /// that entry is missing, obfuscated or unknown.
+    fn last_address(&self, header: &str) -> Option<IpAddr> {
+        let last = header.rsplit(',').next()?;
+        match self {
+            ForwardedHeader::XForwardedFor => parse_node(last.trim()),
+            ForwardedHeader::Forwarded => parse_forwarded_element(last),
+        }
+    }
+}
+
+impl Default for ForwardedHeader {
+    fn default() -> Self {
+        ForwardedHeader::XForwardedFor
+    }
+}
+
+impl FromStr for ForwardedHeader {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_lowercase().as_str() {
+            "x-forwarded-for" => Ok(ForwardedHeader::XForwardedFor),
+            "forwarded" => Ok(ForwardedHeader::Forwarded),
+            _ => bail!(
+                "\"{}\" is not a recognized forwarding header; use X-Forwarded-For or Forwarded.",
+                s
+            ),
+        }
+    }
+}
+
+/// The networks of the reverse proxies whose forwarding header is trusted to contain the client's IP address, and
+/// which header that is.
+#[derive(Debug, Clone, Default)]
+pub struct TrustedProxies {
+    networks: Vec<IpNet>,
+    header: ForwardedHeader,
+}
+
+impl TrustedProxies {
+    pub fn new(proxies: Vec<IpNet>) -> Self {
+        TrustedProxies {
+            networks: proxies,
+            header: ForwardedHeader::default(),
+        }
+    }
+
+    /// Trust the given header rather than `X-Forwarded-For`.
+    pub fn with_header(mut self, header: ForwardedHeader) -> Self {
+        self.header = header;
+        self
+    }
+
+    /// Read the comma-separated list of trusted proxy addresses or networks, and the header they set, from the
+    /// environment. No proxies are trusted if the environment variable isn't set.
+    pub fn from_env() -> Result<Self> {
+        let proxies = match get_env_var(TRUSTED_PROXIES) {
+            Ok(v) => TrustedProxies::from_str(&v)?,
+            Err(_) => TrustedProxies::default(),
+        };
+        Ok(match get_env_var(TRUSTED_PROXY_HEADER) {
+            Ok(h) => proxies
+                .with_header(ForwardedHeader::from_str(&h).with_context(|| {
+                    format!("{} is not a valid setting.", TRUSTED_PROXY_HEADER)
+                })?),
+            Err(_) => proxies,
+        })
+    }
+
+    fn is_trusted(&self, ip: &IpAddr) -> bool {
+        is_in_any(*ip, &self.networks)
+    }
+}
+
+impl FromStr for TrustedProxies {
+    type Err = anyhow::Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let proxies = s
+            .split(',')
+            .filter(|p| !p.trim().is_empty())
+            .map(parse_network)
+            .collect::<Result<Vec<IpNet>>>()?;
+        Ok(TrustedProxies::new(proxies))
+    }
+}
+
+/// Get the IP address of the client that made the request. Returns [None] if the peer address is unknown, as it is for
+/// requests over a Unix domain socket.
+pub fn get_client_ip(req: &HttpRequest, proxies: &TrustedProxies) -> Option<IpAddr> {
+    let peer = canonical_ip(req.peer_addr()?.ip());
+    if !proxies.is_trusted(&peer) {
+        return Some(peer);
+    }
+
+    // A proxy appends to the header the client sent, or adds another header after it, so only the very last entry is
+    // known to come from the proxy; anything before it could have been written by the client itself.
+    let forwarded = req
+        .headers()
+        .get_all(proxies.header.name())
+        .last()
+        .and_then(|h| h.to_str().ok())
+        .and_then(|h| proxies.header.last_address(h));
+    Some(forwarded.unwrap_or(peer))
+}
+
+/// Read the `for=` address from an element of a `Forwarded` header, such as `for="[2001:db8:cafe::17]:4711";proto=https`.
+fn parse_forwarded_element(element: &str) -> Option<IpAddr> {
+    element
+        .split(';')
+        .filter_map(|pair| pair.split_once('='))
+        .find(|(k, _)| k.trim().eq_ignore_ascii_case("for"))
+        .and_then(|(_, v)| parse_node(v.trim().trim_matches('"')))
+}
+
+/// Parse an address that may carry a port or IPv6 brackets.
+fn parse_node(node: &str) -> Option<IpAddr> {
+    IpAddr::from_str(node)
+        .ok()
+        .or_else(|| SocketAddr::from_str(node).ok().map(|s| s.ip()))
+        .or_else(|| IpAddr::from_str(node.strip_prefix('[')?.strip_suffix(']')?).ok())
+        .map(canonical_ip)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use actix_web::test::TestRequest;
+
+    fn ip(s: &str) -> IpAddr {
+        IpAddr::from_str(s).unwrap()
+    }
+
+    fn request(peer: &str, headers: &[(&str, &str)]) -> HttpRequest {
+        let mut request = TestRequest::default().peer_addr(SocketAddr::from_str(peer).unwrap());
+        for header in headers {
+            request = request.append_header(*header);
+        }
+        request.to_http_request()
+    }
+
+    #[test]
+    fn get_client_ip_ignores_header_given_untrusted_peer() {
+        // Arrange
+        let proxies = TrustedProxies::from_str("10.0.0.1").unwrap();
+        let req = request("203.0.113.7:4000", &[("X-Forwarded-For", "198.51.100.1")]);
+
+        // Act
+        let actual = get_client_ip(&req, &proxies);
+
+        // Assert
+        assert_eq!(Some(ip("203.0.113.7")), actual);
+    }
+
+    #[test]
+    fn get_client_ip_returns_last_entry_given_trusted_peer() {
+        // Arrange
+        let proxies = TrustedProxies::from_str("10.0.0.0/24").unwrap();
+        let req = request(
+            "10.0.0.5:4000",
+            &[
+                ("X-Forwarded-For", "192.0.2.66"),
+                ("X-Forwarded-For", "10.0.0.9, 198.51.100.1"),
+            ],
+        );
+
+        // Act
+        let actual = get_client_ip(&req, &proxies);
+
+        // Assert
+        assert_eq!(Some(ip("198.51.100.1")), actual);
+    }
+
+    #[test]
+    fn get_client_ip_ignores_header_that_is_not_configured() {
+        // Arrange
+        let proxies = TrustedProxies::from_str("10.0.0.0/24").unwrap();
+        let req = request(
+            "10.0.0.5:4000",
+            &[
+                ("Forwarded", "for=192.0.2.66"),
+                ("X-Forwarded-For", "198.51.100.1"),
+            ],
+        );
+
+        // Act
+        let actual = get_client_ip(&req, &proxies);
+
+        // Assert
+        assert_eq!(Some(ip("198.51.100.1")), actual);
+    }
+
+    #[test]
+    fn get_client_ip_returns_peer_given_obfuscated_last_entry() {
+        // Arrange
+        let proxies = TrustedProxies::from_str("10.0.0.0/24")
+            .unwrap()
+            .with_header(ForwardedHeader::Forwarded);
+        let req = request(
+            "10.0.0.5:4000",
+            &[("Forwarded", "for=192.0.2.60, for=_hidden")],
+        );
+
+        // Act
+        let actual = get_client_ip(&req, &proxies);
+
+        // Assert
+        assert_eq!(Some(ip("10.0.0.5")), actual);
+    }
+
+    #[test]
+    fn last_address_reads_rfc_7239_element() {
+        // Act
+        let actual = ForwardedHeader::Forwarded.last_address(
+            "for=192.0.2.60;proto=http;by=203.0.113.43, For=\"[2001:db8:cafe::17]:4711\";proto=https",
+        );
+
+        // Assert
+        assert_eq!(Some(ip("2001:db8:cafe::17")), actual);
+    }
+
+    #[test]
+    fn from_str_returns_error_given_invalid_address() {
+        // Act
+        let actual = TrustedProxies::from_str("10.0.0.1,not-an-ip");
+
+        // Assert
+        assert!(actual.is_err());
+    }
+}
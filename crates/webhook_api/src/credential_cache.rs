@@ -0,0 +1,242 @@
+//! Remembers recently verified webhook credentials so that a burst of requests from the same Sonarr/Radarr
+//! instance doesn't run Argon2id for every request.
+
+use std::collections::HashMap;
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::Mutex;
+use std::time::{Duration, Instant};
+use tracing::debug;
+use uuid::Uuid;
+use yarrbot_common::crypto::{generate_token, hmac_sha256};
+use yarrbot_common::metrics::CREDENTIAL_CACHE_LOOKUPS;
+use yarrbot_db::models::Webhook;
+
+const DEFAULT_CAPACITY: usize = 1024;
+const DEFAULT_TIME_TO_LIVE: Duration = Duration::from_secs(300);
+
+#[derive(Debug)]
+struct CacheEntry {
+    /// Keyed digest of the webhook's stored password hash at the time of verification. The entry no longer
+    /// matches once the password changes.
+    password_digest: Vec<u8>,
+    expires_at: Instant,
+}
+
+/// Counters describing how well the cache is working and how much time verification costs.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct CredentialCacheStats {
+    pub hits: u64,
+    pub misses: u64,
+    /// The number of full password verifications performed.
+    pub verifications: u64,
+    /// The total time spent in full password verifications, in microseconds.
+    pub verification_micros: u64,
+}
+
+/// A bounded cache of successful verifications, keyed by the webhook ID and a keyed digest of the presented
+/// credentials. The digest key is generated at startup and never stored, so the cache never holds anything that
+/// could be used to recover or replay a password.
+///
+/// Entries never need to be removed by hand: lookups are made against the webhook as just read from the database, so
+/// a deleted webhook is never looked up, a webhook that no longer uses Basic authentication never consults the cache,
+/// and an entry stops matching as soon as the webhook's password is rotated.
+pub struct CredentialCache {
+    key: Vec<u8>,
+    capacity: usize,
+    time_to_live: Duration,
+    entries: Mutex<HashMap<(Uuid, Vec<u8>), CacheEntry>>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    verifications: AtomicU64,
+    verification_micros: AtomicU64,
+}
+
+impl Default for CredentialCache {
+    fn default() -> Self {
+        CredentialCache::new(DEFAULT_CAPACITY, DEFAULT_TIME_TO_LIVE)
+    }
+}
+
+impl CredentialCache {
+    pub fn new(capacity: usize, time_to_live: Duration) -> Self {
+        CredentialCache {
+            key: generate_token().into_bytes(),
+            capacity,
+            time_to_live,
+            entries: Mutex::new(HashMap::new()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            verifications: AtomicU64::new(0),
+            verification_micros: AtomicU64::new(0),
+        }
+    }
+
+    fn credentials_digest(&self, username: &str, password: &str) -> Vec<u8> {
+        let mut presented = Vec::with_capacity(username.len() + 1 + password.len());
+        presented.extend_from_slice(username.as_bytes());
+        presented.push(0);
+        presented.extend_from_slice(password.as_bytes());
+        hmac_sha256(&self.key, &presented)
+    }
+
+    /// Whether the credentials were recently verified for the webhook with its current password.
+    pub fn contains(&self, webhook: &Webhook, username: &str, password: &str) -> bool {
+        self.contains_at(webhook, username, password, Instant::now())
+    }
+
+    fn contains_at(&self, webhook: &Webhook, username: &str, password: &str, now: Instant) -> bool {
+        let key = (webhook.id, self.credentials_digest(username, password));
+        let password_digest = hmac_sha256(&self.key, &webhook.password);
+        let mut entries = self.entries.lock().unwrap();
+        let is_hit = match entries.get(&key) {
+            Some(e) if e.expires_at > now && e.password_digest == password_digest => true,
+            Some(_) => {
+                // Expired, or the webhook's password has changed since.
+                entries.remove(&key);
+                false
+            }
+            None => false,
+        };
+
+        if is_hit {
+            self.hits.fetch_add(1, Ordering::Relaxed);
+        } else {
+            self.misses.fetch_add(1, Ordering::Relaxed);
+        }
+        CREDENTIAL_CACHE_LOOKUPS
+            .with_label_values(&[if is_hit { "hit" } else { "miss" }])
+            .inc();
+        is_hit
+    }
+
+    /// Remember that the credentials were successfully verified for the webhook.
+    pub fn insert(&self, webhook: &Webhook, username: &str, password: &str) {
+        self.insert_at(webhook, username, password, Instant::now())
+    }
+
+    fn insert_at(&self, webhook: &Webhook, username: &str, password: &str, now: Instant) {
+        let key = (webhook.id, self.credentials_digest(username, password));
+        let entry = CacheEntry {
+            password_digest: hmac_sha256(&self.key, &webhook.password),
+            expires_at: now + self.time_to_live,
+        };
+        let mut entries = self.entries.lock().unwrap();
+        if entries.len() >= self.capacity && !entries.contains_key(&key) {
+            entries.retain(|_, e| e.expires_at > now);
+            if entries.len() >= self.capacity {
+                // Still full of live entries; make room by dropping whichever expires soonest.
+                let soonest = entries
+                    .iter()
+                    .min_by_key(|(_, e)| e.expires_at)
+                    .map(|(k, _)| k.clone());
+                if let Some(k) = soonest {
+                    entries.remove(&k);
+                }
+            }
+        }
+        entries.insert(key, entry);
+    }
+
+    /// Record the time taken by a full password verification.
+    pub fn record_verification(&self, elapsed: Duration) {
+        let micros = u64::try_from(elapsed.as_micros()).unwrap_or(u64::MAX);
+        self.verifications.fetch_add(1, Ordering::Relaxed);
+        self.verification_micros
+            .fetch_add(micros, Ordering::Relaxed);
+        debug!(verification_micros = micros, "Verified webhook password.");
+    }
+
+    pub fn stats(&self) -> CredentialCacheStats {
+        CredentialCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            verifications: self.verifications.load(Ordering::Relaxed),
+            verification_micros: self.verification_micros.load(Ordering::Relaxed),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use yarrbot_db::models::{NewUser, NewWebhook, User};
+
+    fn webhook(password: &[u8]) -> Webhook {
+        let owner = User::from(NewUser::new("@owner:example.org", None));
+        Webhook::from(NewWebhook::new("testuser", password.to_vec(), &owner, None))
+    }
+
+    #[test]
+    fn contains_returns_true_given_verified_credentials() {
+        // Arrange
+        let cache = CredentialCache::default();
+        let w = webhook(b"hash");
+        cache.insert(&w, "testuser", "password");
+
+        // Act
+        let hit = cache.contains(&w, "testuser", "password");
+        let wrong_password = cache.contains(&w, "testuser", "Password");
+
+        // Assert
+        assert!(hit);
+        assert!(!wrong_password);
+        assert_eq!(1, cache.stats().hits);
+        assert_eq!(1, cache.stats().misses);
+    }
+
+    #[test]
+    fn contains_returns_false_given_changed_password() {
+        // Arrange
+        let cache = CredentialCache::default();
+        let mut w = webhook(b"old hash");
+        cache.insert(&w, "testuser", "password");
+        w.password = b"new hash".to_vec();
+
+        // Act
+        let actual = cache.contains(&w, "testuser", "password");
+
+        // Assert
+        assert!(!actual);
+    }
+
+    #[test]
+    fn contains_at_returns_false_given_expired_entry() {
+        // Arrange
+        let cache = CredentialCache::new(10, Duration::from_secs(60));
+        let w = webhook(b"hash");
+        let now = Instant::now();
+        cache.insert_at(&w, "testuser", "password", now);
+
+        // Act
+        let fresh = cache.contains_at(&w, "testuser", "password", now + Duration::from_secs(59));
+        let expired = cache.contains_at(&w, "testuser", "password", now + Duration::from_secs(60));
+
+        // Assert
+        assert!(fresh);
+        assert!(!expired);
+    }
+
+    #[test]
+    fn insert_evicts_entry_given_full_cache() {
+        // Arrange
+        let cache = CredentialCache::new(2, Duration::from_secs(60));
+        let (first, second, third) = (webhook(b"1"), webhook(b"2"), webhook(b"3"));
+        let now = Instant::now();
+        cache.insert_at(&first, "testuser", "password", now);
+        cache.insert_at(
+            &second,
+            "testuser",
+            "password",
+            now + Duration::from_secs(1),
+        );
+
+        // Act
+        cache.insert_at(&third, "testuser", "password", now + Duration::from_secs(2));
+
+        // Assert
+        let later = now + Duration::from_secs(3);
+        assert!(!cache.contains_at(&first, "testuser", "password", later));
+        assert!(cache.contains_at(&second, "testuser", "password", later));
+        assert!(cache.contains_at(&third, "testuser", "password", later));
+    }
+}
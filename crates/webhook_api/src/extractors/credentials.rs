@@ -0,0 +1,164 @@
+//! Reads the credentials for the token and HMAC [WebhookAuthMode]s from a request.
+
+use actix_web::HttpRequest;
+use chrono::Utc;
+use yarrbot_common::crypto::verify_hmac_sha256;
+
+/// The header containing the Unix timestamp (in seconds) at which a signed request was sent.
+pub const TIMESTAMP_HEADER: &str = "X-Yarrbot-Timestamp";
+
+/// The header containing the hex encoded HMAC-SHA256 signature of a signed request, prefixed with `sha256=`.
+pub const SIGNATURE_HEADER: &str = "X-Yarrbot-Signature";
+
+const API_KEY_HEADER: &str = "X-Api-Key";
+const TOKEN_QUERY_PARAMETER: &str = "token";
+const SIGNATURE_PREFIX: &str = "sha256=";
+
+/// Signed requests with timestamps further than this from the current time are rejected, which limits how long a
+/// captured request could be replayed; the [SignatureReplayGuard](crate::SignatureReplayGuard) rejects replays within
+/// it.
+const MAX_TIMESTAMP_SKEW_SECONDS: i64 = 300;
+
+/// Get the token from the `token` query parameter.
+pub fn get_url_token(req: &HttpRequest) -> Option<String> {
+    url::form_urlencoded::parse(req.query_string().as_bytes())
+        .find(|(k, _)| k == TOKEN_QUERY_PARAMETER)
+        .map(|(_, v)| v.into_owned())
+}
+
+/// Get the token from the `X-Api-Key` header, or from an `Authorization: Bearer` header.
+pub fn get_header_token(req: &HttpRequest) -> Option<String> {
+    let headers = req.headers();
+    if let Some(k) = headers.get(API_KEY_HEADER).and_then(|h| h.to_str().ok()) {
+        return Some(String::from(k.trim()));
+    }
+
+    let authorization = headers.get("Authorization")?.to_str().ok()?;
+    let (scheme, token) = authorization.trim().split_once(' ')?;
+    if scheme.eq_ignore_ascii_case("bearer") {
+        Some(String::from(token.trim()))
+    } else {
+        None
+    }
+}
+
+/// The signature and timestamp of a signed request. The signature can only be checked once the body is read.
+#[derive(Debug)]
+pub struct RequestSignature {
+    timestamp: String,
+    signature: Vec<u8>,
+}
+
+impl RequestSignature {
+    /// Read the signature headers from the request. Returns [None] if either header is missing or malformed.
+    pub fn from_request(req: &HttpRequest) -> Option<Self> {
+        let headers = req.headers();
+        let timestamp = headers.get(TIMESTAMP_HEADER)?.to_str().ok()?;
+        let signature = headers.get(SIGNATURE_HEADER)?.to_str().ok()?;
+        RequestSignature::parse(timestamp, signature)
+    }
+
+    fn parse(timestamp: &str, signature: &str) -> Option<Self> {
+        let hex_signature = signature.trim().strip_prefix(SIGNATURE_PREFIX)?;
+        Some(RequestSignature {
+            timestamp: String::from(timestamp.trim()),
+            signature: hex::decode(hex_signature).ok()?,
+        })
+    }
+
+    /// Whether the request's timestamp is close enough to the current time to accept the request.
+    pub fn is_fresh(&self) -> bool {
+        self.is_fresh_at(Utc::now().timestamp())
+    }
+
+    fn is_fresh_at(&self, now: i64) -> bool {
+        match self.timestamp.parse::<i64>() {
+            Ok(t) => (now - t).abs() <= MAX_TIMESTAMP_SKEW_SECONDS,
+            Err(_) => false,
+        }
+    }
+
+    /// The last Unix time at which the request's timestamp is fresh, or [None] if the timestamp is malformed.
+    pub fn fresh_until(&self) -> Option<i64> {
+        let timestamp = self.timestamp.parse::<i64>().ok()?;
+        Some(timestamp + MAX_TIMESTAMP_SKEW_SECONDS)
+    }
+
+    pub fn signature(&self) -> &[u8] {
+        &self.signature
+    }
+
+    /// Verify the signature against `{timestamp}.{body}` with the webhook's secret.
+    pub fn verify(&self, secret: &str, body: &[u8]) -> bool {
+        let mut signed = Vec::with_capacity(self.timestamp.len() + 1 + body.len());
+        signed.extend_from_slice(self.timestamp.as_bytes());
+        signed.push(b'.');
+        signed.extend_from_slice(body);
+        verify_hmac_sha256(secret.as_bytes(), &signed, &self.signature)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use yarrbot_common::crypto::hmac_sha256;
+
+    fn sign(secret: &str, timestamp: &str, body: &str) -> String {
+        let signed = format!("{}.{}", timestamp, body);
+        format!(
+            "{}{}",
+            SIGNATURE_PREFIX,
+            hex::encode(hmac_sha256(secret.as_bytes(), signed.as_bytes()))
+        )
+    }
+
+    #[test]
+    fn parse_returns_none_given_signature_without_prefix() {
+        // Act
+        let actual = RequestSignature::parse("1634515200", "deadbeef");
+
+        // Assert
+        assert!(actual.is_none());
+    }
+
+    #[test]
+    fn verify_returns_true_given_matching_signature() {
+        // Arrange
+        let header = sign("secret", "1634515200", "{}");
+        let signature = RequestSignature::parse("1634515200", &header).unwrap();
+
+        // Act
+        let actual = signature.verify("secret", b"{}");
+
+        // Assert
+        assert!(actual);
+    }
+
+    #[test]
+    fn verify_returns_false_given_tampered_body() {
+        // Arrange
+        let header = sign("secret", "1634515200", "{}");
+        let signature = RequestSignature::parse("1634515200", &header).unwrap();
+
+        // Act
+        let actual = signature.verify("secret", b"{\"eventType\":\"Test\"}");
+
+        // Assert
+        assert!(!actual);
+    }
+
+    #[test]
+    fn is_fresh_at_returns_false_given_old_timestamp() {
+        // Arrange
+        let header = sign("secret", "1634515200", "{}");
+        let signature = RequestSignature::parse("1634515200", &header).unwrap();
+
+        // Act
+        let fresh = signature.is_fresh_at(1634515200 + MAX_TIMESTAMP_SKEW_SECONDS);
+        let stale = signature.is_fresh_at(1634515200 + MAX_TIMESTAMP_SKEW_SECONDS + 1);
+
+        // Assert
+        assert!(fresh);
+        assert!(!stale);
+    }
+}
@@ -1 +1,2 @@
+pub(crate) mod credentials;
 pub mod webhook_extractor;
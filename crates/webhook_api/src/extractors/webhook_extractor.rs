@@ -1,24 +1,164 @@
-//! Extract the webhook ID from the request, verifies that the credentials in the `Authorization` header are
-//! correct for the given webhook, then returns the webhook for use for a particular code path.
+//! Extract the webhook ID from the request, verifies that the request's credentials are correct for the given
+//! webhook's [WebhookAuthMode], then returns the webhook for use for a particular code path.
 
+use crate::client_ip::{get_client_ip, TrustedProxies};
+use crate::credential_cache::CredentialCache;
+use crate::extractors::credentials::{get_header_token, get_url_token, RequestSignature};
+use crate::lockout::AuthFailureTracker;
+use crate::replay_guard::SignatureReplayGuard;
 use crate::yarrbot_api_error::YarrbotApiError;
 use actix_web::dev::Payload;
-use actix_web::web::{block, Data};
+use actix_web::web::Data;
 use actix_web::{Error, FromRequest, HttpRequest};
-use anyhow::Error as AnyhowError;
+use chrono::Utc;
 use std::future::Future;
+use std::net::IpAddr;
 use std::pin::Pin;
-use tracing::{debug, error, info};
+use std::time::Instant;
+use tracing::{debug, error, info, warn};
 use uuid::Uuid;
-use yarrbot_common::{crypto::verify, short_id::ShortId};
-use yarrbot_db::actions::webhook_actions::WebhookActions;
+use yarrbot_common::crypto::{digest_matches, hash, hash_parameters, needs_rehash, verify};
+use yarrbot_common::metrics::{PASSWORD_VERIFICATION_DURATION, WEBHOOK_AUTH_FAILURES};
+use yarrbot_common::network::{is_in_any, parse_network, IpNet};
+use yarrbot_common::short_id::ShortId;
+use yarrbot_db::enums::WebhookAuthMode;
 use yarrbot_db::models::Webhook;
-use yarrbot_db::DbPool;
+use yarrbot_db::repositories::Repositories;
+use yarrbot_matrix_client::message::MessageData;
 
 /// Wrapper struct for the final webhook model from the database.
 pub struct WebhookInfo {
     pub webhook: Webhook,
     pub short_id: String,
+    /// The signature of a request to a [WebhookAuthMode::Hmac] webhook, which is checked once the body is read.
+    signature: Option<RequestSignature>,
+    replay_guard: Option<Data<SignatureReplayGuard>>,
+    failures: FailureReporter,
+}
+
+impl WebhookInfo {
+    /// Verify the request body's signature if the webhook requires signed requests, rejecting signatures that were
+    /// already accepted. Always true otherwise.
+    pub fn verify_body(&self, body: &[u8]) -> bool {
+        let is_verified = match (&self.signature, &self.webhook.hmac_secret) {
+            (None, _) => true,
+            (Some(s), Some(secret)) => s.verify(secret, body) && self.is_first_use(s),
+            (Some(_), None) => false,
+        };
+        if is_verified {
+            self.failures.succeed(&self.webhook);
+        } else {
+            self.failures.fail(Some(&self.webhook), &self.short_id);
+        }
+
+        is_verified
+    }
+
+    fn is_first_use(&self, signature: &RequestSignature) -> bool {
+        let is_first_use = match &self.replay_guard {
+            Some(g) => g.record(&self.webhook.id, signature),
+            None => true,
+        };
+        if !is_first_use {
+            warn!("Rejected a signed request that was already accepted.");
+        }
+        is_first_use
+    }
+}
+
+/// Reports the outcome of a request's authentication to the [AuthFailureTracker], if one is registered.
+#[derive(Clone)]
+struct FailureReporter {
+    tracker: Option<Data<AuthFailureTracker>>,
+    repositories: Data<Repositories>,
+    client_ip: Option<IpAddr>,
+}
+
+impl FailureReporter {
+    fn check_ip(&self) -> Result<(), Error> {
+        match &self.tracker {
+            Some(t) => t.check_ip(self.client_ip).map_err(|retry_after| {
+                debug!(client_ip = ?self.client_ip, "Client IP address is locked out.");
+                YarrbotApiError::too_many_requests(retry_after).into()
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn check_webhook(&self, webhook: &Webhook) -> Result<(), Error> {
+        match &self.tracker {
+            Some(t) => t.check_webhook(&webhook.id).map_err(|retry_after| {
+                debug!("Webhook is locked out.");
+                YarrbotApiError::too_many_requests(retry_after).into()
+            }),
+            None => Ok(()),
+        }
+    }
+
+    fn succeed(&self, webhook: &Webhook) {
+        if let Some(t) = &self.tracker {
+            t.record_success(self.client_ip, &webhook.id);
+        }
+    }
+
+    fn fail(&self, webhook: Option<&Webhook>, short_id: &str) {
+        WEBHOOK_AUTH_FAILURES.inc();
+        let tracker = match &self.tracker {
+            Some(t) => t,
+            None => return,
+        };
+        if !tracker.record_failure(self.client_ip, webhook.map(|w| w.id)) {
+            return;
+        }
+        if let (Some(client), Some(w)) = (tracker.alert_client(), webhook) {
+            let repositories = self.repositories.clone();
+            let user_id = w.user_id;
+            let server = w
+                .server_name
+                .as_deref()
+                .map_or_else(String::new, |s| format!(" for {}", s));
+            let from = self
+                .client_ip
+                .map_or_else(|| String::from("an unknown address"), |ip| ip.to_string());
+            let text = format!(
+                "Webhook {}{} has been locked out after repeated failed authentication attempts, most recently \
+                from {}. If you don't recognize these requests, consider changing the webhook's password.",
+                short_id, server, from
+            );
+            actix_web::rt::spawn(async move {
+                let owner = match repositories.users.try_get(&user_id).await {
+                    Ok(Some(u)) => u,
+                    _ => {
+                        warn!("Failed to look up the owner of the locked out webhook.");
+                        return;
+                    }
+                };
+                if let Err(e) = client
+                    .send_direct_message(&owner.service_username, MessageData::from(text.as_str()))
+                    .await
+                {
+                    error!(error = ?e, "Failed to notify the webhook's owner of the lockout.");
+                }
+            });
+        }
+    }
+}
+
+/// All of the credentials a request may carry; which of them are checked depends on the [WebhookAuthMode].
+struct RequestCredentials {
+    basic: Option<WebhookAuth>,
+    url_token: Option<String>,
+    header_token: Option<String>,
+    signature: Option<RequestSignature>,
+}
+
+impl RequestCredentials {
+    fn is_empty(&self) -> bool {
+        self.basic.is_none()
+            && self.url_token.is_none()
+            && self.header_token.is_none()
+            && self.signature.is_none()
+    }
 }
 
 /// Represents the decoded username and password from the webhook.
@@ -49,9 +189,143 @@ fn get_webhook_auth(header: &str) -> Option<WebhookAuth> {
     })
 }
 
-/// Verify the username and password with what was stored in the database [Webhook].
-async fn is_authorized_for_webhook(auth: WebhookAuth, webhook: &Webhook) -> bool {
-    webhook.username == auth.user && verify(auth.password, webhook.password.as_slice()).await
+/// Verify the username and password with what was stored in the database [Webhook], skipping the expensive
+/// password verification if the [CredentialCache] recently verified the same credentials. The password replaced by
+/// the last rotation is also accepted until its grace period ends.
+async fn is_authorized_for_webhook(
+    auth: WebhookAuth,
+    webhook: &Webhook,
+    cache: Option<&CredentialCache>,
+    repositories: &Data<Repositories>,
+) -> bool {
+    if webhook.username != auth.user {
+        return false;
+    }
+    if let Some(c) = cache {
+        if c.contains(webhook, &auth.user, &auth.password) {
+            debug!("Webhook credentials found in the verified credential cache.");
+            return true;
+        }
+    }
+
+    let start = Instant::now();
+    let is_current = verify(auth.password.clone(), webhook.password.as_slice()).await;
+    let elapsed = start.elapsed();
+    PASSWORD_VERIFICATION_DURATION.observe(elapsed.as_secs_f64());
+    if let Some(c) = cache {
+        c.record_verification(elapsed);
+    }
+    if is_current {
+        if let Some(c) = cache {
+            c.insert(webhook, &auth.user, &auth.password);
+        }
+        if needs_rehash(&webhook.password_params) {
+            rehash_password(repositories.clone(), webhook.clone(), auth.password);
+        }
+        return true;
+    }
+
+    match (
+        &webhook.previous_password,
+        webhook.previous_password_expires_at,
+    ) {
+        (Some(previous), Some(expires_at)) if expires_at > Utc::now() => {
+            let is_previous = verify(auth.password, previous.as_slice()).await;
+            if is_previous {
+                info!("Request authorized with the webhook's previous password.");
+            }
+            is_previous
+        }
+        _ => false,
+    }
+}
+
+/// Re-hash the webhook's password with the current parameters in the background.
+fn rehash_password(repositories: Data<Repositories>, webhook: Webhook, password: String) {
+    actix_web::rt::spawn(async move {
+        let hashed = match hash(password).await {
+            Ok(h) => h.to_vec(),
+            Err(e) => {
+                error!(error = ?e, "Failed to re-hash the webhook's password.");
+                return;
+            }
+        };
+        let params = hash_parameters(&hashed).unwrap_or_default();
+        match repositories
+            .webhooks
+            .rehash_password(&webhook, hashed, params)
+            .await
+        {
+            Ok(_) => info!("Re-hashed the webhook's password with the current parameters."),
+            Err(e) => error!(error = ?e, "Failed to save the webhook's re-hashed password."),
+        }
+    });
+}
+
+/// Check the client's IP address against the [Webhook]'s allowed networks. Requests from unknown addresses are only
+/// allowed if the webhook doesn't restrict its sources.
+fn is_allowed_source(webhook: &Webhook, client_ip: Option<IpAddr>) -> bool {
+    if webhook.allowed_sources.is_empty() {
+        return true;
+    }
+
+    let networks: Vec<IpNet> = webhook
+        .allowed_sources
+        .iter()
+        .filter_map(|s| match parse_network(s) {
+            Ok(n) => Some(n),
+            Err(e) => {
+                warn!(error = ?e, "Ignoring malformed allowed source.");
+                None
+            }
+        })
+        .collect();
+    match client_ip {
+        Some(ip) => is_in_any(ip, &networks),
+        None => false,
+    }
+}
+
+/// Verify a token against the digest stored in the database [Webhook].
+fn is_token_valid(token: Option<String>, webhook: &Webhook) -> bool {
+    match (token, &webhook.auth_token_digest) {
+        (Some(t), Some(d)) => digest_matches(t.as_bytes(), d),
+        _ => false,
+    }
+}
+
+/// Check the request's credentials according to the [Webhook]'s [WebhookAuthMode]. Returns the pending
+/// [RequestSignature] for signed requests, which must still be verified against the body.
+async fn authorize(
+    credentials: RequestCredentials,
+    webhook: &Webhook,
+    cache: Option<&CredentialCache>,
+    repositories: &Data<Repositories>,
+) -> Result<Option<RequestSignature>, ()> {
+    let is_authorized = match webhook.auth_mode {
+        WebhookAuthMode::Basic => match credentials.basic {
+            Some(b) => is_authorized_for_webhook(b, webhook, cache, repositories).await,
+            None => false,
+        },
+        WebhookAuthMode::UrlToken => is_token_valid(credentials.url_token, webhook),
+        WebhookAuthMode::HeaderToken => is_token_valid(credentials.header_token, webhook),
+        WebhookAuthMode::Hmac => {
+            return match credentials.signature {
+                Some(s) if webhook.hmac_secret.is_some() && s.is_fresh() => Ok(Some(s)),
+                Some(_) => {
+                    debug!("Signed request was missing a secret or was too old.");
+                    Err(())
+                }
+                None => Err(()),
+            };
+        }
+    };
+
+    if is_authorized {
+        Ok(None)
+    } else {
+        Err(())
+    }
 }
 
 impl FromRequest for WebhookInfo {
@@ -60,56 +334,56 @@ impl FromRequest for WebhookInfo {
 
     #[tracing::instrument(name = "webhook_extractor", skip(req, _payload))]
     fn from_request(req: &HttpRequest, _payload: &mut Payload) -> Self::Future {
-        let pool = req.app_data::<Data<DbPool>>().unwrap().clone();
+        let repositories = req.app_data::<Data<Repositories>>().unwrap().clone();
+        let tracker = req.app_data::<Data<AuthFailureTracker>>().cloned();
+        let cache = req.app_data::<Data<CredentialCache>>().cloned();
+        let replay_guard = req.app_data::<Data<SignatureReplayGuard>>().cloned();
+        let client_ip = match req.app_data::<Data<TrustedProxies>>() {
+            Some(p) => get_client_ip(req, p),
+            None => get_client_ip(req, &TrustedProxies::default()),
+        };
+        let failures = FailureReporter {
+            client_ip,
+            tracker,
+            repositories: repositories.clone(),
+        };
         let webhook_id = String::from(req.match_info().get("webhook_id").unwrap());
         let auth_header = match req.headers().get("Authorization") {
             Some(h) => String::from(h.to_str().unwrap_or("")),
             None => String::from(""),
         };
+        let credentials = RequestCredentials {
+            basic: get_webhook_auth(&auth_header),
+            url_token: get_url_token(req),
+            header_token: get_header_token(req),
+            signature: RequestSignature::from_request(req),
+        };
 
         debug!("Processing webhook request.");
         Box::pin(async move {
-            // Get login information for the webhook.
-            debug!("Attempting to retrieve login information for webhook.");
-            let webhook_auth = match get_webhook_auth(&auth_header) {
-                Some(a) => a,
-                _ => return Err(YarrbotApiError::unauthorized(None).into()),
-            };
+            // Turn away locked out clients before doing any work on their behalf.
+            failures.check_ip()?;
+
             // Get the UUID for the webhook from the short ID.
             debug!("Converting webhook short ID back into a UUID.");
             let uuid = match Uuid::from_short_id(&webhook_id) {
                 Ok(u) => u,
-                _ => return Err(YarrbotApiError::not_found(None).into()),
+                _ => {
+                    failures.fail(None, &webhook_id);
+                    return Err(YarrbotApiError::not_found(None).into());
+                }
             };
-            let uuid2 = uuid.clone();
 
             // Retrieve the webhook from the database.
             debug!("Getting webhook from the database.");
-            let conn = match pool.get_ref().get() {
-                Ok(c) => c,
-                Err(e) => {
-                    return Err(YarrbotApiError::internal_server_error(
-                        AnyhowError::new(e)
-                            .context("Encountered an error while retrieving connection."),
-                    )
-                    .into());
-                }
-            };
-            let optional_webhook = match block(move || Webhook::try_get(&conn, &uuid)).await {
-                Ok(Ok(w)) => w,
+            let optional_webhook = match repositories.webhooks.try_get(&uuid).await {
+                Ok(w) => w,
                 Err(e) => {
                     error!(
-                        webhook_id = %uuid2.to_string(),
+                        webhook_id = %uuid.to_string(),
                         "Failed to retrieve webhook with from the database."
                     );
-                    return Err(YarrbotApiError::internal_server_error(AnyhowError::new(e)).into());
-                }
-                Ok(Err(e)) => {
-                    error!(
-                        webhook_id = %uuid2.to_string(),
-                        "Failed to retrieve webhook with from the database."
-                    );
-                    return Err(YarrbotApiError::internal_server_error(AnyhowError::new(e)).into());
+                    return Err(YarrbotApiError::internal_server_error(e).into());
                 }
             };
             let webhook = match optional_webhook {
@@ -119,20 +393,56 @@ impl FromRequest for WebhookInfo {
                 }
                 _ => {
                     debug!("Failed to find webhook in the database.");
+                    failures.fail(None, &webhook_id);
                     return Err(YarrbotApiError::not_found(None).into());
                 }
             };
 
+            // Reject requests from outside the webhook's allowed networks before checking credentials.
+            if !is_allowed_source(&webhook, failures.client_ip) {
+                info!(
+                    client_ip = ?failures.client_ip,
+                    "Request came from outside of the webhook's allowed networks."
+                );
+                return Err(YarrbotApiError::forbidden(None).into());
+            }
+            failures.check_webhook(&webhook)?;
+
+            // Requests without any kind of credentials can't be authorized.
+            debug!("Attempting to retrieve login information for webhook.");
+            if credentials.is_empty() {
+                failures.fail(Some(&webhook), &webhook_id);
+                return Err(YarrbotApiError::unauthorized(None).into());
+            }
+
             // Check if the user is authorized for the webhook and return it if so.
-            if is_authorized_for_webhook(webhook_auth, &webhook).await {
-                info!("Webhook retrieved and authorized.");
-                Ok(WebhookInfo {
-                    webhook,
-                    short_id: webhook_id,
-                })
-            } else {
-                debug!("Current request is not authorized for webhook.");
-                Err(YarrbotApiError::unauthorized(None).into())
+            match authorize(
+                credentials,
+                &webhook,
+                cache.as_ref().map(|c| c.get_ref()),
+                &repositories,
+            )
+            .await
+            {
+                Ok(signature) => {
+                    info!(auth_mode = ?webhook.auth_mode, "Webhook retrieved and authorized.");
+                    // Signed requests aren't successful until the body has been verified.
+                    if signature.is_none() {
+                        failures.succeed(&webhook);
+                    }
+                    Ok(WebhookInfo {
+                        webhook,
+                        short_id: webhook_id,
+                        signature,
+                        replay_guard,
+                        failures,
+                    })
+                }
+                Err(_) => {
+                    debug!("Current request is not authorized for webhook.");
+                    failures.fail(Some(&webhook), &webhook_id);
+                    Err(YarrbotApiError::unauthorized(None).into())
+                }
             }
         })
     }
@@ -0,0 +1,163 @@
+//! Selects the image, such as a series poster, to send alongside a notification.
+
+use crate::models::common::{ArrImage, ArrImageCoverType};
+use crate::models::ArrWebhook;
+use tracing::{debug, warn};
+use url::Url;
+use yarrbot_db::models::Webhook;
+use yarrbot_matrix_client::message::MessageImage;
+
+/// Pick the poster (or the fanart if there's no poster) for the series or movie in the webhook.
+///
+/// The image is retrieved from the *arr's MediaCover endpoint if the [Webhook] has a URL and API key for the *arr;
+/// otherwise the image is retrieved from the *arr's metadata source.
+pub fn get_message_image(body: &ArrWebhook, webhook: &Webhook) -> Option<MessageImage> {
+    let (title, images) = match body {
+        ArrWebhook::Sonarr(w) => w.series().map(|s| (&s.title, &s.images)),
+        ArrWebhook::Radarr(w) => w.movie().map(|m| (&m.title, &m.images)),
+    }?;
+    let images = images.as_ref()?;
+    let image = find_cover(images, ArrImageCoverType::Poster)
+        .or_else(|| find_cover(images, ArrImageCoverType::Fanart))?;
+
+    let local = match (&webhook.arr_url, &webhook.arr_api_key, &image.url) {
+        (Some(base), Some(api_key), Some(path)) => {
+            get_local_url(base, path, &image.cover_type).map(|u| (u, Some(api_key.clone())))
+        }
+        _ => None,
+    };
+    let (source_url, api_key) = match local {
+        Some(l) => l,
+        None => {
+            let remote = Url::parse(image.remote_url.as_ref()?)
+                .map_err(|e| warn!(error = ?e, "Could not parse the image's remote URL."))
+                .ok()?;
+            (remote, None)
+        }
+    };
+
+    debug!(url = %source_url, "Selected image to send with the notification.");
+    Some(MessageImage::new(source_url, api_key, title))
+}
+
+fn find_cover(images: &[ArrImage], cover_type: ArrImageCoverType) -> Option<&ArrImage> {
+    images.iter().find(|i| i.cover_type == cover_type)
+}
+
+/// Build the URL to a thumbnail of the image on the *arr itself. The *arrs keep resized copies of each cover next
+/// to the original, e.g. `poster-500.jpg` next to `poster.jpg`.
+fn get_local_url(base: &str, path: &str, cover_type: &ArrImageCoverType) -> Option<Url> {
+    let mut url = match Url::parse(base).and_then(|b| b.join(path)) {
+        Ok(u) => u,
+        Err(e) => {
+            warn!(error = ?e, "Could not build the image URL from the *arr URL.");
+            return None;
+        }
+    };
+    let size = match cover_type {
+        ArrImageCoverType::Poster => "500",
+        ArrImageCoverType::Fanart => "360",
+        _ => return Some(url),
+    };
+    let thumbnail = match url.path().rsplit_once('.') {
+        Some((name, extension)) => format!("{}-{}.{}", name, size, extension),
+        None => return Some(url),
+    };
+    url.set_path(&thumbnail);
+
+    Some(url)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::sonarr::{SonarrSeries, SonarrSeriesType, SonarrWebhook};
+    use yarrbot_db::models::{NewUser, NewWebhook, User};
+
+    fn get_body(images: Option<Vec<ArrImage>>) -> ArrWebhook {
+        ArrWebhook::Sonarr(SonarrWebhook::Test {
+            series: SonarrSeries {
+                id: 1,
+                title: String::from("Test Title"),
+                path: String::from("C:\\testpath"),
+                tvdb_id: Some(1234),
+                tv_maze_id: None,
+                imdb_id: None,
+                series_type: SonarrSeriesType::Standard,
+                images,
+            },
+            episodes: vec![],
+        })
+    }
+
+    fn get_webhook(arr_url: Option<&str>, arr_api_key: Option<&str>) -> Webhook {
+        let owner = User::from(NewUser::new("@owner:example.org", None));
+        let mut webhook = Webhook::from(NewWebhook::new("testuser", vec![], &owner, None));
+        webhook.arr_url = arr_url.map(String::from);
+        webhook.arr_api_key = arr_api_key.map(String::from);
+        webhook
+    }
+
+    fn get_images() -> Vec<ArrImage> {
+        vec![
+            ArrImage {
+                cover_type: ArrImageCoverType::Fanart,
+                url: Some(String::from("/MediaCover/1/fanart.jpg?lastWrite=1")),
+                remote_url: Some(String::from("https://artworks.example.org/fanart.jpg")),
+            },
+            ArrImage {
+                cover_type: ArrImageCoverType::Poster,
+                url: Some(String::from("/MediaCover/1/poster.jpg?lastWrite=1")),
+                remote_url: Some(String::from("https://artworks.example.org/poster.jpg")),
+            },
+        ]
+    }
+
+    #[test]
+    fn get_message_image_returns_remote_poster_given_no_arr_connection() {
+        // Arrange
+        let body = get_body(Some(get_images()));
+        let webhook = get_webhook(None, None);
+
+        // Act
+        let actual = get_message_image(&body, &webhook).unwrap();
+
+        // Assert
+        assert_eq!(
+            "https://artworks.example.org/poster.jpg",
+            actual.source_url.as_str()
+        );
+        assert!(actual.api_key.is_none());
+        assert_eq!("Test Title", actual.description);
+    }
+
+    #[test]
+    fn get_message_image_returns_local_thumbnail_given_arr_connection() {
+        // Arrange
+        let body = get_body(Some(get_images()));
+        let webhook = get_webhook(Some("http://localhost:8989"), Some("abc123"));
+
+        // Act
+        let actual = get_message_image(&body, &webhook).unwrap();
+
+        // Assert
+        assert_eq!(
+            "http://localhost:8989/MediaCover/1/poster-500.jpg?lastWrite=1",
+            actual.source_url.as_str()
+        );
+        assert_eq!(Some(String::from("abc123")), actual.api_key);
+    }
+
+    #[test]
+    fn get_message_image_returns_none_given_no_images() {
+        // Arrange
+        let body = get_body(None);
+        let webhook = get_webhook(None, None);
+
+        // Act
+        let actual = get_message_image(&body, &webhook);
+
+        // Assert
+        assert!(actual.is_none());
+    }
+}
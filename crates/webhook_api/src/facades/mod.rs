@@ -1,22 +1,26 @@
 //! Services for reading webhook data from Sonarr/Radarr and sending it out
 //! via Matrix.
 
+mod image_facade;
+mod path_redaction;
 mod radarr_facade;
 mod sonarr_facade;
 
 use crate::models::common::ArrHealthCheckResult;
-use actix_web::web::block;
+use chrono::{DateTime, Utc};
 use futures::stream::FuturesUnordered;
 use futures::StreamExt;
+pub use image_facade::get_message_image;
+pub use path_redaction::redact_paths;
 pub use radarr_facade::handle_radarr_webhook;
 pub use sonarr_facade::handle_sonarr_webhook;
 use std::option::Option::Some;
 use std::sync::Arc;
 use tracing::{error, info, info_span, warn};
 use uuid::Uuid;
-use yarrbot_db::actions::matrix_room_actions::MatrixRoomActions;
-use yarrbot_db::models::MatrixRoom;
-use yarrbot_db::DbPool;
+use yarrbot_common::metrics::{send_outcome, MATRIX_MESSAGES_SENT};
+use yarrbot_db::models::{MatrixRoom, NewHeldMessage};
+use yarrbot_db::repositories::Repositories;
 use yarrbot_matrix_client::message::{
     Message, MessageData, MessageDataBuilder, SectionHeadingLevel,
 };
@@ -27,57 +31,109 @@ pub use sonarr_facade::SONARR_NAME;
 use tracing_futures::Instrument;
 
 pub async fn send_matrix_messages<T: MatrixClient>(
-    pool: &DbPool,
+    repositories: &Repositories,
     webhook_id: &Uuid,
     client: &T,
     message_data: MessageData,
 ) {
-    let conn = match pool.get() {
-        Ok(c) => c,
+    let now = Utc::now();
+    let rooms: Vec<MatrixRoom> = match repositories.rooms.get_by_webhook_id(webhook_id).await {
+        Ok(r) => r.into_iter().filter(|r| !r.is_muted(now)).collect(),
         Err(e) => {
-            error!(error = ?e, "Failed to retrieve database connection from the pool.");
-            return;
-        }
-    };
-    let id = *webhook_id;
-    let rooms = match block(move || MatrixRoom::get_by_webhook_id(&conn, &id)).await {
-        Ok(Ok(r)) => r,
-        Err(e) => {
-            error!(
-                error = ?e,
-                "Failed to retrieve rooms due to a blocking error."
-            );
-            Vec::new()
-        }
-        Ok(Err(e)) => {
-            error!(
-                error = ?e,
-                "Failed to retrieve rooms due to a database error."
-            );
+            error!(error = ?e, "Failed to retrieve the webhook's rooms.");
             Vec::new()
         }
     };
-    info!("Sending a webhook message to {} room(s).", rooms.len());
+    let (held_rooms, mut rooms): (Vec<MatrixRoom>, Vec<MatrixRoom>) = rooms
+        .into_iter()
+        .partition(|r| should_hold(r, &message_data, now));
+    let spoiler_safe = message_data.without_spoilers().map(Arc::new);
     let arc = Arc::new(message_data);
+    let mut held = 0;
+    for room in held_rooms {
+        let data = match &spoiler_safe {
+            Some(s) if room.hide_spoilers => s,
+            _ => &arc,
+        };
+        match repositories
+            .held_messages
+            .create_held_message(NewHeldMessage::new(&room, &summarize(data)))
+            .await
+        {
+            Ok(_) => held += 1,
+            Err(e) => {
+                // Posting during quiet hours beats dropping the message entirely.
+                error!(error = ?e, "Failed to hold a message until the room's quiet hours end; posting it now.");
+                rooms.push(room);
+            }
+        }
+    }
+    info!(
+        held,
+        "Sending a webhook message to {} room(s).",
+        rooms.len()
+    );
+    let mut failures = 0;
     let tasks = rooms
         .iter()
-        .map(|r| Message::new(r.room_id.as_str(), arc.clone()))
-        .map(|m| client.send_message(m));
+        .map(|r| {
+            let data = match &spoiler_safe {
+                Some(s) if r.hide_spoilers => s.clone(),
+                _ => arc.clone(),
+            };
+            Message::new(r.room_id.as_str(), data).for_webhook(webhook_id)
+        })
+        .map(|m| {
+            let room_id = m.destination.clone();
+            async move { (room_id, client.send_message(m).await) }
+        });
     let mut stream = tasks.collect::<FuturesUnordered<_>>();
-    while let Some(item) = stream
+    while let Some((room_id, item)) = stream
         .next()
         .instrument(info_span!("Sending Matrix Message"))
         .await
     {
-        if item.is_err() {
+        if let Err(e) = item {
+            failures += 1;
+            // Messages that were queued are counted, and charged to the webhook if they fail, once the
+            // SendMessageActor finishes with them.
+            MATRIX_MESSAGES_SENT
+                .with_label_values(&[&room_id, send_outcome(false)])
+                .inc();
             error!(
-                error = ?item.unwrap_err(),
+                error = ?e,
                 "Encountered error while posting to matrix room."
             );
         }
     }
 
     info!("Finished sending webhook messages.");
+    // Messages that were queued and fail later are recorded by the SendMessageActor.
+    if let Err(e) = repositories
+        .webhooks
+        .record_event(webhook_id, failures)
+        .await
+    {
+        warn!(error = ?e, "Failed to record the webhook's event.");
+    }
+}
+
+/// Whether a message should be held for a room until the room's quiet hours end rather than posted now.
+fn should_hold(room: &MatrixRoom, message_data: &MessageData, now: DateTime<Utc>) -> bool {
+    let in_quiet_hours = room.quiet_hours().map_or(false, |q| q.contains(now));
+    in_quiet_hours && !(message_data.critical && room.quiet_hours_bypass_health)
+}
+
+/// Describe a message in a single line for the summary posted once a room's quiet hours end: its heading, without
+/// Markdown formatting.
+fn summarize(message_data: &MessageData) -> String {
+    message_data
+        .plain
+        .lines()
+        .map(|l| l.trim_matches(|c: char| c == '#' || c == '*' || c.is_whitespace()))
+        .find(|l| !l.is_empty())
+        .map(String::from)
+        .unwrap_or_else(|| String::from("Notification"))
 }
 
 fn add_heading(
@@ -119,6 +175,7 @@ fn on_health_check(
 
     let mut builder = MessageDataBuilder::new();
     add_heading(&mut builder, arr_type, "Health Check", server_name);
+    let critical = matches!(level, Some(ArrHealthCheckResult::Error));
     if level.is_some() {
         let l = match level.as_ref().unwrap() {
             ArrHealthCheckResult::Ok => "Ok",
@@ -154,5 +211,7 @@ fn on_health_check(
             .unwrap_or(&String::from("No Message Given")),
     );
 
-    builder.to_message_data()
+    let mut message_data = builder.to_message_data();
+    message_data.critical = critical;
+    message_data
 }
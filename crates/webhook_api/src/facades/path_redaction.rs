@@ -0,0 +1,208 @@
+//! Applies a webhook's [PathPolicy] to the file system paths in a webhook body before it is rendered, so that
+//! rooms don't learn more about the server's disk layout than the webhook's owner wants them to.
+
+use crate::models::radarr::RadarrWebhook;
+use crate::models::sonarr::SonarrWebhook;
+use crate::models::ArrWebhook;
+use tracing::{debug, warn};
+use yarrbot_db::enums::PathPolicy;
+use yarrbot_db::models::Webhook;
+
+/// Shown in place of paths under [PathPolicy::Hide].
+pub const HIDDEN_PATH: &str = "(hidden)";
+
+/// Rewrites individual paths according to a [PathPolicy].
+pub struct PathRedactor {
+    policy: PathPolicy,
+    /// Prefix replacements, longest prefix first.
+    replacements: Vec<(String, String)>,
+}
+
+impl PathRedactor {
+    pub fn new(policy: PathPolicy, replacements: &[String]) -> Self {
+        let mut parsed: Vec<(String, String)> = replacements
+            .iter()
+            .filter_map(|r| match r.split_once('=') {
+                Some((from, to)) if !from.is_empty() => {
+                    Some((String::from(from), String::from(to)))
+                }
+                _ => {
+                    warn!(replacement = %r, "Ignoring malformed path replacement.");
+                    None
+                }
+            })
+            .collect();
+        parsed.sort_by_key(|r| std::cmp::Reverse(r.0.len()));
+
+        PathRedactor {
+            policy,
+            replacements: parsed,
+        }
+    }
+
+    /// Rewrite a single path. Under [PathPolicy::Replace], paths that don't start with any configured prefix are
+    /// left as-is.
+    pub fn redact(&self, path: &str) -> String {
+        match self.policy {
+            PathPolicy::Full => String::from(path),
+            PathPolicy::Hide => String::from(HIDDEN_PATH),
+            PathPolicy::Basename => {
+                let trimmed = path.trim_end_matches(is_separator);
+                String::from(trimmed.rsplit(is_separator).next().unwrap_or(trimmed))
+            }
+            PathPolicy::Replace => self
+                .replacements
+                .iter()
+                .find_map(|(from, to)| {
+                    path.strip_prefix(from.as_str())
+                        .map(|rest| format!("{}{}", to, rest))
+                })
+                .unwrap_or_else(|| String::from(path)),
+        }
+    }
+
+    fn redact_in_place(&self, path: &mut String) {
+        *path = self.redact(path);
+    }
+
+    fn redact_optional(&self, path: &mut Option<String>) {
+        if let Some(p) = path {
+            self.redact_in_place(p);
+        }
+    }
+}
+
+impl From<&Webhook> for PathRedactor {
+    fn from(webhook: &Webhook) -> Self {
+        PathRedactor::new(webhook.path_policy, &webhook.path_replacements)
+    }
+}
+
+fn is_separator(c: char) -> bool {
+    c == '/' || c == '\\'
+}
+
+/// Apply the [Webhook]'s [PathPolicy] to every path in the webhook body.
+pub fn redact_paths(body: &mut ArrWebhook, webhook: &Webhook) {
+    if matches!(webhook.path_policy, PathPolicy::Full) {
+        return;
+    }
+
+    debug!(policy = ?webhook.path_policy, "Redacting paths in the webhook body.");
+    let redactor = PathRedactor::from(webhook);
+    match body {
+        ArrWebhook::Sonarr(w) => redact_sonarr(w, &redactor),
+        ArrWebhook::Radarr(w) => redact_radarr(w, &redactor),
+    }
+}
+
+fn redact_sonarr(body: &mut SonarrWebhook, redactor: &PathRedactor) {
+    match body {
+        SonarrWebhook::Grab { series, .. }
+        | SonarrWebhook::SeriesDelete { series, .. }
+        | SonarrWebhook::Test { series, .. } => redactor.redact_in_place(&mut series.path),
+        SonarrWebhook::Download {
+            series,
+            episode_file,
+            ..
+        } => {
+            redactor.redact_in_place(&mut series.path);
+            redactor.redact_in_place(&mut episode_file.relative_path);
+            redactor.redact_in_place(&mut episode_file.path);
+        }
+        SonarrWebhook::Rename {
+            series,
+            renamed_episode_files,
+        } => {
+            redactor.redact_in_place(&mut series.path);
+            for file in renamed_episode_files {
+                redactor.redact_optional(&mut file.relative_path);
+                redactor.redact_optional(&mut file.path);
+                redactor.redact_optional(&mut file.previous_relative_path);
+                redactor.redact_optional(&mut file.previous_path);
+            }
+        }
+        SonarrWebhook::EpisodeFileDelete {
+            series,
+            episode_file,
+            ..
+        } => {
+            redactor.redact_in_place(&mut series.path);
+            redactor.redact_in_place(&mut episode_file.relative_path);
+            redactor.redact_in_place(&mut episode_file.path);
+        }
+        SonarrWebhook::Health { .. } => {}
+    }
+}
+
+fn redact_radarr(body: &mut RadarrWebhook, redactor: &PathRedactor) {
+    let (movie, movie_file) = match body {
+        RadarrWebhook::Test { movie, .. }
+        | RadarrWebhook::Grab { movie, .. }
+        | RadarrWebhook::Rename { movie }
+        | RadarrWebhook::MovieDelete { movie, .. } => (movie, None),
+        RadarrWebhook::Download {
+            movie, movie_file, ..
+        }
+        | RadarrWebhook::MovieFileDelete {
+            movie, movie_file, ..
+        } => (movie, Some(movie_file)),
+        RadarrWebhook::Health { .. } => return,
+    };
+    redactor.redact_optional(&mut movie.file_path);
+    redactor.redact_optional(&mut movie.folder_path);
+    if let Some(f) = movie_file {
+        redactor.redact_in_place(&mut f.relative_path);
+        redactor.redact_in_place(&mut f.path);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redact_returns_last_component_given_basename_policy() {
+        // Arrange
+        let redactor = PathRedactor::new(PathPolicy::Basename, &[]);
+
+        // Act
+        let unix = redactor.redact("/mnt/media/tv/Show/Season 1/");
+        let windows = redactor.redact("C:\\Media\\TV\\Show\\episode.mkv");
+
+        // Assert
+        assert_eq!("Season 1", unix);
+        assert_eq!("episode.mkv", windows);
+    }
+
+    #[test]
+    fn redact_returns_placeholder_given_hide_policy() {
+        // Arrange
+        let redactor = PathRedactor::new(PathPolicy::Hide, &[]);
+
+        // Act
+        let actual = redactor.redact("/mnt/media/tv/Show");
+
+        // Assert
+        assert_eq!(HIDDEN_PATH, actual);
+    }
+
+    #[test]
+    fn redact_replaces_longest_matching_prefix_given_replace_policy() {
+        // Arrange
+        let replacements = vec![
+            String::from("/mnt=/m"),
+            String::from("/mnt/media/tv=TV:"),
+            String::from("malformed"),
+        ];
+        let redactor = PathRedactor::new(PathPolicy::Replace, &replacements);
+
+        // Act
+        let replaced = redactor.redact("/mnt/media/tv/Show/episode.mkv");
+        let unmatched = redactor.redact("/srv/Show/episode.mkv");
+
+        // Assert
+        assert_eq!("TV:/Show/episode.mkv", replaced);
+        assert_eq!("/srv/Show/episode.mkv", unmatched);
+    }
+}
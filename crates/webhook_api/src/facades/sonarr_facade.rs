@@ -75,7 +75,7 @@ fn add_episodes(builder: &mut MessageDataBuilder, episodes: &[SonarrEpisode]) {
     for episode in episodes {
         builder.add_key_value("Season", &format!("{:0>2}", &episode.season_number));
         builder.add_key_value("Episode", &format!("{:0>2}", &episode.episode_number));
-        builder.add_key_value("Title", &episode.title);
+        builder.add_key_value_spoiler("Title", &episode.title);
         if episode.air_date_utc.is_some() {
             let air = episode.air_date_utc.unwrap();
             builder.add_key_value("Air Date (UTC)", &air.format("%Y-%m-%d").to_string());
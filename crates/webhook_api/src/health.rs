@@ -0,0 +1,117 @@
+//! Liveness and readiness endpoints for container orchestrators and uptime monitors.
+
+use actix_web::rt::time::timeout;
+use actix_web::{web, HttpResponse};
+use serde::Serialize;
+use std::time::Duration;
+use tracing::{error, warn};
+use yarrbot_db::AsyncDbPool;
+use yarrbot_matrix_client::MatrixHealth;
+
+/// How long to wait for a database connection before reporting the database as unavailable.
+const DB_TIMEOUT: Duration = Duration::from_secs(2);
+/// Syncs long-poll the homeserver for up to 30 seconds, so a healthy client syncs well within this.
+const MAX_SYNC_AGE: Duration = Duration::from_secs(300);
+/// More unsent messages than this means sending has stalled or can't keep up.
+const MAX_PENDING_MESSAGES: usize = 100;
+
+/// Configure the `/healthz` and `/readyz` endpoints.
+pub fn health_config(cfg: &mut web::ServiceConfig) {
+    cfg.route("/healthz", web::get().to(healthz))
+        .route("/readyz", web::get().to(readyz));
+}
+
+#[derive(Serialize)]
+struct Readiness {
+    ready: bool,
+    checks: ReadinessChecks,
+}
+
+#[derive(Serialize)]
+struct ReadinessChecks {
+    database: DatabaseCheck,
+    matrix: MatrixCheck,
+    send_queue: SendQueueCheck,
+}
+
+#[derive(Serialize)]
+struct DatabaseCheck {
+    ready: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    error: Option<String>,
+}
+
+#[derive(Serialize)]
+struct MatrixCheck {
+    ready: bool,
+    logged_in: bool,
+    seconds_since_last_sync: Option<u64>,
+}
+
+#[derive(Serialize)]
+struct SendQueueCheck {
+    ready: bool,
+    pending_messages: usize,
+}
+
+/// The process is up and able to answer requests.
+async fn healthz() -> HttpResponse {
+    HttpResponse::Ok().json(serde_json::json!({ "status": "ok" }))
+}
+
+/// Yarrbot can accept webhooks and deliver their messages.
+async fn readyz(pool: web::Data<AsyncDbPool>, health: web::Data<MatrixHealth>) -> HttpResponse {
+    let database = check_database(pool.get_ref()).await;
+
+    let status = health.status();
+    let synced_recently = matches!(status.since_last_sync, Some(s) if s <= MAX_SYNC_AGE);
+    let matrix = MatrixCheck {
+        ready: status.logged_in && synced_recently,
+        logged_in: status.logged_in,
+        seconds_since_last_sync: status.since_last_sync.map(|s| s.as_secs()),
+    };
+    let send_queue = SendQueueCheck {
+        ready: status.pending_messages <= MAX_PENDING_MESSAGES,
+        pending_messages: status.pending_messages,
+    };
+
+    let readiness = Readiness {
+        ready: database.ready && matrix.ready && send_queue.ready,
+        checks: ReadinessChecks {
+            database,
+            matrix,
+            send_queue,
+        },
+    };
+    if readiness.ready {
+        HttpResponse::Ok().json(readiness)
+    } else {
+        warn!(
+            database = readiness.checks.database.ready,
+            matrix = readiness.checks.matrix.ready,
+            send_queue = readiness.checks.send_queue.ready,
+            "Yarrbot is not ready."
+        );
+        HttpResponse::ServiceUnavailable().json(readiness)
+    }
+}
+
+/// Check that the database answers. The endpoint is unauthenticated, so the error reported is generic; the actual
+/// error is logged.
+async fn check_database(pool: &AsyncDbPool) -> DatabaseCheck {
+    let ready = match timeout(DB_TIMEOUT, pool.ping()).await {
+        Ok(Ok(())) => true,
+        Ok(Err(e)) => {
+            error!(error = ?e, "The database readiness check failed.");
+            false
+        }
+        Err(_) => {
+            error!("Timed out waiting for a database connection during the readiness check.");
+            false
+        }
+    };
+    DatabaseCheck {
+        ready,
+        error: (!ready).then(|| String::from("database unavailable")),
+    }
+}
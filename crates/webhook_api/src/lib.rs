@@ -1,29 +1,54 @@
 //! Configuration and handling of webhook pushes from Sonarr/Radarr.
 
 use crate::facades::{
-    handle_radarr_webhook, handle_sonarr_webhook, send_matrix_messages, RADARR_NAME, SONARR_NAME,
+    get_message_image, handle_radarr_webhook, handle_sonarr_webhook, redact_paths,
+    send_matrix_messages, RADARR_NAME, SONARR_NAME,
 };
 use crate::models::ArrWebhook;
 use actix_web::{web, HttpResponse};
 use anyhow::{bail, Context, Result};
+use chrono::Utc;
+pub use client_ip::{ForwardedHeader, TrustedProxies};
+pub use credential_cache::{CredentialCache, CredentialCacheStats};
 use extractors::webhook_extractor::WebhookInfo;
 use futures_util::StreamExt;
+pub use health::health_config;
+pub use lockout::{AuthFailureTracker, LockoutSettings};
+pub use metrics::metrics_config;
+pub use paused_events::PausedEvents;
+pub use quiet_hours::release_held_messages;
+pub use replay_guard::SignatureReplayGuard;
+pub use request_id::{RequestIdHeader, REQUEST_ID_HEADER};
 use std::str;
-use tracing::{error, error_span, info_span};
+use tracing::{error, error_span, info, info_span, warn};
 use tracing_actix_web::RootSpan;
 use tracing_futures::Instrument;
-use yarrbot_db::DbPool;
+use yarrbot_common::metrics::{
+    UNKNOWN, WEBHOOKS_RECEIVED, WEBHOOK_DESERIALIZATION_FAILURES, WEBHOOK_DURATION,
+};
+use yarrbot_db::models::Webhook;
+use yarrbot_db::repositories::Repositories;
 use yarrbot_matrix_client::message::MessageData;
 use yarrbot_matrix_client::MatrixClient;
 pub use yarrbot_root_span::YarrbotRootSpan;
 
+mod client_ip;
+mod credential_cache;
 mod extractors;
 mod facades;
+mod health;
+mod lockout;
+mod metrics;
 mod models;
+mod paused_events;
+mod quiet_hours;
+mod replay_guard;
+mod request_id;
 mod yarrbot_api_error;
 mod yarrbot_root_span;
 
 const MAX_SIZE: usize = 262_144; // Limit max payload size to 256k.
+const MAX_LOGGED_BODY_CHARS: usize = 512;
 
 /// Configure the webhook API endpoints.
 pub fn webhook_config<T: MatrixClient + Send + Sync + 'static + Clone>(
@@ -41,18 +66,36 @@ pub fn webhook_config<T: MatrixClient + Send + Sync + 'static + Clone>(
 fn deserialize_body(body: web::BytesMut) -> Result<ArrWebhook> {
     serde_json::from_slice::<ArrWebhook>(&body).with_context(|| {
         const ERR_MESSAGE: &str = "Encountered an error while parsing webhook request body.";
+        WEBHOOK_DESERIALIZATION_FAILURES.inc();
         let str_body = str::from_utf8(&body).unwrap_or("Could not convert body to string.");
-        error!(request_body = str_body, "{}", ERR_MESSAGE);
+        error!(
+            request_body = truncate_body(str_body),
+            request_body_length = body.len(),
+            "{}",
+            ERR_MESSAGE
+        );
         ERR_MESSAGE
     })
 }
 
+/// Shorten a request body before logging it; bodies contain file paths and titles that don't belong in
+/// long-lived log storage, and only the start of the body is needed to see why it couldn't be parsed.
+fn truncate_body(body: &str) -> &str {
+    match body.char_indices().nth(MAX_LOGGED_BODY_CHARS) {
+        Some((i, _)) => &body[..i],
+        None => body,
+    }
+}
+
 async fn handle_webhook(
-    body: ArrWebhook,
+    mut body: ArrWebhook,
     root_span: &RootSpan,
-    server_name: &Option<String>,
+    webhook: &Webhook,
 ) -> Result<MessageData> {
-    match body {
+    redact_paths(&mut body, webhook);
+    let image = get_message_image(&body, webhook);
+    let server_name = &webhook.server_name;
+    let mut message = match body {
         ArrWebhook::Sonarr(w) => {
             root_span.record("webhook_arr_type", &SONARR_NAME);
             handle_sonarr_webhook(w, server_name).await
@@ -61,18 +104,24 @@ async fn handle_webhook(
             root_span.record("webhook_arr_type", &RADARR_NAME);
             handle_radarr_webhook(w, server_name).await
         }
-    }
+    }?;
+    message.image = image;
+
+    Ok(message)
 }
 
 async fn index<T: MatrixClient>(
     root_span: RootSpan,
     webhook_info: WebhookInfo,
-    pool: web::Data<DbPool>,
+    repositories: web::Data<Repositories>,
     matrix_client: web::Data<T>,
+    paused_events: Option<web::Data<PausedEvents>>,
     mut payload: web::Payload,
 ) -> HttpResponse {
     root_span.record("webhook_short_id", &webhook_info.short_id.as_str());
+    let _timer = WEBHOOK_DURATION.start_timer();
 
+    let webhook_info_ref = &webhook_info;
     let deserialization_result = async move {
         // Essentially copied from: https://actix.rs/docs/request/
         let mut body = web::BytesMut::new();
@@ -84,33 +133,98 @@ async fn index<T: MatrixClient>(
             body.extend_from_slice(&chunk);
         }
 
-        deserialize_body(body)
+        if !webhook_info_ref.verify_body(&body) {
+            return Ok(None);
+        }
+        deserialize_body(body).map(Some)
     }
     .instrument(error_span!("Deserializing Request Body"))
     .await;
 
-    if let Ok(body) = deserialization_result {
+    if let Ok(None) = deserialization_result {
+        warn!("Request body did not match the request's signature, or the signature was already used.");
+        record_webhook(UNKNOWN, UNKNOWN, "unauthorized");
+        return HttpResponse::Unauthorized().finish();
+    }
+
+    if let Ok(Some(body)) = deserialization_result {
+        let (app, event_type) = match &body {
+            ArrWebhook::Sonarr(w) => (SONARR_NAME, w.event_type()),
+            ArrWebhook::Radarr(w) => (RADARR_NAME, w.event_type()),
+        };
         let webhook = &webhook_info.webhook;
-        let message = handle_webhook(body, &root_span, &webhook.server_name)
+        if webhook.is_paused(Utc::now()) {
+            let setting = paused_events.map_or_else(PausedEvents::default, |p| *p.get_ref());
+            skip_paused_event(repositories.get_ref(), webhook, setting).await;
+            record_webhook(app, event_type, "paused");
+            return HttpResponse::Ok().finish();
+        }
+        let message = handle_webhook(body, &root_span, webhook)
             .instrument(info_span!("Converting Webhook to Matrix Message"))
             .await;
         match message {
             Ok(m) => {
-                send_matrix_messages(pool.get_ref(), &webhook.id, matrix_client.get_ref(), m)
-                    .instrument(info_span!("Sending Matrix Messages"))
-                    .await;
+                send_matrix_messages(
+                    repositories.get_ref(),
+                    &webhook.id,
+                    matrix_client.get_ref(),
+                    m,
+                )
+                .instrument(info_span!("Sending Matrix Messages"))
+                .await;
+                record_webhook(app, event_type, "accepted");
             }
             Err(e) => {
                 error!(
                     error = ?e,
                     "Encountered error during webhook to Matrix message conversion."
                 );
+                record_webhook(app, event_type, "error");
                 return HttpResponse::InternalServerError().finish();
             }
         }
     } else {
+        record_webhook(UNKNOWN, UNKNOWN, "invalid");
         return HttpResponse::BadRequest().finish();
     }
 
     HttpResponse::Ok().finish()
 }
+
+/// Handle an event sent to a paused webhook without posting it to the webhook's rooms.
+async fn skip_paused_event(repositories: &Repositories, webhook: &Webhook, setting: PausedEvents) {
+    info!(
+        ?setting,
+        "The webhook is paused; its event won't be posted."
+    );
+    if setting == PausedEvents::Record {
+        if let Err(e) = repositories.webhooks.record_event(&webhook.id, 0).await {
+            warn!(error = ?e, "Failed to record the paused webhook's event.");
+        }
+    }
+}
+
+fn record_webhook(app: &str, event_type: &str, outcome: &str) {
+    WEBHOOKS_RECEIVED
+        .with_label_values(&[app, event_type, outcome])
+        .inc();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn truncate_body_keeps_short_bodies() {
+        assert_eq!("{}", truncate_body("{}"));
+    }
+
+    #[test]
+    fn truncate_body_shortens_long_bodies_on_a_char_boundary() {
+        let body = "é".repeat(MAX_LOGGED_BODY_CHARS + 10);
+
+        let result = truncate_body(&body);
+
+        assert_eq!(MAX_LOGGED_BODY_CHARS, result.chars().count());
+    }
+}
@@ -0,0 +1,283 @@
+//! Tracks failed webhook authentication attempts and locks out the client IP addresses and webhooks that fail too
+//! often, doubling the length of the lockout with each further failure.
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+use tracing::warn;
+use uuid::Uuid;
+use yarrbot_matrix_client::MatrixClient;
+
+/// Stale records are only pruned once this many are being tracked.
+const MAX_TRACKED_RECORDS: usize = 10_000;
+
+/// Limits on failed authentication attempts.
+#[derive(Debug, Clone)]
+pub struct LockoutSettings {
+    /// The number of failures from a single IP address before it is locked out.
+    pub ip_threshold: u32,
+    /// The number of failures against a single webhook before it is locked out and its owner is notified.
+    pub webhook_threshold: u32,
+    /// The length of the first lockout; each failure after that doubles it.
+    pub base_lockout: Duration,
+    pub max_lockout: Duration,
+    /// Failures older than this are forgotten.
+    pub forget_after: Duration,
+}
+
+impl Default for LockoutSettings {
+    fn default() -> Self {
+        LockoutSettings {
+            ip_threshold: 5,
+            webhook_threshold: 20,
+            base_lockout: Duration::from_secs(30),
+            max_lockout: Duration::from_secs(3600),
+            forget_after: Duration::from_secs(3600),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum FailureKey {
+    Ip(IpAddr),
+    Webhook(Uuid),
+}
+
+#[derive(Debug)]
+struct FailureRecord {
+    failures: u32,
+    locked_until: Option<Instant>,
+    last_failure: Instant,
+}
+
+/// Shared across workers as app data; if it isn't registered, failed attempts are not limited.
+pub struct AuthFailureTracker {
+    settings: LockoutSettings,
+    records: Mutex<HashMap<FailureKey, FailureRecord>>,
+    alert_client: Option<Arc<dyn MatrixClient + Send + Sync>>,
+}
+
+impl AuthFailureTracker {
+    pub fn new(settings: LockoutSettings) -> Self {
+        AuthFailureTracker {
+            settings,
+            records: Mutex::new(HashMap::new()),
+            alert_client: None,
+        }
+    }
+
+    /// Direct message webhook owners through the given client when their webhook is locked out.
+    pub fn with_alerts(mut self, client: Arc<dyn MatrixClient + Send + Sync>) -> Self {
+        self.alert_client = Some(client);
+        self
+    }
+
+    pub fn alert_client(&self) -> Option<Arc<dyn MatrixClient + Send + Sync>> {
+        self.alert_client.clone()
+    }
+
+    /// Returns the time remaining if the IP address is locked out.
+    pub fn check_ip(&self, ip: Option<IpAddr>) -> Result<(), Duration> {
+        match ip {
+            Some(ip) => self.check_at(FailureKey::Ip(ip), Instant::now()),
+            None => Ok(()),
+        }
+    }
+
+    /// Returns the time remaining if the webhook is locked out.
+    pub fn check_webhook(&self, webhook_id: &Uuid) -> Result<(), Duration> {
+        self.check_at(FailureKey::Webhook(*webhook_id), Instant::now())
+    }
+
+    /// Record a failed attempt. Returns true if this failure locked out the webhook for the first time since its
+    /// failures were last reset, in which case its owner should be notified.
+    pub fn record_failure(&self, ip: Option<IpAddr>, webhook_id: Option<Uuid>) -> bool {
+        let now = Instant::now();
+        if let Some(ip) = ip {
+            self.record_failure_at(FailureKey::Ip(ip), now);
+        }
+        match webhook_id {
+            Some(id) => {
+                self.record_failure_at(FailureKey::Webhook(id), now)
+                    == self.settings.webhook_threshold
+            }
+            None => false,
+        }
+    }
+
+    /// Forget past failures after a successful attempt.
+    pub fn record_success(&self, ip: Option<IpAddr>, webhook_id: &Uuid) {
+        let mut records = self.records.lock().unwrap();
+        if let Some(ip) = ip {
+            records.remove(&FailureKey::Ip(ip));
+        }
+        records.remove(&FailureKey::Webhook(*webhook_id));
+    }
+
+    fn threshold(&self, key: &FailureKey) -> u32 {
+        match key {
+            FailureKey::Ip(_) => self.settings.ip_threshold,
+            FailureKey::Webhook(_) => self.settings.webhook_threshold,
+        }
+    }
+
+    fn check_at(&self, key: FailureKey, now: Instant) -> Result<(), Duration> {
+        let records = self.records.lock().unwrap();
+        match records.get(&key).and_then(|r| r.locked_until) {
+            Some(until) if until > now => Err(until - now),
+            _ => Ok(()),
+        }
+    }
+
+    /// Returns the number of failures now recorded against the key.
+    fn record_failure_at(&self, key: FailureKey, now: Instant) -> u32 {
+        let threshold = self.threshold(&key);
+        let mut records = self.records.lock().unwrap();
+        if records.len() >= MAX_TRACKED_RECORDS {
+            let forget_after = self.settings.forget_after;
+            records.retain(|_, r| {
+                matches!(r.locked_until, Some(u) if u > now)
+                    || now.duration_since(r.last_failure) < forget_after
+            });
+        }
+
+        let record = records.entry(key).or_insert(FailureRecord {
+            failures: 0,
+            locked_until: None,
+            last_failure: now,
+        });
+        let is_locked = matches!(record.locked_until, Some(u) if u > now);
+        if !is_locked && now.duration_since(record.last_failure) >= self.settings.forget_after {
+            record.failures = 0;
+        }
+        record.failures = record.failures.saturating_add(1);
+        record.last_failure = now;
+
+        if record.failures >= threshold {
+            let doublings = (record.failures - threshold).min(31);
+            let lockout = self
+                .settings
+                .base_lockout
+                .checked_mul(1 << doublings)
+                .unwrap_or(self.settings.max_lockout)
+                .min(self.settings.max_lockout);
+            record.locked_until = Some(now + lockout);
+            warn!(
+                key = ?key,
+                failures = record.failures,
+                lockout_secs = lockout.as_secs(),
+                "Locking out after repeated authentication failures."
+            );
+        }
+
+        record.failures
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::str::FromStr;
+
+    fn tracker() -> AuthFailureTracker {
+        AuthFailureTracker::new(LockoutSettings {
+            ip_threshold: 3,
+            webhook_threshold: 5,
+            ..LockoutSettings::default()
+        })
+    }
+
+    fn ip_key() -> FailureKey {
+        FailureKey::Ip(IpAddr::from_str("203.0.113.7").unwrap())
+    }
+
+    #[test]
+    fn check_at_returns_ok_given_failures_below_threshold() {
+        // Arrange
+        let tracker = tracker();
+        let now = Instant::now();
+        tracker.record_failure_at(ip_key(), now);
+        tracker.record_failure_at(ip_key(), now);
+
+        // Act
+        let actual = tracker.check_at(ip_key(), now);
+
+        // Assert
+        assert!(actual.is_ok());
+    }
+
+    #[test]
+    fn check_at_doubles_lockout_given_failures_past_threshold() {
+        // Arrange
+        let tracker = tracker();
+        let now = Instant::now();
+        for _ in 0..3 {
+            tracker.record_failure_at(ip_key(), now);
+        }
+        let first = tracker.check_at(ip_key(), now);
+        tracker.record_failure_at(ip_key(), now);
+
+        // Act
+        let second = tracker.check_at(ip_key(), now);
+
+        // Assert
+        assert_eq!(Err(Duration::from_secs(30)), first);
+        assert_eq!(Err(Duration::from_secs(60)), second);
+        assert!(tracker
+            .check_at(ip_key(), now + Duration::from_secs(61))
+            .is_ok());
+    }
+
+    #[test]
+    fn record_failure_at_caps_lockout_at_max() {
+        // Arrange
+        let tracker = tracker();
+        let now = Instant::now();
+
+        // Act
+        for _ in 0..64 {
+            tracker.record_failure_at(ip_key(), now);
+        }
+
+        // Assert
+        assert_eq!(
+            Err(Duration::from_secs(3600)),
+            tracker.check_at(ip_key(), now)
+        );
+    }
+
+    #[test]
+    fn record_failure_returns_true_only_when_webhook_reaches_threshold() {
+        // Arrange
+        let tracker = tracker();
+        let webhook_id = Uuid::new_v4();
+
+        // Act
+        let alerts: Vec<bool> = (0..7)
+            .map(|_| tracker.record_failure(None, Some(webhook_id)))
+            .collect();
+
+        // Assert
+        assert_eq!(vec![false, false, false, false, true, false, false], alerts);
+        assert!(tracker.check_webhook(&webhook_id).is_err());
+    }
+
+    #[test]
+    fn record_success_clears_lockout() {
+        // Arrange
+        let tracker = tracker();
+        let ip = Some(IpAddr::from_str("203.0.113.7").unwrap());
+        let webhook_id = Uuid::new_v4();
+        for _ in 0..5 {
+            tracker.record_failure(ip, Some(webhook_id));
+        }
+
+        // Act
+        tracker.record_success(ip, &webhook_id);
+
+        // Assert
+        assert!(tracker.check_ip(ip).is_ok());
+        assert!(tracker.check_webhook(&webhook_id).is_ok());
+    }
+}
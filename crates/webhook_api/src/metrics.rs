@@ -0,0 +1,28 @@
+//! Prometheus metrics endpoint.
+
+use actix_web::{web, HttpResponse};
+use tracing::error;
+use yarrbot_common::metrics::{
+    render, CONTENT_TYPE, DB_POOL_CONNECTIONS, DB_POOL_IDLE_CONNECTIONS, DB_POOL_MAX_CONNECTIONS,
+};
+use yarrbot_db::AsyncDbPool;
+
+/// Configure the `/metrics` endpoint.
+pub fn metrics_config(cfg: &mut web::ServiceConfig) {
+    cfg.route("/metrics", web::get().to(metrics));
+}
+
+async fn metrics(pool: web::Data<AsyncDbPool>) -> HttpResponse {
+    // The pool's utilisation is only meaningful at the moment it's scraped.
+    DB_POOL_CONNECTIONS.set(pool.size().into());
+    DB_POOL_IDLE_CONNECTIONS.set(pool.num_idle() as i64);
+    DB_POOL_MAX_CONNECTIONS.set(pool.max_size().into());
+
+    match render() {
+        Ok(body) => HttpResponse::Ok().content_type(CONTENT_TYPE).body(body),
+        Err(e) => {
+            error!(error = ?e, "Failed to render metrics.");
+            HttpResponse::InternalServerError().finish()
+        }
+    }
+}
@@ -11,3 +11,28 @@ pub enum ArrHealthCheckResult {
     #[serde(other)]
     Unknown,
 }
+
+/// The kinds of media covers an *arr may send.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub enum ArrImageCoverType {
+    Poster,
+    Banner,
+    Fanart,
+    Screenshot,
+    Headshot,
+    Clearlogo,
+    #[serde(other)]
+    Unknown,
+}
+
+/// Some media cover for a series or movie.
+#[derive(Serialize, Deserialize, Debug, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ArrImage {
+    pub cover_type: ArrImageCoverType,
+    /// A path to the image on the *arr itself, e.g. `/MediaCover/1/poster.jpg`.
+    pub url: Option<String>,
+    /// A URL to the image at the *arr's metadata source.
+    pub remote_url: Option<String>,
+}
@@ -49,6 +49,7 @@ mod tests {
                 tv_maze_id: None,
                 imdb_id: None,
                 series_type: SonarrSeriesType::Standard,
+                images: None,
             },
             episodes: vec![SonarrEpisode {
                 id: 123,
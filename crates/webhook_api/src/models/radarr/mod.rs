@@ -64,3 +64,31 @@ pub enum RadarrWebhook {
         wiki_url: Option<String>,
     },
 }
+
+impl RadarrWebhook {
+    /// The movie that the webhook is about, if any.
+    pub fn movie(&self) -> Option<&RadarrMovie> {
+        match self {
+            RadarrWebhook::Test { movie, .. }
+            | RadarrWebhook::Grab { movie, .. }
+            | RadarrWebhook::Download { movie, .. }
+            | RadarrWebhook::Rename { movie }
+            | RadarrWebhook::MovieDelete { movie, .. }
+            | RadarrWebhook::MovieFileDelete { movie, .. } => Some(movie),
+            RadarrWebhook::Health { .. } => None,
+        }
+    }
+
+    /// The webhook's `eventType`.
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            RadarrWebhook::Test { .. } => "Test",
+            RadarrWebhook::Grab { .. } => "Grab",
+            RadarrWebhook::Download { .. } => "Download",
+            RadarrWebhook::Rename { .. } => "Rename",
+            RadarrWebhook::MovieDelete { .. } => "MovieDelete",
+            RadarrWebhook::MovieFileDelete { .. } => "MovieFileDelete",
+            RadarrWebhook::Health { .. } => "Health",
+        }
+    }
+}
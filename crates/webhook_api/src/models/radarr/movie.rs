@@ -1,3 +1,4 @@
+use crate::models::common::ArrImage;
 use chrono::NaiveDate;
 use serde::{Deserialize, Serialize};
 
@@ -13,4 +14,5 @@ pub struct RadarrMovie {
     pub folder_path: Option<String>,
     pub tmdb_id: Option<u32>,
     pub imdb_id: Option<String>,
+    pub images: Option<Vec<ArrImage>>,
 }
@@ -81,6 +81,34 @@ pub enum SonarrWebhook {
     },
 }
 
+impl SonarrWebhook {
+    /// The series that the webhook is about, if any.
+    pub fn series(&self) -> Option<&SonarrSeries> {
+        match self {
+            SonarrWebhook::Grab { series, .. }
+            | SonarrWebhook::Download { series, .. }
+            | SonarrWebhook::Rename { series, .. }
+            | SonarrWebhook::SeriesDelete { series, .. }
+            | SonarrWebhook::EpisodeFileDelete { series, .. }
+            | SonarrWebhook::Test { series, .. } => Some(series),
+            SonarrWebhook::Health { .. } => None,
+        }
+    }
+
+    /// The webhook's `eventType`.
+    pub fn event_type(&self) -> &'static str {
+        match self {
+            SonarrWebhook::Grab { .. } => "Grab",
+            SonarrWebhook::Download { .. } => "Download",
+            SonarrWebhook::Rename { .. } => "Rename",
+            SonarrWebhook::SeriesDelete { .. } => "SeriesDelete",
+            SonarrWebhook::EpisodeFileDelete { .. } => "EpisodeFileDelete",
+            SonarrWebhook::Test { .. } => "Test",
+            SonarrWebhook::Health { .. } => "Health",
+        }
+    }
+}
+
 #[cfg(test)]
 mod test {
     use super::*;
@@ -126,6 +154,7 @@ mod test {
                 tv_maze_id: None,
                 imdb_id: None,
                 series_type: SonarrSeriesType::Standard,
+                images: None,
             },
             episodes: vec![SonarrEpisode {
                 id: 67,
@@ -198,6 +227,7 @@ mod test {
                 tv_maze_id: None,
                 imdb_id: None,
                 series_type: SonarrSeriesType::Standard,
+                images: None,
             },
             episodes: vec![SonarrEpisode {
                 id: 67,
@@ -255,6 +285,7 @@ mod test {
                 tv_maze_id: None,
                 imdb_id: None,
                 series_type: SonarrSeriesType::Standard,
+                images: None,
             },
             renamed_episode_files: vec![],
         };
@@ -297,6 +328,7 @@ mod test {
                 tv_maze_id: None,
                 imdb_id: None,
                 series_type: SonarrSeriesType::Standard,
+                images: None,
             },
             episodes: vec![SonarrEpisode {
                 id: 123,
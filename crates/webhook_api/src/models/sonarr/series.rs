@@ -1,3 +1,4 @@
+use crate::models::common::ArrImage;
 use serde::{Deserialize, Serialize};
 
 /// The type of series, usually indicating the method that new episodes are aired.
@@ -21,4 +22,5 @@ pub struct SonarrSeries {
     pub imdb_id: Option<String>,
     #[serde(rename = "type")]
     pub series_type: SonarrSeriesType,
+    pub images: Option<Vec<ArrImage>>,
 }
@@ -0,0 +1,68 @@
+//! Decides what happens to events sent to a paused webhook.
+
+use anyhow::{bail, Context, Error, Result};
+use std::str::FromStr;
+use yarrbot_common::environment::{get_env_var, variables::PAUSED_EVENTS};
+
+/// What to do with an event sent to a paused webhook. The request succeeds either way, so that the *arr doesn't
+/// consider the webhook broken while it is paused.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum PausedEvents {
+    /// Count the event in the webhook's activity without posting it.
+    Record,
+
+    /// Discard the event as if it was never sent.
+    Drop,
+}
+
+impl PausedEvents {
+    /// Read the setting from the environment, recording events if it isn't set.
+    pub fn from_env() -> Result<Self> {
+        match get_env_var(PAUSED_EVENTS) {
+            Ok(raw) => PausedEvents::from_str(&raw)
+                .with_context(|| format!("{} is not a valid setting.", PAUSED_EVENTS)),
+            Err(_) => Ok(PausedEvents::default()),
+        }
+    }
+}
+
+impl Default for PausedEvents {
+    fn default() -> Self {
+        PausedEvents::Record
+    }
+}
+
+impl FromStr for PausedEvents {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "record" => Ok(PausedEvents::Record),
+            "drop" => Ok(PausedEvents::Drop),
+            _ => bail!("\"{}\" is neither \"record\" nor \"drop\".", s),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn from_str_ignores_case() {
+        // Act
+        let actual = PausedEvents::from_str("Drop");
+
+        // Assert
+        assert_eq!(PausedEvents::Drop, actual.unwrap());
+    }
+
+    #[test]
+    fn from_str_returns_error_given_unknown_setting() {
+        // Act
+        let actual = PausedEvents::from_str("queue");
+
+        // Assert
+        assert!(actual.is_err());
+    }
+}
@@ -0,0 +1,127 @@
+//! Posts the messages held during rooms' quiet hours as a single summary per room once the quiet hours end.
+
+use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::sync::Arc;
+use tracing::{error, info, warn};
+use uuid::Uuid;
+use yarrbot_db::models::{HeldMessage, MatrixRoom};
+use yarrbot_db::quiet_hours::QuietHours;
+use yarrbot_db::repositories::Repositories;
+use yarrbot_matrix_client::message::{Message, MessageData, MessageDataBuilder};
+use yarrbot_matrix_client::MatrixClient;
+
+/// Post a summary of the held messages to each room whose quiet hours have ended, then discard the messages. Rooms
+/// whose quiet hours were removed get their summary straight away. If a summary can't be queued for sending, its
+/// messages are kept for the next attempt; once queued, they're discarded even if the homeserver later rejects it.
+#[tracing::instrument(skip(repositories, client))]
+pub async fn release_held_messages<T: MatrixClient>(repositories: &Repositories, client: &T) {
+    let messages = match repositories.held_messages.get_all().await {
+        Ok(m) => m,
+        Err(e) => {
+            error!(error = ?e, "Failed to retrieve the held messages.");
+            return;
+        }
+    };
+    if messages.is_empty() {
+        return;
+    }
+    let rooms: HashMap<Uuid, MatrixRoom> = match repositories.rooms.get_all().await {
+        Ok(r) => r.into_iter().map(|r| (r.id, r)).collect(),
+        Err(e) => {
+            error!(error = ?e, "Failed to retrieve the rooms of the held messages.");
+            return;
+        }
+    };
+    let mut by_room: HashMap<Uuid, Vec<HeldMessage>> = HashMap::new();
+    for message in messages {
+        by_room
+            .entry(message.matrix_room_id)
+            .or_default()
+            .push(message);
+    }
+
+    let now = Utc::now();
+    for (room_id, messages) in by_room {
+        let room = match rooms.get(&room_id) {
+            Some(r) => r,
+            // The room was removed after the messages were held; they'll be deleted along with it.
+            None => continue,
+        };
+        let quiet_hours = room.quiet_hours();
+        if quiet_hours.map_or(false, |q| q.contains(now)) {
+            continue;
+        }
+
+        let summary = summarize_held_messages(&messages, quiet_hours.as_ref());
+        let message = Message::new(&room.room_id, Arc::new(summary));
+        if let Err(e) = client.send_message(message).await {
+            warn!(error = ?e, room_id = %room.room_id, "Failed to queue the summary of held messages.");
+            continue;
+        }
+        info!(count = messages.len(), room_id = %room.room_id, "Queued the summary of held messages.");
+        for message in &messages {
+            if let Err(e) = repositories.held_messages.delete(message).await {
+                error!(error = ?e, "Failed to delete a held message after queueing it.");
+            }
+        }
+    }
+}
+
+/// Build the summary of the messages held for a room, showing when each arrived in the room's local time.
+fn summarize_held_messages(
+    messages: &[HeldMessage],
+    quiet_hours: Option<&QuietHours>,
+) -> MessageData {
+    let mut builder = MessageDataBuilder::new();
+    builder.add_line(&format!(
+        "{} notification(s) arrived during quiet hours:",
+        messages.len()
+    ));
+    for message in messages {
+        builder.add_key_value(
+            &format_local_time(message.received_at, quiet_hours),
+            &message.summary,
+        );
+    }
+    builder.to_message_data()
+}
+
+fn format_local_time(time: DateTime<Utc>, quiet_hours: Option<&QuietHours>) -> String {
+    match quiet_hours {
+        Some(q) => time
+            .with_timezone(&q.timezone)
+            .format("%Y-%m-%d %H:%M %Z")
+            .to_string(),
+        None => time.format("%Y-%m-%d %H:%M UTC").to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn summarize_held_messages_shows_local_arrival_times() {
+        // Arrange
+        let quiet_hours = QuietHours::parse("22:00-07:00", "Europe/Berlin").unwrap();
+        let messages = vec![HeldMessage {
+            id: Uuid::new_v4(),
+            matrix_room_id: Uuid::new_v4(),
+            received_at: Utc.ymd(2026, 7, 1).and_hms(1, 15, 0),
+            summary: String::from("Sonarr: Download"),
+        }];
+
+        // Act
+        let actual = summarize_held_messages(&messages, Some(&quiet_hours));
+
+        // Assert
+        assert!(actual
+            .plain
+            .starts_with("1 notification(s) arrived during quiet hours:"));
+        assert!(actual
+            .plain
+            .contains("**2026-07-01 03:15 CEST**: Sonarr: Download"));
+    }
+}
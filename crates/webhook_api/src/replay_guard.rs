@@ -0,0 +1,78 @@
+//! Remembers the signatures of recently accepted signed requests so that a captured request can't be sent again while
+//! its timestamp is still fresh.
+
+use crate::extractors::credentials::RequestSignature;
+use chrono::Utc;
+use std::collections::HashMap;
+use std::sync::Mutex;
+use uuid::Uuid;
+
+/// The signatures accepted for each webhook, along with the Unix time at which each stops being fresh. Once a
+/// signature's timestamp is stale the request is rejected anyway, so the signature is forgotten.
+#[derive(Default)]
+pub struct SignatureReplayGuard {
+    seen: Mutex<HashMap<(Uuid, Vec<u8>), i64>>,
+}
+
+impl SignatureReplayGuard {
+    /// Record a verified signature, returning false if it was already accepted for the webhook.
+    pub fn record(&self, webhook_id: &Uuid, signature: &RequestSignature) -> bool {
+        match signature.fresh_until() {
+            Some(fresh_until) => self.record_at(
+                webhook_id,
+                signature.signature(),
+                fresh_until,
+                Utc::now().timestamp(),
+            ),
+            None => false,
+        }
+    }
+
+    fn record_at(&self, webhook_id: &Uuid, signature: &[u8], fresh_until: i64, now: i64) -> bool {
+        let mut seen = self.seen.lock().unwrap();
+        seen.retain(|_, f| *f >= now);
+        let key = (*webhook_id, signature.to_vec());
+        if seen.contains_key(&key) {
+            return false;
+        }
+        seen.insert(key, fresh_until);
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn record_at_returns_false_given_repeated_signature() {
+        // Arrange
+        let guard = SignatureReplayGuard::default();
+        let (webhook_id, other_webhook_id) = (Uuid::new_v4(), Uuid::new_v4());
+
+        // Act
+        let first = guard.record_at(&webhook_id, b"signature", 1300, 1000);
+        let repeated = guard.record_at(&webhook_id, b"signature", 1300, 1001);
+        let other_webhook = guard.record_at(&other_webhook_id, b"signature", 1300, 1001);
+
+        // Assert
+        assert!(first);
+        assert!(!repeated);
+        assert!(other_webhook);
+    }
+
+    #[test]
+    fn record_at_forgets_signatures_once_stale() {
+        // Arrange
+        let guard = SignatureReplayGuard::default();
+        let webhook_id = Uuid::new_v4();
+        guard.record_at(&webhook_id, b"signature", 1300, 1000);
+
+        // Act
+        let actual = guard.record_at(&webhook_id, b"other", 1600, 1301);
+
+        // Assert
+        assert!(actual);
+        assert_eq!(1, guard.seen.lock().unwrap().len());
+    }
+}
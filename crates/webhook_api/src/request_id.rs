@@ -0,0 +1,63 @@
+//! Echo the ID `TracingLogger` assigns to each request back to the client.
+
+use actix_web::dev::{forward_ready, Service, ServiceRequest, ServiceResponse, Transform};
+use actix_web::http::header::{HeaderName, HeaderValue};
+use actix_web::{Error, HttpMessage};
+use futures_util::future::{ready, LocalBoxFuture, Ready};
+use tracing_actix_web::RequestId;
+
+pub const REQUEST_ID_HEADER: &str = "x-request-id";
+
+/// Middleware adding an `X-Request-Id` header to every response, matching the `request_id` recorded on
+/// the request's root span. Register it before `TracingLogger` so that it runs within the logger.
+pub struct RequestIdHeader;
+
+impl<S, B> Transform<S, ServiceRequest> for RequestIdHeader
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Transform = RequestIdHeaderMiddleware<S>;
+    type InitError = ();
+    type Future = Ready<Result<Self::Transform, Self::InitError>>;
+
+    fn new_transform(&self, service: S) -> Self::Future {
+        ready(Ok(RequestIdHeaderMiddleware { service }))
+    }
+}
+
+pub struct RequestIdHeaderMiddleware<S> {
+    service: S,
+}
+
+impl<S, B> Service<ServiceRequest> for RequestIdHeaderMiddleware<S>
+where
+    S: Service<ServiceRequest, Response = ServiceResponse<B>, Error = Error>,
+    S::Future: 'static,
+    B: 'static,
+{
+    type Response = ServiceResponse<B>;
+    type Error = Error;
+    type Future = LocalBoxFuture<'static, Result<Self::Response, Self::Error>>;
+
+    forward_ready!(service);
+
+    fn call(&self, req: ServiceRequest) -> Self::Future {
+        let request_id = req.extensions().get::<RequestId>().copied();
+        let fut = self.service.call(req);
+        Box::pin(async move {
+            let mut response = fut.await?;
+            if let Some(value) =
+                request_id.and_then(|id| HeaderValue::from_str(&id.to_string()).ok())
+            {
+                response
+                    .headers_mut()
+                    .insert(HeaderName::from_static(REQUEST_ID_HEADER), value);
+            }
+            Ok(response)
+        })
+    }
+}
@@ -1,8 +1,10 @@
 //! Helper utilities for returning API errors to clients.
 
+use actix_web::http::header::RETRY_AFTER;
 use actix_web::http::StatusCode;
 use actix_web::{error::ResponseError, HttpResponse};
 use serde::Serialize;
+use std::time::Duration;
 use thiserror::Error;
 use tracing::error;
 
@@ -14,6 +16,8 @@ pub enum YarrbotApiError {
         message: String,
         source: Option<anyhow::Error>,
     },
+    #[error("Too Many Requests")]
+    TooManyRequests { retry_after: Duration },
     #[error("Internal Server Error")]
     InternalError(#[from] anyhow::Error),
 }
@@ -23,6 +27,7 @@ pub enum YarrbotStatusCode {
     BadRequest,
     NotFound,
     Unauthorized,
+    Forbidden,
 }
 
 #[derive(Debug, Serialize)]
@@ -38,7 +43,9 @@ impl ResponseError for YarrbotApiError {
                 YarrbotStatusCode::BadRequest => StatusCode::BAD_REQUEST,
                 YarrbotStatusCode::NotFound => StatusCode::NOT_FOUND,
                 YarrbotStatusCode::Unauthorized => StatusCode::UNAUTHORIZED,
+                YarrbotStatusCode::Forbidden => StatusCode::FORBIDDEN,
             },
+            YarrbotApiError::TooManyRequests { .. } => StatusCode::TOO_MANY_REQUESTS,
             YarrbotApiError::InternalError(_) => StatusCode::INTERNAL_SERVER_ERROR,
         }
     }
@@ -53,6 +60,16 @@ impl ResponseError for YarrbotApiError {
                     message,
                 })
             }
+            YarrbotApiError::TooManyRequests { retry_after } => {
+                // Round up so that clients honoring the header don't retry a moment too early.
+                let seconds = retry_after.as_secs() + u64::from(retry_after.subsec_nanos() > 0);
+                HttpResponse::build(StatusCode::TOO_MANY_REQUESTS)
+                    .insert_header((RETRY_AFTER, seconds.to_string()))
+                    .json(YarrbotUserErrorMessage {
+                        status: StatusCode::TOO_MANY_REQUESTS.as_u16(),
+                        message: "Too Many Requests",
+                    })
+            }
             YarrbotApiError::InternalError(_source) => HttpResponse::build(
                 StatusCode::INTERNAL_SERVER_ERROR,
             )
@@ -89,6 +106,14 @@ impl YarrbotApiError {
         Self::new("Unauthorized", YarrbotStatusCode::Unauthorized, inner)
     }
 
+    pub fn forbidden(inner: Option<anyhow::Error>) -> Self {
+        Self::new("Forbidden", YarrbotStatusCode::Forbidden, inner)
+    }
+
+    pub fn too_many_requests(retry_after: Duration) -> Self {
+        YarrbotApiError::TooManyRequests { retry_after }
+    }
+
     pub fn internal_server_error(inner: anyhow::Error) -> Self {
         YarrbotApiError::InternalError(inner)
     }
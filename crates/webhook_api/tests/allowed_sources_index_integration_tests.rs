@@ -0,0 +1,169 @@
+use crate::common::SpyMatrixClient;
+use actix_web::http::header::ContentType;
+use actix_web::http::Method;
+use actix_web::http::StatusCode;
+use actix_web::{test, web, App};
+use std::net::SocketAddr;
+use std::str::FromStr;
+use tracing_actix_web::TracingLogger;
+use yarrbot_webhook_api::{webhook_config, ForwardedHeader, TrustedProxies, YarrbotRootSpan};
+
+mod common;
+
+// Leads to a webhook using the header_token auth mode with the token "test-token" that only accepts requests from
+// 10.0.0.0/8.
+const ALLOWED_SOURCES_WEBHOOK_SHORTID: &str = "0qfE4TtYT5aODVobnH9uVQ";
+const TEST_BODY: &str = "{
+    \"eventType\": \"Test\",
+    \"series\": {
+        \"id\": 1,
+        \"title\": \"Test Title\",
+        \"path\": \"C:\\\\testpath\",
+        \"tvdbId\": 1234,
+        \"type\": \"standard\"
+    },
+    \"episodes\": []
+}";
+
+fn request(peer_addr: &str) -> test::TestRequest {
+    test::TestRequest::default()
+        .peer_addr(SocketAddr::from_str(peer_addr).unwrap())
+        .insert_header(("X-Api-Key", "test-token"))
+        .insert_header(ContentType::json())
+        .method(Method::POST)
+        .uri(format!("/api/v1/webhook/{}", ALLOWED_SOURCES_WEBHOOK_SHORTID).as_str())
+        .set_payload(TEST_BODY)
+}
+
+#[actix_rt::test]
+async fn index_post_returns_200_given_allowed_source() {
+    // Arrange
+    common::setup();
+    let client = SpyMatrixClient::new();
+    let app = test::init_service(
+        App::new()
+            .wrap(TracingLogger::<YarrbotRootSpan>::new())
+            .app_data(web::Data::new(common::repositories().await))
+            .app_data(web::Data::new(client.clone()))
+            .service(web::scope("/api/v1").configure(webhook_config::<SpyMatrixClient>)),
+    )
+    .await;
+    let req = request("10.1.2.3:4000").to_request();
+
+    // Act
+    let resp = test::call_service(&app, req).await;
+
+    // Assert
+    assert!(resp.status().is_success());
+}
+
+#[actix_rt::test]
+async fn index_post_returns_403_given_source_outside_allowed_networks() {
+    // Arrange
+    common::setup();
+    let client = SpyMatrixClient::new();
+    let app = test::init_service(
+        App::new()
+            .wrap(TracingLogger::<YarrbotRootSpan>::new())
+            .app_data(web::Data::new(common::repositories().await))
+            .app_data(web::Data::new(client.clone()))
+            .service(web::scope("/api/v1").configure(webhook_config::<SpyMatrixClient>)),
+    )
+    .await;
+    // Forwarding headers from untrusted peers are ignored.
+    let req = request("192.0.2.1:4000")
+        .insert_header(("X-Forwarded-For", "10.1.2.3"))
+        .to_request();
+
+    // Act
+    let resp = test::call_service(&app, req).await;
+
+    // Assert
+    assert_eq!(StatusCode::FORBIDDEN, resp.status());
+}
+
+#[actix_rt::test]
+async fn index_post_returns_200_given_allowed_source_forwarded_by_trusted_proxy() {
+    // Arrange
+    common::setup();
+    let client = SpyMatrixClient::new();
+    let app = test::init_service(
+        App::new()
+            .wrap(TracingLogger::<YarrbotRootSpan>::new())
+            .app_data(web::Data::new(common::repositories().await))
+            .app_data(web::Data::new(client.clone()))
+            .app_data(web::Data::new(
+                TrustedProxies::from_str("192.0.2.0/24")
+                    .unwrap()
+                    .with_header(ForwardedHeader::Forwarded),
+            ))
+            .service(web::scope("/api/v1").configure(webhook_config::<SpyMatrixClient>)),
+    )
+    .await;
+    let req = request("192.0.2.1:4000")
+        .insert_header(("Forwarded", "for=10.1.2.3;proto=https"))
+        .to_request();
+
+    // Act
+    let resp = test::call_service(&app, req).await;
+
+    // Assert
+    assert!(resp.status().is_success());
+}
+
+#[actix_rt::test]
+async fn index_post_returns_403_given_allowed_source_in_header_that_is_not_trusted() {
+    // Arrange
+    common::setup();
+    let client = SpyMatrixClient::new();
+    let app = test::init_service(
+        App::new()
+            .wrap(TracingLogger::<YarrbotRootSpan>::new())
+            .app_data(web::Data::new(common::repositories().await))
+            .app_data(web::Data::new(client.clone()))
+            .app_data(web::Data::new(
+                TrustedProxies::from_str("192.0.2.0/24").unwrap(),
+            ))
+            .service(web::scope("/api/v1").configure(webhook_config::<SpyMatrixClient>)),
+    )
+    .await;
+    // The proxy records the client in X-Forwarded-For, so a Forwarded header was written by the client.
+    let req = request("192.0.2.1:4000")
+        .insert_header(("Forwarded", "for=10.1.2.3"))
+        .insert_header(("X-Forwarded-For", "198.51.100.1"))
+        .to_request();
+
+    // Act
+    let resp = test::call_service(&app, req).await;
+
+    // Assert
+    assert_eq!(StatusCode::FORBIDDEN, resp.status());
+}
+
+#[actix_rt::test]
+async fn index_post_returns_403_given_source_outside_allowed_networks_without_credentials() {
+    // Arrange
+    common::setup();
+    let client = SpyMatrixClient::new();
+    let app = test::init_service(
+        App::new()
+            .wrap(TracingLogger::<YarrbotRootSpan>::new())
+            .app_data(web::Data::new(common::repositories().await))
+            .app_data(web::Data::new(client.clone()))
+            .service(web::scope("/api/v1").configure(webhook_config::<SpyMatrixClient>)),
+    )
+    .await;
+    let req = test::TestRequest::default()
+        .peer_addr(SocketAddr::from_str("192.0.2.1:4000").unwrap())
+        .insert_header(ContentType::json())
+        .method(Method::POST)
+        .uri(format!("/api/v1/webhook/{}", ALLOWED_SOURCES_WEBHOOK_SHORTID).as_str())
+        .set_payload(TEST_BODY)
+        .to_request();
+
+    // Act
+    let resp = test::call_service(&app, req).await;
+
+    // Assert
+    assert_eq!(StatusCode::FORBIDDEN, resp.status());
+}
@@ -0,0 +1,219 @@
+use crate::common::SpyMatrixClient;
+use actix_web::http::header::ContentType;
+use actix_web::http::Method;
+use actix_web::http::StatusCode;
+use actix_web::{test, web, App};
+use tracing_actix_web::TracingLogger;
+use yarrbot_common::crypto::hmac_sha256;
+use yarrbot_webhook_api::{webhook_config, SignatureReplayGuard, YarrbotRootSpan};
+
+mod common;
+
+// Leads to a webhook using the header_token auth mode with the token "test-token".
+const HEADER_TOKEN_WEBHOOK_SHORTID: &str = "bH1SpIoOT36dZA8bHJo-EQ";
+// Leads to a webhook using the hmac auth mode with the secret "test-secret".
+const HMAC_WEBHOOK_SHORTID: &str = "niuPE11MS2qh8Dx-LZtKIg";
+const TEST_BODY: &str = "{
+    \"eventType\": \"Test\",
+    \"series\": {
+        \"id\": 1,
+        \"title\": \"Test Title\",
+        \"path\": \"C:\\\\testpath\",
+        \"tvdbId\": 1234,
+        \"type\": \"standard\"
+    },
+    \"episodes\": []
+}";
+
+fn sign(timestamp: &str, body: &str) -> String {
+    let signed = format!("{}.{}", timestamp, body);
+    format!(
+        "sha256={}",
+        hex::encode(hmac_sha256(b"test-secret", signed.as_bytes()))
+    )
+}
+
+#[actix_rt::test]
+async fn index_post_returns_200_given_valid_bearer_token() {
+    // Arrange
+    common::setup();
+    let client = SpyMatrixClient::new();
+    let app = test::init_service(
+        App::new()
+            .wrap(TracingLogger::<YarrbotRootSpan>::new())
+            .app_data(web::Data::new(common::repositories().await))
+            .app_data(web::Data::new(client.clone()))
+            .service(web::scope("/api/v1").configure(webhook_config::<SpyMatrixClient>)),
+    )
+    .await;
+    let req = test::TestRequest::default()
+        .insert_header(("authorization", "Bearer test-token"))
+        .insert_header(ContentType::json())
+        .method(Method::POST)
+        .uri(format!("/api/v1/webhook/{}", HEADER_TOKEN_WEBHOOK_SHORTID).as_str())
+        .set_payload(TEST_BODY)
+        .to_request();
+
+    // Act
+    let resp = test::call_service(&app, req).await;
+
+    // Assert
+    assert!(resp.status().is_success());
+}
+
+#[actix_rt::test]
+async fn index_post_returns_401_given_basic_auth_for_header_token_webhook() {
+    // Arrange
+    common::setup();
+    let client = SpyMatrixClient::new();
+    let app = test::init_service(
+        App::new()
+            .wrap(TracingLogger::<YarrbotRootSpan>::new())
+            .app_data(web::Data::new(common::repositories().await))
+            .app_data(web::Data::new(client.clone()))
+            .service(web::scope("/api/v1").configure(webhook_config::<SpyMatrixClient>)),
+    )
+    .await;
+    let req = test::TestRequest::default()
+        .insert_header((
+            "authorization",
+            format!("Basic {}", common::DEFAULT_B64).as_str(),
+        ))
+        .insert_header(ContentType::json())
+        .method(Method::POST)
+        .uri(format!("/api/v1/webhook/{}", HEADER_TOKEN_WEBHOOK_SHORTID).as_str())
+        .set_payload(TEST_BODY)
+        .to_request();
+
+    // Act
+    let resp = test::call_service(&app, req).await;
+
+    // Assert
+    assert_eq!(StatusCode::UNAUTHORIZED, resp.status());
+}
+
+#[actix_rt::test]
+async fn index_post_returns_200_given_valid_signature() {
+    // Arrange
+    common::setup();
+    let client = SpyMatrixClient::new();
+    let app = test::init_service(
+        App::new()
+            .wrap(TracingLogger::<YarrbotRootSpan>::new())
+            .app_data(web::Data::new(common::repositories().await))
+            .app_data(web::Data::new(client.clone()))
+            .service(web::scope("/api/v1").configure(webhook_config::<SpyMatrixClient>)),
+    )
+    .await;
+    let timestamp = chrono::Utc::now().timestamp().to_string();
+    let req = test::TestRequest::default()
+        .insert_header(("X-Yarrbot-Timestamp", timestamp.as_str()))
+        .insert_header(("X-Yarrbot-Signature", sign(&timestamp, TEST_BODY).as_str()))
+        .insert_header(ContentType::json())
+        .method(Method::POST)
+        .uri(format!("/api/v1/webhook/{}", HMAC_WEBHOOK_SHORTID).as_str())
+        .set_payload(TEST_BODY)
+        .to_request();
+
+    // Act
+    let resp = test::call_service(&app, req).await;
+
+    // Assert
+    assert!(resp.status().is_success());
+}
+
+#[actix_rt::test]
+async fn index_post_returns_401_given_signature_for_different_body() {
+    // Arrange
+    common::setup();
+    let client = SpyMatrixClient::new();
+    let app = test::init_service(
+        App::new()
+            .wrap(TracingLogger::<YarrbotRootSpan>::new())
+            .app_data(web::Data::new(common::repositories().await))
+            .app_data(web::Data::new(client.clone()))
+            .service(web::scope("/api/v1").configure(webhook_config::<SpyMatrixClient>)),
+    )
+    .await;
+    let timestamp = chrono::Utc::now().timestamp().to_string();
+    let req = test::TestRequest::default()
+        .insert_header(("X-Yarrbot-Timestamp", timestamp.as_str()))
+        .insert_header(("X-Yarrbot-Signature", sign(&timestamp, "{}").as_str()))
+        .insert_header(ContentType::json())
+        .method(Method::POST)
+        .uri(format!("/api/v1/webhook/{}", HMAC_WEBHOOK_SHORTID).as_str())
+        .set_payload(TEST_BODY)
+        .to_request();
+
+    // Act
+    let resp = test::call_service(&app, req).await;
+
+    // Assert
+    assert_eq!(StatusCode::UNAUTHORIZED, resp.status());
+}
+
+#[actix_rt::test]
+async fn index_post_returns_401_given_replayed_signature() {
+    // Arrange
+    common::setup();
+    let client = SpyMatrixClient::new();
+    let app = test::init_service(
+        App::new()
+            .wrap(TracingLogger::<YarrbotRootSpan>::new())
+            .app_data(web::Data::new(common::repositories().await))
+            .app_data(web::Data::new(client.clone()))
+            .app_data(web::Data::new(SignatureReplayGuard::default()))
+            .service(web::scope("/api/v1").configure(webhook_config::<SpyMatrixClient>)),
+    )
+    .await;
+    let timestamp = chrono::Utc::now().timestamp().to_string();
+    let signature = sign(&timestamp, TEST_BODY);
+    let build_request = || {
+        test::TestRequest::default()
+            .insert_header(("X-Yarrbot-Timestamp", timestamp.as_str()))
+            .insert_header(("X-Yarrbot-Signature", signature.as_str()))
+            .insert_header(ContentType::json())
+            .method(Method::POST)
+            .uri(format!("/api/v1/webhook/{}", HMAC_WEBHOOK_SHORTID).as_str())
+            .set_payload(TEST_BODY)
+            .to_request()
+    };
+
+    // Act
+    let first = test::call_service(&app, build_request()).await;
+    let replayed = test::call_service(&app, build_request()).await;
+
+    // Assert
+    assert!(first.status().is_success());
+    assert_eq!(StatusCode::UNAUTHORIZED, replayed.status());
+}
+
+#[actix_rt::test]
+async fn index_post_returns_401_given_stale_timestamp() {
+    // Arrange
+    common::setup();
+    let client = SpyMatrixClient::new();
+    let app = test::init_service(
+        App::new()
+            .wrap(TracingLogger::<YarrbotRootSpan>::new())
+            .app_data(web::Data::new(common::repositories().await))
+            .app_data(web::Data::new(client.clone()))
+            .service(web::scope("/api/v1").configure(webhook_config::<SpyMatrixClient>)),
+    )
+    .await;
+    let timestamp = (chrono::Utc::now().timestamp() - 3600).to_string();
+    let req = test::TestRequest::default()
+        .insert_header(("X-Yarrbot-Timestamp", timestamp.as_str()))
+        .insert_header(("X-Yarrbot-Signature", sign(&timestamp, TEST_BODY).as_str()))
+        .insert_header(ContentType::json())
+        .method(Method::POST)
+        .uri(format!("/api/v1/webhook/{}", HMAC_WEBHOOK_SHORTID).as_str())
+        .set_payload(TEST_BODY)
+        .to_request();
+
+    // Act
+    let resp = test::call_service(&app, req).await;
+
+    // Assert
+    assert_eq!(StatusCode::UNAUTHORIZED, resp.status());
+}
@@ -1,7 +1,6 @@
 extern crate dotenv;
 
 use async_trait::async_trait;
-use lazy_static::{initialize, lazy_static};
 use std::sync::{Arc, Once};
 use tokio::sync::RwLock;
 use tracing::level_filters::LevelFilter;
@@ -9,23 +8,20 @@ use tracing_log::LogTracer;
 use tracing_subscriber::layer::SubscriberExt;
 use tracing_subscriber::{EnvFilter, Registry};
 use yarrbot_common::environment::variables::LOG_FILTER;
-use yarrbot_db::{build_pool, DbPool};
-use yarrbot_matrix_client::message::Message;
+use yarrbot_db::repositories::Repositories;
+use yarrbot_db::{build_async_pool, AsyncDbPool};
+use yarrbot_matrix_client::message::{Message, MessageData};
 use yarrbot_matrix_client::MatrixClient;
 
 static INIT: Once = Once::new();
 
-lazy_static! {
-    pub static ref POOL: DbPool = build_pool().unwrap();
-}
-
 // testuser:myP@ssw0rd123
+#[allow(dead_code)] // Not every integration test binary uses basic authentication.
 pub const DEFAULT_B64: &str = "dGVzdHVzZXI6bXlQQHNzdzByZDEyMw==";
 
 pub fn setup() {
     INIT.call_once(|| {
         dotenv::from_filename("integrationtest.env").ok();
-        initialize(&POOL);
 
         // Copy and paste of what is in main.rs of the bin crate.
         // Extracting this to a function in common.rs causes tracing to stop working.
@@ -42,6 +38,19 @@ pub fn setup() {
     });
 }
 
+/// A pool of connections to the integration test database. Each test runs on its own runtime, which the pool's
+/// connections are bound to, so every test builds its own pool after calling [setup].
+#[allow(dead_code)] // Not every integration test binary uses the database.
+pub async fn pool() -> AsyncDbPool {
+    build_async_pool().await.unwrap()
+}
+
+/// [Repositories] backed by the integration test database.
+#[allow(dead_code)] // Not every integration test binary handles webhooks.
+pub async fn repositories() -> Repositories {
+    Repositories::database(pool().await)
+}
+
 /// Fake implementation of [MatrixClient] that captures the [MessageData]+[MatrixRoom] information provided to it.
 #[derive(Clone)]
 pub struct SpyMatrixClient {
@@ -54,6 +63,13 @@ impl SpyMatrixClient {
             messages: Arc::new(RwLock::new(Vec::<Message>::new())),
         }
     }
+
+    /// The destinations of every message sent so far, in the order they were sent.
+    #[allow(dead_code)] // Not every integration test binary inspects the sent messages.
+    pub async fn destinations(&self) -> Vec<String> {
+        let messages = self.messages.read().await;
+        messages.iter().map(|m| m.destination.clone()).collect()
+    }
 }
 
 impl Default for SpyMatrixClient {
@@ -70,4 +86,15 @@ impl MatrixClient for SpyMatrixClient {
 
         Ok(())
     }
+
+    async fn send_direct_message(
+        &self,
+        user_id: &str,
+        message_data: MessageData,
+    ) -> anyhow::Result<()> {
+        let mut messages = self.messages.write().await;
+        messages.push(Message::new(user_id, Arc::new(message_data)));
+
+        Ok(())
+    }
 }
@@ -4,7 +4,7 @@ use actix_web::http::Method;
 use actix_web::http::StatusCode;
 use actix_web::{test, web, App};
 use tracing_actix_web::TracingLogger;
-use yarrbot_webhook_api::{webhook_config, YarrbotRootSpan};
+use yarrbot_webhook_api::{webhook_config, CredentialCache, YarrbotRootSpan};
 
 mod common;
 
@@ -40,7 +40,7 @@ async fn index_post_returns_200_given_valid_info() {
     let app = test::init_service(
         App::new()
             .wrap(TracingLogger::<YarrbotRootSpan>::new())
-            .app_data(web::Data::new(common::POOL.clone()))
+            .app_data(web::Data::new(common::repositories().await))
             .app_data(web::Data::new(client.clone()))
             .service(web::scope("/api/v1").configure(webhook_config::<SpyMatrixClient>)),
     )
@@ -63,6 +63,46 @@ async fn index_post_returns_200_given_valid_info() {
     assert!(resp.status().is_success());
 }
 
+#[actix_rt::test]
+async fn index_post_verifies_password_once_given_credential_cache() {
+    // Arrange
+    common::setup();
+    let client = SpyMatrixClient::new();
+    let cache = web::Data::new(CredentialCache::default());
+    let app = test::init_service(
+        App::new()
+            .wrap(TracingLogger::<YarrbotRootSpan>::new())
+            .app_data(web::Data::new(common::repositories().await))
+            .app_data(web::Data::new(client.clone()))
+            .app_data(cache.clone())
+            .service(web::scope("/api/v1").configure(webhook_config::<SpyMatrixClient>)),
+    )
+    .await;
+    let request = || {
+        test::TestRequest::default()
+            .insert_header((
+                "authorization",
+                format!("Basic {}", common::DEFAULT_B64).as_str(),
+            ))
+            .insert_header(ContentType::json())
+            .method(Method::POST)
+            .uri(format!("/api/v1/webhook/{}", DEFAULT_WEBHOOK_SHORTID).as_str())
+            .set_payload(TEST_BODY)
+            .to_request()
+    };
+
+    // Act
+    let first = test::call_service(&app, request()).await;
+    let second = test::call_service(&app, request()).await;
+
+    // Assert
+    assert!(first.status().is_success());
+    assert!(second.status().is_success());
+    let stats = cache.stats();
+    assert_eq!(1, stats.verifications);
+    assert_eq!(1, stats.hits);
+}
+
 #[actix_rt::test]
 async fn index_returns_401_unauthorized_given_invalid_credentials() {
     // Arrange
@@ -71,7 +111,7 @@ async fn index_returns_401_unauthorized_given_invalid_credentials() {
     let app = test::init_service(
         App::new()
             .wrap(TracingLogger::<YarrbotRootSpan>::new())
-            .app_data(web::Data::new(common::POOL.clone()))
+            .app_data(web::Data::new(common::repositories().await))
             .app_data(web::Data::new(client.clone()))
             .service(web::scope("/api/v1").configure(webhook_config::<SpyMatrixClient>)),
     )
@@ -101,7 +141,7 @@ async fn index_returns_404_not_found_given_short_id_not_uuid() {
     let app = test::init_service(
         App::new()
             .wrap(TracingLogger::<YarrbotRootSpan>::new())
-            .app_data(web::Data::new(common::POOL.clone()))
+            .app_data(web::Data::new(common::repositories().await))
             .app_data(web::Data::new(client.clone()))
             .service(web::scope("/api/v1").configure(webhook_config::<SpyMatrixClient>)),
     )
@@ -132,7 +172,7 @@ async fn index_returns_404_not_found_given_valid_short_id_but_not_in_db() {
     let app = test::init_service(
         App::new()
             .wrap(TracingLogger::<YarrbotRootSpan>::new())
-            .app_data(web::Data::new(common::POOL.clone()))
+            .app_data(web::Data::new(common::repositories().await))
             .app_data(web::Data::new(client.clone()))
             .service(web::scope("/api/v1").configure(webhook_config::<SpyMatrixClient>)),
     )
@@ -164,7 +204,7 @@ async fn index_post_returns_400_given_invalid_request_body() {
     let app = test::init_service(
         App::new()
             .wrap(TracingLogger::<YarrbotRootSpan>::new())
-            .app_data(web::Data::new(common::POOL.clone()))
+            .app_data(web::Data::new(common::repositories().await))
             .app_data(web::Data::new(client.clone()))
             .service(web::scope("/api/v1").configure(webhook_config::<SpyMatrixClient>)),
     )
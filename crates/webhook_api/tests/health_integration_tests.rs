@@ -0,0 +1,108 @@
+use actix_web::http::StatusCode;
+use actix_web::{test, web, App};
+use serde_json::Value;
+use yarrbot_matrix_client::MatrixHealth;
+use yarrbot_webhook_api::health_config;
+
+mod common;
+
+fn healthy_matrix() -> MatrixHealth {
+    let health = MatrixHealth::default();
+    health.record_login(true);
+    health.record_sync();
+    health
+}
+
+#[actix_rt::test]
+async fn healthz_returns_200() {
+    // Arrange
+    common::setup();
+    let app = test::init_service(App::new().configure(health_config)).await;
+    let req = test::TestRequest::get().uri("/healthz").to_request();
+
+    // Act
+    let resp = test::call_service(&app, req).await;
+
+    // Assert
+    assert_eq!(StatusCode::OK, resp.status());
+}
+
+#[actix_rt::test]
+async fn readyz_returns_200_given_healthy_dependencies() {
+    // Arrange
+    common::setup();
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::pool().await))
+            .app_data(web::Data::new(healthy_matrix()))
+            .configure(health_config),
+    )
+    .await;
+    let req = test::TestRequest::get().uri("/readyz").to_request();
+
+    // Act
+    let resp = test::call_service(&app, req).await;
+
+    // Assert
+    assert_eq!(StatusCode::OK, resp.status());
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(Value::Bool(true), body["ready"]);
+    assert_eq!(Value::Bool(true), body["checks"]["database"]["ready"]);
+    assert_eq!(0, body["checks"]["send_queue"]["pending_messages"]);
+}
+
+#[actix_rt::test]
+async fn readyz_returns_503_given_matrix_never_synced() {
+    // Arrange
+    common::setup();
+    let health = MatrixHealth::default();
+    health.record_login(true);
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::pool().await))
+            .app_data(web::Data::new(health))
+            .configure(health_config),
+    )
+    .await;
+    let req = test::TestRequest::get().uri("/readyz").to_request();
+
+    // Act
+    let resp = test::call_service(&app, req).await;
+
+    // Assert
+    assert_eq!(StatusCode::SERVICE_UNAVAILABLE, resp.status());
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(Value::Bool(false), body["checks"]["matrix"]["ready"]);
+    assert_eq!(
+        Value::Null,
+        body["checks"]["matrix"]["seconds_since_last_sync"]
+    );
+    assert_eq!(Value::Bool(true), body["checks"]["send_queue"]["ready"]);
+}
+
+#[actix_rt::test]
+async fn readyz_returns_503_given_send_backlog() {
+    // Arrange
+    common::setup();
+    let health = healthy_matrix();
+    for _ in 0..500 {
+        health.message_queued();
+    }
+    let app = test::init_service(
+        App::new()
+            .app_data(web::Data::new(common::pool().await))
+            .app_data(web::Data::new(health))
+            .configure(health_config),
+    )
+    .await;
+    let req = test::TestRequest::get().uri("/readyz").to_request();
+
+    // Act
+    let resp = test::call_service(&app, req).await;
+
+    // Assert
+    assert_eq!(StatusCode::SERVICE_UNAVAILABLE, resp.status());
+    let body: Value = test::read_body_json(resp).await;
+    assert_eq!(Value::Bool(false), body["checks"]["send_queue"]["ready"]);
+    assert_eq!(500, body["checks"]["send_queue"]["pending_messages"]);
+}
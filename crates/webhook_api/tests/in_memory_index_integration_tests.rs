@@ -0,0 +1,273 @@
+use crate::common::SpyMatrixClient;
+use actix_web::http::header::ContentType;
+use actix_web::http::StatusCode;
+use actix_web::{test, web, App};
+use chrono::{Duration, Utc};
+use tracing_actix_web::TracingLogger;
+use yarrbot_common::crypto::hash;
+use yarrbot_common::short_id::ShortId;
+use yarrbot_db::models::{NewMatrixRoom, NewUser, NewWebhook, Webhook};
+use yarrbot_db::quiet_hours::QuietHours;
+use yarrbot_db::repositories::Repositories;
+use yarrbot_webhook_api::{release_held_messages, webhook_config, PausedEvents, YarrbotRootSpan};
+
+// These tests don't touch the database, so they don't call common::setup.
+#[allow(dead_code)]
+mod common;
+
+const ROOM_ID: &str = "!in-memory:example.org";
+const TEST_BODY: &str = "{
+    \"eventType\": \"Test\",
+    \"series\": {
+        \"id\": 1,
+        \"title\": \"Test Title\",
+        \"path\": \"C:\\\\testpath\",
+        \"tvdbId\": 1234,
+        \"type\": \"standard\"
+    },
+    \"episodes\": []
+}";
+
+/// Create a user with a webhook posting to [ROOM_ID], using the credentials in [common::DEFAULT_B64].
+async fn seed(repositories: &Repositories) -> Webhook {
+    let user = repositories
+        .users
+        .create_user(NewUser::new("@owner:example.org", None))
+        .await
+        .unwrap();
+    let password = hash(String::from("myP@ssw0rd123")).await.unwrap();
+    let webhook = repositories
+        .webhooks
+        .create_webhook(NewWebhook::new("testuser", password.to_vec(), &user, None))
+        .await
+        .unwrap();
+    repositories
+        .rooms
+        .create_room(NewMatrixRoom::new(ROOM_ID, &webhook))
+        .await
+        .unwrap();
+    webhook
+}
+
+#[actix_rt::test]
+async fn index_post_sends_message_to_rooms_given_in_memory_repositories() {
+    // Arrange
+    let repositories = Repositories::in_memory();
+    let webhook = seed(&repositories).await;
+    let client = SpyMatrixClient::new();
+    let app = test::init_service(
+        App::new()
+            .wrap(TracingLogger::<YarrbotRootSpan>::new())
+            .app_data(web::Data::new(repositories.clone()))
+            .app_data(web::Data::new(client.clone()))
+            .service(web::scope("/api/v1").configure(webhook_config::<SpyMatrixClient>)),
+    )
+    .await;
+    let req = test::TestRequest::post()
+        .uri(format!("/api/v1/webhook/{}", webhook.id.to_short_id()).as_str())
+        .insert_header((
+            "authorization",
+            format!("Basic {}", common::DEFAULT_B64).as_str(),
+        ))
+        .insert_header(ContentType::json())
+        .set_payload(TEST_BODY)
+        .to_request();
+
+    // Act
+    let resp = test::call_service(&app, req).await;
+
+    // Assert
+    assert_eq!(StatusCode::OK, resp.status());
+    assert_eq!(vec![ROOM_ID], client.destinations().await);
+    let webhook = repositories
+        .webhooks
+        .try_get(&webhook.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(1, webhook.events_received);
+    assert!(webhook.last_event_at.is_some());
+}
+
+#[actix_rt::test]
+async fn index_post_returns_404_given_webhook_missing_from_in_memory_repositories() {
+    // Arrange
+    let repositories = Repositories::in_memory();
+    let webhook = seed(&repositories).await;
+    repositories.webhooks.delete(&webhook).await.unwrap();
+    let client = SpyMatrixClient::new();
+    let app = test::init_service(
+        App::new()
+            .wrap(TracingLogger::<YarrbotRootSpan>::new())
+            .app_data(web::Data::new(repositories.clone()))
+            .app_data(web::Data::new(client.clone()))
+            .service(web::scope("/api/v1").configure(webhook_config::<SpyMatrixClient>)),
+    )
+    .await;
+    let req = test::TestRequest::post()
+        .uri(format!("/api/v1/webhook/{}", webhook.id.to_short_id()).as_str())
+        .insert_header((
+            "authorization",
+            format!("Basic {}", common::DEFAULT_B64).as_str(),
+        ))
+        .insert_header(ContentType::json())
+        .set_payload(TEST_BODY)
+        .to_request();
+
+    // Act
+    let resp = test::call_service(&app, req).await;
+
+    // Assert
+    assert_eq!(StatusCode::NOT_FOUND, resp.status());
+    assert!(client.destinations().await.is_empty());
+}
+
+#[actix_rt::test]
+async fn index_post_returns_200_without_sending_given_paused_webhook() {
+    // Arrange
+    let repositories = Repositories::in_memory();
+    let webhook = seed(&repositories).await;
+    repositories
+        .webhooks
+        .update_paused(&webhook, true, None)
+        .await
+        .unwrap();
+    let client = SpyMatrixClient::new();
+    let app = test::init_service(
+        App::new()
+            .wrap(TracingLogger::<YarrbotRootSpan>::new())
+            .app_data(web::Data::new(repositories.clone()))
+            .app_data(web::Data::new(client.clone()))
+            .app_data(web::Data::new(PausedEvents::Drop))
+            .service(web::scope("/api/v1").configure(webhook_config::<SpyMatrixClient>)),
+    )
+    .await;
+    let req = test::TestRequest::post()
+        .uri(format!("/api/v1/webhook/{}", webhook.id.to_short_id()).as_str())
+        .insert_header((
+            "authorization",
+            format!("Basic {}", common::DEFAULT_B64).as_str(),
+        ))
+        .insert_header(ContentType::json())
+        .set_payload(TEST_BODY)
+        .to_request();
+
+    // Act
+    let resp = test::call_service(&app, req).await;
+
+    // Assert
+    assert_eq!(StatusCode::OK, resp.status());
+    assert!(client.destinations().await.is_empty());
+    let webhook = repositories
+        .webhooks
+        .try_get(&webhook.id)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(0, webhook.events_received);
+}
+
+#[actix_rt::test]
+async fn index_post_skips_muted_rooms_given_in_memory_repositories() {
+    // Arrange
+    let repositories = Repositories::in_memory();
+    let webhook = seed(&repositories).await;
+    repositories
+        .rooms
+        .create_room(NewMatrixRoom::new("!muted:example.org", &webhook))
+        .await
+        .unwrap();
+    repositories
+        .rooms
+        .update_muted_until(
+            &webhook.id,
+            Some("!muted:example.org"),
+            Some(Utc::now() + Duration::hours(1)),
+        )
+        .await
+        .unwrap();
+    let client = SpyMatrixClient::new();
+    let app = test::init_service(
+        App::new()
+            .wrap(TracingLogger::<YarrbotRootSpan>::new())
+            .app_data(web::Data::new(repositories.clone()))
+            .app_data(web::Data::new(client.clone()))
+            .service(web::scope("/api/v1").configure(webhook_config::<SpyMatrixClient>)),
+    )
+    .await;
+    let req = test::TestRequest::post()
+        .uri(format!("/api/v1/webhook/{}", webhook.id.to_short_id()).as_str())
+        .insert_header((
+            "authorization",
+            format!("Basic {}", common::DEFAULT_B64).as_str(),
+        ))
+        .insert_header(ContentType::json())
+        .set_payload(TEST_BODY)
+        .to_request();
+
+    // Act
+    let resp = test::call_service(&app, req).await;
+
+    // Assert
+    assert_eq!(StatusCode::OK, resp.status());
+    assert_eq!(vec![ROOM_ID], client.destinations().await);
+}
+
+#[actix_rt::test]
+async fn index_post_holds_messages_during_quiet_hours_until_they_end() {
+    // Arrange
+    let repositories = Repositories::in_memory();
+    let webhook = seed(&repositories).await;
+    let now = Utc::now();
+    let quiet_hours = QuietHours::from_parts(
+        &(now - Duration::hours(1)).format("%H:%M").to_string(),
+        &(now + Duration::hours(1)).format("%H:%M").to_string(),
+        "UTC",
+    )
+    .unwrap();
+    repositories
+        .rooms
+        .update_quiet_hours(&webhook.id, None, Some(&quiet_hours), false)
+        .await
+        .unwrap();
+    let client = SpyMatrixClient::new();
+    let app = test::init_service(
+        App::new()
+            .wrap(TracingLogger::<YarrbotRootSpan>::new())
+            .app_data(web::Data::new(repositories.clone()))
+            .app_data(web::Data::new(client.clone()))
+            .service(web::scope("/api/v1").configure(webhook_config::<SpyMatrixClient>)),
+    )
+    .await;
+    let req = test::TestRequest::post()
+        .uri(format!("/api/v1/webhook/{}", webhook.id.to_short_id()).as_str())
+        .insert_header((
+            "authorization",
+            format!("Basic {}", common::DEFAULT_B64).as_str(),
+        ))
+        .insert_header(ContentType::json())
+        .set_payload(TEST_BODY)
+        .to_request();
+
+    // Act
+    let resp = test::call_service(&app, req).await;
+    release_held_messages(&repositories, &client).await;
+    let destinations_during_quiet_hours = client.destinations().await;
+    repositories
+        .rooms
+        .update_quiet_hours(&webhook.id, None, None, false)
+        .await
+        .unwrap();
+    release_held_messages(&repositories, &client).await;
+
+    // Assert
+    assert_eq!(StatusCode::OK, resp.status());
+    assert!(destinations_during_quiet_hours.is_empty());
+    assert_eq!(vec![ROOM_ID], client.destinations().await);
+    assert!(repositories
+        .held_messages
+        .get_all()
+        .await
+        .unwrap()
+        .is_empty());
+}
@@ -0,0 +1,135 @@
+use crate::common::SpyMatrixClient;
+use actix_web::http::header::{ContentType, RETRY_AFTER};
+use actix_web::http::Method;
+use actix_web::http::StatusCode;
+use actix_web::{test, web, App};
+use std::net::SocketAddr;
+use std::str::FromStr;
+use std::sync::Arc;
+use std::time::Duration;
+use tracing_actix_web::TracingLogger;
+use yarrbot_webhook_api::{webhook_config, AuthFailureTracker, LockoutSettings, YarrbotRootSpan};
+
+mod common;
+
+// Leads to a webhook using the header_token auth mode with the token "test-token".
+const HEADER_TOKEN_WEBHOOK_SHORTID: &str = "bH1SpIoOT36dZA8bHJo-EQ";
+const OWNER: &str = "testuser@localhost";
+const TEST_BODY: &str = "{
+    \"eventType\": \"Test\",
+    \"series\": {
+        \"id\": 1,
+        \"title\": \"Test Title\",
+        \"path\": \"C:\\\\testpath\",
+        \"tvdbId\": 1234,
+        \"type\": \"standard\"
+    },
+    \"episodes\": []
+}";
+
+fn tracker(ip_threshold: u32, webhook_threshold: u32) -> AuthFailureTracker {
+    AuthFailureTracker::new(LockoutSettings {
+        ip_threshold,
+        webhook_threshold,
+        ..LockoutSettings::default()
+    })
+}
+
+fn request(token: &str, peer_addr: &str) -> test::TestRequest {
+    test::TestRequest::default()
+        .peer_addr(SocketAddr::from_str(peer_addr).unwrap())
+        .insert_header(("X-Api-Key", token))
+        .insert_header(ContentType::json())
+        .method(Method::POST)
+        .uri(format!("/api/v1/webhook/{}", HEADER_TOKEN_WEBHOOK_SHORTID).as_str())
+        .set_payload(TEST_BODY)
+}
+
+#[actix_rt::test]
+async fn index_post_returns_429_given_locked_out_ip() {
+    // Arrange
+    common::setup();
+    let client = SpyMatrixClient::new();
+    let app = test::init_service(
+        App::new()
+            .wrap(TracingLogger::<YarrbotRootSpan>::new())
+            .app_data(web::Data::new(common::repositories().await))
+            .app_data(web::Data::new(client.clone()))
+            .app_data(web::Data::new(tracker(3, 100)))
+            .service(web::scope("/api/v1").configure(webhook_config::<SpyMatrixClient>)),
+    )
+    .await;
+    for _ in 0..3 {
+        let resp = test::call_service(
+            &app,
+            request("wrong-token", "203.0.113.7:4000").to_request(),
+        )
+        .await;
+        assert_eq!(StatusCode::UNAUTHORIZED, resp.status());
+    }
+
+    // Act
+    let locked =
+        test::call_service(&app, request("test-token", "203.0.113.7:4000").to_request()).await;
+    let other = test::call_service(
+        &app,
+        request("test-token", "198.51.100.1:4000").to_request(),
+    )
+    .await;
+
+    // Assert
+    assert_eq!(StatusCode::TOO_MANY_REQUESTS, locked.status());
+    assert_eq!("30", locked.headers().get(RETRY_AFTER).unwrap());
+    assert!(other.status().is_success());
+}
+
+#[actix_rt::test]
+async fn index_post_notifies_owner_given_locked_out_webhook() {
+    // Arrange
+    common::setup();
+    let client = SpyMatrixClient::new();
+    let app = test::init_service(
+        App::new()
+            .wrap(TracingLogger::<YarrbotRootSpan>::new())
+            .app_data(web::Data::new(common::repositories().await))
+            .app_data(web::Data::new(client.clone()))
+            .app_data(web::Data::new(
+                tracker(100, 2).with_alerts(Arc::new(client.clone())),
+            ))
+            .service(web::scope("/api/v1").configure(webhook_config::<SpyMatrixClient>)),
+    )
+    .await;
+    test::call_service(
+        &app,
+        request("wrong-token", "203.0.113.7:4000").to_request(),
+    )
+    .await;
+    // Basic credentials don't authenticate a header token webhook, even when they are otherwise correct.
+    test::call_service(
+        &app,
+        request("", "198.51.100.1:4000")
+            .insert_header((
+                "authorization",
+                format!("Basic {}", common::DEFAULT_B64).as_str(),
+            ))
+            .to_request(),
+    )
+    .await;
+
+    // Act
+    let resp =
+        test::call_service(&app, request("test-token", "192.0.2.66:4000").to_request()).await;
+
+    // Assert
+    assert_eq!(StatusCode::TOO_MANY_REQUESTS, resp.status());
+    // The owner is notified in the background.
+    let mut destinations = Vec::new();
+    for _ in 0..50 {
+        destinations = client.destinations().await;
+        if !destinations.is_empty() {
+            break;
+        }
+        actix_rt::time::sleep(Duration::from_millis(100)).await;
+    }
+    assert_eq!(vec![String::from(OWNER)], destinations);
+}
@@ -0,0 +1,61 @@
+use crate::common::SpyMatrixClient;
+use actix_web::http::header::ContentType;
+use actix_web::http::StatusCode;
+use actix_web::{test, web, App};
+use tracing_actix_web::TracingLogger;
+use yarrbot_webhook_api::{metrics_config, webhook_config, YarrbotRootSpan};
+
+mod common;
+
+// Leads to a webhook using the header_token auth mode with the token "test-token".
+const HEADER_TOKEN_WEBHOOK_SHORTID: &str = "bH1SpIoOT36dZA8bHJo-EQ";
+const TEST_BODY: &str = "{
+    \"eventType\": \"Test\",
+    \"series\": {
+        \"id\": 1,
+        \"title\": \"Test Title\",
+        \"path\": \"C:\\\\testpath\",
+        \"tvdbId\": 1234,
+        \"type\": \"standard\"
+    },
+    \"episodes\": []
+}";
+
+#[actix_rt::test]
+async fn metrics_returns_webhook_counts_after_webhook_received() {
+    // Arrange
+    common::setup();
+    let client = SpyMatrixClient::new();
+    let app = test::init_service(
+        App::new()
+            .wrap(TracingLogger::<YarrbotRootSpan>::new())
+            .app_data(web::Data::new(common::pool().await))
+            .app_data(web::Data::new(common::repositories().await))
+            .app_data(web::Data::new(client.clone()))
+            .configure(metrics_config)
+            .service(web::scope("/api/v1").configure(webhook_config::<SpyMatrixClient>)),
+    )
+    .await;
+    let webhook_req = test::TestRequest::post()
+        .uri(format!("/api/v1/webhook/{}", HEADER_TOKEN_WEBHOOK_SHORTID).as_str())
+        .insert_header(("X-Api-Key", "test-token"))
+        .insert_header(ContentType::json())
+        .set_payload(TEST_BODY)
+        .to_request();
+    let webhook_resp = test::call_service(&app, webhook_req).await;
+    assert_eq!(StatusCode::OK, webhook_resp.status());
+    let req = test::TestRequest::get().uri("/metrics").to_request();
+
+    // Act
+    let resp = test::call_service(&app, req).await;
+
+    // Assert
+    assert_eq!(StatusCode::OK, resp.status());
+    let body = test::read_body(resp).await;
+    let body = std::str::from_utf8(&body).unwrap();
+    assert!(body.contains(
+        "yarrbot_webhooks_received_total{app=\"Sonarr\",event_type=\"Test\",outcome=\"accepted\"}"
+    ));
+    assert!(body.contains("yarrbot_webhook_duration_seconds_count"));
+    assert!(body.contains("yarrbot_db_pool_max_connections"));
+}
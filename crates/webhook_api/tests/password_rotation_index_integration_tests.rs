@@ -0,0 +1,87 @@
+use crate::common::SpyMatrixClient;
+use actix_web::http::header::ContentType;
+use actix_web::http::Method;
+use actix_web::http::StatusCode;
+use actix_web::{test, web, App};
+use tracing_actix_web::TracingLogger;
+use yarrbot_webhook_api::{webhook_config, YarrbotRootSpan};
+
+mod common;
+
+// Leads to a rotated webhook whose previous password is still within its grace period.
+const GRACE_PERIOD_WEBHOOK_SHORTID: &str = "Txwqnns9ToqcYS1fjgt6Mw";
+// Leads to a rotated webhook whose previous password's grace period has ended.
+const EXPIRED_WEBHOOK_SHORTID: &str = "qD5dFyyUSw-OanHD-dKwRA";
+const TEST_BODY: &str = "{
+    \"eventType\": \"Test\",
+    \"series\": {
+        \"id\": 1,
+        \"title\": \"Test Title\",
+        \"path\": \"C:\\\\testpath\",
+        \"tvdbId\": 1234,
+        \"type\": \"standard\"
+    },
+    \"episodes\": []
+}";
+
+#[actix_rt::test]
+async fn index_post_returns_200_given_previous_password_within_grace_period() {
+    // Arrange
+    common::setup();
+    let client = SpyMatrixClient::new();
+    let app = test::init_service(
+        App::new()
+            .wrap(TracingLogger::<YarrbotRootSpan>::new())
+            .app_data(web::Data::new(common::repositories().await))
+            .app_data(web::Data::new(client.clone()))
+            .service(web::scope("/api/v1").configure(webhook_config::<SpyMatrixClient>)),
+    )
+    .await;
+    let req = test::TestRequest::default()
+        .insert_header((
+            "authorization",
+            format!("Basic {}", common::DEFAULT_B64).as_str(),
+        ))
+        .insert_header(ContentType::json())
+        .method(Method::POST)
+        .uri(format!("/api/v1/webhook/{}", GRACE_PERIOD_WEBHOOK_SHORTID).as_str())
+        .set_payload(TEST_BODY)
+        .to_request();
+
+    // Act
+    let resp = test::call_service(&app, req).await;
+
+    // Assert
+    assert!(resp.status().is_success());
+}
+
+#[actix_rt::test]
+async fn index_post_returns_401_given_previous_password_after_grace_period() {
+    // Arrange
+    common::setup();
+    let client = SpyMatrixClient::new();
+    let app = test::init_service(
+        App::new()
+            .wrap(TracingLogger::<YarrbotRootSpan>::new())
+            .app_data(web::Data::new(common::repositories().await))
+            .app_data(web::Data::new(client.clone()))
+            .service(web::scope("/api/v1").configure(webhook_config::<SpyMatrixClient>)),
+    )
+    .await;
+    let req = test::TestRequest::default()
+        .insert_header((
+            "authorization",
+            format!("Basic {}", common::DEFAULT_B64).as_str(),
+        ))
+        .insert_header(ContentType::json())
+        .method(Method::POST)
+        .uri(format!("/api/v1/webhook/{}", EXPIRED_WEBHOOK_SHORTID).as_str())
+        .set_payload(TEST_BODY)
+        .to_request();
+
+    // Act
+    let resp = test::call_service(&app, req).await;
+
+    // Assert
+    assert_eq!(StatusCode::UNAUTHORIZED, resp.status());
+}
@@ -0,0 +1,33 @@
+use actix_web::http::StatusCode;
+use actix_web::{test, web, App};
+use tracing_actix_web::TracingLogger;
+use yarrbot_webhook_api::{metrics_config, RequestIdHeader, YarrbotRootSpan, REQUEST_ID_HEADER};
+
+mod common;
+
+#[actix_rt::test]
+async fn responses_include_request_id_header() {
+    // Arrange
+    common::setup();
+    let app = test::init_service(
+        App::new()
+            .wrap(RequestIdHeader)
+            .wrap(TracingLogger::<YarrbotRootSpan>::new())
+            .app_data(web::Data::new(common::pool().await))
+            .configure(metrics_config),
+    )
+    .await;
+    let first_req = test::TestRequest::get().uri("/metrics").to_request();
+    let second_req = test::TestRequest::get().uri("/metrics").to_request();
+
+    // Act
+    let first_resp = test::call_service(&app, first_req).await;
+    let second_resp = test::call_service(&app, second_req).await;
+
+    // Assert
+    assert_eq!(StatusCode::OK, first_resp.status());
+    let first_id = first_resp.headers().get(REQUEST_ID_HEADER).unwrap();
+    let second_id = second_resp.headers().get(REQUEST_ID_HEADER).unwrap();
+    assert!(!first_id.is_empty());
+    assert_ne!(first_id, second_id);
+}
@@ -0,0 +1,2 @@
+ALTER TABLE IF EXISTS webhooks DROP COLUMN IF EXISTS arr_api_key;
+ALTER TABLE IF EXISTS webhooks DROP COLUMN IF EXISTS arr_url;
\ No newline at end of file
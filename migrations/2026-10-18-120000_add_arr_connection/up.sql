@@ -0,0 +1,3 @@
+-- The base URL and API key of the *arr that invokes the webhook; used to retrieve media covers from the *arr.
+ALTER TABLE IF EXISTS webhooks ADD COLUMN IF NOT EXISTS arr_url TEXT NULL;
+ALTER TABLE IF EXISTS webhooks ADD COLUMN IF NOT EXISTS arr_api_key TEXT NULL;
@@ -0,0 +1 @@
+ALTER TABLE IF EXISTS matrix_rooms DROP COLUMN IF EXISTS hide_spoilers;
\ No newline at end of file
@@ -0,0 +1,2 @@
+-- Whether to hide episode titles and other potential spoilers in notifications sent to the room.
+ALTER TABLE IF EXISTS matrix_rooms ADD COLUMN IF NOT EXISTS hide_spoilers BOOLEAN NOT NULL DEFAULT FALSE;
\ No newline at end of file
@@ -0,0 +1,3 @@
+ALTER TABLE IF EXISTS webhooks DROP COLUMN IF EXISTS path_replacements;
+ALTER TABLE IF EXISTS webhooks DROP COLUMN IF EXISTS path_policy;
+DROP TYPE IF EXISTS path_policy;
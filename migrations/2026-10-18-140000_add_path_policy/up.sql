@@ -0,0 +1,6 @@
+CREATE TYPE path_policy AS ENUM ('full', 'basename', 'hide', 'replace');
+
+-- How file system paths are shown in the notifications sent for the webhook.
+ALTER TABLE IF EXISTS webhooks ADD COLUMN IF NOT EXISTS path_policy path_policy NOT NULL DEFAULT 'full';
+-- Path prefixes to replace when the policy is 'replace', each in the form "from=to".
+ALTER TABLE IF EXISTS webhooks ADD COLUMN IF NOT EXISTS path_replacements TEXT[] NOT NULL DEFAULT '{}';
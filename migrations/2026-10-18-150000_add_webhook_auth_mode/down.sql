@@ -0,0 +1,4 @@
+ALTER TABLE IF EXISTS webhooks DROP COLUMN IF EXISTS hmac_secret;
+ALTER TABLE IF EXISTS webhooks DROP COLUMN IF EXISTS auth_token_digest;
+ALTER TABLE IF EXISTS webhooks DROP COLUMN IF EXISTS auth_mode;
+DROP TYPE IF EXISTS webhook_auth_mode;
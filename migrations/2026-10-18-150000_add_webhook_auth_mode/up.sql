@@ -0,0 +1,8 @@
+CREATE TYPE webhook_auth_mode AS ENUM ('basic', 'url_token', 'header_token', 'hmac');
+
+-- How requests to the webhook are authenticated.
+ALTER TABLE IF EXISTS webhooks ADD COLUMN IF NOT EXISTS auth_mode webhook_auth_mode NOT NULL DEFAULT 'basic';
+-- The SHA-256 digest of the token for the 'url_token' and 'header_token' modes.
+ALTER TABLE IF EXISTS webhooks ADD COLUMN IF NOT EXISTS auth_token_digest TEXT NULL;
+-- The shared secret used to sign requests in the 'hmac' mode.
+ALTER TABLE IF EXISTS webhooks ADD COLUMN IF NOT EXISTS hmac_secret TEXT NULL;
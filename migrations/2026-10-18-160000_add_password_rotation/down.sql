@@ -0,0 +1,3 @@
+ALTER TABLE IF EXISTS webhooks DROP COLUMN IF EXISTS previous_password_expires_at;
+ALTER TABLE IF EXISTS webhooks DROP COLUMN IF EXISTS previous_password;
+ALTER TABLE IF EXISTS webhooks DROP COLUMN IF EXISTS password_params;
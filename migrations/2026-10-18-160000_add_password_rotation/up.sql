@@ -0,0 +1,7 @@
+-- The Argon2id parameters the password was hashed with, e.g. 'argon2id$v=19$m=65536,t=2,p=1'.
+ALTER TABLE IF EXISTS webhooks ADD COLUMN IF NOT EXISTS password_params TEXT NOT NULL DEFAULT '';
+UPDATE webhooks
+SET password_params = COALESCE(substring(encode(password, 'escape') from '^\$([^$]+\$v=[0-9]+\$[^$]+)\$'), '');
+-- The password hash replaced by the last rotation, which is accepted until the grace period ends.
+ALTER TABLE IF EXISTS webhooks ADD COLUMN IF NOT EXISTS previous_password BYTEA NULL;
+ALTER TABLE IF EXISTS webhooks ADD COLUMN IF NOT EXISTS previous_password_expires_at TIMESTAMPTZ NULL;
@@ -0,0 +1 @@
+ALTER TABLE IF EXISTS webhooks DROP COLUMN IF EXISTS allowed_sources;
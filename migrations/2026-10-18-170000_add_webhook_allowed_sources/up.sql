@@ -0,0 +1,2 @@
+-- Networks in CIDR notation that requests to the webhook must come from; any source is allowed if empty.
+ALTER TABLE IF EXISTS webhooks ADD COLUMN IF NOT EXISTS allowed_sources TEXT[] NOT NULL DEFAULT '{}';
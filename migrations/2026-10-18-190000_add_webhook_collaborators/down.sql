@@ -0,0 +1,8 @@
+DROP TABLE IF EXISTS webhook_collaborators;
+
+-- Postgres can't remove a value from an enum, so the type is recreated without it.
+DELETE FROM users WHERE user_role = 'viewer';
+ALTER TYPE user_role RENAME TO user_role_old;
+CREATE TYPE user_role AS ENUM ('system_administrator', 'administrator');
+ALTER TABLE users ALTER COLUMN user_role TYPE user_role USING user_role::TEXT::user_role;
+DROP TYPE user_role_old;
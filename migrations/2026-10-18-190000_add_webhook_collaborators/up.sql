@@ -0,0 +1,18 @@
+-- Users that can see webhooks shared with them and only change them as granted.
+ALTER TYPE user_role ADD VALUE IF NOT EXISTS 'viewer';
+
+-- Users granted permissions on webhooks that they don't own.
+CREATE TABLE IF NOT EXISTS webhook_collaborators (
+    id UUID PRIMARY KEY NOT NULL,
+    webhook_id UUID NOT NULL,
+    user_id UUID NOT NULL,
+    -- Whether the user may change which rooms the webhook posts to and the rooms' settings.
+    manage_rooms BOOLEAN NOT NULL DEFAULT FALSE,
+    -- Whether the user may change what the webhook's notifications show.
+    manage_filters BOOLEAN NOT NULL DEFAULT FALSE,
+    -- Whether the user may replace the webhook's password.
+    rotate_credentials BOOLEAN NOT NULL DEFAULT FALSE,
+    UNIQUE (webhook_id, user_id),
+    FOREIGN KEY(webhook_id) REFERENCES webhooks(id) ON DELETE CASCADE,
+    FOREIGN KEY(user_id) REFERENCES users(id) ON DELETE CASCADE
+);
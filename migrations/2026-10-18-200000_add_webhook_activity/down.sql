@@ -0,0 +1,4 @@
+ALTER TABLE IF EXISTS webhooks DROP COLUMN IF EXISTS delivery_failures;
+ALTER TABLE IF EXISTS webhooks DROP COLUMN IF EXISTS events_received;
+ALTER TABLE IF EXISTS webhooks DROP COLUMN IF EXISTS last_event_at;
+ALTER TABLE IF EXISTS webhooks DROP COLUMN IF EXISTS created_at;
@@ -0,0 +1,6 @@
+-- When the webhook was created; unknown for webhooks created before it was recorded.
+ALTER TABLE IF EXISTS webhooks ADD COLUMN IF NOT EXISTS created_at TIMESTAMPTZ NULL;
+-- When the webhook last received an event, how many it has received, and how many messages for them weren't sent.
+ALTER TABLE IF EXISTS webhooks ADD COLUMN IF NOT EXISTS last_event_at TIMESTAMPTZ NULL;
+ALTER TABLE IF EXISTS webhooks ADD COLUMN IF NOT EXISTS events_received BIGINT NOT NULL DEFAULT 0;
+ALTER TABLE IF EXISTS webhooks ADD COLUMN IF NOT EXISTS delivery_failures BIGINT NOT NULL DEFAULT 0;
@@ -0,0 +1,3 @@
+ALTER TABLE IF EXISTS matrix_rooms DROP COLUMN IF EXISTS muted_until;
+ALTER TABLE IF EXISTS webhooks DROP COLUMN IF EXISTS paused_until;
+ALTER TABLE IF EXISTS webhooks DROP COLUMN IF EXISTS paused;
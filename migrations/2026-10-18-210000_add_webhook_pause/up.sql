@@ -0,0 +1,5 @@
+-- Whether the webhook is paused, and when a timed pause ends. Paused webhooks don't post to any of their rooms.
+ALTER TABLE IF EXISTS webhooks ADD COLUMN IF NOT EXISTS paused BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE IF EXISTS webhooks ADD COLUMN IF NOT EXISTS paused_until TIMESTAMPTZ NULL;
+-- When the room's mute ends; the webhook doesn't post to the room until then.
+ALTER TABLE IF EXISTS matrix_rooms ADD COLUMN IF NOT EXISTS muted_until TIMESTAMPTZ NULL;
@@ -0,0 +1,5 @@
+DROP TABLE IF EXISTS held_messages;
+ALTER TABLE IF EXISTS matrix_rooms DROP COLUMN IF EXISTS quiet_hours_bypass_health;
+ALTER TABLE IF EXISTS matrix_rooms DROP COLUMN IF EXISTS quiet_hours_timezone;
+ALTER TABLE IF EXISTS matrix_rooms DROP COLUMN IF EXISTS quiet_hours_end;
+ALTER TABLE IF EXISTS matrix_rooms DROP COLUMN IF EXISTS quiet_hours_start;
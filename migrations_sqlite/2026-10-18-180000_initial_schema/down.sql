@@ -0,0 +1,3 @@
+DROP TABLE IF EXISTS matrix_rooms;
+DROP TABLE IF EXISTS webhooks;
+DROP TABLE IF EXISTS users;
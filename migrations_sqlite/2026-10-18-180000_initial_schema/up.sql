@@ -0,0 +1,49 @@
+-- SQLite has no UUID, enum, array, or timestamp with time zone types. UUIDs are stored as hyphenated text, enums as
+-- their snake_case names, arrays as JSON arrays, and timestamps as UTC text.
+
+-- Users that interact with the bot.
+CREATE TABLE IF NOT EXISTS users (
+    id TEXT PRIMARY KEY NOT NULL,
+    service_username TEXT NOT NULL UNIQUE, -- The user's unique ID on the aforementioned service.
+    user_role TEXT NOT NULL CHECK (user_role IN ('system_administrator', 'administrator')) -- The user's role with the bot.
+);
+
+-- Definitions of webhooks from an *arr (e.g. Sonarr or Radarr).
+CREATE TABLE IF NOT EXISTS webhooks (
+    id TEXT PRIMARY KEY NOT NULL,
+    username TEXT NOT NULL, -- The username that secures the webhook.
+    password BLOB NOT NULL, -- The password hash that secures the webhook.
+    user_id TEXT NOT NULL, -- The user that created and thus owns this webhook.
+    server_name TEXT NULL,
+    -- The base URL and API key of the *arr that invokes the webhook; used to retrieve media covers from the *arr.
+    arr_url TEXT NULL,
+    arr_api_key TEXT NULL,
+    -- How file system paths are shown in the notifications sent for the webhook.
+    path_policy TEXT NOT NULL DEFAULT 'full' CHECK (path_policy IN ('full', 'basename', 'hide', 'replace')),
+    -- Path prefixes to replace when the policy is 'replace', each in the form "from=to".
+    path_replacements TEXT NOT NULL DEFAULT '[]',
+    -- How requests to the webhook are authenticated.
+    auth_mode TEXT NOT NULL DEFAULT 'basic' CHECK (auth_mode IN ('basic', 'url_token', 'header_token', 'hmac')),
+    -- The SHA-256 digest of the token for the 'url_token' and 'header_token' modes.
+    auth_token_digest TEXT NULL,
+    -- The shared secret used to sign requests in the 'hmac' mode.
+    hmac_secret TEXT NULL,
+    -- The Argon2id parameters the password was hashed with, e.g. 'argon2id$v=19$m=65536,t=2,p=1'.
+    password_params TEXT NOT NULL DEFAULT '',
+    -- The password hash replaced by the last rotation, which is accepted until the grace period ends.
+    previous_password BLOB NULL,
+    previous_password_expires_at TEXT NULL,
+    -- Networks in CIDR notation that requests to the webhook must come from; any source is allowed if empty.
+    allowed_sources TEXT NOT NULL DEFAULT '[]',
+    FOREIGN KEY(user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Matrix rooms to post messages from webhooks in.
+CREATE TABLE IF NOT EXISTS matrix_rooms (
+    id TEXT PRIMARY KEY NOT NULL,
+    room_id TEXT NOT NULL, -- The room ID of the Matrix room (_not_ an alias).
+    webhook_id TEXT NOT NULL, -- The webhook to get message data from for messages posted in this room.
+    -- Whether to hide episode titles and other potential spoilers in notifications sent to the room.
+    hide_spoilers BOOLEAN NOT NULL DEFAULT FALSE,
+    FOREIGN KEY(webhook_id) REFERENCES webhooks(id) ON DELETE CASCADE
+);
@@ -0,0 +1,64 @@
+DROP TABLE IF EXISTS webhook_collaborators;
+
+-- Recreate the tables as in up.sql to restore the CHECK constraint without the 'viewer' role.
+DELETE FROM users WHERE user_role = 'viewer';
+CREATE TEMPORARY TABLE users_backup AS SELECT * FROM users;
+CREATE TEMPORARY TABLE webhooks_backup AS SELECT * FROM webhooks;
+CREATE TEMPORARY TABLE matrix_rooms_backup AS SELECT * FROM matrix_rooms;
+DROP TABLE matrix_rooms;
+DROP TABLE webhooks;
+DROP TABLE users;
+
+-- Users that interact with the bot.
+CREATE TABLE users (
+    id TEXT PRIMARY KEY NOT NULL,
+    service_username TEXT NOT NULL UNIQUE, -- The user's unique ID on the aforementioned service.
+    user_role TEXT NOT NULL CHECK (user_role IN ('system_administrator', 'administrator')) -- The user's role with the bot.
+);
+
+-- Definitions of webhooks from an *arr (e.g. Sonarr or Radarr).
+CREATE TABLE webhooks (
+    id TEXT PRIMARY KEY NOT NULL,
+    username TEXT NOT NULL, -- The username that secures the webhook.
+    password BLOB NOT NULL, -- The password hash that secures the webhook.
+    user_id TEXT NOT NULL, -- The user that created and thus owns this webhook.
+    server_name TEXT NULL,
+    -- The base URL and API key of the *arr that invokes the webhook; used to retrieve media covers from the *arr.
+    arr_url TEXT NULL,
+    arr_api_key TEXT NULL,
+    -- How file system paths are shown in the notifications sent for the webhook.
+    path_policy TEXT NOT NULL DEFAULT 'full' CHECK (path_policy IN ('full', 'basename', 'hide', 'replace')),
+    -- Path prefixes to replace when the policy is 'replace', each in the form "from=to".
+    path_replacements TEXT NOT NULL DEFAULT '[]',
+    -- How requests to the webhook are authenticated.
+    auth_mode TEXT NOT NULL DEFAULT 'basic' CHECK (auth_mode IN ('basic', 'url_token', 'header_token', 'hmac')),
+    -- The SHA-256 digest of the token for the 'url_token' and 'header_token' modes.
+    auth_token_digest TEXT NULL,
+    -- The shared secret used to sign requests in the 'hmac' mode.
+    hmac_secret TEXT NULL,
+    -- The Argon2id parameters the password was hashed with, e.g. 'argon2id$v=19$m=65536,t=2,p=1'.
+    password_params TEXT NOT NULL DEFAULT '',
+    -- The password hash replaced by the last rotation, which is accepted until the grace period ends.
+    previous_password BLOB NULL,
+    previous_password_expires_at TEXT NULL,
+    -- Networks in CIDR notation that requests to the webhook must come from; any source is allowed if empty.
+    allowed_sources TEXT NOT NULL DEFAULT '[]',
+    FOREIGN KEY(user_id) REFERENCES users(id) ON DELETE CASCADE
+);
+
+-- Matrix rooms to post messages from webhooks in.
+CREATE TABLE matrix_rooms (
+    id TEXT PRIMARY KEY NOT NULL,
+    room_id TEXT NOT NULL, -- The room ID of the Matrix room (_not_ an alias).
+    webhook_id TEXT NOT NULL, -- The webhook to get message data from for messages posted in this room.
+    -- Whether to hide episode titles and other potential spoilers in notifications sent to the room.
+    hide_spoilers BOOLEAN NOT NULL DEFAULT FALSE,
+    FOREIGN KEY(webhook_id) REFERENCES webhooks(id) ON DELETE CASCADE
+);
+
+INSERT INTO users SELECT * FROM users_backup;
+INSERT INTO webhooks SELECT * FROM webhooks_backup;
+INSERT INTO matrix_rooms SELECT * FROM matrix_rooms_backup;
+DROP TABLE users_backup;
+DROP TABLE webhooks_backup;
+DROP TABLE matrix_rooms_backup;
@@ -0,0 +1,4 @@
+ALTER TABLE webhooks DROP COLUMN delivery_failures;
+ALTER TABLE webhooks DROP COLUMN events_received;
+ALTER TABLE webhooks DROP COLUMN last_event_at;
+ALTER TABLE webhooks DROP COLUMN created_at;
@@ -0,0 +1,6 @@
+-- When the webhook was created; unknown for webhooks created before it was recorded.
+ALTER TABLE webhooks ADD COLUMN created_at TEXT NULL;
+-- When the webhook last received an event, how many it has received, and how many messages for them weren't sent.
+ALTER TABLE webhooks ADD COLUMN last_event_at TEXT NULL;
+ALTER TABLE webhooks ADD COLUMN events_received INTEGER NOT NULL DEFAULT 0;
+ALTER TABLE webhooks ADD COLUMN delivery_failures INTEGER NOT NULL DEFAULT 0;
@@ -0,0 +1,3 @@
+ALTER TABLE matrix_rooms DROP COLUMN muted_until;
+ALTER TABLE webhooks DROP COLUMN paused_until;
+ALTER TABLE webhooks DROP COLUMN paused;
@@ -0,0 +1,5 @@
+-- Whether the webhook is paused, and when a timed pause ends. Paused webhooks don't post to any of their rooms.
+ALTER TABLE webhooks ADD COLUMN paused BOOLEAN NOT NULL DEFAULT FALSE;
+ALTER TABLE webhooks ADD COLUMN paused_until TEXT NULL;
+-- When the room's mute ends; the webhook doesn't post to the room until then.
+ALTER TABLE matrix_rooms ADD COLUMN muted_until TEXT NULL;
@@ -0,0 +1,5 @@
+DROP TABLE IF EXISTS held_messages;
+ALTER TABLE matrix_rooms DROP COLUMN quiet_hours_bypass_health;
+ALTER TABLE matrix_rooms DROP COLUMN quiet_hours_timezone;
+ALTER TABLE matrix_rooms DROP COLUMN quiet_hours_end;
+ALTER TABLE matrix_rooms DROP COLUMN quiet_hours_start;
@@ -0,0 +1,17 @@
+-- The room's daily quiet hours, given as HH:MM times in an IANA time zone, during which notifications are held and
+-- then posted as a summary once the quiet hours end.
+ALTER TABLE matrix_rooms ADD COLUMN quiet_hours_start TEXT NULL;
+ALTER TABLE matrix_rooms ADD COLUMN quiet_hours_end TEXT NULL;
+ALTER TABLE matrix_rooms ADD COLUMN quiet_hours_timezone TEXT NULL;
+-- Whether health check errors are posted during quiet hours rather than held.
+ALTER TABLE matrix_rooms ADD COLUMN quiet_hours_bypass_health BOOLEAN NOT NULL DEFAULT FALSE;
+
+-- Notifications held during a room's quiet hours.
+CREATE TABLE IF NOT EXISTS held_messages (
+    id TEXT PRIMARY KEY NOT NULL,
+    matrix_room_id TEXT NOT NULL,
+    received_at TEXT NOT NULL,
+    -- A one-line description of the notification to include in the summary.
+    summary TEXT NOT NULL,
+    FOREIGN KEY(matrix_room_id) REFERENCES matrix_rooms(id) ON DELETE CASCADE
+);
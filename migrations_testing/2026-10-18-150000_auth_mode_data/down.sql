@@ -0,0 +1 @@
+DELETE FROM webhooks WHERE id IN ('6c7d52a4-8a0e-4f7e-9d64-0f1b1c9a3e11', '9e2b8f13-5d4c-4b6a-a1f0-3c7e2d9b4a22');
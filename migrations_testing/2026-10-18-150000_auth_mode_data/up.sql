@@ -0,0 +1,18 @@
+-- Token: test-token
+INSERT INTO webhooks (id, username, password, user_id, auth_mode, auth_token_digest)
+VALUES ('6c7d52a4-8a0e-4f7e-9d64-0f1b1c9a3e11',
+        'testuser',
+        -- Null terminator-padded bytea representation of the password hash for: myP@ssw0rd123
+        E'\\x246172676F6E32696424763D3139246D3D36353533362C743D322C703D312479304579687361564C32507138562F6C626C4B4F2B772472796D6461416C6E42616D626A42585175752B6E2B78307939586C653142743664463257724E654A67695500000000000000000000000000000000000000000000000000000000000000',
+        '33a370ef-e309-4b8f-ab72-0e75632282af',
+        'header_token',
+        'TF3Jt3CJBfd_Xl0WMWtd-0JeaMsybc1VqGDpCncHAx4');
+-- Secret: test-secret
+INSERT INTO webhooks (id, username, password, user_id, auth_mode, hmac_secret)
+VALUES ('9e2b8f13-5d4c-4b6a-a1f0-3c7e2d9b4a22',
+        'testuser',
+        -- Null terminator-padded bytea representation of the password hash for: myP@ssw0rd123
+        E'\\x246172676F6E32696424763D3139246D3D36353533362C743D322C703D312479304579687361564C32507138562F6C626C4B4F2B772472796D6461416C6E42616D626A42585175752B6E2B78307939586C653142743664463257724E654A67695500000000000000000000000000000000000000000000000000000000000000',
+        '33a370ef-e309-4b8f-ab72-0e75632282af',
+        'hmac',
+        'test-secret');
@@ -0,0 +1 @@
+DELETE FROM webhooks WHERE id IN ('4f1c2a9e-7b3d-4e8a-9c61-2d5f8e0b7a33', 'a83e5d17-2c94-4b0f-8e6a-71c3f9d2b044');
@@ -0,0 +1,20 @@
+-- The seeded passwords were hashed with the current parameters.
+UPDATE webhooks
+SET password_params = COALESCE(substring(encode(password, 'escape') from '^\$([^$]+\$v=[0-9]+\$[^$]+)\$'), '');
+-- Rotated webhooks whose previous password is myP@ssw0rd123; the current passwords are unusable placeholders.
+INSERT INTO webhooks (id, username, password, user_id, previous_password, previous_password_expires_at)
+VALUES ('4f1c2a9e-7b3d-4e8a-9c61-2d5f8e0b7a33',
+        'testuser',
+        E'\\x00',
+        '33a370ef-e309-4b8f-ab72-0e75632282af',
+        -- Null terminator-padded bytea representation of the password hash for: myP@ssw0rd123
+        E'\\x246172676F6E32696424763D3139246D3D36353533362C743D322C703D312479304579687361564C32507138562F6C626C4B4F2B772472796D6461416C6E42616D626A42585175752B6E2B78307939586C653142743664463257724E654A67695500000000000000000000000000000000000000000000000000000000000000',
+        NOW() + INTERVAL '1 day');
+INSERT INTO webhooks (id, username, password, user_id, previous_password, previous_password_expires_at)
+VALUES ('a83e5d17-2c94-4b0f-8e6a-71c3f9d2b044',
+        'testuser',
+        E'\\x00',
+        '33a370ef-e309-4b8f-ab72-0e75632282af',
+        -- Null terminator-padded bytea representation of the password hash for: myP@ssw0rd123
+        E'\\x246172676F6E32696424763D3139246D3D36353533362C743D322C703D312479304579687361564C32507138562F6C626C4B4F2B772472796D6461416C6E42616D626A42585175752B6E2B78307939586C653142743664463257724E654A67695500000000000000000000000000000000000000000000000000000000000000',
+        NOW() - INTERVAL '1 day');
@@ -0,0 +1 @@
+DELETE FROM webhooks WHERE id = 'd2a7c4e1-3b58-4f96-8e0d-5a1b9c7f6e55';
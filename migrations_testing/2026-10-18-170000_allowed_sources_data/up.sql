@@ -0,0 +1,9 @@
+-- Token: test-token; only accepts requests from 10.0.0.0/8.
+INSERT INTO webhooks (id, username, password, user_id, auth_mode, auth_token_digest, allowed_sources)
+VALUES ('d2a7c4e1-3b58-4f96-8e0d-5a1b9c7f6e55',
+        'testuser',
+        E'\\x00',
+        '33a370ef-e309-4b8f-ab72-0e75632282af',
+        'header_token',
+        'TF3Jt3CJBfd_Xl0WMWtd-0JeaMsybc1VqGDpCncHAx4',
+        '{10.0.0.0/8}');
@@ -0,0 +1,59 @@
+//! `yarrbot export` and `yarrbot import`: back up Yarrbot's configuration or move it to another database.
+
+use super::OnConflict;
+use anyhow::{Context, Result};
+use std::fs;
+use std::io::{self, Write};
+use std::path::Path;
+use yarrbot_db::export::{export, import, ConfigurationExport, ImportCounts};
+use yarrbot_db::repositories::Repositories;
+
+/// Write the configuration as JSON to the given file, or to stdout.
+pub async fn run_export(
+    repositories: &Repositories,
+    output: Option<&Path>,
+    include_secrets: bool,
+) -> Result<()> {
+    let json = serde_json::to_string_pretty(&export(repositories, include_secrets).await?)?;
+    match output {
+        Some(path) => {
+            fs::write(path, json)
+                .with_context(|| format!("Could not write to {}.", path.display()))?;
+            eprintln!("Exported the configuration to {}.", path.display());
+        }
+        None => writeln!(io::stdout(), "{}", json)?,
+    }
+    Ok(())
+}
+
+/// Import a configuration previously written by [run_export].
+pub async fn run_import(
+    repositories: &Repositories,
+    file: &Path,
+    on_conflict: OnConflict,
+    dry_run: bool,
+) -> Result<()> {
+    let contents =
+        fs::read_to_string(file).with_context(|| format!("Could not read {}.", file.display()))?;
+    let document: ConfigurationExport = serde_json::from_str(&contents)
+        .with_context(|| format!("{} is not a Yarrbot export.", file.display()))?;
+    let report = import(repositories, document, on_conflict.into(), dry_run).await?;
+
+    if dry_run {
+        println!("Dry run; nothing was changed. The import would have:");
+    } else {
+        println!("Imported the configuration:");
+    }
+    print_counts("users", report.users);
+    print_counts("webhooks", report.webhooks);
+    print_counts("rooms", report.rooms);
+    print_counts("collaborators", report.collaborators);
+    Ok(())
+}
+
+fn print_counts(kind: &str, counts: ImportCounts) {
+    println!(
+        "  {}: {} created, {} overwritten, {} skipped",
+        kind, counts.created, counts.overwritten, counts.skipped
+    );
+}
@@ -0,0 +1,22 @@
+//! `yarrbot migrate`: run or revert database migrations.
+
+use super::MigrateCommand;
+use anyhow::Result;
+use std::path::PathBuf;
+use yarrbot_db::{migrate, revert_latest_migration, DbPoolConnection};
+
+pub fn run(connection: DbPoolConnection, command: MigrateCommand) -> Result<()> {
+    match command {
+        MigrateCommand::Run => {
+            migrate(connection)?;
+            println!("The database is up to date.");
+        }
+        MigrateCommand::Revert { directory } => {
+            let directory = directory
+                .unwrap_or_else(|| PathBuf::from(connection.backend().migrations_directory()));
+            let name = revert_latest_migration(&connection, &directory)?;
+            println!("Reverted migration {}.", name);
+        }
+    }
+    Ok(())
+}
@@ -0,0 +1,240 @@
+//! The subcommands of the `yarrbot` binary. Apart from `serve`, these work directly against the database so that an
+//! operator can manage Yarrbot without Matrix, such as when the only administrator's account is lost.
+
+mod backup;
+mod migrate;
+mod user;
+mod webhook;
+
+use crate::configuration::validate_configuration;
+use crate::logging::LogFilterHandle;
+use crate::serve::serve;
+use anyhow::{Context, Result};
+use clap::{ArgEnum, Parser, Subcommand};
+use std::path::PathBuf;
+use yarrbot_db::enums::UserRole;
+use yarrbot_db::export::ConflictPolicy;
+use yarrbot_db::repositories::Repositories;
+use yarrbot_db::{build_async_pool, build_pool, validate_database_configuration, DbPoolConnection};
+
+#[derive(Parser)]
+#[clap(
+    version,
+    about = "A Matrix bot that relays Sonarr and Radarr webhooks to Matrix rooms."
+)]
+pub struct Cli {
+    /// What to do; defaults to `serve`.
+    #[clap(subcommand)]
+    pub command: Option<Command>,
+}
+
+#[derive(Subcommand)]
+pub enum Command {
+    /// Connect to Matrix and serve the webhook API.
+    Serve,
+
+    /// Run or revert database migrations without connecting to Matrix.
+    #[clap(subcommand)]
+    Migrate(MigrateCommand),
+
+    /// Manage the users allowed to interact with Yarrbot.
+    #[clap(subcommand)]
+    User(UserCommand),
+
+    /// Manage webhooks.
+    #[clap(subcommand)]
+    Webhook(WebhookCommand),
+
+    /// Export all users, webhooks, rooms and collaborators as JSON, including hashed webhook passwords.
+    Export {
+        /// The file to write to; the export is written to stdout if not given.
+        #[clap(long, short, value_parser)]
+        output: Option<PathBuf>,
+        /// Also export webhooks' *arr API keys and HMAC secrets, which are stored as given rather than hashed. Without
+        /// them, imported webhooks have to be given them again.
+        #[clap(long, action)]
+        include_secrets: bool,
+    },
+
+    /// Import a configuration written by `export`.
+    Import {
+        /// The exported file.
+        #[clap(value_parser)]
+        file: PathBuf,
+        /// Run the import and roll it back, reporting what it would have done.
+        #[clap(long, action)]
+        dry_run: bool,
+        /// What to do with records that already exist.
+        #[clap(long, arg_enum, value_parser, default_value = "fail")]
+        on_conflict: OnConflict,
+    },
+
+    /// Check the configuration and exit.
+    CheckConfig,
+}
+
+#[derive(Subcommand)]
+pub enum MigrateCommand {
+    /// Run any pending migrations.
+    Run,
+
+    /// Revert the most recently run migration.
+    Revert {
+        /// The directory containing Yarrbot's migrations, as found in its source code; defaults to `migrations` for
+        /// Postgres and `migrations_sqlite` for SQLite.
+        #[clap(long, value_parser)]
+        directory: Option<PathBuf>,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum UserCommand {
+    /// Allow a Matrix user to interact with Yarrbot.
+    Add {
+        /// The fully qualified Matrix user ID, such as `@you:example.org`.
+        #[clap(value_parser)]
+        username: String,
+        #[clap(long, arg_enum, value_parser, default_value = "administrator")]
+        role: Role,
+    },
+
+    /// List all users.
+    List,
+
+    /// Remove a user along with their webhooks.
+    Remove {
+        #[clap(value_parser)]
+        username: String,
+    },
+
+    /// Change a user's role.
+    SetRole {
+        #[clap(value_parser)]
+        username: String,
+        #[clap(arg_enum, value_parser)]
+        role: Role,
+    },
+}
+
+#[derive(Subcommand)]
+pub enum WebhookCommand {
+    /// Add a webhook posting to a room. Yarrbot joins the room the next time it starts.
+    Add {
+        /// The Matrix user ID of the webhook's owner, who must already be a user.
+        #[clap(value_parser)]
+        owner: String,
+        /// The ID of the room to post to, such as `!roomId:example.org`; aliases can't be resolved without Matrix.
+        #[clap(value_parser)]
+        room_id: String,
+        /// The username the *arr authenticates with.
+        #[clap(value_parser)]
+        username: String,
+        /// The password the *arr authenticates with; one is generated if not given.
+        #[clap(long, value_parser)]
+        password: Option<String>,
+        /// A name for the *arr shown in notifications.
+        #[clap(long, value_parser)]
+        server_name: Option<String>,
+    },
+
+    /// List webhooks, optionally only those of a single owner.
+    List {
+        #[clap(long, value_parser)]
+        owner: Option<String>,
+    },
+
+    /// Remove a webhook.
+    Remove {
+        /// The webhook's ID as shown by `webhook list`.
+        #[clap(value_parser)]
+        id: String,
+    },
+
+    /// Replace a webhook's password.
+    Rotate {
+        /// The webhook's ID as shown by `webhook list`.
+        #[clap(value_parser)]
+        id: String,
+        /// The new password; one is generated if not given.
+        #[clap(long, value_parser)]
+        password: Option<String>,
+        /// How many hours the current password keeps working.
+        #[clap(long, value_parser, default_value = "0")]
+        grace_hours: u64,
+    },
+}
+
+#[derive(ArgEnum, Clone, Copy)]
+pub enum Role {
+    SystemAdministrator,
+    Administrator,
+    Viewer,
+}
+
+impl From<Role> for UserRole {
+    fn from(role: Role) -> Self {
+        match role {
+            Role::SystemAdministrator => UserRole::SystemAdministrator,
+            Role::Administrator => UserRole::Administrator,
+            Role::Viewer => UserRole::Viewer,
+        }
+    }
+}
+
+#[derive(ArgEnum, Clone, Copy)]
+pub enum OnConflict {
+    /// Abort the import.
+    Fail,
+    /// Keep the existing record.
+    Skip,
+    /// Replace the existing record.
+    Overwrite,
+}
+
+impl From<OnConflict> for ConflictPolicy {
+    fn from(on_conflict: OnConflict) -> Self {
+        match on_conflict {
+            OnConflict::Fail => ConflictPolicy::Fail,
+            OnConflict::Skip => ConflictPolicy::Skip,
+            OnConflict::Overwrite => ConflictPolicy::Overwrite,
+        }
+    }
+}
+
+/// Run the given subcommand.
+pub async fn run(command: Command, log_filter: LogFilterHandle) -> Result<()> {
+    match command {
+        Command::Serve => serve(log_filter).await,
+        Command::Migrate(c) => migrate::run(connect()?, c),
+        Command::User(c) => user::run(&repositories().await?, c).await,
+        Command::Webhook(c) => webhook::run(&repositories().await?, c).await,
+        Command::Export {
+            output,
+            include_secrets,
+        } => backup::run_export(&repositories().await?, output.as_deref(), include_secrets).await,
+        Command::Import {
+            file,
+            dry_run,
+            on_conflict,
+        } => backup::run_import(&repositories().await?, &file, on_conflict, dry_run).await,
+        Command::CheckConfig => {
+            validate_configuration()?;
+            println!("The configuration is valid.");
+            Ok(())
+        }
+    }
+}
+
+/// Connect to the database for running migrations, which Diesel does.
+fn connect() -> Result<DbPoolConnection> {
+    validate_database_configuration()?;
+    build_pool()?
+        .get()
+        .context("Could not retrieve a connection from the connection pool.")
+}
+
+/// Connect to the database for everything but migrations.
+async fn repositories() -> Result<Repositories> {
+    validate_database_configuration()?;
+    Ok(Repositories::database(build_async_pool().await?))
+}
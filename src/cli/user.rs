@@ -0,0 +1,210 @@
+//! `yarrbot user`: manage the users allowed to interact with Yarrbot.
+
+use super::UserCommand;
+use anyhow::{bail, ensure, Context, Result};
+use yarrbot_db::models::{NewUser, User};
+use yarrbot_db::repositories::Repositories;
+
+pub async fn run(repositories: &Repositories, command: UserCommand) -> Result<()> {
+    match command {
+        UserCommand::Add { username, role } => {
+            ensure!(
+                yarrbot_matrix_client::is_user_id(&username),
+                "\"{}\" is not a valid Matrix user ID.",
+                username
+            );
+            if repositories
+                .users
+                .try_get_by_username(&username)
+                .await?
+                .is_some()
+            {
+                bail!("{} is already a user.", username);
+            }
+            let user = repositories
+                .users
+                .create_user(NewUser::new(&username, Some(role.into())))
+                .await?;
+            println!(
+                "Added {} as {}.",
+                user.service_username,
+                user.user_role.as_ref()
+            );
+        }
+        UserCommand::List => {
+            for user in repositories.users.get_all().await? {
+                println!("{}\t{}", user.service_username, user.user_role.as_ref());
+            }
+        }
+        UserCommand::Remove { username } => {
+            let user = get_user(repositories, &username).await?;
+            ensure!(
+                repositories.users.delete(&user).await?,
+                "{} is the last System Administrator and can't be removed.",
+                username
+            );
+            println!("Removed {} and their webhooks.", username);
+        }
+        UserCommand::SetRole { username, role } => {
+            let user = get_user(repositories, &username).await?;
+            let user = repositories
+                .users
+                .update_role(&user, role.into())
+                .await?
+                .with_context(|| {
+                    format!(
+                        "{} is the last System Administrator and can't be demoted.",
+                        username
+                    )
+                })?;
+            println!(
+                "{} is now {}.",
+                user.service_username,
+                user.user_role.as_ref()
+            );
+        }
+    }
+    Ok(())
+}
+
+/// Retrieve a [User] by username, returning an error if they don't exist.
+pub(super) async fn get_user(repositories: &Repositories, username: &str) -> Result<User> {
+    repositories
+        .users
+        .try_get_by_username(username)
+        .await?
+        .with_context(|| format!("{} is not a user.", username))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::super::{connect, repositories, Role};
+    use super::*;
+    use tokio::sync::{Mutex, MutexGuard};
+    use uuid::Uuid;
+    use yarrbot_common::environment::variables::DB_URL;
+    use yarrbot_db::enums::UserRole;
+
+    /// The database URL is read from the environment, so tests connecting to their own database take turns.
+    static ENVIRONMENT: Mutex<()> = Mutex::const_new(());
+
+    /// Connect to a new, migrated SQLite database in the temporary directory the same way the CLI does. The database's
+    /// URL stays in the environment until the returned guard is dropped.
+    async fn sqlite_repositories() -> (MutexGuard<'static, ()>, Repositories) {
+        let guard = ENVIRONMENT.lock().await;
+        let path = std::env::temp_dir().join(format!("yarrbot-cli-{}.db", Uuid::new_v4()));
+        std::env::set_var(DB_URL, format!("sqlite://{}", path.display()));
+        yarrbot_db::migrate(connect().unwrap()).unwrap();
+        (guard, repositories().await.unwrap())
+    }
+
+    #[actix::test]
+    async fn remove_refuses_last_system_administrator() {
+        // Arrange
+        let (_environment, repositories) = sqlite_repositories().await;
+        let username = String::from("@sysadmin:example.org");
+        run(
+            &repositories,
+            UserCommand::Add {
+                username: username.clone(),
+                role: Role::SystemAdministrator,
+            },
+        )
+        .await
+        .unwrap();
+
+        // Act
+        let actual = run(&repositories, UserCommand::Remove { username }).await;
+
+        // Assert
+        assert!(actual.is_err());
+        assert_eq!(1, repositories.users.get_all().await.unwrap().len());
+    }
+
+    #[actix::test]
+    async fn set_role_refuses_demoting_last_system_administrator() {
+        // Arrange
+        let (_environment, repositories) = sqlite_repositories().await;
+        let username = String::from("@sysadmin:example.org");
+        run(
+            &repositories,
+            UserCommand::Add {
+                username: username.clone(),
+                role: Role::SystemAdministrator,
+            },
+        )
+        .await
+        .unwrap();
+
+        // Act
+        let actual = run(
+            &repositories,
+            UserCommand::SetRole {
+                username: username.clone(),
+                role: Role::Viewer,
+            },
+        )
+        .await;
+
+        // Assert
+        assert!(actual.is_err());
+        let user = get_user(&repositories, &username).await.unwrap();
+        assert!(matches!(user.user_role, UserRole::SystemAdministrator));
+    }
+
+    #[actix::test]
+    async fn set_role_demotes_system_administrator_given_another() {
+        // Arrange
+        let (_environment, repositories) = sqlite_repositories().await;
+        for username in ["@a:example.org", "@b:example.org"] {
+            run(
+                &repositories,
+                UserCommand::Add {
+                    username: String::from(username),
+                    role: Role::SystemAdministrator,
+                },
+            )
+            .await
+            .unwrap();
+        }
+
+        // Act
+        run(
+            &repositories,
+            UserCommand::SetRole {
+                username: String::from("@a:example.org"),
+                role: Role::Viewer,
+            },
+        )
+        .await
+        .unwrap();
+
+        // Assert
+        let user = get_user(&repositories, "@a:example.org").await.unwrap();
+        assert!(matches!(user.user_role, UserRole::Viewer));
+    }
+
+    #[actix::test]
+    async fn remove_deletes_user() {
+        // Arrange
+        let (_environment, repositories) = sqlite_repositories().await;
+        let username = String::from("@admin:example.org");
+        run(
+            &repositories,
+            UserCommand::Add {
+                username: username.clone(),
+                role: Role::Administrator,
+            },
+        )
+        .await
+        .unwrap();
+
+        // Act
+        run(&repositories, UserCommand::Remove { username })
+            .await
+            .unwrap();
+
+        // Assert
+        assert!(!repositories.users.any().await.unwrap());
+    }
+}
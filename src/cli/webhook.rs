@@ -0,0 +1,123 @@
+//! `yarrbot webhook`: manage webhooks.
+
+use super::user::get_user;
+use super::WebhookCommand;
+use anyhow::{ensure, Context, Result};
+use std::time::Duration;
+use uuid::Uuid;
+use yarrbot_common::crypto::{generate_password, hash, hash_parameters, initialize_cryptography};
+use yarrbot_common::short_id::ShortId;
+use yarrbot_db::models::{NewMatrixRoom, NewWebhook, Webhook};
+use yarrbot_db::repositories::Repositories;
+
+pub async fn run(repositories: &Repositories, command: WebhookCommand) -> Result<()> {
+    initialize_cryptography()?;
+    match command {
+        WebhookCommand::Add {
+            owner,
+            room_id,
+            username,
+            password,
+            server_name,
+        } => {
+            ensure!(
+                yarrbot_matrix_client::is_room_id(&room_id),
+                "\"{}\" is not a valid Matrix room ID.",
+                room_id
+            );
+            let user = get_user(repositories, &owner).await?;
+            let password = get_password(password)?;
+            let hashed = hash(password.clone()).await?.to_vec();
+            let new_webhook = NewWebhook::new(&username, hashed, &user, server_name);
+            let webhook = repositories.webhooks.create_webhook(new_webhook).await?;
+            repositories
+                .rooms
+                .create_room(NewMatrixRoom::new(&room_id, &webhook))
+                .await?;
+            println!("ID: {}", webhook.id.to_short_id());
+            println!("Username: {}", webhook.username);
+            println!("Password: {}", password);
+        }
+        WebhookCommand::List { owner } => {
+            let webhooks = match owner {
+                Some(o) => {
+                    let user = get_user(repositories, &o).await?;
+                    repositories.webhooks.get_all_by_user_id(&user.id).await?
+                }
+                None => repositories.webhooks.get_all().await?,
+            };
+            for webhook in webhooks {
+                let owner = repositories
+                    .users
+                    .try_get(&webhook.user_id)
+                    .await?
+                    .map(|u| u.service_username)
+                    .unwrap_or_default();
+                let rooms: Vec<String> = repositories
+                    .rooms
+                    .get_by_webhook_id(&webhook.id)
+                    .await?
+                    .into_iter()
+                    .map(|r| r.room_id)
+                    .collect();
+                println!(
+                    "{}\t{}\t{}\t{}",
+                    webhook.id.to_short_id(),
+                    webhook.username,
+                    owner,
+                    rooms.join(",")
+                );
+            }
+        }
+        WebhookCommand::Remove { id } => {
+            let webhook = get_webhook(repositories, &id).await?;
+            repositories.webhooks.delete(&webhook).await?;
+            println!("Removed webhook {}.", id);
+        }
+        WebhookCommand::Rotate {
+            id,
+            password,
+            grace_hours,
+        } => {
+            let webhook = get_webhook(repositories, &id).await?;
+            let password = get_password(password)?;
+            let hashed = hash(password.clone()).await?.to_vec();
+            let params = hash_parameters(&hashed).unwrap_or_default();
+            let grace_period = match grace_hours {
+                0 => None,
+                h => Some(Duration::from_secs(h * 60 * 60)),
+            };
+            let webhook = repositories
+                .webhooks
+                .rotate_password(&webhook, hashed, params, grace_period)
+                .await?;
+            println!("Username: {}", webhook.username);
+            println!("Password: {}", password);
+            if let Some(expires_at) = webhook.previous_password_expires_at {
+                println!(
+                    "The previous password keeps working until {}.",
+                    expires_at.format("%Y-%m-%d %H:%M UTC")
+                );
+            }
+        }
+    }
+    Ok(())
+}
+
+fn get_password(password: Option<String>) -> Result<String> {
+    match password {
+        Some(p) => Ok(p),
+        None => generate_password(None),
+    }
+}
+
+/// Retrieve a [Webhook] by its short ID, returning an error if it doesn't exist.
+async fn get_webhook(repositories: &Repositories, id: &str) -> Result<Webhook> {
+    let uuid =
+        Uuid::from_short_id(id).with_context(|| format!("\"{}\" is not a webhook ID.", id))?;
+    repositories
+        .webhooks
+        .try_get(&uuid)
+        .await?
+        .with_context(|| format!("Webhook {} doesn't exist.", id))
+}
@@ -0,0 +1,97 @@
+//! Loads the optional configuration file, checks all of Yarrbot's settings before it starts, and reloads the settings
+//! that can change while it runs.
+
+use crate::listeners::Listeners;
+use crate::logging::{reload_log_filter, LogFilterHandle};
+use actix::{Actor, Context, Handler};
+use anyhow::{bail, Result};
+use std::path::PathBuf;
+use tracing::{debug, error, info, warn};
+use yarrbot_common::configuration::{load_configuration_file, reload_configuration_file};
+use yarrbot_common::environment::{
+    get_env_var,
+    variables::{CONFIG_FILE, LOG_FILTER},
+};
+use yarrbot_common::ReloadNotice;
+use yarrbot_db::validate_database_configuration;
+use yarrbot_matrix_client::client::validate_matrix_configuration;
+use yarrbot_webhook_api::{PausedEvents, TrustedProxies};
+
+/// Load the configuration file named by `YARRBOT_CONFIG_FILE`, if any.
+pub fn load_configuration() -> Result<()> {
+    if let Ok(path) = get_env_var(CONFIG_FILE) {
+        load_configuration_file(&PathBuf::from(path))?;
+    }
+    Ok(())
+}
+
+/// Check every setting Yarrbot needs to start, reporting all of the problems found rather than only the first.
+pub fn validate_configuration() -> Result<()> {
+    let results = vec![
+        validate_database_configuration(),
+        validate_matrix_configuration(),
+        Listeners::from_env().map(|_| ()),
+        TrustedProxies::from_env().map(|_| ()),
+        PausedEvents::from_env().map(|_| ()),
+    ];
+    let problems: Vec<String> = results
+        .into_iter()
+        .filter_map(Result::err)
+        .map(|e| format!("  - {:#}", e))
+        .collect();
+    if !problems.is_empty() {
+        bail!(
+            "Yarrbot's configuration is invalid:\n{}",
+            problems.join("\n")
+        );
+    }
+    Ok(())
+}
+
+/// Re-reads the configuration file and applies the settings that don't require a restart upon each [ReloadNotice].
+/// Currently only the log filter can be changed while Yarrbot is running; changes to any other setting are logged as
+/// waiting for a restart.
+pub struct ConfigurationReloader {
+    log_filter: LogFilterHandle,
+}
+
+impl ConfigurationReloader {
+    pub fn new(log_filter: LogFilterHandle) -> Self {
+        ConfigurationReloader { log_filter }
+    }
+}
+
+impl Actor for ConfigurationReloader {
+    type Context = Context<Self>;
+
+    fn started(&mut self, _ctx: &mut Self::Context) {
+        debug!("Started ConfigurationReloader.");
+    }
+}
+
+impl Handler<ReloadNotice> for ConfigurationReloader {
+    type Result = ();
+
+    fn handle(&mut self, _msg: ReloadNotice, _ctx: &mut Self::Context) -> Self::Result {
+        let changed = match reload_configuration_file() {
+            Ok(c) => c,
+            Err(e) => {
+                error!(error = ?e, "Failed to reload the configuration file; keeping the previous settings.");
+                return;
+            }
+        };
+        let needs_restart: Vec<&str> = changed.into_iter().filter(|s| *s != LOG_FILTER).collect();
+        if !needs_restart.is_empty() {
+            warn!(
+                settings = ?needs_restart,
+                "Some changed settings only take effect after Yarrbot restarts."
+            );
+        }
+        match reload_log_filter(&self.log_filter) {
+            Ok(_) => info!("Reloaded the log filter."),
+            Err(e) => {
+                error!(error = ?e, "Failed to reload the log filter; keeping the previous filter.")
+            }
+        }
+    }
+}
@@ -1,21 +1,19 @@
 use anyhow::{ensure, Context, Result};
 use tracing::{debug, info};
 use yarrbot_common::environment::{get_env_var, variables::FIRST_MATRIX_USER};
-use yarrbot_db::actions::user_actions::UserActions;
 use yarrbot_db::enums::UserRole;
-use yarrbot_db::models::{NewUser, User};
-use yarrbot_db::{DbPool, DbPoolConnection};
+use yarrbot_db::models::NewUser;
+use yarrbot_db::repositories::Repositories;
 
-pub fn first_time_initialization(pool: &DbPool) -> Result<()> {
-    let conn = pool.get()?;
-    initialize_first_user(&conn)?;
+pub async fn first_time_initialization(repositories: &Repositories) -> Result<()> {
+    initialize_first_user(repositories).await?;
 
     Ok(())
 }
 
-fn initialize_first_user(conn: &DbPoolConnection) -> Result<()> {
+async fn initialize_first_user(repositories: &Repositories) -> Result<()> {
     debug!("Beginning initialization of the first user.");
-    if User::any(conn)? {
+    if repositories.users.any().await? {
         debug!("At least one user exists. Moving to the next step.");
         return Ok(());
     }
@@ -32,7 +30,10 @@ fn initialize_first_user(conn: &DbPoolConnection) -> Result<()> {
     );
 
     let new_user = NewUser::new(&user_id_raw, Some(UserRole::SystemAdministrator));
-    User::create_user(conn, new_user)
+    repositories
+        .users
+        .create_user(new_user)
+        .await
         .with_context(|| format!("Failed to create the User record for {}.", user_id_raw))?;
 
     info!(user = %user_id_raw, "First run user has been set up to interact with Yarrbot.");
@@ -0,0 +1,188 @@
+//! Reads where the web API should listen from the environment: any number of TCP addresses, an optional Unix domain
+//! socket, and an optional certificate and key to serve HTTPS with.
+
+use anyhow::{bail, Context, Result};
+use rustls::{Certificate, PrivateKey, ServerConfig};
+use rustls_pemfile::Item;
+use std::fs::File;
+use std::io::BufReader;
+use std::net::{IpAddr, SocketAddr};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use yarrbot_common::environment::{
+    get_env_var,
+    variables::{BIND_ADDRESSES, TLS_CERTIFICATE, TLS_KEY, UNIX_SOCKET, WEB_PORT},
+};
+
+const DEFAULT_PORT: u16 = 8080;
+const DEFAULT_ADDRESS: &str = "127.0.0.1";
+
+pub struct Listeners {
+    /// The TCP addresses to listen on.
+    pub addresses: Vec<SocketAddr>,
+    /// The path of a Unix domain socket to listen on; never uses TLS. Its requests have no client IP address, so
+    /// webhooks only accepting certain sources reject them.
+    pub unix_socket: Option<PathBuf>,
+    /// When present, TCP listeners serve HTTPS with this configuration.
+    pub tls: Option<ServerConfig>,
+}
+
+impl Listeners {
+    pub fn from_env() -> Result<Self> {
+        let port = get_port()?;
+        let addresses = match get_env_var(BIND_ADDRESSES) {
+            Ok(v) => parse_bind_addresses(&v, port)?,
+            Err(_) => parse_bind_addresses(DEFAULT_ADDRESS, port)?,
+        };
+        let unix_socket = get_env_var(UNIX_SOCKET).ok().map(PathBuf::from);
+        let tls = match (get_env_var(TLS_CERTIFICATE), get_env_var(TLS_KEY)) {
+            (Ok(cert), Ok(key)) => Some(load_tls_config(Path::new(&cert), Path::new(&key))?),
+            (Err(_), Err(_)) => None,
+            _ => bail!(
+                "Both {} and {} must be set to serve HTTPS.",
+                TLS_CERTIFICATE,
+                TLS_KEY
+            ),
+        };
+
+        Ok(Listeners {
+            addresses,
+            unix_socket,
+            tls,
+        })
+    }
+}
+
+fn get_port() -> Result<u16> {
+    match get_env_var(WEB_PORT) {
+        Ok(v) => {
+            u16::from_str(&v).with_context(|| format!("Failed to parse \"{}\" as a valid port.", v))
+        }
+        Err(_) => Ok(DEFAULT_PORT),
+    }
+}
+
+/// Parse a comma-separated list of addresses such as `0.0.0.0`, `[::]:8443`, or `192.168.1.10:8080`. Addresses
+/// without a port use the given port.
+fn parse_bind_addresses(value: &str, default_port: u16) -> Result<Vec<SocketAddr>> {
+    let addresses = value
+        .split(',')
+        .map(str::trim)
+        .filter(|a| !a.is_empty())
+        .map(|a| {
+            SocketAddr::from_str(a)
+                .ok()
+                .or_else(|| {
+                    let ip = a
+                        .strip_prefix('[')
+                        .and_then(|i| i.strip_suffix(']'))
+                        .unwrap_or(a);
+                    IpAddr::from_str(ip)
+                        .ok()
+                        .map(|ip| SocketAddr::new(ip, default_port))
+                })
+                .with_context(|| format!("Failed to parse \"{}\" as a bind address.", a))
+        })
+        .collect::<Result<Vec<SocketAddr>>>()?;
+    if addresses.is_empty() {
+        bail!("{} doesn't contain any addresses.", BIND_ADDRESSES);
+    }
+    Ok(addresses)
+}
+
+/// Build the TLS configuration from PEM encoded certificate chain and private key files.
+fn load_tls_config(cert_path: &Path, key_path: &Path) -> Result<ServerConfig> {
+    let mut cert_reader = BufReader::new(
+        File::open(cert_path)
+            .with_context(|| format!("Failed to open certificate {}.", cert_path.display()))?,
+    );
+    let certs: Vec<Certificate> = rustls_pemfile::certs(&mut cert_reader)
+        .with_context(|| format!("Failed to read certificate {}.", cert_path.display()))?
+        .into_iter()
+        .map(Certificate)
+        .collect();
+    if certs.is_empty() {
+        bail!("No certificates found in {}.", cert_path.display());
+    }
+
+    let mut key_reader = BufReader::new(
+        File::open(key_path)
+            .with_context(|| format!("Failed to open private key {}.", key_path.display()))?,
+    );
+    let key = rustls_pemfile::read_all(&mut key_reader)
+        .with_context(|| format!("Failed to read private key {}.", key_path.display()))?
+        .into_iter()
+        .find_map(|item| match item {
+            Item::PKCS8Key(k) | Item::RSAKey(k) | Item::ECKey(k) => Some(PrivateKey(k)),
+            _ => None,
+        })
+        .with_context(|| format!("No private key found in {}.", key_path.display()))?;
+
+    ServerConfig::builder()
+        .with_safe_defaults()
+        .with_no_client_auth()
+        .with_single_cert(certs, key)
+        .context("The TLS certificate and private key are invalid.")
+}
+
+/// Remove a socket left behind by an earlier run, which would otherwise prevent binding to the same path.
+#[cfg(unix)]
+pub fn remove_stale_socket(path: &Path) -> Result<()> {
+    use std::os::unix::fs::FileTypeExt;
+
+    if let Ok(metadata) = std::fs::metadata(path) {
+        if metadata.file_type().is_socket() {
+            std::fs::remove_file(path)
+                .with_context(|| format!("Failed to remove stale socket {}.", path.display()))?;
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_bind_addresses_uses_default_port_given_addresses_without_port() {
+        // Act
+        let actual = parse_bind_addresses("0.0.0.0, [::], 192.168.1.10:8443", 8080).unwrap();
+
+        // Assert
+        assert_eq!(
+            vec![
+                SocketAddr::from_str("0.0.0.0:8080").unwrap(),
+                SocketAddr::from_str("[::]:8080").unwrap(),
+                SocketAddr::from_str("192.168.1.10:8443").unwrap(),
+            ],
+            actual
+        );
+    }
+
+    #[test]
+    fn parse_bind_addresses_returns_error_given_invalid_address() {
+        // Act
+        let invalid = parse_bind_addresses("0.0.0.0, localhost", 8080);
+        let empty = parse_bind_addresses(" , ", 8080);
+
+        // Assert
+        assert!(invalid.is_err());
+        assert!(empty.is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn remove_stale_socket_allows_binding_to_the_same_path() {
+        // Arrange
+        let path = std::env::temp_dir().join(format!("yarrbot-{}.sock", uuid::Uuid::new_v4()));
+        drop(std::os::unix::net::UnixListener::bind(&path).unwrap());
+
+        // Act
+        remove_stale_socket(&path).unwrap();
+        let actual = std::os::unix::net::UnixListener::bind(&path);
+
+        // Assert
+        assert!(actual.is_ok());
+        std::fs::remove_file(&path).unwrap();
+    }
+}
@@ -0,0 +1,165 @@
+//! Reads how Yarrbot should log from the environment: the filter, the format of each line, and whether lines are
+//! written to stdout or to a rotating set of files.
+
+use crate::telemetry;
+use anyhow::{bail, Context, Error, Result};
+use std::path::PathBuf;
+use std::str::FromStr;
+use tracing_appender::non_blocking::WorkerGuard;
+use tracing_appender::rolling::{RollingFileAppender, Rotation};
+use tracing_log::LogTracer;
+use tracing_subscriber::layer::SubscriberExt;
+use tracing_subscriber::{filter::EnvFilter, fmt, reload, Registry};
+use yarrbot_common::environment::{
+    get_env_var,
+    variables::{LOG_DIRECTORY, LOG_FILTER, LOG_FORMAT, LOG_ROTATION},
+};
+
+/// Yarrbot's own crates log informational messages; its dependencies only log warnings, apart from the web server
+/// announcing its workers.
+const DEFAULT_TRACE_FILTER: &str = "warn,yarrbot=info,actix_server=info";
+const LOG_FILE_PREFIX: &str = "yarrbot.log";
+
+/// The format of each logged line.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum LogFormat {
+    /// `tracing_subscriber`'s default single line format.
+    Full,
+
+    /// A shorter single line format which omits the span context.
+    Compact,
+
+    /// Multi-line output intended for reading in a terminal.
+    Pretty,
+
+    /// One JSON object per line, with each event's fields as top-level keys.
+    Json,
+}
+
+impl FromStr for LogFormat {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.to_lowercase().as_str() {
+            "full" => Ok(LogFormat::Full),
+            "compact" => Ok(LogFormat::Compact),
+            "pretty" => Ok(LogFormat::Pretty),
+            "json" => Ok(LogFormat::Json),
+            _ => bail!("\"{}\" is not a recognized log format.", s),
+        }
+    }
+}
+
+/// Swaps the log filter while Yarrbot is running.
+pub type LogFilterHandle = reload::Handle<EnvFilter, Registry>;
+
+struct LogSettings {
+    filter: EnvFilter,
+    format: LogFormat,
+    /// When present, logs are written to files in this directory instead of stdout.
+    directory: Option<PathBuf>,
+    rotation: Rotation,
+}
+
+impl LogSettings {
+    fn from_env() -> Result<Self> {
+        let filter = get_filter()?;
+        let format = match get_env_var(LOG_FORMAT) {
+            Ok(f) => LogFormat::from_str(&f)?,
+            Err(_) => LogFormat::Full,
+        };
+        let rotation = match get_env_var(LOG_ROTATION) {
+            Ok(r) => parse_rotation(&r)?,
+            Err(_) => Rotation::DAILY,
+        };
+
+        Ok(LogSettings {
+            filter,
+            format,
+            directory: get_env_var(LOG_DIRECTORY).ok().map(PathBuf::from),
+            rotation,
+        })
+    }
+}
+
+fn get_filter() -> Result<EnvFilter> {
+    match get_env_var(LOG_FILTER) {
+        Ok(f) => EnvFilter::from_str(&f)
+            .with_context(|| format!("Failed to parse tracer filter string \"{}\".", f)),
+        Err(_) => Ok(
+            EnvFilter::from_str(DEFAULT_TRACE_FILTER).expect("Default trace filter is invalid.")
+        ),
+    }
+}
+
+fn parse_rotation(value: &str) -> Result<Rotation> {
+    match value.to_lowercase().as_str() {
+        "minutely" => Ok(Rotation::MINUTELY),
+        "hourly" => Ok(Rotation::HOURLY),
+        "daily" => Ok(Rotation::DAILY),
+        "never" => Ok(Rotation::NEVER),
+        _ => bail!("\"{}\" is not a recognized log rotation.", value),
+    }
+}
+
+/// Install the global tracing subscriber. Buffered log lines are only guaranteed to be written while the returned
+/// guard is alive, so hold onto it until Yarrbot exits. The returned handle is used to [reload_log_filter].
+pub fn initialize_logging() -> Result<(WorkerGuard, LogFilterHandle)> {
+    LogTracer::init().context("Could not initialize the LogTracer.")?;
+    let settings = LogSettings::from_env()?;
+    let (writer, guard) = match &settings.directory {
+        Some(d) => tracing_appender::non_blocking(RollingFileAppender::new(
+            settings.rotation,
+            d,
+            LOG_FILE_PREFIX,
+        )),
+        None => tracing_appender::non_blocking(std::io::stdout()),
+    };
+    // Colors are only useful in a terminal.
+    let ansi = settings.directory.is_none();
+    // A macro rather than a closure, since each layer sits at a different depth of the subscriber and so has its own
+    // type.
+    macro_rules! layer {
+        () => {
+            fmt::Layer::default()
+                .with_writer(writer.clone())
+                .with_ansi(ansi)
+        };
+    }
+
+    // Only one of the following is present; the others are no-ops.
+    let format = settings.format;
+    let full = (format == LogFormat::Full).then(|| layer!());
+    let compact = (format == LogFormat::Compact).then(|| layer!().compact());
+    let pretty = (format == LogFormat::Pretty).then(|| layer!().pretty());
+    let json = (format == LogFormat::Json).then(|| {
+        layer!()
+            .json()
+            .flatten_event(true)
+            .with_current_span(true)
+            .with_span_list(true)
+    });
+    let otel = telemetry::build_otlp_tracer()?
+        .map(|tracer| tracing_opentelemetry::layer().with_tracer(tracer));
+
+    let (filter, handle) = reload::Layer::new(settings.filter);
+    let subscriber = Registry::default()
+        .with(filter)
+        .with(full)
+        .with(compact)
+        .with(pretty)
+        .with(json)
+        .with(otel);
+    tracing::subscriber::set_global_default(subscriber)
+        .context("Could not set global subscriber for tracing.")?;
+
+    Ok((guard, handle))
+}
+
+/// Replace the log filter with the one currently configured.
+pub fn reload_log_filter(handle: &LogFilterHandle) -> Result<()> {
+    let filter = get_filter()?;
+    handle
+        .reload(filter)
+        .context("Could not replace the log filter.")
+}
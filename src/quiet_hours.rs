@@ -0,0 +1,52 @@
+//! Periodically posts the messages held during rooms' quiet hours once the quiet hours end.
+
+use actix::{Actor, ActorFutureExt, AsyncContext, Context, WrapFuture};
+use std::time::Duration;
+use tracing::debug;
+use yarrbot_db::repositories::Repositories;
+use yarrbot_matrix_client::client::YarrbotMatrixClient;
+use yarrbot_webhook_api::release_held_messages;
+
+/// How often to check for rooms whose quiet hours have ended.
+const RELEASE_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Checks every [RELEASE_INTERVAL] for rooms whose quiet hours have ended and posts the summary of their held
+/// messages. A check that's due while the previous one is still running is skipped, so summaries aren't posted twice.
+pub struct HeldMessageReleaser {
+    repositories: Repositories,
+    client: YarrbotMatrixClient,
+    releasing: bool,
+}
+
+impl HeldMessageReleaser {
+    pub fn new(repositories: Repositories, client: YarrbotMatrixClient) -> Self {
+        HeldMessageReleaser {
+            repositories,
+            client,
+            releasing: false,
+        }
+    }
+}
+
+impl Actor for HeldMessageReleaser {
+    type Context = Context<Self>;
+
+    fn started(&mut self, ctx: &mut Self::Context) {
+        debug!("Started HeldMessageReleaser.");
+        ctx.run_interval(RELEASE_INTERVAL, |releaser, ctx| {
+            if releaser.releasing {
+                debug!("Skipping the release of held messages; the previous one is still running.");
+                return;
+            }
+            releaser.releasing = true;
+            let repositories = releaser.repositories.clone();
+            let client = releaser.client.clone();
+            let fut = async move {
+                release_held_messages(&repositories, &client).await;
+            }
+            .into_actor(releaser)
+            .map(|_result, releaser, _ctx| releaser.releasing = false);
+            ctx.spawn(fut);
+        });
+    }
+}
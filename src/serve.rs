@@ -0,0 +1,158 @@
+//! Runs Yarrbot: connects to the Matrix homeserver and serves the web API until a shutdown signal is received.
+
+use crate::configuration::{self, ConfigurationReloader};
+use crate::first_time_initialization;
+use crate::listeners::{self, Listeners};
+use crate::logging::LogFilterHandle;
+use crate::quiet_hours::HeldMessageReleaser;
+use crate::telemetry;
+use actix::{Actor, Arbiter};
+use actix_web::{web, App, HttpServer};
+use anyhow::{Context, Result};
+use std::sync::Arc;
+use tracing::{info, warn};
+use tracing_actix_web::TracingLogger;
+use yarrbot_common::crypto::initialize_cryptography;
+use yarrbot_common::{ShutdownActor, SubscribeToReload, SubscribeToShutdown};
+use yarrbot_db::repositories::Repositories;
+use yarrbot_db::{build_async_pool, build_pool, migrate};
+use yarrbot_matrix_client::{
+    client::{
+        initialize_matrix_actors, initialize_matrix_sdk_client, initialize_yarrbot_matrix_client,
+        YarrbotMatrixClient,
+    },
+    MatrixHealth, MatrixSyncActor,
+};
+use yarrbot_webhook_api::{
+    health_config, metrics_config, webhook_config, AuthFailureTracker, CredentialCache,
+    LockoutSettings, PausedEvents, RequestIdHeader, SignatureReplayGuard, TrustedProxies,
+    YarrbotRootSpan,
+};
+
+/// Start Yarrbot, returning once it has shut down.
+pub async fn serve(log_filter: LogFilterHandle) -> Result<()> {
+    info!("Initializing Yarrbot...");
+
+    configuration::validate_configuration()?;
+
+    initialize_cryptography()?;
+
+    // Migrations are run by Diesel, which only needs its blocking pool until they're done.
+    {
+        let connection = build_pool()?
+            .get()
+            .context("Could not retrieve a connection from the connection pool.")?;
+        info!("Migrating the database...");
+        migrate(connection)?;
+    }
+
+    info!("Initializing database connection pool...");
+    let pool = build_async_pool().await?;
+    let repositories = Repositories::database(pool.clone());
+
+    info!("Running any first-time setup functions...");
+    first_time_initialization::first_time_initialization(&repositories).await?;
+
+    // Set up the shutdown actor, which listens for signals telling Yarrbot to stop.
+    let shutdown_addr = ShutdownActor::default().start();
+    let reloader_addr = ConfigurationReloader::new(log_filter).start();
+    shutdown_addr.do_send(SubscribeToReload(reloader_addr.recipient()));
+
+    info!("Starting up the connection to the Matrix server...");
+    let matrix_client = initialize_matrix_sdk_client().await?;
+    let matrix_health = MatrixHealth::default();
+    let (_room_message_addr, send_addr, _stripped_state_addr) = initialize_matrix_actors(
+        matrix_client.clone(),
+        repositories.clone(),
+        matrix_health.clone(),
+    )?;
+    let yarrbot_matrix_client = initialize_yarrbot_matrix_client(
+        matrix_client.clone(),
+        repositories.clone(),
+        send_addr.clone(),
+    )
+    .await?;
+
+    // The Matrix SDK sync loop locks up the system event loop, so we move it to its own arbiter (thus its own thread).
+    let sync_arbiter = Arbiter::new();
+    let sync_shutdown_addr = shutdown_addr.clone();
+    let sync_health = matrix_health.clone();
+    let sync_fut = async move {
+        let sync_addr = MatrixSyncActor::new(matrix_client, sync_health).start();
+        sync_shutdown_addr.do_send(SubscribeToShutdown(sync_addr.recipient()));
+    };
+    sync_arbiter.spawn(sync_fut);
+
+    // Messages held during rooms' quiet hours are posted as a summary once the quiet hours end.
+    HeldMessageReleaser::new(repositories.clone(), yarrbot_matrix_client.clone()).start();
+
+    // Failed authentication attempts are tracked across all of the server's workers.
+    let failure_tracker = web::Data::new(
+        AuthFailureTracker::new(LockoutSettings::default())
+            .with_alerts(Arc::new(yarrbot_matrix_client.clone())),
+    );
+    let trusted_proxies = web::Data::new(TrustedProxies::from_env()?);
+    let paused_events = web::Data::new(PausedEvents::from_env()?);
+
+    let credential_cache = web::Data::new(CredentialCache::default());
+    let replay_guard = web::Data::new(SignatureReplayGuard::default());
+
+    let listeners = Listeners::from_env()?;
+    let limited_webhooks = repositories
+        .webhooks
+        .get_all()
+        .await?
+        .iter()
+        .filter(|w| !w.allowed_sources.is_empty())
+        .count();
+    if listeners.unix_socket.is_some() && limited_webhooks > 0 {
+        warn!(
+            limited_webhooks,
+            "Requests over the Unix socket have no client IP address, so webhooks limited to certain sources reject them."
+        );
+    }
+
+    info!("Yarrbot started.");
+    let mut server = HttpServer::new(move || {
+        App::new()
+            .wrap(RequestIdHeader)
+            .wrap(TracingLogger::<YarrbotRootSpan>::new())
+            .app_data(web::Data::new(pool.clone()))
+            .app_data(web::Data::new(repositories.clone()))
+            .app_data(web::Data::new(yarrbot_matrix_client.clone()))
+            .app_data(trusted_proxies.clone())
+            .app_data(paused_events.clone())
+            .app_data(failure_tracker.clone())
+            .app_data(credential_cache.clone())
+            .app_data(replay_guard.clone())
+            .app_data(web::Data::new(matrix_health.clone()))
+            .configure(health_config)
+            .configure(metrics_config)
+            .service(web::scope("/api/v1").configure(webhook_config::<YarrbotMatrixClient>))
+    });
+    for address in &listeners.addresses {
+        server = match &listeners.tls {
+            Some(tls) => server.bind_rustls(address, tls.clone()),
+            None => server.bind(address),
+        }
+        .with_context(|| format!("Failed to bind to {}.", address))?;
+        info!(address = %address, tls = listeners.tls.is_some(), "Listening for webhooks.");
+    }
+    if let Some(path) = &listeners.unix_socket {
+        #[cfg(unix)]
+        {
+            listeners::remove_stale_socket(path)?;
+            server = server
+                .bind_uds(path)
+                .with_context(|| format!("Failed to bind to {}.", path.display()))?;
+            info!(path = %path.display(), "Listening for webhooks.");
+        }
+        #[cfg(not(unix))]
+        anyhow::bail!("Unix domain sockets are only supported on Unix.");
+    }
+    server.run().await?;
+
+    telemetry::shutdown();
+    info!("Yarrbot shut down.");
+    Ok(())
+}
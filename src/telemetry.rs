@@ -0,0 +1,43 @@
+//! Optional export of Yarrbot's traces to an OpenTelemetry collector.
+
+use anyhow::{Context, Result};
+use opentelemetry::sdk::propagation::TraceContextPropagator;
+use opentelemetry::sdk::trace::{self, Tracer};
+use opentelemetry::sdk::Resource;
+use opentelemetry::{global, runtime, KeyValue};
+use opentelemetry_otlp::WithExportConfig;
+use yarrbot_common::environment::{get_env_var, variables::OTLP_ENDPOINT};
+
+const SERVICE_NAME: &str = "yarrbot";
+
+/// Build a tracer exporting spans over OTLP/HTTP when `YARRBOT_OTLP_ENDPOINT` is set; returns
+/// [Option::None] otherwise.
+pub fn build_otlp_tracer() -> Result<Option<Tracer>> {
+    let endpoint = match get_env_var(OTLP_ENDPOINT) {
+        Ok(e) => e,
+        Err(_) => return Ok(None),
+    };
+    // Continue traces started by whatever sent the webhook if it provides a W3C `traceparent` header.
+    global::set_text_map_propagator(TraceContextPropagator::new());
+    let tracer = opentelemetry_otlp::new_pipeline()
+        .tracing()
+        .with_exporter(
+            opentelemetry_otlp::new_exporter()
+                .http()
+                .with_endpoint(endpoint.as_str()),
+        )
+        .with_trace_config(
+            trace::config().with_resource(Resource::new(vec![KeyValue::new(
+                "service.name",
+                SERVICE_NAME,
+            )])),
+        )
+        .install_batch(runtime::TokioCurrentThread)
+        .with_context(|| format!("Failed to set up trace export to {}.", endpoint))?;
+    Ok(Some(tracer))
+}
+
+/// Export any spans which are still buffered.
+pub fn shutdown() {
+    global::shutdown_tracer_provider();
+}